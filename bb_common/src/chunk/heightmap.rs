@@ -0,0 +1,195 @@
+use super::Section;
+use crate::{
+  math::SectionRelPos,
+  nbt::{Tag, NBT},
+};
+use bb_macros::Transfer;
+use bb_transfer::{MessageRead, MessageReader, MessageWrite, MessageWriter, ReadError, WriteError};
+
+/// The height of every column in a chunk. This is sent to 1.14+ clients in
+/// the chunk data packet.
+///
+/// Each height is one above the highest matching block in that column, or 0 if
+/// no block in the column matches. Heights are counted from the bottom of the
+/// world, so they are never negative.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heightmap {
+  heights: [u32; 256],
+}
+
+/// The heightmaps that are sent to clients. The server keeps these up to date
+/// for each chunk, and sends them along with the chunk data.
+#[derive(Transfer, Debug, Default, Clone, PartialEq, Eq)]
+pub struct Heightmaps {
+  /// The highest block that blocks motion, or contains a fluid.
+  pub motion_blocking: Heightmap,
+  /// The highest non-air block.
+  pub world_surface:   Heightmap,
+}
+
+impl Default for Heightmap {
+  fn default() -> Self { Self::new() }
+}
+
+impl MessageRead<'_> for Heightmap {
+  fn read(r: &mut MessageReader) -> Result<Self, ReadError> {
+    let mut heights = [0; 256];
+    for (h, v) in heights.iter_mut().zip(r.read_list::<u32>()?) {
+      *h = v?;
+    }
+    Ok(Heightmap { heights })
+  }
+}
+impl MessageWrite for Heightmap {
+  fn write<W: std::io::Write>(&self, w: &mut MessageWriter<W>) -> Result<(), WriteError> {
+    w.write_list(self.heights.iter())
+  }
+}
+
+impl Heightmaps {
+  /// Builds the NBT sent to 1.14+ clients. See [`Heightmap::to_long_array`]
+  /// for `world_height` and `compact`.
+  pub fn to_nbt(&self, world_height: u32, compact: bool) -> NBT {
+    NBT::new(
+      "",
+      Tag::new_compound(&[
+        (
+          "MOTION_BLOCKING",
+          Tag::LongArray(self.motion_blocking.to_long_array(world_height, compact)),
+        ),
+        ("WORLD_SURFACE", Tag::LongArray(self.world_surface.to_long_array(world_height, compact))),
+      ]),
+    )
+  }
+}
+
+impl Heightmap {
+  /// Creates a heightmap where every column is empty.
+  pub fn new() -> Self { Heightmap { heights: [0; 256] } }
+
+  /// Builds a heightmap of the highest non-air blocks in the given chunk
+  /// sections. The first section is the bottom of the world.
+  pub fn from_sections<S: Section>(sections: &[Option<S>]) -> Self {
+    let mut heights = [0; 256];
+    for z in 0..16 {
+      for x in 0..16 {
+        heights[z as usize * 16 + x as usize] = column_height(sections, x, z);
+      }
+    }
+    Heightmap { heights }
+  }
+
+  /// Returns the height of the given column. X and Z must be within `0..16`.
+  pub fn height(&self, x: u8, z: u8) -> u32 { self.heights[z as usize * 16 + x as usize] }
+  /// Sets the height of the given column. X and Z must be within `0..16`.
+  pub fn set_height(&mut self, x: u8, z: u8, height: u32) {
+    self.heights[z as usize * 16 + x as usize] = height;
+  }
+
+  /// Packs this heightmap into a long array, for use in NBT. `world_height` is
+  /// the total height of the world, which decides how many bits are used for
+  /// each entry (9 bits for 256 and 384 block tall worlds).
+  ///
+  /// If `compact` is false, entries will span across two longs (the 1.14 and
+  /// 1.15 format). If it is true, entries never span two longs, and the extra
+  /// bits at the top of each long are left empty (the 1.16+ format).
+  ///
+  /// The only reason these are signed is because of NBT long arrays. In
+  /// reality, they should be read as unsigned longs.
+  pub fn to_long_array(&self, world_height: u32, compact: bool) -> Vec<i64> {
+    // Enough bits to store every height from 0 to `world_height`.
+    let bits = (32 - world_height.leading_zeros()) as usize;
+    let mut longs;
+    if compact {
+      let per_long = 64 / bits;
+      longs = vec![0_u64; (256 + per_long - 1) / per_long];
+      for (i, &h) in self.heights.iter().enumerate() {
+        longs[i / per_long] |= u64::from(h) << (i % per_long * bits);
+      }
+    } else {
+      longs = vec![0_u64; (256 * bits + 63) / 64];
+      for (i, &h) in self.heights.iter().enumerate() {
+        let index = i * bits / 64;
+        let shift = i * bits % 64;
+        longs[index] |= u64::from(h) << shift;
+        if shift + bits > 64 {
+          longs[index + 1] |= u64::from(h) >> (64 - shift);
+        }
+      }
+    }
+    longs.into_iter().map(|v| v as i64).collect()
+  }
+}
+
+fn column_height<S: Section>(sections: &[Option<S>], x: u8, z: u8) -> u32 {
+  for (index, section) in sections.iter().enumerate().rev() {
+    let s = match section {
+      Some(s) => s,
+      None => continue,
+    };
+    for y in (0..16).rev() {
+      // This is correct; it is not a transparent check, just an air check.
+      if s.get_block(SectionRelPos::new(x, y, z)) != 0 {
+        return index as u32 * 16 + u32::from(y) + 1;
+      }
+    }
+  }
+  0
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::chunk::paletted;
+
+  fn heightmap() -> Heightmap {
+    let mut sections: Vec<Option<paletted::Section>> = (0..16).map(|_| None).collect();
+    let mut set = |x, y: u32, z, ty| {
+      sections[y as usize / 16]
+        .get_or_insert_with(|| paletted::Section::new(15))
+        .set_block(SectionRelPos::new(x, (y % 16) as u8, z), ty);
+    };
+    set(0, 5, 0, 1);
+    set(1, 0, 0, 1);
+    set(15, 255, 15, 1);
+    // Air at the top of a section shouldn't count.
+    set(2, 47, 0, 0);
+    Heightmap::from_sections(&sections)
+  }
+
+  #[test]
+  fn test_heights() {
+    let map = heightmap();
+    assert_eq!(map.height(0, 0), 6);
+    assert_eq!(map.height(1, 0), 1);
+    assert_eq!(map.height(2, 0), 0);
+    assert_eq!(map.height(15, 15), 256);
+  }
+
+  #[test]
+  fn test_pack() {
+    let map = heightmap();
+    let longs = map.to_long_array(256, true);
+    assert_eq!(longs.len(), 37);
+    assert_eq!(longs[0], 6 | 1 << 9);
+    // 255 % 7 = 3, so the last entry is the 4th entry in the last long.
+    assert_eq!(longs[36], 256 << 27);
+
+    let longs = map.to_long_array(256, false);
+    assert_eq!(longs.len(), 36);
+    assert_eq!(longs[0], 6 | 1 << 9);
+    // The last entry starts at bit 255 * 9 = 2295, which is bit 55 of the last
+    // long.
+    assert_eq!(longs[35] as u64, 256 << 55);
+  }
+
+  #[test]
+  fn test_pack_spanning() {
+    let mut heights = [0; 256];
+    // Entry 7 starts at bit 63, so it spans the first two longs.
+    heights[7] = 0b1_1111_1111;
+    let longs = Heightmap { heights }.to_long_array(256, false);
+    assert_eq!(longs[0] as u64, 1 << 63);
+    assert_eq!(longs[1], 0b1111_1111);
+  }
+}
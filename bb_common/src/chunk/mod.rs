@@ -1,8 +1,10 @@
 pub mod fixed;
+mod heightmap;
 pub mod light;
 pub mod paletted;
 mod section;
 
+pub use heightmap::{Heightmap, Heightmaps};
 pub use light::LightChunk;
 pub use section::Section;
 
@@ -161,62 +163,15 @@ impl<S: Section> Chunk<S> {
     }
   }
 
-  /// Builds a heightmap of this chunk. Each long contains 9 bit entries, where
-  /// each entry is the height of the world at the given X, Z coordinate. This
-  /// is used within 1.14+ protocol data, and is a needlessly complicated format
-  /// that you shouldn't waste any time thinking about.
-  ///
-  /// The only reason these are signed is because of NBT long arrays. In
-  /// reality, they should be read as unsigned longs.
-  pub fn build_heightmap_old(&self) -> Vec<i64> {
-    let mut heightmap = vec![0; 256 * 9 / 64];
-    let mut shift = 0;
-    let mut index = 0;
-    for z in 0..16 {
-      for x in 0..16 {
-        let v = self.height_at(RelPos::new(x, 0, z)).unwrap() as u64;
-        if shift > 64 - 9 {
-          heightmap[index] |= (v.overflowing_shl(shift).0 & 0b111111111 << (64 - 9)) as i64;
-          heightmap[index + 1] |= (v >> (64 - shift)) as i64;
-        } else {
-          heightmap[index] |= (v.overflowing_shl(shift).0) as i64;
-        }
-        shift += 9;
-        if shift > 64 {
-          shift -= 64;
-          index += 1;
-        }
-      }
-    }
-    heightmap
+  /// Builds a heightmap of this chunk, in the 1.14 and 1.15 format. See
+  /// [`Heightmap::to_long_array`] for details.
+  pub fn build_heightmap_old(&self, world_height: u32) -> Vec<i64> {
+    Heightmap::from_sections(&self.sections).to_long_array(world_height, false)
   }
-  /// Builds a heightmap of this chunk. Each long contains 9 bit entries, where
-  /// each entry is the height of the world at the given X, Z coordinate. This
-  /// is used within 1.16.5+ protocol data, and is a needlessly complicated
-  /// format that you shouldn't waste any time thinking about.
-  ///
-  /// The reason this is a different function is because of compacted long
-  /// arrays. In 1.16, they stopped making entries overlap two longs. This makes
-  /// it easier to serialize/deserialize, but uses slightly more storage.
-  ///
-  /// The only reason these are signed is because of NBT long arrays. In
-  /// reality, they should be read as unsigned longs.
-  pub fn build_heightmap_new(&self) -> Vec<i64> {
-    let mut heightmap = vec![0; (256.0 / (64 / 9) as f32).ceil() as usize];
-    let mut index = 0;
-    let mut shift = 0;
-    for z in 0..16 {
-      for x in 0..16 {
-        let v = self.height_at(RelPos::new(x, 0, z)).unwrap() as u64;
-        heightmap[index] |= (v.overflowing_shl(shift).0) as i64;
-        shift += 9;
-        if shift > 64 {
-          shift = 0; // Important! We just subtract 64 in the other one
-          index += 1;
-        }
-      }
-    }
-    heightmap
+  /// Builds a heightmap of this chunk, in the 1.16+ format. See
+  /// [`Heightmap::to_long_array`] for details.
+  pub fn build_heightmap_new(&self, world_height: u32) -> Vec<i64> {
+    Heightmap::from_sections(&self.sections).to_long_array(world_height, true)
   }
   /// Returns the world height at the given position. This is a simple loop, and
   /// should be avoided.
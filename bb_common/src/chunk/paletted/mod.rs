@@ -136,6 +136,33 @@ impl Section {
   // track all the amounts of blocks within the chunk, this is a single Vec
   // lookup.
   pub fn non_air_blocks(&self) -> u32 { 4096 - self.block_amounts[0] }
+  /// Returns an iterator over every non-air block in this section, along with
+  /// its global id. Air is always stored as a 0 in the block data (in both
+  /// paletted and direct mode), so any long that is 0 is skipped entirely.
+  /// Iteration also stops as soon as every non-air block has been found.
+  pub fn iter_non_air(&self) -> impl Iterator<Item = (SectionRelPos, u32)> + '_ {
+    let epl = 64 / self.data.bpe() as usize;
+    self
+      .data
+      .long_array()
+      .iter()
+      .enumerate()
+      .filter(|(_, long)| **long != 0)
+      .flat_map(move |(i, _)| i * epl..((i + 1) * epl).min(4096))
+      .filter_map(move |index| {
+        let pos = SectionRelPos::new(
+          (index & 0xf) as u8,
+          (index >> 8 & 0xf) as u8,
+          (index >> 4 & 0xf) as u8,
+        );
+        // SAFETY: `index` is within 0..4096, as we clamp the range above.
+        match unsafe { self.get_palette(pos) } {
+          0 => None,
+          _ => Some((pos, self.get_block(pos))),
+        }
+      })
+      .take(self.non_air_blocks() as usize)
+  }
 
   pub fn into_palette_data(self) -> (Vec<u32>, Vec<u64>) { (self.palette, self.data.into_inner()) }
 }
@@ -262,3 +262,39 @@ fn test_fill() {
   }
   assert_eq!(s.block_amounts[0] + s.block_amounts[1], 4096);
 }
+#[test]
+fn test_iter_non_air() {
+  let mut s = Section::new(MAX_BPE);
+  assert_eq!(s.iter_non_air().count(), 0);
+
+  s.set_block(SectionRelPos::new(3, 0, 0), 5);
+  s.set_block(SectionRelPos::new(15, 15, 15), 20);
+  s.set_block(SectionRelPos::new(0, 7, 2), 5);
+  assert_eq!(
+    s.iter_non_air().collect::<Vec<_>>(),
+    vec![
+      (SectionRelPos::new(3, 0, 0), 5),
+      (SectionRelPos::new(0, 7, 2), 5),
+      (SectionRelPos::new(15, 15, 15), 20),
+    ]
+  );
+  assert_eq!(s.iter_non_air().count() as u32, s.non_air_blocks());
+
+  s.fill(SectionRelPos::new(0, 0, 0), SectionRelPos::new(15, 15, 15), 20);
+  assert_eq!(s.iter_non_air().count(), 4096);
+}
+
+#[test]
+fn test_non_air_blocks() {
+  let mut s = Section::new(MAX_BPE);
+  assert_eq!(s.non_air_blocks(), 0);
+
+  s.fill(SectionRelPos::new(0, 0, 0), SectionRelPos::new(15, 15, 15), 5);
+  assert_eq!(s.non_air_blocks(), 4096);
+  s.set_block(SectionRelPos::new(1, 2, 3), 0);
+  assert_eq!(s.non_air_blocks(), 4095);
+  s.fill(SectionRelPos::new(0, 0, 0), SectionRelPos::new(15, 7, 15), 0);
+  assert_eq!(s.non_air_blocks(), 2048);
+  s.fill(SectionRelPos::new(0, 8, 0), SectionRelPos::new(15, 15, 15), 0);
+  assert_eq!(s.non_air_blocks(), 0);
+}
@@ -107,6 +107,13 @@ impl Metadata {
   pub fn set_item(&mut self, idx: u8, value: Item) { self.fields.insert(idx, Field::Item(value)); }
   pub fn set_pose(&mut self, idx: u8, pose: Pose) { self.fields.insert(idx, Field::Pose(pose)); }
 
+  /// Returns the byte at the given index, or 0 if it is unset or not a byte.
+  pub fn get_byte(&self, idx: u8) -> u8 {
+    match self.fields.get(&idx) {
+      Some(Field::Byte(v)) => *v,
+      _ => 0,
+    }
+  }
   pub fn get_item(&self, idx: u8) -> Item {
     self
       .fields
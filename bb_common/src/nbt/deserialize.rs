@@ -11,6 +11,8 @@ impl fmt::Display for ParseError {
       Self::InvalidString(e) => write!(f, "invalid string: {}", e),
       Self::IO(e) => write!(f, "io error: {}", e),
       Self::BufferError(e) => write!(f, "buffer error: {}", e),
+      Self::NegativeLen(len) => write!(f, "len `{}` is negative", len),
+      Self::TooDeep => write!(f, "nbt is nested more than {} tags deep", MAX_DEPTH),
     }
   }
 }
@@ -27,6 +29,16 @@ impl From<BufferError> for ParseError {
 
 impl Error for ParseError {}
 
+/// The deepest that compounds and lists can be nested. This is the same limit
+/// as vanilla, and stops a small packet from overflowing the stack.
+const MAX_DEPTH: u32 = 512;
+
+/// Reads an array length, and makes sure it isn't negative.
+fn read_len<T: AsRef<[u8]>>(buf: &mut Buffer<T>) -> Result<usize, ParseError> {
+  let len = buf.read_i32()?;
+  len.try_into().map_err(|_| ParseError::NegativeLen(len))
+}
+
 impl NBT {
   pub fn deserialize_file(buf: Vec<u8>) -> Result<Self, ParseError> {
     if buf.len() >= 2 && buf[0] == 0x1f && buf[1] == 0x8b {
@@ -61,13 +73,22 @@ impl NBT {
     } else {
       let len = buf.read_u16()?;
       let name = String::from_utf8(buf.read_buf(len as usize)?)?;
-      Ok(NBT::new(&name, Tag::deserialize(ty, buf)?))
+      Ok(NBT::new(&name, Tag::deserialize(ty, buf, 0)?))
     }
   }
 }
 
 impl Tag {
-  fn deserialize<T: AsRef<[u8]>>(ty: u8, buf: &mut Buffer<T>) -> Result<Self, ParseError> {
+  fn deserialize<T: AsRef<[u8]>>(
+    ty: u8,
+    buf: &mut Buffer<T>,
+    depth: u32,
+  ) -> Result<Self, ParseError> {
+    if depth > MAX_DEPTH {
+      return Err(ParseError::TooDeep);
+    }
+    // None of the capacities below can be larger than the remaining data, so
+    // that a large length doesn't allocate a bunch of memory.
     match ty {
       0 => Ok(Self::End),
       1 => Ok(Self::Byte(buf.read_i8()?)),
@@ -77,8 +98,8 @@ impl Tag {
       5 => Ok(Self::Float(buf.read_f32()?)),
       6 => Ok(Self::Double(buf.read_f64()?)),
       7 => {
-        let len = buf.read_i32()?;
-        Ok(Self::ByteArr(buf.read_buf(len as usize)?))
+        let len = read_len(buf)?;
+        Ok(Self::ByteArr(buf.read_buf(len)?))
       }
       8 => {
         let len = buf.read_u16()?;
@@ -89,10 +110,14 @@ impl Tag {
       }
       9 => {
         let inner_ty = buf.read_u8()?;
-        let len = buf.read_i32()?;
-        let mut inner = Vec::with_capacity(len as usize);
+        let len = read_len(buf)?;
+        // End tags take up no space, so a list of them could be any length.
+        if inner_ty == Self::End.ty() && len > 0 {
+          return Err(ParseError::InvalidType(inner_ty));
+        }
+        let mut inner = Vec::with_capacity(len.min(buf.remaining()));
         for _ in 0..len {
-          inner.push(Tag::deserialize(inner_ty, buf)?);
+          inner.push(Tag::deserialize(inner_ty, buf, depth + 1)?);
         }
         Ok(Self::List(inner))
       }
@@ -104,23 +129,23 @@ impl Tag {
             break;
           }
           let len = buf.read_u16()?;
-          let name = String::from_utf8(buf.read_buf(len as usize)?).unwrap();
-          let tag = Tag::deserialize(ty, buf)?;
+          let name = String::from_utf8(buf.read_buf(len as usize)?)?;
+          let tag = Tag::deserialize(ty, buf, depth + 1)?;
           inner.insert(name, tag);
         }
         Ok(inner.into())
       }
       11 => {
-        let len = buf.read_i32()?;
-        let mut inner = Vec::with_capacity(len as usize);
+        let len = read_len(buf)?;
+        let mut inner = Vec::with_capacity(len.min(buf.remaining() / 4));
         for _ in 0..len {
           inner.push(buf.read_i32()?);
         }
         Ok(Self::IntArray(inner))
       }
       12 => {
-        let len = buf.read_i32()?;
-        let mut inner = Vec::with_capacity(len as usize);
+        let len = read_len(buf)?;
+        let mut inner = Vec::with_capacity(len.min(buf.remaining() / 8));
         for _ in 0..len {
           inner.push(buf.read_i64()?);
         }
@@ -247,4 +272,32 @@ mod tests {
     // assert_eq!(v, expected);
     Ok(())
   }
+
+  #[test]
+  fn deserialize_invalid() {
+    // An unnamed list, with the given element type and length.
+    fn list(ty: u8, len: i32) -> Vec<u8> {
+      let mut out = vec![9, 0, 0, ty];
+      out.extend(len.to_be_bytes());
+      out
+    }
+    assert!(matches!(NBT::deserialize(list(3, -1)), Err(ParseError::NegativeLen(-1))));
+    // This shouldn't try to allocate 2 billion ints.
+    assert!(NBT::deserialize(list(3, i32::MAX)).is_err());
+    assert!(matches!(NBT::deserialize(list(0, i32::MAX)), Err(ParseError::InvalidType(0))));
+    assert!(NBT::deserialize(list(0, 0)).is_ok());
+
+    // A list of lists of lists, nested too deep.
+    let mut data = list(9, 1);
+    for _ in 0..MAX_DEPTH {
+      data.extend([9, 0, 0, 0, 1]);
+    }
+    assert!(matches!(NBT::deserialize(data), Err(ParseError::TooDeep)));
+
+    // Compound keys must be valid utf8.
+    assert!(matches!(
+      NBT::deserialize(vec![10, 0, 0, 1, 0, 1, 0xff, 5]),
+      Err(ParseError::InvalidString(_))
+    ));
+  }
 }
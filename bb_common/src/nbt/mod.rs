@@ -1,11 +1,15 @@
 #[cfg(feature = "host")]
 mod deserialize;
+mod pretty;
 #[cfg(feature = "host")]
 mod serde;
 mod serialize;
+mod snbt;
 
 #[cfg(feature = "host")]
-pub use self::serde::{to_nbt, to_tag};
+pub use self::serde::{from_nbt, from_tag, to_nbt, to_tag, IntArray, LongArray};
+pub use pretty::to_pretty_string;
+pub use snbt::SnbtError;
 
 use crate::util::BufferError;
 use std::{collections::HashMap, fmt, io, ops::Index, string::FromUtf8Error};
@@ -16,6 +20,8 @@ pub enum ParseError {
   InvalidString(FromUtf8Error),
   IO(io::Error),
   BufferError(BufferError),
+  NegativeLen(i32),
+  TooDeep,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -27,6 +33,11 @@ impl fmt::Display for WrongTag {
 
 impl std::error::Error for WrongTag {}
 
+impl WrongTag {
+  /// Creates an error for a tag that had the wrong type or contents.
+  pub fn new(tag: Tag) -> Self { WrongTag(tag) }
+}
+
 /// This is an nbt tag. It has a name, and any amount of data. This can be used
 /// to store item data, entity data, level data, and more.
 #[derive(Debug, Clone, PartialEq)]
@@ -240,6 +251,7 @@ impl Tag {
   getter!(byte_arr -> ByteArr(&[u8]));
   getter!(list -> List(&Vec<Tag>));
   getter!(compound -> Compound(&Compound));
+  getter!(int_arr -> IntArray(&Vec<i32>));
   getter!(long_arr -> LongArray(&Vec<i64>));
 
   pub fn compound_mut(&mut self) -> Result<&mut Compound, WrongTag> {
@@ -278,4 +290,35 @@ mod tests {
       NBT::new("hello world", Tag::new_compound(&[("name", Tag::String("Bananrama".into()))]))
     );
   }
+
+  #[test]
+  fn test_pretty() {
+    let nbt = NBT::new(
+      "root",
+      Tag::new_compound(&[
+        ("pos", Tag::List(vec![Tag::Double(1.5), Tag::Double(2.0)])),
+        ("id", Tag::String("minecraft:stone".into())),
+        ("nested", Tag::new_compound(&[("count", Tag::Byte(3))])),
+        ("data", Tag::IntArray(vec![1, 2])),
+      ]),
+    );
+    assert_eq!(
+      nbt.to_string(),
+      "TAG_Compound('root'): 4 entries
+{
+  TAG_Int_Array('data'): [2 ints] [1, 2]
+  TAG_String('id'): 'minecraft:stone'
+  TAG_Compound('nested'): 1 entry
+  {
+    TAG_Byte('count'): 3
+  }
+  TAG_List('pos'): 2 entries
+  {
+    TAG_Double(None): 1.5
+    TAG_Double(None): 2
+  }
+}"
+    );
+    assert_eq!(to_pretty_string(&Tag::Int(5)), "TAG_Int(None): 5");
+  }
 }
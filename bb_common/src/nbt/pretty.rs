@@ -0,0 +1,100 @@
+use super::{Tag, NBT};
+use std::fmt;
+
+/// Formats the given tag in a human readable form. This is the same format
+/// used by NBTExplorer and the original NBT spec, where every tag is printed
+/// with its type, and compounds/lists are indented. This is not meant to be
+/// parsed, and is only useful for debugging.
+///
+/// Compound keys are sorted, so the output is the same every time.
+///
+/// ```
+/// # use bb_common::nbt::{to_pretty_string, Tag};
+/// let tag = Tag::new_compound(&[("name", Tag::String("Bananrama".into()))]);
+/// assert_eq!(
+///   to_pretty_string(&tag),
+///   "TAG_Compound(None): 1 entry\n{\n  TAG_String('name'): 'Bananrama'\n}",
+/// );
+/// ```
+pub fn to_pretty_string(tag: &Tag) -> String { tag.to_string() }
+
+impl fmt::Display for Tag {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write_tag(f, None, self, 0) }
+}
+
+impl fmt::Display for NBT {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write_tag(f, Some(&self.name), &self.tag, 0)
+  }
+}
+
+fn entries(len: usize) -> &'static str {
+  if len == 1 {
+    "entry"
+  } else {
+    "entries"
+  }
+}
+
+fn write_tag(f: &mut fmt::Formatter, name: Option<&str>, tag: &Tag, indent: usize) -> fmt::Result {
+  write!(f, "{:indent$}{}", "", tag_name(tag), indent = indent * 2)?;
+  match name {
+    Some(name) => write!(f, "('{name}'): ")?,
+    None => write!(f, "(None): ")?,
+  }
+  match tag {
+    Tag::End => Ok(()),
+    Tag::Byte(v) => write!(f, "{v}"),
+    Tag::Short(v) => write!(f, "{v}"),
+    Tag::Int(v) => write!(f, "{v}"),
+    Tag::Long(v) => write!(f, "{v}"),
+    Tag::Float(v) => write!(f, "{v}"),
+    Tag::Double(v) => write!(f, "{v}"),
+    Tag::String(v) => write!(f, "'{v}'"),
+    Tag::ByteArr(v) => write!(f, "[{} bytes] {v:?}", v.len()),
+    Tag::IntArray(v) => write!(f, "[{} ints] {v:?}", v.len()),
+    Tag::LongArray(v) => write!(f, "[{} longs] {v:?}", v.len()),
+    Tag::List(v) => {
+      write!(f, "{} {}", v.len(), entries(v.len()))?;
+      writeln!(f)?;
+      write!(f, "{:indent$}{{", "", indent = indent * 2)?;
+      for tag in v {
+        writeln!(f)?;
+        write_tag(f, None, tag, indent + 1)?;
+      }
+      writeln!(f)?;
+      write!(f, "{:indent$}}}", "", indent = indent * 2)
+    }
+    Tag::Compound(v) => {
+      write!(f, "{} {}", v.inner.len(), entries(v.inner.len()))?;
+      writeln!(f)?;
+      write!(f, "{:indent$}{{", "", indent = indent * 2)?;
+      let mut keys: Vec<_> = v.inner.keys().collect();
+      keys.sort();
+      for key in keys {
+        writeln!(f)?;
+        write_tag(f, Some(key), &v.inner[key], indent + 1)?;
+      }
+      writeln!(f)?;
+      write!(f, "{:indent$}}}", "", indent = indent * 2)
+    }
+  }
+}
+
+fn tag_name(tag: &Tag) -> &'static str {
+  match tag {
+    Tag::End => "TAG_End",
+    Tag::Byte(_) => "TAG_Byte",
+    Tag::Short(_) => "TAG_Short",
+    Tag::Int(_) => "TAG_Int",
+    Tag::Long(_) => "TAG_Long",
+    Tag::Float(_) => "TAG_Float",
+    Tag::Double(_) => "TAG_Double",
+    Tag::ByteArr(_) => "TAG_Byte_Array",
+    Tag::String(_) => "TAG_String",
+    Tag::List(_) => "TAG_List",
+    Tag::Compound(_) => "TAG_Compound",
+    Tag::IntArray(_) => "TAG_Int_Array",
+    Tag::LongArray(_) => "TAG_Long_Array",
+  }
+}
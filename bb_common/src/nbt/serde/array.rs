@@ -0,0 +1,74 @@
+use super::super::Tag;
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// The newtype struct names used to mark arrays. The NBT serializer looks for
+/// these, and writes an array tag instead of a list. Other serializers will
+/// just see a newtype around a `Vec`.
+pub(super) const INT_ARRAY: &str = "__bb_nbt_int_array";
+pub(super) const LONG_ARRAY: &str = "__bb_nbt_long_array";
+
+/// A list of ints, which is serialized as `TAG_Int_Array` instead of a
+/// `TAG_List` of ints. A plain `Vec<i32>` will always become a list.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IntArray(pub Vec<i32>);
+
+/// A list of longs, which is serialized as `TAG_Long_Array` instead of a
+/// `TAG_List` of longs. This is used for things like heightmaps.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LongArray(pub Vec<i64>);
+
+macro_rules! array {
+  ( $name:ident, $marker:ident, $variant:ident, $expecting:expr ) => {
+    impl From<$name> for Tag {
+      fn from(v: $name) -> Self { Tag::$variant(v.0) }
+    }
+
+    impl Serialize for $name {
+      fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct($marker, &self.0)
+      }
+    }
+
+    impl<'de> Deserialize<'de> for $name {
+      fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ArrayVisitor;
+        impl<'de> de::Visitor<'de> for ArrayVisitor {
+          type Value = $name;
+
+          fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result { f.write_str($expecting) }
+
+          fn visit_newtype_struct<D: Deserializer<'de>>(self, d: D) -> Result<$name, D::Error> {
+            Ok($name(Vec::deserialize(d)?))
+          }
+          // For formats that don't keep newtype structs.
+          fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<$name, A::Error> {
+            Ok($name(Vec::deserialize(de::value::SeqAccessDeserializer::new(seq))?))
+          }
+        }
+        deserializer.deserialize_newtype_struct($marker, ArrayVisitor)
+      }
+    }
+  };
+}
+
+array!(IntArray, INT_ARRAY, IntArray, "an array of ints");
+array!(LongArray, LONG_ARRAY, LongArray, "an array of longs");
+
+/// Converts the list produced by serializing the inner `Vec` of an array into
+/// an array tag. `marker` is the newtype struct name, which must be
+/// `INT_ARRAY` or `LONG_ARRAY`.
+pub(super) fn list_to_array<E: ser::Error>(marker: &str, tag: Tag) -> Result<Tag, E> {
+  let items = match tag {
+    Tag::List(items) => items,
+    other => return Err(E::custom(format!("expected a list in {marker}, got {other:?}"))),
+  };
+  let wrong = |t: &Tag| E::custom(format!("wrong element type in {marker}: {t:?}"));
+  if marker == INT_ARRAY {
+    let values = items.iter().map(|t| t.int().map_err(|_| wrong(t)));
+    Ok(Tag::IntArray(values.collect::<Result<_, _>>()?))
+  } else {
+    let values = items.iter().map(|t| t.long().map_err(|_| wrong(t)));
+    Ok(Tag::LongArray(values.collect::<Result<_, _>>()?))
+  }
+}
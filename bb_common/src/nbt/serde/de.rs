@@ -1 +1,286 @@
+use super::{
+  super::{Compound, Tag, NBT},
+  error::{Error, Result},
+};
+use serde::{
+  de,
+  de::{
+    value::{BorrowedStrDeserializer, SeqDeserializer},
+    Visitor,
+  },
+  forward_to_deserialize_any, Deserialize,
+};
+use std::{collections::hash_map, slice};
 
+/// Deserializes from a borrowed tag. All of the strings and byte arrays can be
+/// borrowed from the tag, so this never needs to copy them.
+pub struct Deserializer<'de> {
+  tag: &'de Tag,
+}
+
+struct ListAccess<'de> {
+  iter: slice::Iter<'de, Tag>,
+}
+struct CompoundAccess<'de> {
+  iter:  hash_map::Iter<'de, String, Tag>,
+  value: Option<&'de Tag>,
+}
+
+/// Deserializes a value from the tag in `nbt`. The name is ignored.
+pub fn from_nbt<'de, T>(nbt: &'de NBT) -> Result<T>
+where
+  T: Deserialize<'de>,
+{
+  from_tag(nbt.tag())
+}
+
+/// Deserializes a value from the given tag. Lists are read into sequences, so
+/// a list of any type (including an empty list, which has no element type)
+/// can be read into a `Vec<T>`, as long as every element can be read as a
+/// `T`.
+pub fn from_tag<'de, T>(tag: &'de Tag) -> Result<T>
+where
+  T: Deserialize<'de>,
+{
+  T::deserialize(Deserializer { tag })
+}
+
+fn visit_compound<'de, V>(c: &'de Compound, visitor: V) -> Result<V::Value>
+where
+  V: Visitor<'de>,
+{
+  visitor.visit_map(CompoundAccess { iter: c.inner.iter(), value: None })
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+  type Error = Error;
+
+  // NBT is self-describing, so the tag type decides what we give to the
+  // visitor. If that isn't what the visitor expected, it will produce an error.
+  fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    match self.tag {
+      Tag::End => visitor.visit_unit(),
+      Tag::Byte(v) => visitor.visit_i8(*v),
+      Tag::Short(v) => visitor.visit_i16(*v),
+      Tag::Int(v) => visitor.visit_i32(*v),
+      Tag::Long(v) => visitor.visit_i64(*v),
+      Tag::Float(v) => visitor.visit_f32(*v),
+      Tag::Double(v) => visitor.visit_f64(*v),
+      Tag::ByteArr(v) => visitor.visit_seq(SeqDeserializer::new(v.iter().copied())),
+      Tag::String(v) => visitor.visit_borrowed_str(v),
+      Tag::List(v) => visitor.visit_seq(ListAccess { iter: v.iter() }),
+      Tag::Compound(v) => visit_compound(v, visitor),
+      Tag::IntArray(v) => visitor.visit_seq(SeqDeserializer::new(v.iter().copied())),
+      Tag::LongArray(v) => visitor.visit_seq(SeqDeserializer::new(v.iter().copied())),
+    }
+  }
+
+  // Bools are stored as bytes.
+  fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    match self.tag {
+      Tag::Byte(v) => visitor.visit_bool(*v != 0),
+      _ => self.deserialize_any(visitor),
+    }
+  }
+
+  fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    match self.tag {
+      Tag::ByteArr(v) => visitor.visit_borrowed_bytes(v),
+      _ => self.deserialize_any(visitor),
+    }
+  }
+  fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    self.deserialize_bytes(visitor)
+  }
+
+  // `None` can't be serialized, so anything that is present is `Some`. Missing
+  // fields are handled by serde.
+  fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    visitor.visit_some(self)
+  }
+
+  fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    visitor.visit_newtype_struct(self)
+  }
+
+  // We don't support enums, just like the serializer.
+  fn deserialize_enum<V>(
+    self,
+    _name: &'static str,
+    _variants: &'static [&'static str],
+    _visitor: V,
+  ) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    Err(Error::Enum)
+  }
+
+  fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+  where
+    V: Visitor<'de>,
+  {
+    visitor.visit_unit()
+  }
+
+  forward_to_deserialize_any! {
+    i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+    unit unit_struct seq tuple tuple_struct map struct identifier
+  }
+}
+
+impl<'de> de::SeqAccess<'de> for ListAccess<'de> {
+  type Error = Error;
+
+  fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+  where
+    T: de::DeserializeSeed<'de>,
+  {
+    match self.iter.next() {
+      Some(tag) => seed.deserialize(Deserializer { tag }).map(Some),
+      None => Ok(None),
+    }
+  }
+
+  fn size_hint(&self) -> Option<usize> { Some(self.iter.len()) }
+}
+
+impl<'de> de::MapAccess<'de> for CompoundAccess<'de> {
+  type Error = Error;
+
+  fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+  where
+    K: de::DeserializeSeed<'de>,
+  {
+    match self.iter.next() {
+      Some((key, value)) => {
+        self.value = Some(value);
+        seed.deserialize(BorrowedStrDeserializer::new(key)).map(Some)
+      }
+      None => Ok(None),
+    }
+  }
+
+  fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+  where
+    V: de::DeserializeSeed<'de>,
+  {
+    // Serde always calls `next_key_seed` first.
+    let tag = self.value.take().expect("next_value_seed called before next_key_seed");
+    seed.deserialize(Deserializer { tag })
+  }
+
+  fn size_hint(&self) -> Option<usize> { Some(self.iter.len()) }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+use super::to_tag;
+#[cfg(test)]
+use serde::Serialize;
+
+#[test]
+fn test_primitives() {
+  assert_eq!(from_tag::<i32>(&Tag::Int(5)).unwrap(), 5);
+  assert_eq!(from_tag::<u8>(&Tag::Byte(5)).unwrap(), 5);
+  assert!(from_tag::<bool>(&Tag::Byte(1)).unwrap());
+  assert_eq!(from_tag::<&str>(&Tag::String("hello".into())).unwrap(), "hello");
+  assert_eq!(from_tag::<Vec<i64>>(&Tag::LongArray(vec![1, 2])).unwrap(), vec![1, 2]);
+  assert!(from_tag::<i32>(&Tag::String("5".into())).is_err());
+}
+
+#[test]
+fn test_list_round_trip() {
+  #[derive(Debug, PartialEq, Serialize, Deserialize)]
+  struct Item {
+    id:    String,
+    count: u8,
+  }
+  #[derive(Debug, PartialEq, Serialize, Deserialize)]
+  struct Inventory {
+    items: Vec<Item>,
+    empty: Vec<i32>,
+  }
+
+  let inv = Inventory {
+    items: vec![
+      Item { id: "minecraft:stone".into(), count: 64 },
+      Item { id: "minecraft:dirt".into(), count: 3 },
+    ],
+    empty: vec![],
+  };
+  let tag = to_tag(&inv).unwrap();
+  assert_eq!(
+    tag.compound().unwrap()["items"],
+    Tag::List(vec![
+      Tag::new_compound(&[("id", "minecraft:stone".into()), ("count", Tag::Byte(64))]),
+      Tag::new_compound(&[("id", "minecraft:dirt".into()), ("count", Tag::Byte(3))]),
+    ])
+  );
+  assert_eq!(from_tag::<Inventory>(&tag).unwrap(), inv);
+
+  // An empty list is written with an element type of `End`, so this makes
+  // sure it can still be read as a list of ints.
+  let nbt = NBT::deserialize(NBT::new("", tag).serialize()).unwrap();
+  assert_eq!(from_nbt::<Inventory>(&nbt).unwrap(), inv);
+}
+
+#[test]
+fn test_mixed_list() {
+  assert_eq!(to_tag(&(1, "a")).unwrap_err(), Error::ListType(Tag::Int(1), Tag::String("a".into())));
+}
+
+#[test]
+fn test_arrays() {
+  use super::{IntArray, LongArray};
+
+  #[derive(Debug, PartialEq, Serialize, Deserialize)]
+  struct Heightmaps {
+    #[serde(rename = "MOTION_BLOCKING")]
+    motion_blocking: LongArray,
+    ints:            IntArray,
+    list:            Vec<i64>,
+  }
+
+  let maps = Heightmaps {
+    motion_blocking: LongArray(vec![1, -2, i64::MAX]),
+    ints:            IntArray(vec![]),
+    list:            vec![3, 4],
+  };
+  let tag = to_tag(&maps).unwrap();
+  let compound = tag.compound().unwrap();
+  assert_eq!(compound["MOTION_BLOCKING"], Tag::LongArray(vec![1, -2, i64::MAX]));
+  assert_eq!(compound["ints"], Tag::IntArray(vec![]));
+  assert_eq!(compound["list"], Tag::List(vec![Tag::Long(3), Tag::Long(4)]));
+
+  // The tag ids on the wire should be 12 and 11, not 9.
+  let data = super::to_nbt("", &LongArray(vec![5])).unwrap().serialize();
+  assert_eq!(data, [12, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 5]);
+  let nbt = NBT::deserialize(data).unwrap();
+  assert_eq!(nbt.tag().ty(), 12);
+  assert_eq!(from_nbt::<LongArray>(&nbt).unwrap(), LongArray(vec![5]));
+  let data = super::to_nbt("", &IntArray(vec![5])).unwrap().serialize();
+  assert_eq!(data[0], 11);
+
+  let nbt = NBT::deserialize(NBT::new("", tag).serialize()).unwrap();
+  assert_eq!(from_nbt::<Heightmaps>(&nbt).unwrap(), maps);
+}
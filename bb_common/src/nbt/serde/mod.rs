@@ -1,7 +1,10 @@
 //! Serde bindings for NBT data.
 
+mod array;
 mod de;
 mod error;
 mod ser;
 
+pub use array::{IntArray, LongArray};
+pub use de::{from_nbt, from_tag};
 pub use ser::{to_nbt, to_tag};
@@ -1,5 +1,6 @@
 use super::{
   super::{Tag, NBT},
+  array::{list_to_array, INT_ARRAY, LONG_ARRAY},
   error::{Error, Result},
 };
 use serde::{ser, Serialize};
@@ -137,12 +138,18 @@ impl<'a> ser::Serializer for &'a mut Serializer {
   fn serialize_unit_struct(self, _name: &'static str) -> Result<()> { self.serialize_unit() }
 
   // As is done here, serializers are encouraged to treat newtype structs as
-  // insignificant wrappers around the data they contain.
-  fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+  // insignificant wrappers around the data they contain. The only exception is
+  // `IntArray` and `LongArray`, which need to produce array tags.
+  fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<()>
   where
     T: ?Sized + Serialize,
   {
-    value.serialize(self)
+    value.serialize(&mut *self)?;
+    if name == INT_ARRAY || name == LONG_ARRAY {
+      let tag = std::mem::replace(&mut self.tag, Tag::End);
+      self.tag = list_to_array(name, tag)?;
+    }
+    Ok(())
   }
 
   // Now we get to the serialization of compound types.
@@ -0,0 +1,363 @@
+use super::{Compound, Tag};
+use std::{error::Error, fmt};
+
+/// An error while parsing stringified NBT. `pos` is the byte index into the
+/// text where the error happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnbtError {
+  pub pos:      usize,
+  pub expected: &'static str,
+}
+
+impl fmt::Display for SnbtError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "expected {} at {}", self.expected, self.pos)
+  }
+}
+
+impl Error for SnbtError {}
+
+impl Tag {
+  /// Parses the given stringified NBT. This is the format used in commands,
+  /// like `{display:{Name:'"Sword"'},Unbreakable:1b}`. The entire string must
+  /// be a single tag, surrounded by optional whitespace.
+  ///
+  /// ```
+  /// # use bb_common::nbt::Tag;
+  /// assert_eq!(Tag::from_snbt("{count: 3b}").unwrap(), Tag::new_compound(&[("count", Tag::Byte(3))]));
+  /// ```
+  pub fn from_snbt(text: &str) -> Result<Tag, SnbtError> {
+    let (tag, len) = Tag::from_snbt_prefix(text)?;
+    let mut p = SnbtParser { text, pos: len, depth: 0 };
+    p.skip_whitespace();
+    if p.pos != text.len() {
+      return Err(p.err("the end of the tag"));
+    }
+    Ok(tag)
+  }
+
+  /// Parses a single stringified NBT tag from the start of `text`. Returns the
+  /// tag, and the number of bytes that were read. Anything after the tag is
+  /// ignored, which is useful when the tag is part of a larger command.
+  pub fn from_snbt_prefix(text: &str) -> Result<(Tag, usize), SnbtError> {
+    let mut p = SnbtParser { text, pos: 0, depth: 0 };
+    p.skip_whitespace();
+    let tag = p.read_value()?;
+    Ok((tag, p.pos))
+  }
+}
+
+/// The maximum number of nested compounds and lists. This is the same limit
+/// vanilla uses, and it stops deeply nested input from overflowing the stack.
+const MAX_DEPTH: usize = 512;
+
+struct SnbtParser<'a> {
+  text:  &'a str,
+  pos:   usize,
+  /// The number of compounds and lists we are currently inside of.
+  depth: usize,
+}
+
+fn is_unquoted(c: char) -> bool { c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+') }
+
+impl<'a> SnbtParser<'a> {
+  fn err(&self, expected: &'static str) -> SnbtError { SnbtError { pos: self.pos, expected } }
+
+  fn peek(&self) -> Option<char> { self.text[self.pos..].chars().next() }
+  fn next(&mut self) -> Option<char> {
+    let c = self.peek()?;
+    self.pos += c.len_utf8();
+    Some(c)
+  }
+  fn skip_whitespace(&mut self) {
+    while self.peek().map(|c| c.is_whitespace()).unwrap_or(false) {
+      self.next();
+    }
+  }
+  fn expect(&mut self, c: char, expected: &'static str) -> Result<(), SnbtError> {
+    self.skip_whitespace();
+    if self.peek() == Some(c) {
+      self.next();
+      Ok(())
+    } else {
+      Err(self.err(expected))
+    }
+  }
+
+  fn read_value(&mut self) -> Result<Tag, SnbtError> {
+    self.skip_whitespace();
+    match self.peek() {
+      Some(c @ ('{' | '[')) => {
+        if self.depth >= MAX_DEPTH {
+          return Err(self.err("a less deeply nested tag"));
+        }
+        self.depth += 1;
+        let res = if c == '{' { self.read_compound() } else { self.read_list() };
+        self.depth -= 1;
+        res
+      }
+      Some('"' | '\'') => Ok(Tag::String(self.read_quoted()?)),
+      Some(c) if is_unquoted(c) => {
+        let text = self.read_unquoted();
+        Ok(parse_unquoted(text))
+      }
+      _ => Err(self.err("a value")),
+    }
+  }
+
+  fn read_compound(&mut self) -> Result<Tag, SnbtError> {
+    self.expect('{', "`{`")?;
+    let mut compound = Compound::new();
+    self.skip_whitespace();
+    if self.peek() == Some('}') {
+      self.next();
+      return Ok(Tag::Compound(compound));
+    }
+    loop {
+      self.skip_whitespace();
+      let key = match self.peek() {
+        Some('"' | '\'') => self.read_quoted()?,
+        Some(c) if is_unquoted(c) => self.read_unquoted().into(),
+        _ => return Err(self.err("a key")),
+      };
+      self.expect(':', "`:`")?;
+      let value = self.read_value()?;
+      compound.insert(key, value);
+      self.skip_whitespace();
+      match self.peek() {
+        Some(',') => {}
+        Some('}') => {
+          self.next();
+          return Ok(Tag::Compound(compound));
+        }
+        _ => return Err(self.err("`,` or `}`")),
+      }
+      self.next();
+    }
+  }
+
+  fn read_list(&mut self) -> Result<Tag, SnbtError> {
+    self.expect('[', "`[`")?;
+    let rest = &self.text[self.pos..];
+    for prefix in ["B;", "I;", "L;"] {
+      if rest.starts_with(prefix) {
+        self.pos += 2;
+        return self.read_array(prefix.as_bytes()[0]);
+      }
+    }
+    let mut items = vec![];
+    self.skip_whitespace();
+    if self.peek() == Some(']') {
+      self.next();
+      return Ok(Tag::List(items));
+    }
+    loop {
+      self.skip_whitespace();
+      let start = self.pos;
+      let item = self.read_value()?;
+      if let Some(first) = items.first() {
+        if std::mem::discriminant(first) != std::mem::discriminant(&item) {
+          self.pos = start;
+          return Err(self.err("a value with the same type as the rest of the list"));
+        }
+      }
+      items.push(item);
+      self.skip_whitespace();
+      match self.peek() {
+        Some(',') => {}
+        Some(']') => {
+          self.next();
+          return Ok(Tag::List(items));
+        }
+        _ => return Err(self.err("`,` or `]`")),
+      }
+      self.next();
+    }
+  }
+
+  fn read_array(&mut self, ty: u8) -> Result<Tag, SnbtError> {
+    let mut values = vec![];
+    self.skip_whitespace();
+    if self.peek() != Some(']') {
+      loop {
+        self.skip_whitespace();
+        let start = self.pos;
+        let text = self.read_unquoted();
+        let text = match ty {
+          b'B' => text.strip_suffix(['b', 'B']),
+          b'L' => text.strip_suffix(['l', 'L']),
+          _ => Some(text),
+        }
+        .unwrap_or(text);
+        let v = match text.parse::<i64>() {
+          Ok(v) => v,
+          Err(_) => {
+            self.pos = start;
+            return Err(self.err("a number"));
+          }
+        };
+        let in_range = match ty {
+          b'B' => i8::try_from(v).is_ok(),
+          b'I' => i32::try_from(v).is_ok(),
+          _ => true,
+        };
+        if !in_range {
+          self.pos = start;
+          return Err(self.err("a number within the range of the array type"));
+        }
+        values.push(v);
+        self.skip_whitespace();
+        match self.peek() {
+          Some(',') => {
+            self.next();
+          }
+          _ => break,
+        }
+      }
+    }
+    self.expect(']', "`,` or `]`")?;
+    Ok(match ty {
+      // Values were range checked above, so these casts never truncate.
+      b'B' => Tag::ByteArr(values.into_iter().map(|v| v as i8 as u8).collect()),
+      b'I' => Tag::IntArray(values.into_iter().map(|v| v as i32).collect()),
+      _ => Tag::LongArray(values),
+    })
+  }
+
+  fn read_quoted(&mut self) -> Result<String, SnbtError> {
+    let quote = self.next().unwrap();
+    let mut out = String::new();
+    loop {
+      match self.next() {
+        Some('\\') => match self.peek() {
+          Some(c) if c == '\\' || c == quote => {
+            self.next();
+            out.push(c);
+          }
+          _ => return Err(self.err("a valid escape sequence")),
+        },
+        Some(c) if c == quote => return Ok(out),
+        Some(c) => out.push(c),
+        None => return Err(self.err("a closing quote")),
+      }
+    }
+  }
+
+  fn read_unquoted(&mut self) -> &'a str {
+    let start = self.pos;
+    while self.peek().map(is_unquoted).unwrap_or(false) {
+      self.next();
+    }
+    &self.text[start..self.pos]
+  }
+}
+
+/// Parses an unquoted value. Numbers with a suffix are converted to that type,
+/// and numbers without one are ints or doubles. `true` and `false` are bytes.
+/// Anything else is a string.
+fn parse_unquoted(text: &str) -> Tag {
+  match text {
+    "true" => return Tag::Byte(1),
+    "false" => return Tag::Byte(0),
+    _ => {}
+  }
+  let (num, suffix) = match text.chars().last() {
+    Some(c @ ('b' | 'B' | 's' | 'S' | 'l' | 'L' | 'f' | 'F' | 'd' | 'D')) => {
+      (&text[..text.len() - 1], Some(c.to_ascii_lowercase()))
+    }
+    _ => (text, None),
+  };
+  let parsed = match suffix {
+    Some('b') => num.parse().ok().map(Tag::Byte),
+    Some('s') => num.parse().ok().map(Tag::Short),
+    Some('l') => num.parse().ok().map(Tag::Long),
+    Some('f') => num.parse().ok().map(Tag::Float),
+    Some('d') => num.parse().ok().map(Tag::Double),
+    _ => num.parse().ok().map(Tag::Int).or_else(|| {
+      // Things like `inf` and `NaN` would parse as floats, so we only allow
+      // digits here.
+      if num.contains(|c: char| c.is_ascii_digit()) && !num.contains(char::is_alphabetic) {
+        num.parse().ok().map(Tag::Double)
+      } else {
+        None
+      }
+    }),
+  };
+  parsed.unwrap_or_else(|| Tag::String(text.into()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_values() {
+    assert_eq!(Tag::from_snbt("3b"), Ok(Tag::Byte(3)));
+    assert_eq!(Tag::from_snbt("-3s"), Ok(Tag::Short(-3)));
+    assert_eq!(Tag::from_snbt("3"), Ok(Tag::Int(3)));
+    assert_eq!(Tag::from_snbt("3L"), Ok(Tag::Long(3)));
+    assert_eq!(Tag::from_snbt("1.5f"), Ok(Tag::Float(1.5)));
+    assert_eq!(Tag::from_snbt("1.5"), Ok(Tag::Double(1.5)));
+    assert_eq!(Tag::from_snbt("2d"), Ok(Tag::Double(2.0)));
+    assert_eq!(Tag::from_snbt("true"), Ok(Tag::Byte(1)));
+    assert_eq!(Tag::from_snbt("stone"), Ok(Tag::String("stone".into())));
+    assert_eq!(Tag::from_snbt("inf"), Ok(Tag::String("inf".into())));
+    assert_eq!(Tag::from_snbt(r#""a \"b\"""#), Ok(Tag::String(r#"a "b""#.into())));
+    assert_eq!(Tag::from_snbt(r#"'{"text":"hi"}'"#), Ok(Tag::String(r#"{"text":"hi"}"#.into())));
+  }
+
+  #[test]
+  fn test_nested() {
+    assert_eq!(
+      Tag::from_snbt("{display: {Name: 'Sword', Lore: ['a', 'b']}, Unbreakable: 1b}"),
+      Ok(Tag::new_compound(&[
+        (
+          "display",
+          Tag::new_compound(&[
+            ("Name", Tag::String("Sword".into())),
+            ("Lore", Tag::List(vec![Tag::String("a".into()), Tag::String("b".into())])),
+          ])
+        ),
+        ("Unbreakable", Tag::Byte(1)),
+      ]))
+    );
+    assert_eq!(Tag::from_snbt("[]"), Ok(Tag::List(vec![])));
+    assert_eq!(Tag::from_snbt("{}"), Ok(Tag::new_compound(&[])));
+    assert_eq!(Tag::from_snbt("[I; 1, 2, 3]"), Ok(Tag::IntArray(vec![1, 2, 3])));
+    assert_eq!(Tag::from_snbt("[L;1L,2L]"), Ok(Tag::LongArray(vec![1, 2])));
+    assert_eq!(Tag::from_snbt("[B;1b]"), Ok(Tag::ByteArr(vec![1])));
+  }
+
+  #[test]
+  fn test_errors() {
+    assert_eq!(Tag::from_snbt("{a:1"), Err(SnbtError { pos: 4, expected: "`,` or `}`" }));
+    assert_eq!(Tag::from_snbt("{:1}"), Err(SnbtError { pos: 1, expected: "a key" }));
+    assert_eq!(
+      Tag::from_snbt("[1, a]"),
+      Err(SnbtError {
+        pos:      4,
+        expected: "a value with the same type as the rest of the list",
+      })
+    );
+    assert_eq!(Tag::from_snbt("'abc"), Err(SnbtError { pos: 4, expected: "a closing quote" }));
+    assert_eq!(Tag::from_snbt("{} 5"), Err(SnbtError { pos: 3, expected: "the end of the tag" }));
+    assert_eq!(
+      Tag::from_snbt_prefix("{a: 1} 5"),
+      Ok((Tag::new_compound(&[("a", Tag::Int(1))]), 6))
+    );
+    assert_eq!(Tag::from_snbt("[B;-1b]"), Ok(Tag::ByteArr(vec![255])));
+    assert_eq!(
+      Tag::from_snbt("[B;1b, 300b]"),
+      Err(SnbtError { pos: 7, expected: "a number within the range of the array type" })
+    );
+    assert_eq!(
+      Tag::from_snbt("[I;3000000000]"),
+      Err(SnbtError { pos: 3, expected: "a number within the range of the array type" })
+    );
+    let deep = "[".repeat(MAX_DEPTH + 1);
+    assert_eq!(
+      Tag::from_snbt(&deep),
+      Err(SnbtError { pos: MAX_DEPTH, expected: "a less deeply nested tag" })
+    );
+  }
+}
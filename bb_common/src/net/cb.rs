@@ -1,8 +1,8 @@
 use crate::{
-  chunk::{paletted::Section, LightChunk},
+  chunk::{paletted::Section, Heightmaps, LightChunk},
   math::{ChunkPos, FPos, Pos},
   metadata::Metadata,
-  util::{chat::Color, Chat, GameMode, Hand, Item, SwitchMode, UUID},
+  util::{chat::Color, Chat, Dimension, Effect, GameMode, Hand, Item, SwitchMode, UUID},
 };
 use bb_macros::Transfer;
 use std::{collections::HashMap, net::SocketAddr};
@@ -28,6 +28,17 @@ macro_rules! packets {
       )*
     }
 
+    impl Packet {
+      /// Returns the name of this packet's variant, such as `"KeepAlive"`.
+      pub fn name(&self) -> &'static str {
+        match self {
+          $(
+            Packet::$name(_) => stringify!($name),
+          )*
+        }
+      }
+    }
+
     pub mod packet {
       use super::*;
       $(
@@ -91,6 +102,8 @@ packets! {
     sections:    Vec<Option<Section>>,
     sky_light:   Option<LightChunk>,
     block_light: LightChunk,
+    /// The heightmaps of the whole chunk, even if this isn't a full chunk.
+    heightmaps:  Heightmaps,
   },
   #[id = 4]
   CommandList {
@@ -100,6 +113,14 @@ packets! {
   },
   #[id = 31]
   CollectItem { item_eid: i32, player_eid: i32, amount: u8 },
+  /// Kicks the client, and shows them `reason`.
+  #[id = 52]
+  Disconnect { reason: Chat },
+  /// Adds a status effect to an entity. `duration` is in ticks. If
+  /// `particles` is false, the effect won't show any particles around the
+  /// entity.
+  #[id = 49]
+  EntityEffect { eid: i32, effect: Effect, amplifier: u8, duration: i32, particles: bool },
   /// An entity's armor/holding item
   #[id = 33]
   EntityEquipment { eid: i32, slot: EquipmentSlot, item: Item },
@@ -145,10 +166,12 @@ packets! {
     eid:                   i32,
     hardcore_mode:         bool,
     game_mode:             GameMode,
-    dimension:             i8,
+    dimension:             Dimension,
     level_type:            String,
     difficulty:            u8,
     view_distance:         u16,
+    /// Only applies to 1.18+ clients. Ignored for older clients.
+    simulation_distance:   u16,
     reduced_debug_info:    bool,
     /// Only applies to 1.16+ clients. Ignored for older clients.
     enable_respawn_screen: bool,
@@ -203,15 +226,31 @@ packets! {
   },
   #[id = 15]
   PluginMessage { channel: String, data: Vec<u8> },
+  /// Sends `data` to the client as a packet with the given id, without any
+  /// conversion. Both the id and the data must be valid for the client's
+  /// version. This can be used for packets the proxy doesn't support yet.
+  #[id = 51]
+  Raw { id: i32, data: Vec<u8> },
   #[id = 30]
   RemoveEntities { eids: Vec<i32> },
+  /// Removes a status effect from an entity.
+  #[id = 50]
+  RemoveEntityEffect { eid: i32, effect: Effect },
+  /// Prompts the client to download a resource pack. `forced` and `prompt` are
+  /// only used on 1.17+ clients.
+  #[id = 46]
+  ResourcePack { url: String, hash: String, forced: bool, prompt: Option<Chat> },
   #[id = 44]
   Respawn {
-    game_mode:  GameMode,
-    dimension:  i8,
-    level_type: String,
-    difficulty: u8,
-    reset_meta: bool,
+    game_mode:    GameMode,
+    dimension:    Dimension,
+    level_type:   String,
+    difficulty:   u8,
+    reset_meta:   bool,
+    /// Only applies to 1.18+ clients. Ignored for older clients.
+    world_height: u32,
+    /// Only applies to 1.18+ clients. Ignored for older clients.
+    world_min_y:  i32,
   },
   #[id = 25]
   ScoreboardDisplay {
@@ -232,6 +271,10 @@ packets! {
     #[must_exist]
     action:    ScoreboardAction,
   },
+  /// Sets the experience bar and level shown above the hotbar. `bar` is the
+  /// progress to the next level, from 0 to 1.
+  #[id = 48]
+  SetExperience { bar: f32, level: i32, total: i32 },
   #[id = 16]
   SetPosLook {
     pos:             FPos,
@@ -285,6 +328,9 @@ packets! {
     pitch: i8,
     meta:  Metadata,
   },
+  /// Sets the world spawn point. Compasses point to this position.
+  #[id = 47]
+  SpawnPosition { pos: Pos },
   /// A special packet. This will cause the proxy to start moving this player to
   /// a new server. If the new server accepts the connection, the proxy will
   /// simply disconnect the player from the old server. If the connection
@@ -328,6 +374,10 @@ packets! {
   /// the inventory packets.
   #[id = 24]
   WindowItem { wid: u8, slot: i32, item: Item },
+  /// Sets a property in a window, such as a furnace's progress bar, or an
+  /// anvil's repair cost. The meaning of `property` depends on the window type.
+  #[id = 45]
+  WindowProperty { wid: u8, property: i16, value: i16 },
 }
 
 #[derive(Transfer, Debug, Clone, PartialEq)]
@@ -464,6 +514,10 @@ pub enum TitleAction {
   /// times will show the same title again.
   #[id = 3]
   Clear(bool),
+  /// Shows a message above the player's hotbar. On versions without a
+  /// dedicated packet for this, it will be sent as a chat message.
+  #[id = 4]
+  ActionBar(Chat),
 }
 
 #[derive(Transfer, Debug, Clone, PartialEq)]
@@ -19,6 +19,8 @@ pub enum Packet {
   ChangeHeldItem { slot: u8 },
   #[id = 4]
   Chat { msg: String },
+  #[id = 20]
+  ClientStatus { status: ClientStatus },
   #[id = 13]
   ClickWindow {
     wid:  u8,
@@ -47,8 +49,14 @@ pub enum Packet {
     pitch:     f32,
     on_ground: bool,
   },
+  /// Sent when the client types into the text box of an anvil.
+  #[id = 18]
+  NameItem { name: String },
   #[id = 11]
   PluginMessage { channel: String, data: Vec<u8> },
+  /// Sent by the client in response to a `ResourcePack` packet.
+  #[id = 19]
+  ResourcePackStatus { status: ResourcePackStatus },
   #[id = 12]
   UseItem { hand: Hand },
   /// Sneaking will not be present on 1.8-1.15 clients. It should be used if it
@@ -70,6 +78,31 @@ pub enum DigStatus {
   Finish,
 }
 
+/// Sent by the client when it wants to respawn, or when it opens the statistics
+/// menu.
+#[derive(Transfer, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientStatus {
+  #[id = 0]
+  Respawn,
+  #[id = 1]
+  RequestStats,
+}
+
+/// The response from a client after being sent a resource pack. A client will
+/// send `Accepted` first, and then either `Loaded` or `Failed` once the
+/// download finishes.
+#[derive(Transfer, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourcePackStatus {
+  #[id = 0]
+  Loaded,
+  #[id = 1]
+  Declined,
+  #[id = 2]
+  Failed,
+  #[id = 3]
+  Accepted,
+}
+
 #[derive(Transfer, Debug, Clone, PartialEq)]
 pub enum UseEntityAction {
   #[id = 0]
@@ -103,6 +136,12 @@ impl Default for UseEntityAction {
 impl Default for PlayerCommand {
   fn default() -> Self { PlayerCommand::StartSneak }
 }
+impl Default for ClientStatus {
+  fn default() -> Self { ClientStatus::Respawn }
+}
+impl Default for ResourcePackStatus {
+  fn default() -> Self { ResourcePackStatus::Loaded }
+}
 
 impl DigStatus {
   pub fn from_id(id: u8) -> Self {
@@ -137,6 +137,8 @@ where
   pub fn len(&self) -> usize { self.data.get_ref().as_ref().len() }
   pub fn is_empty(&self) -> bool { self.len() == 0 }
   pub fn index(&self) -> usize { usize::try_from(self.data.position()).unwrap() }
+  /// Returns the number of bytes left to read.
+  pub fn remaining(&self) -> usize { self.len().saturating_sub(self.index()) }
 
   pub fn read_bool(&mut self) -> Result<bool> { Ok(self.read_u8()? != 0) }
   add_read_byte!(read_u8, u8);
@@ -173,12 +175,34 @@ where
     }
   }
 
+  /// Reads `len` bytes. If there are less than `len` bytes left in the buffer,
+  /// this returns an error before allocating anything, so a bad length prefix
+  /// cannot cause a large allocation.
   pub fn read_buf(&mut self, len: usize) -> Result<Vec<u8>> {
+    if len > self.remaining() {
+      return Err(self.err(io::Error::from(io::ErrorKind::UnexpectedEof), Reading));
+    }
     let mut buf = vec![0; len];
-    self.data.read(&mut buf).map_err(|e| self.err(e, Reading))?;
+    self.data.read_exact(&mut buf).map_err(|e| self.err(e, Reading))?;
     Ok(buf)
   }
 
+  /// Reads a varint length prefix. This returns an error if the length is
+  /// negative. Every element of an array takes at least one byte, so this also
+  /// returns an error if the length is larger than the rest of the buffer.
+  pub fn read_len(&mut self) -> Result<usize> {
+    let len = self.read_varint()?;
+    let len: usize =
+      len.try_into().map_err(|_| self.err(BufferErrorKind::NegativeLen(len), Reading))?;
+    if len > self.remaining() {
+      return Err(self.err(
+        BufferErrorKind::ArrayTooLong { len: len as u64, max: self.remaining() as u64 },
+        Reading,
+      ));
+    }
+    Ok(len)
+  }
+
   /// Reads a string. If the length is longer than the given maximum, this will
   /// fail, and return an error.
   pub fn read_str(&mut self, max_len: u64) -> Result<String> {
@@ -231,7 +255,7 @@ where
 
   /// Reads a length prefixed array of integers.
   pub fn read_i32_arr(&mut self) -> Result<Vec<i32>> {
-    let len = self.read_varint()?.try_into().unwrap();
+    let len = self.read_len()?;
     let mut out = Vec::with_capacity(len);
     for _ in 0..len {
       out.push(self.read_i32()?);
@@ -247,7 +271,7 @@ where
   /// Reads a list from the packet. This is new to 1.17, and simplifies a bunch
   /// of small for loops in previous versions.
   pub fn read_list<U>(&mut self, val: impl Fn(&mut Buffer<T>) -> Result<U>) -> Result<Vec<U>> {
-    let len = self.read_varint()?.try_into().unwrap();
+    let len = self.read_len()?;
     let mut list = Vec::with_capacity(len);
     for _ in 0..len {
       list.push(val(self)?);
@@ -263,7 +287,7 @@ where
     val: impl Fn(&mut Buffer<T>) -> Result<U>,
     max: usize,
   ) -> Result<Vec<U>> {
-    let len: usize = self.read_varint()?.try_into().unwrap();
+    let len = self.read_len()?;
     if len > max {
       return Err(
         self.err(BufferErrorKind::ArrayTooLong { len: len as u64, max: max as u64 }, Reading),
@@ -347,6 +371,23 @@ where
     }
   }
 
+  /// Writes a varlong. This is the same encoding as a varint, but it can take
+  /// up to 10 bytes.
+  pub fn write_varlong(&mut self, v: i64) {
+    let mut val = v as u64;
+    for _ in 0..10 {
+      let mut b: u8 = val as u8 & 0b01111111;
+      val >>= 7;
+      if val != 0 {
+        b |= 0b10000000;
+      }
+      self.write_u8(b);
+      if val == 0 {
+        break;
+      }
+    }
+  }
+
   /// Writes a chunk position, as two i32s.
   pub fn write_chunk_pos(&mut self, p: ChunkPos) {
     self.write_i32(p.x());
@@ -459,4 +500,53 @@ mod tests {
     buf.write_varint(-1);
     assert_eq!(vec![255, 255, 255, 255, 15], data);
   }
+
+  #[test]
+  pub fn write_varlong() {
+    let mut data = vec![];
+    let mut buf = Buffer::new(&mut data);
+    buf.write_varlong(256);
+    assert_eq!(vec![128, 2], data);
+
+    let mut data = vec![];
+    let mut buf = Buffer::new(&mut data);
+    buf.write_varlong(i64::MAX);
+    assert_eq!(vec![255, 255, 255, 255, 255, 255, 255, 255, 127], data);
+
+    let mut data = vec![];
+    let mut buf = Buffer::new(&mut data);
+    buf.write_varlong(-1);
+    assert_eq!(vec![255, 255, 255, 255, 255, 255, 255, 255, 255, 1], data);
+  }
+
+  #[test]
+  pub fn oversized_lengths() {
+    // A string that claims to be 2^31 - 1 bytes long.
+    let mut data = vec![255, 255, 255, 255, 7, b'a'];
+    let mut buf = Buffer::new(&mut data);
+    assert!(buf.read_str(i32::MAX as u64).is_err());
+
+    // A string that is longer than the max.
+    let mut data = vec![];
+    Buffer::new(&mut data).write_str("hello");
+    assert!(Buffer::new(&mut data).read_str(4).is_err());
+    assert_eq!(Buffer::new(&mut data).read_str(5).unwrap(), "hello");
+
+    let mut data = vec![1, 2, 3];
+    let mut buf = Buffer::new(&mut data);
+    assert!(buf.read_buf(usize::MAX).is_err());
+    assert!(buf.read_buf(4).is_err());
+    assert_eq!(buf.read_buf(3).unwrap(), vec![1, 2, 3]);
+
+    // Lists with huge and negative lengths.
+    let mut data = vec![255, 255, 255, 255, 7, 0];
+    let mut buf = Buffer::new(&mut data);
+    assert!(buf.read_list(|buf| buf.read_u8()).is_err());
+    let mut data = vec![255, 255, 255, 255, 15, 0];
+    let mut buf = Buffer::new(&mut data);
+    assert!(buf.read_varint_arr().is_err());
+    let mut data = vec![255, 255, 255, 255, 7, 0];
+    let mut buf = Buffer::new(&mut data);
+    assert!(buf.read_i32_arr().is_err());
+  }
 }
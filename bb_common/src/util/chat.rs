@@ -104,16 +104,110 @@ impl Chat {
   /// features.
   pub fn to_codes(&self) -> String {
     let mut out = String::new();
+    let mut prev = CodeStyle::default();
     for s in &self.sections {
-      s.to_codes(&mut out);
+      s.to_codes(&mut out, &CodeStyle::default(), &mut prev);
     }
     out
   }
 
+  /// Parses a string with legacy `§` color codes. This is the inverse of
+  /// [`to_codes`](Self::to_codes), for everything that can be stored as color
+  /// codes. See [`from_legacy_with`](Self::from_legacy_with) to use a different
+  /// character (like `&`) for the codes.
+  pub fn from_legacy(s: &str) -> Self { Chat::from_legacy_with(s, CODE_SEP) }
+
+  /// Parses a string with legacy color codes, where each code starts with
+  /// `sep`. Like vanilla, a color code will reset all formatting, and `r` will
+  /// reset everything.
+  ///
+  /// RGB colors are accepted in both the `§x§r§r§g§g§b§b` format and the
+  /// `§#rrggbb` format. Any unknown codes are left in the text as-is.
+  pub fn from_legacy_with(s: &str, sep: char) -> Self {
+    let mut sections = vec![];
+    let mut style = Section::default();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+      if c != sep {
+        style.text.push(c);
+        continue;
+      }
+      let code = match chars.peek() {
+        Some(&code) => code.to_ascii_lowercase(),
+        None => {
+          style.text.push(c);
+          continue;
+        }
+      };
+      let hex = match code {
+        'x' => parse_hex(chars.clone().skip(1), Some(sep)),
+        '#' => parse_hex(chars.clone().skip(1), None),
+        _ => None,
+      };
+      let color = match hex {
+        Some(_) => None,
+        None => Color::from_code(code),
+      };
+      if hex.is_none() && color.is_none() && !"klmnor".contains(code) {
+        style.text.push(c);
+        continue;
+      }
+      chars.next();
+      if !style.text.is_empty() {
+        sections.push(Section { text: std::mem::take(&mut style.text), ..style.clone() });
+      }
+      if let Some((color, len)) = hex {
+        // Skip the rest of the hex code.
+        chars.nth(len - 1);
+        style = Section { color: Some(color), ..Default::default() };
+      } else if let Some(color) = color {
+        style = Section { color: Some(color), ..Default::default() };
+      } else {
+        match code {
+          'k' => style.obfuscated = Some(true),
+          'l' => style.bold = Some(true),
+          'm' => style.strikethrough = Some(true),
+          'n' => style.underlined = Some(true),
+          'o' => style.italic = Some(true),
+          _ => style = Section::default(),
+        }
+      }
+    }
+    if !style.text.is_empty() {
+      sections.push(style);
+    }
+    if sections.is_empty() {
+      Chat::empty()
+    } else {
+      Chat { sections }
+    }
+  }
+
   pub fn sections_len(&self) -> usize { self.sections.len() }
   pub fn get_section(&mut self, idx: usize) -> Option<&mut Section> { self.sections.get_mut(idx) }
 }
 
+/// Parses the 6 hex digits of an RGB color code. If `sep` is set, each digit
+/// must come after a `sep` (the `§x§r§r§g§g§b§b` format). Returns the color,
+/// and the number of characters used.
+fn parse_hex(mut chars: impl Iterator<Item = char>, sep: Option<char>) -> Option<(Color, usize)> {
+  let mut hex = String::with_capacity(7);
+  hex.push('#');
+  for _ in 0..6 {
+    if let Some(sep) = sep {
+      if chars.next()? != sep {
+        return None;
+      }
+    }
+    let c = chars.next()?;
+    if !c.is_ascii_hexdigit() {
+      return None;
+    }
+    hex.push(c.to_ascii_lowercase());
+  }
+  Some((Color::Custom(hex), if sep.is_some() { 12 } else { 6 }))
+}
+
 impl Serialize for Chat {
   fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
   where
@@ -348,40 +442,88 @@ impl Section {
     }
   }
 
-  fn to_codes(&self, out: &mut String) {
-    if self.bold == Some(true) {
-      out.push_str("§l");
-    }
-    if self.italic == Some(true) {
-      out.push_str("§o");
-    }
-    if self.underlined == Some(true) {
-      out.push_str("§u");
-    }
-    if self.strikethrough == Some(true) {
-      out.push_str("§m");
+  /// Writes this section, and all of its children, as color codes. `parent` is
+  /// the style inherited from the parent section, and `prev` is the style of
+  /// the last text that was written. Only the codes needed to get from `prev`
+  /// to this section's style are written.
+  fn to_codes(&self, out: &mut String, parent: &CodeStyle, prev: &mut CodeStyle) {
+    let style = CodeStyle {
+      color:         self.color.clone().or_else(|| parent.color.clone()),
+      bold:          self.bold.unwrap_or(parent.bold),
+      italic:        self.italic.unwrap_or(parent.italic),
+      underlined:    self.underlined.unwrap_or(parent.underlined),
+      strikethrough: self.strikethrough.unwrap_or(parent.strikethrough),
+      obfuscated:    self.obfuscated.unwrap_or(parent.obfuscated),
+    };
+    if !self.text.is_empty() {
+      style.write_from(prev, out);
+      out.push_str(&self.text);
+      *prev = style.clone();
     }
-    if self.obfuscated == Some(true) {
-      out.push_str("§k");
+    for e in &self.extra {
+      e.to_codes(out, &style, prev);
     }
-    if let Some(c) = &self.color {
-      if c != &Color::White {
-        out.push('§');
-        out.push(c.code());
+  }
+}
+
+/// The style of some text, as far as color codes are concerned. Used to find
+/// the codes needed between two sections in [`Chat::to_codes`].
+#[derive(Debug, Default, Clone, PartialEq)]
+struct CodeStyle {
+  color:         Option<Color>,
+  bold:          bool,
+  italic:        bool,
+  underlined:    bool,
+  strikethrough: bool,
+  obfuscated:    bool,
+}
+
+impl CodeStyle {
+  /// Writes the codes needed to change the style from `prev` to `self`.
+  fn write_from(&self, prev: &CodeStyle, out: &mut String) {
+    let formats = [
+      (self.bold, prev.bold, 'l'),
+      (self.italic, prev.italic, 'o'),
+      (self.underlined, prev.underlined, 'n'),
+      (self.strikethrough, prev.strikethrough, 'm'),
+      (self.obfuscated, prev.obfuscated, 'k'),
+    ];
+    // Formatting can only be cleared by a color code or a `§r`, which both
+    // clear all of it. So if the color changes, or any formatting needs to be
+    // removed, we start from scratch.
+    let reset = self.color != prev.color || formats.iter().any(|&(new, old, _)| old && !new);
+    if reset {
+      match &self.color {
+        // RGB colors are written in the `§x§r§r§g§g§b§b` format, so that they
+        // can be parsed by `from_legacy`.
+        Some(Color::Custom(hex))
+          if hex.len() == 7
+            && hex.starts_with('#')
+            && hex[1..].chars().all(|c| c.is_ascii_hexdigit()) =>
+        {
+          out.push(CODE_SEP);
+          out.push('x');
+          for c in hex[1..].chars() {
+            out.push(CODE_SEP);
+            out.push(c.to_ascii_lowercase());
+          }
+        }
+        Some(c) => {
+          out.push(CODE_SEP);
+          out.push(c.code());
+        }
+        None => {
+          out.push(CODE_SEP);
+          out.push('r');
+        }
       }
     }
-    out.push_str(&self.text);
-    for e in &self.extra {
-      e.to_codes(out);
+    for (new, old, code) in formats {
+      if new && (reset || !old) {
+        out.push(CODE_SEP);
+        out.push(code);
+      }
     }
-    // This is the lazy way, of just resetting after every section. This is
-    // meant to be after subsections, so that they keep the formatting of their
-    // parent. This only works for a single level deep of children, but this format
-    // isn't meant to be complete anyways.
-    //
-    // TODO: Implement resetting/color codes the correct way, where we track the
-    // color state between every section, and find the difference between each one.
-    out.push_str("§r");
   }
 }
 
@@ -451,7 +593,8 @@ impl Color {
     }
   }
 
-  /// Returns the color code for this color.
+  /// Returns the color code for this color. RGB colors don't have a single
+  /// code, so this returns `f` (white) for them.
   pub const fn code(&self) -> char {
     match self {
       Self::Black => '0',
@@ -474,6 +617,31 @@ impl Color {
     }
   }
 
+  /// Returns the color for the given color code, if it is valid. This is the
+  /// inverse of [`code`](Self::code), and accepts both upper and lower case
+  /// codes.
+  pub fn from_code(code: char) -> Option<Self> {
+    Some(match code.to_ascii_lowercase() {
+      '0' => Self::Black,
+      '1' => Self::DarkBlue,
+      '2' => Self::DarkGreen,
+      '3' => Self::DarkAqua,
+      '4' => Self::DarkRed,
+      '5' => Self::Purple,
+      '6' => Self::Gold,
+      '7' => Self::Gray,
+      '8' => Self::DarkGray,
+      '9' => Self::Blue,
+      'a' => Self::BrightGreen,
+      'b' => Self::Cyan,
+      'c' => Self::Red,
+      'd' => Self::Pink,
+      'e' => Self::Yellow,
+      'f' => Self::White,
+      _ => return None,
+    })
+  }
+
   /// Returns the color id. Used in Teams packet.
   pub const fn id(&self) -> u8 {
     match self {
@@ -619,4 +787,53 @@ mod tests {
       );
     }
   }
+
+  #[test]
+  fn from_legacy() {
+    let mut expected = Chat::new("plain ");
+    expected.add("red").color(Color::Red);
+    expected.add("bold").color(Color::Red).bold();
+    expected.add("reset");
+    assert_eq!(Chat::from_legacy("plain §cred§lbold§rreset"), expected);
+    assert_eq!(Chat::from_legacy_with("plain &Cred&lbold&rreset", '&'), expected);
+
+    // Color codes reset formatting, and unknown codes are left alone.
+    let mut chat = Chat::from_legacy("§la§7b §z c");
+    assert_eq!(chat.get_section(0).unwrap().bold, Some(true));
+    assert_eq!(chat.get_section(1).unwrap().bold, None);
+    assert_eq!(chat.get_section(1).unwrap().text, "b §z c");
+
+    let mut expected = Chat::new("a");
+    expected.add("rgb").color(Color::rgb(0x12, 0xab, 0xef));
+    expected.add("short").color(Color::rgb(0xff, 0x00, 0x00));
+    assert_eq!(Chat::from_legacy("a§x§1§2§A§b§e§frgb§#ff0000short"), expected);
+
+    assert_eq!(Chat::from_legacy(""), Chat::empty());
+    assert_eq!(Chat::from_legacy("§r§l"), Chat::empty());
+    assert_eq!(Chat::from_legacy("trailing§"), Chat::new("trailing§"));
+  }
+
+  #[test]
+  fn legacy_round_trip() {
+    let mut msg = Chat::new("Hello ");
+    msg.add("world").color(Color::BrightGreen).bold().underlined();
+    msg.add("!").italic().strikethrough().obfuscated();
+    msg.add(" gray").color(Color::Gray);
+    // Only the codes needed between each section are written. The `§r` is
+    // needed to clear the bold and underline before `!`.
+    assert_eq!(msg.to_codes(), "Hello §a§l§nworld§r§o§m§k!§7 gray");
+    assert_eq!(Chat::from_legacy(&msg.to_codes()), msg);
+
+    // Children inherit the style of their parent.
+    let mut msg = Chat::empty();
+    msg.add("a ").bold().add_child("b").italic();
+    msg.add(" c");
+    assert_eq!(msg.to_codes(), "§la §ob§r c");
+
+    let mut msg = Chat::new("a");
+    msg.add("rgb").color(Color::rgb(0x12, 0xab, 0xef)).bold();
+    msg.add(" white").color(Color::White);
+    assert_eq!(msg.to_codes(), "a§x§1§2§a§b§e§f§lrgb§f white");
+    assert_eq!(Chat::from_legacy(&msg.to_codes()), msg);
+  }
 }
@@ -0,0 +1,78 @@
+use bb_macros::Transfer;
+use std::{error::Error, fmt, str::FromStr};
+
+/// A status effect, which can be applied to a player with a potion, beacon, or
+/// the `/effect` command. The ids here are the same on every version we
+/// support.
+#[derive(Transfer, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Effect {
+  #[id = 1]
+  Speed,
+  #[id = 2]
+  Slowness,
+  #[id = 10]
+  Regeneration,
+  #[id = 19]
+  Poison,
+}
+
+impl Effect {
+  /// Returns the protocol id of this effect.
+  pub fn id(&self) -> u8 {
+    match self {
+      Self::Speed => 1,
+      Self::Slowness => 2,
+      Self::Regeneration => 10,
+      Self::Poison => 19,
+    }
+  }
+
+  /// Returns the effect with the given protocol id, or `None` if this effect
+  /// hasn't been implemented.
+  pub fn from_id(id: u8) -> Option<Self> {
+    Some(match id {
+      1 => Self::Speed,
+      2 => Self::Slowness,
+      10 => Self::Regeneration,
+      19 => Self::Poison,
+      _ => return None,
+    })
+  }
+
+  /// Returns the name of this effect, without the `minecraft:` prefix.
+  pub fn name(&self) -> &'static str {
+    match self {
+      Self::Speed => "speed",
+      Self::Slowness => "slowness",
+      Self::Regeneration => "regeneration",
+      Self::Poison => "poison",
+    }
+  }
+}
+
+impl fmt::Display for Effect {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{}", self.name()) }
+}
+
+#[derive(Debug)]
+pub struct InvalidEffect(String);
+
+impl fmt::Display for InvalidEffect {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "invalid effect: {}", self.0) }
+}
+
+impl Error for InvalidEffect {}
+
+impl FromStr for Effect {
+  type Err = InvalidEffect;
+
+  fn from_str(s: &str) -> Result<Self, InvalidEffect> {
+    Ok(match s.strip_prefix("minecraft:").unwrap_or(s) {
+      "speed" => Effect::Speed,
+      "slowness" => Effect::Slowness,
+      "regeneration" => Effect::Regeneration,
+      "poison" => Effect::Poison,
+      _ => return Err(InvalidEffect(s.into())),
+    })
+  }
+}
@@ -20,6 +20,9 @@ pub struct ItemData {
   /// zero.
   pub enchantments: Option<HashMap<u32, NonZeroU8>>,
   pub unbreakable:  bool,
+  /// The amount of durability this item has lost. This is only used for items
+  /// that can be damaged, like tools and armor.
+  pub damage:       u32,
 }
 #[derive(Transfer, Default, Debug, Clone, PartialEq)]
 pub struct ItemDisplay {
@@ -49,7 +52,12 @@ impl Item {
 
 impl ItemData {
   pub const fn new() -> Self {
-    ItemData { display: ItemDisplay::new(), enchantments: None, unbreakable: false }
+    ItemData {
+      display:      ItemDisplay::new(),
+      enchantments: None,
+      unbreakable:  false,
+      damage:       0,
+    }
   }
   pub fn enchantments_mut(&mut self) -> &mut HashMap<u32, NonZeroU8> {
     self.enchantments.get_or_insert_with(|| HashMap::new())
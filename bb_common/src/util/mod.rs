@@ -5,6 +5,7 @@ pub use chat::Chat;
 pub use pool::ThreadPool;
 
 mod buffer;
+mod effect;
 mod item;
 
 use crate::math::Pos;
@@ -12,9 +13,10 @@ use bb_macros::Transfer;
 #[cfg(feature = "host")]
 use rand::{rngs::OsRng, RngCore};
 use serde::de::{self, Deserialize, Deserializer, Unexpected, Visitor};
-use std::{error::Error, fmt, io::Write, num::ParseIntError, str::FromStr};
+use std::{error::Error, fmt, io::Write, net::SocketAddr, num::ParseIntError, str::FromStr};
 
 pub use buffer::{Buffer, BufferError, BufferErrorKind, Mode};
+pub use effect::{Effect, InvalidEffect};
 pub use item::{Item, ItemData, ItemDisplay};
 
 pub use num_cpus::get as num_cpus;
@@ -197,6 +199,42 @@ impl FromStr for GameMode {
   }
 }
 
+/// The dimension type of a world. This changes the sky and fog on the client,
+/// and is sent in the join game and respawn packets.
+#[derive(Transfer, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+  #[id = 0]
+  Overworld,
+  #[id = 1]
+  Nether,
+  #[id = 2]
+  End,
+}
+
+impl Default for Dimension {
+  fn default() -> Self { Dimension::Overworld }
+}
+
+impl Dimension {
+  /// Returns the dimension id used by 1.8 - 1.15 clients.
+  pub fn id(&self) -> i8 {
+    match self {
+      Self::Overworld => 0,
+      Self::Nether => -1,
+      Self::End => 1,
+    }
+  }
+
+  /// Returns the namespaced key of this dimension, used by 1.16+ clients.
+  pub fn key(&self) -> &'static str {
+    match self {
+      Self::Overworld => "minecraft:overworld",
+      Self::Nether => "minecraft:the_nether",
+      Self::End => "minecraft:the_end",
+    }
+  }
+}
+
 #[derive(Transfer, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Face {
   #[id = 0]
@@ -218,6 +256,12 @@ impl Default for Face {
 }
 
 impl Face {
+  /// All 6 faces, in id order.
+  pub const ALL: [Face; 6] =
+    [Face::Bottom, Face::Top, Face::North, Face::South, Face::West, Face::East];
+  /// The 4 horizontal faces.
+  pub const SIDES: [Face; 4] = [Face::North, Face::South, Face::West, Face::East];
+
   pub fn id(&self) -> u8 {
     match self {
       Self::Bottom => 0,
@@ -266,6 +310,18 @@ impl Face {
   pub fn is_side(&self) -> bool {
     matches!(self, Self::North | Self::South | Self::West | Self::East)
   }
+
+  /// Returns the face pointing in the opposite direction.
+  pub fn opposite(&self) -> Face {
+    match self {
+      Self::Bottom => Self::Top,
+      Self::Top => Self::Bottom,
+      Self::North => Self::South,
+      Self::South => Self::North,
+      Self::West => Self::East,
+      Self::East => Self::West,
+    }
+  }
 }
 impl From<&str> for Face {
   fn from(s: &str) -> Face {
@@ -407,6 +463,9 @@ pub struct JoinInfo {
   pub uuid:     UUID,
   #[must_exist]
   pub ver:      u32,
+  /// The address of the client connected to the proxy. This is `None` if the
+  /// proxy doesn't know the address.
+  pub addr:     Option<SocketAddr>,
 }
 
 #[derive(Transfer, Debug, Clone)]
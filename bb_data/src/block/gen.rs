@@ -63,6 +63,11 @@ pub fn generate_ty(def: &BlockDef, opts: BlockOpts) -> String {
     });
     gen.write("impl Kind ");
     gen.write_block(|gen| {
+      gen.write_line("/// The number of vanilla block kinds. Any kind with an id at or above");
+      gen.write_line("/// this is a custom kind.");
+      gen.write("pub const COUNT: u32 = ");
+      gen.write(&def.blocks.len().to_string());
+      gen.write_line(";");
       gen.write("pub const fn to_str(&self) -> &'static str ");
       gen.write_block(|gen| {
         gen.write("[");
@@ -193,6 +198,23 @@ fn generate_versions(versions: &[(Version, BlockDef)]) -> String {
   gen.into_output()
 }
 
+#[cfg(test)]
+#[test]
+fn test_plugin_kinds() {
+  let def = BlockDef {
+    blocks: vec![
+      Block { id: 0, name: "air".into(), ..Default::default() },
+      Block { id: 1, name: "stone".into(), ..Default::default() },
+    ],
+  };
+  let server = generate_ty(&def, BlockOpts { versions: true, data: true, kinds: true });
+  let plugin = generate_ty(&def, BlockOpts { versions: false, data: false, kinds: true });
+  // Plugins only get the `Kind` enum, which must be the exact same as the one on
+  // the server, so that kind ids match over FFI.
+  assert!(server.starts_with(&plugin));
+  assert!(plugin.contains("pub const COUNT: u32 = 2;"));
+}
+
 #[cfg(test)]
 pub fn test(versions: Vec<(Version, BlockDef)>) {
   for v in &versions {
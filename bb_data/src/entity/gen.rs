@@ -48,6 +48,7 @@ pub fn generate_ty(def: &EntityDef) -> String {
   });
   gen.write("impl Type ");
   gen.write_block(|gen| {
+    gen.write_count("entities", def.entities.len());
     gen.write("pub fn to_str(&self) -> &'static str");
     gen.write_block(|gen| {
       gen.write("[");
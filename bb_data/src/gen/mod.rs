@@ -346,6 +346,32 @@ impl CodeGen {
     self.write("// ");
     self.write_line(text);
   }
+  /// Writes a documented `COUNT` constant, for the number of variants in a
+  /// generated enum. Example:
+  /// ```
+  /// # use bb_data::gen::CodeGen;
+  /// # let mut gen = CodeGen::new();
+  /// gen.write_count("items", 3);
+  /// # let out = gen.into_output();
+  /// # eprintln!("OUTPUT: {}", out);
+  /// # assert_eq!(out,
+  /// # r#"/// The number of vanilla items.
+  /// # pub const COUNT: u32 = 3;
+  /// # "#);
+  /// ```
+  /// That will produce:
+  /// ```ignore
+  /// /// The number of vanilla items.
+  /// pub const COUNT: u32 = 3;
+  /// ```
+  pub fn write_count(&mut self, name: &str, count: usize) {
+    self.write("/// The number of vanilla ");
+    self.write(name);
+    self.write_line(".");
+    self.write("pub const COUNT: u32 = ");
+    self.write(&count.to_string());
+    self.write_line(";");
+  }
 
   pub fn write(&mut self, src: &str) {
     // Make sure not to indent when we aren't writing anything
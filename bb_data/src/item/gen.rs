@@ -47,6 +47,7 @@ pub fn generate_ty(def: &ItemDef) -> String {
   });
   gen.write("impl Type ");
   gen.write_block(|gen| {
+    gen.write_count("items", def.items.len());
     gen.write("pub fn to_str(&self) -> &'static str");
     gen.write_block(|gen| {
       gen.write("[");
@@ -128,6 +129,7 @@ fn item_data(gen: &mut CodeGen, b: &Item) {
 
   write_prop!(name);
   write_prop!(id);
+  write_prop!(stack_size);
 
   gen.remove_indent();
   gen.write("}");
@@ -27,9 +27,14 @@ pub struct ItemDef {
 #[allow(dead_code)]
 pub struct Item {
   /// The id of the item.
-  id:    u32,
+  id:         u32,
   /// The name id, used everywhere important.
-  name:  String,
+  name:       String,
   /// The full class of this item
-  class: String,
+  class:      String,
+  /// The maximum number of items that can be in a single stack.
+  #[serde(default = "default_stack_size")]
+  stack_size: u32,
 }
+
+fn default_stack_size() -> u32 { 64 }
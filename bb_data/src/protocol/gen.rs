@@ -172,7 +172,7 @@ impl PacketCollection {
           gen.write(&first.name);
           gen.write_line("\",");
         }
-        gen.write_line(r#"v => panic!("invalid protocol version {}", v),"#);
+        gen.write_line(r#"_ => "unknown","#);
       });
     });
 
@@ -220,6 +220,8 @@ impl PacketCollection {
     gen.write_line("/// Unreachable patterns mean the packet has been removed in that version.");
     gen.write_line("/// This is most likely because I messed up the naming. However, some packets");
     gen.write_line("/// have been removed in practice. TODO: Handle removed packets.");
+    gen.write_line("///");
+    gen.write_line("/// Returns -1 if the id doesn't exist on the given version.");
     gen.write_line("#[allow(unreachable_patterns)]");
     gen.write_func(
       "to_sug_id",
@@ -245,10 +247,10 @@ impl PacketCollection {
                   }
                 }
               }
-              gen.write_line("_ => 0,");
+              gen.write_line("_ => -1,");
             });
           }
-          gen.write_line("_ => 0,");
+          gen.write_line("_ => -1,");
         });
       },
     );
@@ -606,7 +608,7 @@ pub fn write_from_tcp_all(gen: &mut CodeGen, packets: &[Vec<(Version, Packet)>])
         gen.write(&first.name);
         gen.write_line("::from_tcp(p, ver)?),");
       }
-      gen.write_line(r#"v => panic!("invalid protocol version {}", v),"#);
+      gen.write_line("_ => return Err(p.unknown_id()),");
     });
     gen.write(")"); // Close the `Ok(` from above the match
   });
@@ -45,6 +45,18 @@ pub struct CChat {
   pub message: CStr,
 }
 
+/// Extra information about a log message. The server adds this to the start of
+/// the message.
+#[ctype]
+#[derive(Debug)]
+pub struct CLogContext {
+  /// The player this message is about. Only valid if `has_player` is set.
+  pub player:     CUUID,
+  pub has_player: CBool,
+  /// The world this message is about, or -1 for none.
+  pub world:      i32,
+}
+
 #[ctype]
 #[derive(Debug)]
 #[cfg_attr(not(feature = "host"), derive(Copy))]
@@ -62,6 +74,18 @@ pub struct CFPos {
   pub y: f64,
   pub z: f64,
 }
+/// An entity in a world, returned from `bb_world_entities_in_radius`.
+#[ctype]
+#[derive(Debug)]
+pub struct CEntity {
+  /// The entity id.
+  pub eid: i32,
+  /// The entity type, using the server's entity ids.
+  pub ty:  u32,
+  /// The position of this entity.
+  pub pos: CFPos,
+}
+
 #[ctype]
 #[derive(Debug)]
 #[cfg_attr(not(feature = "host"), derive(Copy))]
@@ -406,6 +430,20 @@ extern "C" {
     file_len: u32,
     line: u32,
   );
+  /// Logs the given message, with some extra context. `context` may be null.
+  pub fn bb_log_context(
+    level: u32,
+    message_ptr: *const u8,
+    message_len: u32,
+    target_ptr: *const u8,
+    target_len: u32,
+    module_path_ptr: *const u8,
+    module_path_len: u32,
+    file_ptr: *const u8,
+    file_len: u32,
+    line: u32,
+    context: *const CLogContext,
+  );
 
   /// Adds the command to the server.
   pub fn bb_add_command(command: *const CCommand);
@@ -427,6 +465,9 @@ extern "C" {
 
   /// Broadcasts the given chat message to all players.
   pub fn bb_broadcast(message: *const CChat);
+  /// Broadcasts the given chat message to all players in the given world. Does
+  /// nothing if the world doesn't exist.
+  pub fn bb_broadcast_world(wid: u32, message: *const CChat);
   /// Returns the player's username.
   pub fn bb_player_username(player: *const CUUID) -> *mut CStr;
   /// Returns the player's position.
@@ -439,12 +480,41 @@ extern "C" {
   pub fn bb_player_send_message(player: *const CUUID, message: *const CChat);
   /// Sends the given particle to the player.
   pub fn bb_player_send_particle(player: *const CUUID, particle: *const CParticle);
+  /// Adds a status effect to the player. `duration` is in ticks. Returns -1 if
+  /// the player is offline, or if the effect id is invalid.
+  pub fn bb_player_add_effect(
+    player: *const CUUID,
+    effect: u32,
+    amplifier: u32,
+    duration: u32,
+    particles: CBool,
+  ) -> i32;
+  /// Removes a status effect from the player. Returns 1 if the player had the
+  /// effect, 0 if they didn't, and -1 if the player is offline or the effect id
+  /// is invalid.
+  pub fn bb_player_remove_effect(player: *const CUUID, effect: u32) -> i32;
+  /// Returns the player's game mode id, or -1 if the player is offline.
+  pub fn bb_player_game_mode(player: *const CUUID) -> i32;
+  /// Sets the player's game mode. Returns -1 if the player is offline, or if
+  /// the game mode id is invalid.
+  pub fn bb_player_set_game_mode(player: *const CUUID, game_mode: u32) -> i32;
+  /// Returns 1 if the player is vanished, 0 if they aren't, and -1 if the
+  /// player is offline.
+  pub fn bb_player_vanished(player: *const CUUID) -> i32;
+  /// Hides or shows the player for everyone else. Returns -1 if the player is
+  /// offline.
+  pub fn bb_player_set_vanished(player: *const CUUID, vanished: CBool) -> i32;
 
   /// Sets a block in the world. Returns -1 if the block position is invalid.
   pub fn bb_world_set_block(wid: u32, pos: *const CPos, id: u32) -> i32;
   /// Sets a block in the world, using a kind id. Returns -1 if the block
   /// position is invalid.
   pub fn bb_world_set_block_kind(wid: u32, pos: *const CPos, kind: u32) -> i32;
+  /// Fills the region between `min` and `max` (inclusive) with the default type
+  /// of the given kind id. Returns -1 if either position is invalid, -2 if the
+  /// kind id is invalid, and -3 if the region is larger than the world's
+  /// `max-fill-volume`.
+  pub fn bb_world_fill_kind(wid: u32, min: *const CPos, max: *const CPos, kind: u32) -> i32;
   /// Gets a block in the world. Returns `u32::MAX` if the block position is
   /// invalid.
   pub fn bb_world_get_block(wid: u32, pos: *const CPos) -> u32;
@@ -452,6 +522,16 @@ extern "C" {
   pub fn bb_world_players(wid: u32) -> *mut CList<CUUID>;
   /// Spawns a particle in the world.
   pub fn bb_world_spawn_particle(wid: u32, particle: *const CParticle);
+  /// Returns the number of entities in the world, including players. Returns
+  /// -1 if the world doesn't exist.
+  pub fn bb_world_entity_count(wid: u32) -> i32;
+  /// Returns all the entities (including players) within `radius` blocks of
+  /// `center`, closest first.
+  pub fn bb_world_entities_in_radius(
+    wid: u32,
+    center: *const CFPos,
+    radius: f64,
+  ) -> *mut CList<CEntity>;
   /// Raycasts from the `from` position to `to`. Returns null if there is no
   /// collision.
   pub fn bb_world_raycast(from: *const CFPos, to: *const CFPos, water: CBool) -> *mut CFPos;
@@ -1 +1,27 @@
+use crate::FromFfi;
+use bb_common::math::FPos;
+use bb_ffi::CEntity;
 
+/// An entity in a world. This is a snapshot, so the position will not change
+/// if the entity moves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Entity {
+  eid: i32,
+  ty:  u32,
+  pos: FPos,
+}
+
+impl FromFfi for Entity {
+  type Ffi = CEntity;
+
+  fn from_ffi(c: CEntity) -> Self { Entity { eid: c.eid, ty: c.ty, pos: FPos::from_ffi(c.pos) } }
+}
+
+impl Entity {
+  /// Returns the entity id. This is unique across all worlds.
+  pub fn eid(&self) -> i32 { self.eid }
+  /// Returns the type of this entity, using the server's entity ids.
+  pub fn ty_id(&self) -> u32 { self.ty }
+  /// Returns the position of this entity, at the time it was looked up.
+  pub fn pos(&self) -> FPos { self.pos }
+}
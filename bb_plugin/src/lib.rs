@@ -47,12 +47,21 @@ pub trait FromFfi {
 use sync::ConstLock;
 
 impl Bamboo {
+  /// Sends a chat message to every player on the server. Use
+  /// [`World::broadcast`](world::World::broadcast) to only send a message to
+  /// one world.
   pub fn broadcast(&self, message: Chat) {
     unsafe {
       let c_chat = CChat { message: bb_ffi::CStr::new(message.to_codes()) };
       bb_ffi::bb_broadcast(&c_chat);
     }
   }
+  /// Sends a chat message to all of the given players.
+  pub fn broadcast_to(&self, players: &[player::Player], message: Chat) {
+    for p in players {
+      p.send_message(message.clone());
+    }
+  }
 }
 
 use log::{Level, LevelFilter, Metadata, Record};
@@ -65,41 +74,114 @@ impl log::Log for Logger {
 
   fn log(&self, record: &Record) {
     if self.enabled(record.metadata()) {
-      unsafe {
-        if let Some(s) = record.args().as_str() {
-          bb_ffi::bb_log(
-            record.level() as u32,
-            s.as_ptr(),
-            s.len() as u32,
-            record.target().as_ptr(),
-            record.target().len() as u32,
-            record.module_path().unwrap_or("").as_ptr(),
-            record.module_path().unwrap_or("").len() as u32,
-            record.file().unwrap_or("").as_ptr(),
-            record.file().unwrap_or("").len() as u32,
-            record.line().unwrap_or(0),
-          );
-        } else {
-          let s = record.args().to_string();
-          bb_ffi::bb_log(
-            record.level() as u32,
-            s.as_ptr(),
-            s.len() as u32,
-            record.target().as_ptr(),
-            record.target().len() as u32,
-            record.module_path().unwrap_or("").as_ptr(),
-            record.module_path().unwrap_or("").len() as u32,
-            record.file().unwrap_or("").as_ptr(),
-            record.file().unwrap_or("").len() as u32,
-            record.line().unwrap_or(0),
-          );
-        }
-      }
+      send_log(record, None);
     }
   }
   fn flush(&self) {}
 }
 
+/// Extra information to attach to a log message. The server will add the
+/// player's name and the world's name to the start of the message, so that
+/// logs about a single player or world can be searched for.
+///
+/// Use the [`log_with`] macro to log with a context.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogContext {
+  player: Option<util::UUID>,
+  world:  Option<u32>,
+}
+
+impl LogContext {
+  pub fn new() -> Self { LogContext::default() }
+  /// Sets the player this message is about.
+  pub fn player(mut self, player: &player::Player) -> Self {
+    self.player = Some(player.id());
+    self
+  }
+  /// Sets the world this message is about.
+  pub fn world(mut self, world: &world::World) -> Self {
+    self.world = Some(world.wid());
+    self
+  }
+
+  #[doc(hidden)]
+  pub fn log(&self, record: &Record) {
+    if LOGGER.enabled(record.metadata()) {
+      send_log(record, Some(self));
+    }
+  }
+}
+
+/// Logs a message with a [`LogContext`]. This works the same as the `log`
+/// macros, but the first two arguments are the context and the level.
+///
+/// # Example
+///
+/// ```ignore
+/// let ctx = LogContext::new().player(&player);
+/// log_with!(ctx, Level::Info, "{} placed a block", player.username());
+/// ```
+#[macro_export]
+macro_rules! log_with {
+  ($ctx:expr, $lvl:expr, $($arg:tt)+) => {
+    $ctx.log(
+      &$crate::Record::builder()
+        .args(format_args!($($arg)+))
+        .level($lvl)
+        .target(module_path!())
+        .module_path_static(Some(module_path!()))
+        .file_static(Some(file!()))
+        .line(Some(line!()))
+        .build(),
+    )
+  };
+}
+
+fn send_log(record: &Record, context: Option<&LogContext>) {
+  let s = match record.args().as_str() {
+    Some(s) => std::borrow::Cow::Borrowed(s),
+    None => std::borrow::Cow::Owned(record.args().to_string()),
+  };
+  let module_path = record.module_path().unwrap_or("");
+  let file = record.file().unwrap_or("");
+  unsafe {
+    match context {
+      Some(ctx) => {
+        let cctx = bb_ffi::CLogContext {
+          player:     ctx.player.unwrap_or_default().into_ffi(),
+          has_player: bb_ffi::CBool::new(ctx.player.is_some()),
+          world:      ctx.world.map(|w| w as i32).unwrap_or(-1),
+        };
+        bb_ffi::bb_log_context(
+          record.level() as u32,
+          s.as_ptr(),
+          s.len() as u32,
+          record.target().as_ptr(),
+          record.target().len() as u32,
+          module_path.as_ptr(),
+          module_path.len() as u32,
+          file.as_ptr(),
+          file.len() as u32,
+          record.line().unwrap_or(0),
+          &cctx,
+        );
+      }
+      None => bb_ffi::bb_log(
+        record.level() as u32,
+        s.as_ptr(),
+        s.len() as u32,
+        record.target().as_ptr(),
+        record.target().len() as u32,
+        module_path.as_ptr(),
+        module_path.len() as u32,
+        file.as_ptr(),
+        file.len() as u32,
+        record.line().unwrap_or(0),
+      ),
+    }
+  }
+}
+
 pub fn init() {
   std::panic::set_hook(Box::new(|info| {
     let msg = if let Some(msg) = info.payload().downcast_ref::<&str>() {
@@ -1,6 +1,9 @@
 use crate::{math::Vec3, particle::Particle, world::World, FromFfi, IntoFfi};
-use bb_common::{math::FPos, util::UUID};
-use bb_ffi::CUUID;
+use bb_common::{
+  math::FPos,
+  util::{Chat, Effect, GameMode, UUID},
+};
+use bb_ffi::{CBool, CChat, CUUID};
 
 #[derive(Debug)]
 pub struct Player {
@@ -38,6 +41,15 @@ impl Player {
       cstr.into_string()
     }
   }
+  /// Sends a chat message to this player.
+  ///
+  /// This will do nothing if the player has logged off.
+  pub fn send_message(&self, message: Chat) {
+    unsafe {
+      let c_chat = CChat { message: bb_ffi::CStr::new(message.to_codes()) };
+      bb_ffi::bb_player_send_message(&self.id.into_ffi(), &c_chat);
+    }
+  }
   /// Spawns a particle for this player. Other players will not be able to see
   /// this particle.
   ///
@@ -63,4 +75,56 @@ impl Player {
       Vec3::from_ffi(*cpos)
     }
   }
+  /// Adds a status effect to this player, replacing any existing effect of the
+  /// same kind. `duration` is in ticks.
+  ///
+  /// This will do nothing if the player has logged off.
+  pub fn add_effect(&self, effect: Effect, amplifier: u8, duration: u32, particles: bool) {
+    unsafe {
+      bb_ffi::bb_player_add_effect(
+        &self.id.into_ffi(),
+        effect.id().into(),
+        amplifier.into(),
+        duration,
+        CBool::new(particles),
+      );
+    }
+  }
+  /// Removes a status effect from this player. Returns `true` if the player had
+  /// this effect.
+  pub fn remove_effect(&self, effect: Effect) -> bool {
+    unsafe { bb_ffi::bb_player_remove_effect(&self.id.into_ffi(), effect.id().into()) == 1 }
+  }
+  /// Returns this player's game mode.
+  ///
+  /// This will return survival if the player has logged off.
+  pub fn game_mode(&self) -> GameMode {
+    unsafe {
+      match bb_ffi::bb_player_game_mode(&self.id.into_ffi()) {
+        -1 => GameMode::Survival,
+        id => GameMode::from_id(id as u8),
+      }
+    }
+  }
+  /// Sets this player's game mode.
+  ///
+  /// This will do nothing if the player has logged off.
+  pub fn set_game_mode(&self, game_mode: GameMode) {
+    unsafe {
+      bb_ffi::bb_player_set_game_mode(&self.id.into_ffi(), game_mode.id().into());
+    }
+  }
+  /// Returns `true` if this player is vanished. Vanished players are hidden
+  /// from everyone else, but can still see other players.
+  pub fn is_vanished(&self) -> bool {
+    unsafe { bb_ffi::bb_player_vanished(&self.id.into_ffi()) == 1 }
+  }
+  /// Hides or shows this player for everyone else.
+  ///
+  /// This will do nothing if the player has logged off.
+  pub fn set_vanished(&self, vanished: bool) {
+    unsafe {
+      bb_ffi::bb_player_set_vanished(&self.id.into_ffi(), CBool::new(vanished));
+    }
+  }
 }
@@ -1,5 +1,9 @@
-use crate::{block, particle::Particle, player::Player, FromFfi, IntoFfi};
-use bb_common::math::{FPos, Pos, PosError};
+use crate::{block, entity::Entity, particle::Particle, player::Player, FromFfi, IntoFfi};
+use bb_common::{
+  math::{FPos, Pos, PosError},
+  util::Chat,
+};
+use bb_ffi::CChat;
 
 pub struct World {
   wid: u32,
@@ -7,6 +11,8 @@ pub struct World {
 
 impl World {
   pub fn new(wid: u32) -> Self { World { wid } }
+  /// Returns the id of this world.
+  pub fn wid(&self) -> u32 { self.wid }
 
   pub fn get_block(&self, pos: Pos) -> Result<block::Type, PosError> {
     unsafe {
@@ -31,12 +37,52 @@ impl World {
       bb_ffi::bb_world_set_block_kind(self.wid, &pos.into_ffi(), kind.id());
     }
   }
+  /// Fills the region between `min` and `max` (inclusive) with the default
+  /// type of `kind`. The corners can be passed in any order.
+  ///
+  /// Returns an error if any part of the region is outside the world, or if
+  /// the region is larger than the world's `max-fill-volume`.
+  pub fn fill(&self, min: Pos, max: Pos, kind: block::Kind) -> Result<(), PosError> {
+    let res =
+      unsafe { bb_ffi::bb_world_fill_kind(self.wid, &min.into_ffi(), &max.into_ffi(), kind.id()) };
+    match res {
+      -1 => Err(min.err("region is outside the world".into())),
+      -2 => Err(min.err(format!("block kind {kind:?} does not exist on the server"))),
+      -3 => Err(min.err("region is too large".into())),
+      _ => Ok(()),
+    }
+  }
+  /// Returns every player in this world. This is a snapshot, so players that
+  /// join or leave while iterating won't be added or removed.
   pub fn players(&self) -> impl Iterator<Item = Player> {
     unsafe {
       let players = Box::from_raw(bb_ffi::bb_world_players(self.wid)).into_vec();
       players.into_iter().map(Player::from_ffi)
     }
   }
+  /// Returns the number of entities in this world, including players.
+  pub fn entity_count(&self) -> u32 {
+    unsafe { bb_ffi::bb_world_entity_count(self.wid).max(0) as u32 }
+  }
+  /// Returns every entity (including players) within `radius` blocks of
+  /// `center`, closest first.
+  pub fn entities_in_radius(&self, center: FPos, radius: f64) -> Vec<Entity> {
+    unsafe {
+      let cpos = bb_ffi::CFPos { x: center.x(), y: center.y(), z: center.z() };
+      let ptr = bb_ffi::bb_world_entities_in_radius(self.wid, &cpos, radius);
+      if ptr.is_null() {
+        return vec![];
+      }
+      Box::from_raw(ptr).into_vec().into_iter().map(Entity::from_ffi).collect()
+    }
+  }
+  /// Sends a chat message to every player in this world.
+  pub fn broadcast(&self, message: Chat) {
+    unsafe {
+      let c_chat = CChat { message: bb_ffi::CStr::new(message.to_codes()) };
+      bb_ffi::bb_broadcast_world(self.wid, &c_chat);
+    }
+  }
   /// Spawns a particle in the world. Everyone in render distance will be able
   /// to see this particle.
   pub fn spawn_particle(&self, particle: Particle) {
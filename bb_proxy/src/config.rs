@@ -29,6 +29,20 @@ pub struct Config {
   /// The path to the icon.
   #[default("icon.png".into())]
   pub icon:               String,
+
+  /// The message shown under the server name in the server list. This uses
+  /// `&` color codes, like `&aBamboo &7-- &9A Minecraft server`. If this is
+  /// empty, the default message is shown.
+  pub motd:         String,
+  /// The max players shown in the server list. This is only for display, it
+  /// isn't enforced.
+  pub max_players:  i32,
+  /// Names shown when hovering over the player count in the server list.
+  pub sample:       Vec<String>,
+  /// Replaces the version name in the server list. Clients only show this
+  /// when they are on an unsupported version. If this is empty, the range of
+  /// supported versions is shown.
+  pub version_name: String,
 }
 
 #[derive(Clone, Debug, Config, Default, PartialEq)]
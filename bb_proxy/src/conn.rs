@@ -1,7 +1,7 @@
 use crate::{
   config,
   gnet::{cb as gcb, sb as gsb, tcp},
-  packet::{FromTcp, ToTcp, TypeConverter},
+  packet::{FromTcp, ToTcp, TypeConverter, WriteError},
   stream::PacketStream,
   Error, Result,
 };
@@ -21,12 +21,16 @@ use serde::{Deserialize, Serialize};
 use sha1::{Digest, Sha1};
 use std::{
   cell::RefCell,
+  collections::HashMap,
   convert::TryInto,
   fmt, io,
   io::{ErrorKind, Read, Write},
   net::SocketAddr,
   str::FromStr,
-  sync::Arc,
+  sync::{
+    atomic::{AtomicI32, Ordering},
+    Arc,
+  },
 };
 
 #[derive(Debug, Copy, Clone)]
@@ -70,7 +74,11 @@ pub struct Conn<'a, S> {
   /// Used in handshake. This will skip mojang auth and use a bungee-cord
   /// formatted login message for player info.
   forwarding:         config::Forwarding,
-  /// Used in handshake. This is different from `stream.compression`
+  /// The compression threshold sent to the client in the handshake. This is
+  /// the only threshold used on the client stream: backends talk to the proxy
+  /// over the uncompressed gnet protocol, so every packet is decoded from the
+  /// backend and encoded again with this threshold. This stays the same when
+  /// switching servers.
   compression_target: i32,
 
   /// Set when the connection is closed.
@@ -92,6 +100,16 @@ pub struct Conn<'a, S> {
 
   conv:           Arc<TypeConverter>,
   status_builder: Arc<dyn for<'b> Fn(&'b str, ProtocolVersion) -> JsonStatus<'b>>,
+  /// The number of clients in the play state, shared with every other
+  /// connection. This is shown in the server list.
+  online:         Arc<AtomicI32>,
+
+  /// The number of serverbound packets we've dropped, by packet name. Clients
+  /// send plenty of packets we don't handle, so these aren't errors.
+  dropped: HashMap<String, u32>,
+  /// The number of clientbound packets we've skipped, by packet name. These
+  /// are packets from the server that the proxy can't convert.
+  skipped: HashMap<&'static str, u32>,
 }
 thread_local! {
   // Used when reading from the server.
@@ -108,10 +126,20 @@ impl<S: fmt::Debug> fmt::Debug for Conn<'_, S> {
       .field("ver", &self.ver)
       .field("username", &self.username)
       .field("closed", &self.closed)
+      .field("dropped", &self.dropped)
+      .field("skipped", &self.skipped)
       .finish()
   }
 }
 
+impl<S> Drop for Conn<'_, S> {
+  fn drop(&mut self) {
+    if matches!(self.state, State::Play) {
+      self.online.fetch_sub(1, Ordering::Relaxed);
+    }
+  }
+}
+
 #[derive(Deserialize, Debug)]
 pub struct LoginInfo {
   pub id:         UUID,
@@ -190,7 +218,7 @@ impl<'a, S: PacketStream + Send + Sync> Conn<'a, S> {
       key,
       der_key,
       forwarding,
-      compression_target: 0,
+      compression_target: -1,
       closed: false,
       addr,
       server_stream: None,
@@ -199,6 +227,9 @@ impl<'a, S: PacketStream + Send + Sync> Conn<'a, S> {
       from_server: Vec::with_capacity(16 * 1024),
       conv,
       status_builder,
+      online: Arc::new(AtomicI32::new(0)),
+      dropped: HashMap::new(),
+      skipped: HashMap::new(),
     }
   }
   pub fn with_compression(mut self, compression_target: i32) -> Self {
@@ -209,9 +240,18 @@ impl<'a, S: PacketStream + Send + Sync> Conn<'a, S> {
     self.icon = icon;
     self
   }
+  /// Sets the counter of online players. This is incremented once the client
+  /// logs in, and decremented when this connection is dropped.
+  pub fn with_online_counter(mut self, online: Arc<AtomicI32>) -> Self {
+    self.online = online;
+    self
+  }
 
   pub fn ver(&self) -> ProtocolVersion { self.ver }
   pub fn closed(&self) -> bool { self.closed }
+  /// Returns the number of serverbound packets that have been dropped, by
+  /// packet name. Unknown packet ids are named by their id.
+  pub fn dropped_packets(&self) -> &HashMap<String, u32> { &self.dropped }
 
   fn connect_to_server(&mut self, reg: &Registry) -> Result<()> {
     info!("connecting to server at {:?}", self.addr);
@@ -225,6 +265,7 @@ impl<'a, S: PacketStream + Send + Sync> Conn<'a, S> {
         username: s.username.clone().unwrap(),
         uuid:     s.info.as_ref().unwrap().id,
         ver:      s.ver.id(),
+        addr:     s.client_stream.peer_addr().ok(),
       })?;
       Ok(())
     })
@@ -295,8 +336,27 @@ impl<'a, S: PacketStream + Send + Sync> Conn<'a, S> {
           self.from_server.drain(0..parsed);
           match common {
             ccb::Packet::SwitchServer(p) => self.switch_to(reg, p),
+            ccb::Packet::Raw(p) => {
+              debug!("passing through raw packet (tcp id: {0} {0:#x})", p.id);
+              let mut tcp = tcp::Packet::new(p.id, self.ver);
+              tcp.buf().write_buf(&p.data);
+              self.client_stream.write(tcp);
+              self.write_client()?;
+            }
             common => {
-              let packets = common.to_tcp(self).unwrap();
+              // Packets that can't be converted are skipped, so that a new packet on the
+              // server doesn't disconnect the client.
+              let packets = match common.to_tcp(self) {
+                Ok(v) => v,
+                Err(WriteError::Unsupported(kind)) => {
+                  self.skip_packet(kind);
+                  Default::default()
+                }
+                Err(e) => {
+                  warn!("{e}");
+                  Default::default()
+                }
+              };
               if len as usize != parsed {
                 return Err(io::Error::new(
                   ErrorKind::InvalidData,
@@ -366,10 +426,11 @@ impl<'a, S: PacketStream + Send + Sync> Conn<'a, S> {
     loop {
       match self.client_stream.read(self.ver) {
         Ok(Some(mut p)) => match self.state {
-          State::Play => {
-            let packet = gsb::Packet::from_tcp(&mut p, self.ver)?;
-            self.send_to_server(packet)?;
-          }
+          State::Play => match gsb::Packet::from_tcp(&mut p, self.ver) {
+            Ok(packet) => self.send_to_server(packet)?,
+            Err(Error::UnknownId { id, .. }) => self.drop_packet(format!("{id:#x}")),
+            Err(e) => return Err(e),
+          },
           _ => {
             self.handle_handshake(p, reg)?;
           }
@@ -404,6 +465,10 @@ impl<'a, S: PacketStream + Send + Sync> Conn<'a, S> {
       // An error here is for an unimplemented packet
       let common = match csb::Packet::from_tcp(p, s.ver, s.conv.as_ref()) {
         Ok(p) => p,
+        Err(Error::UnknownSB(p)) => {
+          s.drop_packet(gsb::tcp_name(p.tcp_id(s.ver) as i32, s.ver).into());
+          return Ok(());
+        }
         Err(e) => {
           warn!("{e}");
           return Ok(());
@@ -416,6 +481,26 @@ impl<'a, S: PacketStream + Send + Sync> Conn<'a, S> {
     })
   }
 
+  /// Drops a serverbound packet that we don't handle. This is logged the first
+  /// time each kind of packet is dropped.
+  fn drop_packet(&mut self, kind: String) {
+    let count = self.dropped.entry(kind.clone()).or_insert(0);
+    *count += 1;
+    if *count == 1 {
+      debug!("dropping unhandled serverbound packet {kind}");
+    }
+  }
+
+  /// Skips a clientbound packet that we can't convert. This is logged the first
+  /// time each kind of packet is skipped.
+  fn skip_packet(&mut self, kind: &'static str) {
+    let count = self.skipped.entry(kind).or_insert(0);
+    *count += 1;
+    if *count == 1 {
+      warn!("{}", WriteError::Unsupported(kind));
+    }
+  }
+
   fn write_data_to_server(
     &mut self,
     f: impl FnOnce(&mut Self, &mut MessageWriter<&mut Vec<u8>>) -> Result<()>,
@@ -445,8 +530,8 @@ impl<'a, S: PacketStream + Send + Sync> Conn<'a, S> {
   /// Sends the set compression packet, using self.compression_target. The
   /// stream will not be flushed.
   fn send_compression(&mut self) {
-    // Set compression, only if the thresh hold is non-zero
-    if self.compression_target != 0 {
+    // A threshold of -1 disables compression, and 0 compresses every packet.
+    if self.compression_target >= 0 {
       let mut out = tcp::Packet::new(3, self.ver());
       out.write_varint(self.compression_target);
       self.client_stream.write(out);
@@ -479,6 +564,7 @@ impl<'a, S: PacketStream + Send + Sync> Conn<'a, S> {
           username: s.username.clone().unwrap(),
           uuid:     s.info.as_ref().unwrap().id,
           ver:      s.ver.id(),
+          addr:     s.client_stream.peer_addr().ok(),
         })?;
         Ok(())
       }) {
@@ -522,6 +608,7 @@ impl<'a, S: PacketStream + Send + Sync> Conn<'a, S> {
     self.client_stream.write(out);
 
     self.state = State::Play;
+    self.online.fetch_add(1, Ordering::Relaxed);
     match self.connect_to_server(reg) {
       Ok(()) => {}
       Err(e) => {
@@ -580,8 +667,9 @@ impl<'a, S: PacketStream + Send + Sync> Conn<'a, S> {
     }
   }
 
-  /// Runs the entire login process with the client. If compression is 0, then
-  /// compression will not be enabled. The key should always be the server's
+  /// Runs the entire login process with the client. If the compression
+  /// threshold is -1, then compression will not be enabled, and if it is 0,
+  /// every packet will be compressed. The key should always be the server's
   /// private key, even if it won't be used. The der_key should be the der
   /// encoded public key. Although this function could just generate that from
   /// the private key, this is faster. Also, if the der_key is None, then
@@ -740,10 +828,8 @@ impl<'a, S: PacketStream + Send + Sync> Conn<'a, S> {
                 .into(),
               );
             }
-            let len = p.read_varint()?;
-            let received_secret = p.read_buf(len.try_into().unwrap())?;
-            let len = p.read_varint()?;
-            let received_token = p.read_buf(len.try_into().unwrap())?;
+            let received_secret = p.read_byte_arr()?;
+            let received_token = p.read_byte_arr()?;
 
             let decrypted_secret =
               self.key.decrypt(rsa::Pkcs1v15Encrypt, &received_secret).map_err(|e| {
@@ -11,6 +11,12 @@ pub enum Error {
   TransferWrite(bb_transfer::WriteError),
   UnknownCB(Box<cb::Packet>),
   UnknownSB(Box<sb::Packet>),
+  /// A packet id that doesn't exist on the given version.
+  UnknownId {
+    id:  i32,
+    ver: ProtocolVersion,
+    sb:  bool,
+  },
   ParseError {
     msg: &'static str,
     err: Box<dyn std::error::Error>,
@@ -35,6 +41,11 @@ impl fmt::Display for Error {
       Self::TransferWrite(e) => write!(f, "while writing to server: {e}"),
       Self::UnknownCB(p) => write!(f, "unknown clientbound packet {p:?}"),
       Self::UnknownSB(p) => write!(f, "unknown serverbound packet {p:?}"),
+      Self::UnknownId { id, ver, sb } => write!(
+        f,
+        "unknown {} packet id {id:#x} on version {ver:?}",
+        if *sb { "serverbound" } else { "clientbound" },
+      ),
       Self::ParseError { msg, err, id, ver, pos, sb } => {
         write!(
           f,
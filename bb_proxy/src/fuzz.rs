@@ -0,0 +1,244 @@
+//! A fuzz test for everything that parses data sent by clients. This doesn't
+//! need a fuzzer binary, so it runs with `cargo test`. It throws random bytes,
+//! and random packets built out of common field types, at the parsers, and
+//! makes sure they return an error instead of panicking.
+//!
+//! The RNG is seeded, so any failure here will happen every time. Any panic
+//! this finds is a bug, as a client could use it to crash the proxy.
+
+use crate::{
+  gnet::{sb as gsb, tcp},
+  packet::{FromTcp, TypeConverter},
+  stream::java::stream::{decompress, read_frame},
+};
+use bb_common::{
+  math::WyhashRng,
+  net::sb as csb,
+  util::{Buffer, UUID},
+  version::ProtocolVersion,
+};
+use miniz_oxide::deflate::compress_to_vec_zlib;
+use rand::{Rng, RngCore};
+use std::collections::VecDeque;
+
+/// Every version the proxy supports.
+pub(crate) const VERSIONS: &[ProtocolVersion] = &[
+  ProtocolVersion::V1_8,
+  ProtocolVersion::V1_9_4,
+  ProtocolVersion::V1_10_2,
+  ProtocolVersion::V1_11_2,
+  ProtocolVersion::V1_12_2,
+  ProtocolVersion::V1_14_4,
+  ProtocolVersion::V1_15_2,
+  ProtocolVersion::V1_16_5,
+  ProtocolVersion::V1_17_1,
+  ProtocolVersion::V1_18_2,
+  ProtocolVersion::V1_19_4,
+];
+
+/// Lengths and numbers that are likely to break things.
+const EDGE_CASES: &[i32] = &[0, 1, -1, 255, 256, 0x1fffff, 0x200000, i32::MAX, i32::MIN];
+
+fn rng() -> WyhashRng { WyhashRng::new(0x6261_6d62_6f6f) }
+
+fn number(rng: &mut WyhashRng) -> i32 {
+  if rng.gen_bool(0.5) {
+    EDGE_CASES[rng.gen_range(0..EDGE_CASES.len())]
+  } else {
+    rng.gen_range(-16..256)
+  }
+}
+
+/// Writes some NBT, which may or may not be valid.
+fn write_nbt(rng: &mut WyhashRng, buf: &mut Buffer<&mut Vec<u8>>, depth: u32) {
+  let ty = rng.gen_range(0..14);
+  buf.write_u8(ty);
+  match ty {
+    // Lists, and int and long arrays.
+    9 | 11 | 12 => {
+      if ty == 9 {
+        buf.write_u8(rng.gen_range(0..13));
+      }
+      buf.write_i32(number(rng));
+      for _ in 0..rng.gen_range(0..4) {
+        buf.write_i32(rng.gen());
+      }
+    }
+    10 if depth < 4 => {
+      for _ in 0..rng.gen_range(0..3) {
+        buf.write_u16(rng.gen_range(0..4));
+        write_nbt(rng, buf, depth + 1);
+      }
+      buf.write_u8(0);
+    }
+    _ => {
+      let mut data = vec![0; rng.gen_range(0..8)];
+      rng.fill_bytes(&mut data);
+      buf.write_buf(&data);
+    }
+  }
+}
+
+/// Writes a single field, using one of the types that packets are made of.
+fn write_field(rng: &mut WyhashRng, buf: &mut Buffer<&mut Vec<u8>>) {
+  match rng.gen_range(0..9) {
+    0 => buf.write_u8(rng.gen()),
+    1 => buf.write_bool(rng.gen()),
+    2 => buf.write_varint(number(rng)),
+    3 => buf.write_i32(number(rng)),
+    4 => buf.write_u64(rng.gen()),
+    5 => buf.write_f32(rng.gen()),
+    6 => buf.write_uuid(UUID::from_u128(rng.gen())),
+    // A length prefix, followed by a (probably) different number of bytes.
+    7 => {
+      buf.write_varint(number(rng));
+      let mut data = vec![0; rng.gen_range(0..16)];
+      rng.fill_bytes(&mut data);
+      buf.write_buf(&data);
+    }
+    // A slot. The pre-1.13 format is an i16 id, a u8 count, an i16 damage,
+    // and then NBT. The newer format is a bool, a varint id, a u8 count, and
+    // then NBT.
+    _ => {
+      if rng.gen() {
+        buf.write_i16(rng.gen_range(-1..512));
+      } else {
+        buf.write_bool(true);
+        buf.write_varint(number(rng));
+      }
+      buf.write_u8(rng.gen());
+      buf.write_i16(rng.gen());
+      let mut nbt = vec![];
+      write_nbt(rng, &mut Buffer::new(&mut nbt), 0);
+      buf.write_buf(&nbt);
+    }
+  }
+}
+
+/// Generates the body of a packet. This is either completely random, or a list
+/// of random fields.
+fn body(rng: &mut WyhashRng) -> Vec<u8> {
+  let mut out = vec![];
+  if rng.gen_bool(0.2) {
+    out.resize(rng.gen_range(0..64), 0);
+    rng.fill_bytes(&mut out);
+  } else {
+    let mut buf = Buffer::new(&mut out);
+    for _ in 0..rng.gen_range(0..8) {
+      write_field(rng, &mut buf);
+    }
+  }
+  out
+}
+
+/// Generates a packet id and body, without any length prefix.
+fn packet(rng: &mut WyhashRng) -> Vec<u8> {
+  let mut out = vec![];
+  let mut buf = Buffer::new(&mut out);
+  buf.write_varint(if rng.gen_bool(0.9) { rng.gen_range(0..0x40) } else { number(rng) });
+  buf.write_buf(&body(rng));
+  out
+}
+
+/// Parses a packet body the same way the connection does, and makes sure it
+/// doesn't panic. Errors are fine.
+fn parse(data: Vec<u8>, ver: ProtocolVersion, conv: &TypeConverter) {
+  let mut p = match tcp::Packet::from_buf(data, ver) {
+    Ok(p) => p,
+    Err(_) => return,
+  };
+  if let Ok(p) = gsb::Packet::from_tcp(&mut p, ver) {
+    let _ = csb::Packet::from_tcp(p, ver, conv);
+  }
+}
+
+#[test]
+fn fuzz_frames() {
+  let mut rng = rng();
+  for _ in 0..2000 {
+    let compression = if rng.gen() { -1 } else { rng.gen_range(0..512) };
+    let mut recv = VecDeque::new();
+    for _ in 0..rng.gen_range(1..8) {
+      let mut data = packet(&mut rng);
+      if compression >= 0 {
+        let mut framed = vec![];
+        let mut buf = Buffer::new(&mut framed);
+        match rng.gen_range(0..3) {
+          0 => buf.write_varint(0),
+          1 => {
+            buf.write_varint(data.len() as i32);
+            data = compress_to_vec_zlib(&data, 1);
+          }
+          // A length that doesn't match the data.
+          _ => buf.write_varint(number(&mut rng)),
+        }
+        buf.write_buf(&data);
+        data = framed;
+      }
+      let mut len = vec![];
+      let len_value = if rng.gen_bool(0.9) { data.len() as i32 } else { number(&mut rng) };
+      Buffer::new(&mut len).write_varint(len_value);
+      recv.extend(len);
+      recv.extend(data);
+    }
+    // Garbage at the end of the stream.
+    if rng.gen_bool(0.2) {
+      let mut data = vec![0; rng.gen_range(0..8)];
+      rng.fill_bytes(&mut data);
+      recv.extend(data);
+    }
+
+    let ver = VERSIONS[rng.gen_range(0..VERSIONS.len())];
+    loop {
+      let frame = match read_frame(&mut recv) {
+        Ok(Some(frame)) => frame,
+        Ok(None) | Err(_) => break,
+      };
+      let data = if compression >= 0 {
+        match decompress(frame, compression) {
+          Ok(data) => data,
+          Err(_) => break,
+        }
+      } else {
+        frame
+      };
+      if tcp::Packet::from_buf(data, ver).is_err() {
+        break;
+      }
+    }
+  }
+}
+
+#[test]
+fn fuzz_large_frames() {
+  // A length that is too large should fail right away, and a valid length with
+  // missing data should wait for the rest of the packet.
+  let mut recv = VecDeque::from(vec![0xff, 0xff, 0xff, 0xff, 0x07, 1, 2, 3]);
+  assert!(read_frame(&mut recv).is_err());
+  let mut recv = VecDeque::from(vec![0xff, 0xff, 0x7f, 1, 2, 3]);
+  assert!(matches!(read_frame(&mut recv), Ok(None)));
+  assert_eq!(recv.len(), 6);
+  // A varint longer than 5 bytes.
+  let mut recv = VecDeque::from(vec![0xff; 6]);
+  assert!(read_frame(&mut recv).is_err());
+
+  // A list with a huge length in a tiny packet shouldn't allocate anything
+  // large, and should fail once it runs out of data.
+  for len in [i32::MAX, -1] {
+    let mut data = vec![];
+    Buffer::new(&mut data).write_varint(len);
+    let mut p = tcp::Packet::from_buf_id(data, 0, ProtocolVersion::V1_19_4);
+    assert!(p.read_list(|p| p.read_u8()).is_err());
+  }
+}
+
+#[test]
+fn fuzz_serverbound() {
+  let mut rng = rng();
+  let conv = TypeConverter::new();
+  for &ver in VERSIONS {
+    for _ in 0..2000 {
+      parse(packet(&mut rng), ver, &conv);
+    }
+  }
+}
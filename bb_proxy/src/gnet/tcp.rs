@@ -44,15 +44,25 @@ fn item_from_nbt(
       let enchantments = data.enchantments_mut();
       for tag in tag.list()? {
         let t = tag.compound()?;
-        enchantments.insert(
-          conv.enchantment_to_new(t["id"].int()? as u32, ver.block()).unwrap(),
-          NonZeroU8::new(t["lvl"].int()? as u8).unwrap(),
-        );
+        let (id, lvl) = match (t.inner.get("id"), t.inner.get("lvl")) {
+          (Some(id), Some(lvl)) => (id.int()?, lvl.int()?),
+          _ => continue,
+        };
+        // This comes from the client, so we just ignore unknown enchantments, and
+        // enchantments with a level of 0.
+        if let (Some(id), Some(lvl)) =
+          (conv.enchantment_to_new(id as u32, ver.block()), NonZeroU8::new(lvl as u8))
+        {
+          enchantments.insert(id, lvl);
+        }
       }
     }
     if tag.inner.get("Unbreakable").map(|t| t.byte().map(|v| v != 0)) == Some(Ok(true)) {
       data.unbreakable = true;
     }
+    if let Some(Tag::Int(damage)) = tag.inner.get("Damage") {
+      data.damage = (*damage).max(0) as u32;
+    }
     if let Some(tag) = tag.inner.get("display") {
       let tag = tag.compound()?;
       if let Some(lore) = tag.inner.get("Lore") {
@@ -89,6 +99,10 @@ fn item_to_nbt(data: &ItemData, ver: ProtocolVersion, conv: &TypeConverter) -> N
   if data.unbreakable {
     tag.insert("Unbreakable", true);
   }
+  // 1.8-1.12 clients store this in the item damage instead.
+  if data.damage != 0 && ver >= ProtocolVersion::V1_13 {
+    tag.insert("Damage", Tag::Int(data.damage.min(i32::MAX as u32) as i32));
+  }
   let display = tag.get_or_create_compound("display");
   if !data.display.lore.is_empty() {
     let mut lore = vec![];
@@ -137,6 +151,8 @@ impl Packet {
       err: Box::new(e),
     }
   }
+  /// Returns an error for when this packet's id doesn't exist on its version.
+  pub fn unknown_id(&self) -> Error { Error::UnknownId { id: self.id, ver: self.ver, sb: self.sb } }
   pub fn serialize(self) -> Vec<u8> { self.buf.into_inner() }
 
   add_writer!(write_u8, u8);
@@ -180,11 +196,11 @@ impl Packet {
     self.buf().read_buf(len).map_err(|e| self.err(e, "read_buf"))
   }
   pub fn read_byte_arr(&mut self) -> Result<Vec<u8>> {
-    let len = self.read_varint()?.try_into().unwrap();
+    let len = self.buf().read_len().map_err(|e| self.err(e, "read_byte_arr"))?;
     self.buf().read_buf(len).map_err(|e| self.err(e, "read_byte_arr"))
   }
   pub fn read_byte_arr_max(&mut self, max: usize) -> Result<Vec<u8>> {
-    let len = self.read_varint()?.try_into().unwrap();
+    let len = self.buf().read_len().map_err(|e| self.err(e, "read_byte_arr_max"))?;
     if len > max {
       let err = self
         .buf()
@@ -238,10 +254,29 @@ impl Packet {
     }
   }
 
+  /// Reads a varint length prefix. This fails if the length is negative. The
+  /// length isn't checked against the rest of the packet, so anything that
+  /// allocates with it should use [`capacity`](Self::capacity).
+  fn read_len(&mut self, msg: &'static str) -> Result<usize> {
+    let len = self.read_varint()?;
+    match len.try_into() {
+      Ok(len) => Ok(len),
+      Err(_) => {
+        let e = self.buf().err(BufferErrorKind::NegativeLen(len), Mode::Reading);
+        Err(self.err(e, msg))
+      }
+    }
+  }
+  /// Returns the capacity to allocate for a collection with `len` elements.
+  /// Every element takes at least one byte, so we never allocate more than the
+  /// number of bytes left in the packet. This means a client can't make us
+  /// allocate gigabytes by sending a large length in a tiny packet.
+  fn capacity(&self, len: usize) -> usize { len.min(self.remaining()) }
+
   /// Reads a length prefixed array of integers.
   pub fn read_i32_arr(&mut self) -> Result<Vec<i32>> {
-    let len = self.read_varint()?.try_into().unwrap();
-    let mut out = Vec::with_capacity(len);
+    let len = self.read_len("read_i32_arr")?;
+    let mut out = Vec::with_capacity(self.capacity(len));
     for _ in 0..len {
       out.push(self.read_i32()?);
     }
@@ -315,7 +350,12 @@ impl Packet {
         self.write_i16(item.id() as i16);
         if item.id() != -1 {
           self.write_u8(item.count());
-          self.write_i16(item.damage);
+          // Items with durability never have a damage value for their variant.
+          if item.data.damage != 0 {
+            self.write_i16(item.data.damage.min(i16::MAX as u32) as i16);
+          } else {
+            self.write_i16(item.damage);
+          }
           NBT::serialize_buf(&item_to_nbt(&item.data, self.ver, conv), &mut self.buf);
         }
       }
@@ -341,8 +381,8 @@ impl Packet {
   /// Reads a list from the packet. This is new to 1.17, and simplifies a bunch
   /// of small for loops in previous versions.
   pub fn read_list<T>(&mut self, val: impl Fn(&mut Packet) -> Result<T>) -> Result<Vec<T>> {
-    let len = self.read_varint()?.try_into().unwrap();
-    let mut list = Vec::with_capacity(len);
+    let len = self.read_len("read_list")?;
+    let mut list = Vec::with_capacity(self.capacity(len));
     for _ in 0..len {
       list.push(val(self)?);
     }
@@ -363,14 +403,14 @@ impl Packet {
     val: impl Fn(&mut Packet) -> Result<T>,
     max: usize,
   ) -> Result<Vec<T>> {
-    let len: usize = self.read_varint()?.try_into().unwrap();
+    let len = self.read_len("read_list_max")?;
     if len > max {
       let e = self
         .buf()
         .err(BufferErrorKind::ArrayTooLong { len: len as u64, max: max as u64 }, Mode::Reading);
       return Err(self.err(e, "read_list_max"));
     }
-    let mut list = Vec::with_capacity(len);
+    let mut list = Vec::with_capacity(self.capacity(len));
     for _ in 0..len {
       list.push(val(self)?);
     }
@@ -384,8 +424,8 @@ impl Packet {
     key: impl Fn(&mut Packet) -> Result<K>,
     val: impl Fn(&mut Packet) -> Result<V>,
   ) -> Result<HashMap<K, V>> {
-    let len = self.read_varint()?.try_into().unwrap();
-    let mut map = HashMap::with_capacity(len);
+    let len = self.read_len("read_map")?;
+    let mut map = HashMap::with_capacity(self.capacity(len));
     for _ in 0..len {
       map.insert(key(self)?, val(self)?);
     }
@@ -411,8 +451,8 @@ impl Packet {
     &mut self,
     val: impl Fn(&mut Packet) -> Result<T>,
   ) -> Result<HashSet<T>> {
-    let len = self.read_varint()?.try_into().unwrap();
-    let mut set = HashSet::with_capacity(len);
+    let len = self.read_len("read_set")?;
+    let mut set = HashSet::with_capacity(self.capacity(len));
     for _ in 0..len {
       set.insert(val(self)?);
     }
@@ -433,14 +473,14 @@ impl Packet {
     val: impl Fn(&mut Packet) -> Result<T>,
     max: usize,
   ) -> Result<HashSet<T>> {
-    let len = self.read_varint()?.try_into().unwrap();
+    let len = self.read_len("read_set_max")?;
     if len > max {
       let e = self
         .buf()
         .err(BufferErrorKind::ArrayTooLong { len: len as u64, max: max as u64 }, Mode::Reading);
       return Err(self.err(e, "read_set_max"));
     }
-    let mut set = HashSet::with_capacity(len);
+    let mut set = HashSet::with_capacity(self.capacity(len));
     for _ in 0..len {
       set.insert(val(self)?);
     }
@@ -469,8 +509,8 @@ impl Packet {
   pub fn write_varint_arr(&mut self, v: &[i32]) { self.write_list(v, |p, &v| p.write_varint(v)) }
 
   pub fn read_bits(&mut self) -> Result<Vec<u64>> {
-    let longs = self.read_varint()?.try_into().unwrap();
-    let mut out = Vec::with_capacity(longs);
+    let longs = self.read_len("read_bits")?;
+    let mut out = Vec::with_capacity(self.capacity(longs));
     for _ in 0..longs {
       out.push(self.read_u64()?);
     }
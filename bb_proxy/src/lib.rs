@@ -8,6 +8,8 @@ extern crate smallvec;
 pub mod config;
 pub mod conn;
 mod error;
+#[cfg(test)]
+mod fuzz;
 pub mod gnet;
 pub mod packet;
 mod registry;
@@ -19,7 +21,10 @@ pub use error::{Error, Result};
 use base64::{engine::general_purpose, Engine};
 use bb_common::{
   math::der,
-  util::chat::{Chat, Color},
+  util::{
+    chat::{Chat, Color},
+    UUID,
+  },
   version::ProtocolVersion,
 };
 use config::Config;
@@ -30,7 +35,16 @@ use mio::{
 };
 use rand::rngs::OsRng;
 use rsa::RsaPrivateKey;
-use std::{collections::HashMap, io, io::Cursor, net::SocketAddr, sync::Arc};
+use std::{
+  collections::HashMap,
+  io,
+  io::Cursor,
+  net::SocketAddr,
+  sync::{
+    atomic::{AtomicI32, Ordering},
+    Arc,
+  },
+};
 
 use crate::{conn::Conn, packet::TypeConverter, stream::java::stream::JavaStream};
 
@@ -47,6 +61,54 @@ pub fn load_icon(path: &str) -> String {
   result
 }
 
+type StatusBuilder = Arc<dyn for<'a> Fn(&'a str, ProtocolVersion) -> JsonStatus<'a>>;
+
+/// Returns a status builder that uses the server list settings from `config`.
+/// `online` is the number of players connected through this proxy.
+fn config_status_builder(config: &Config, online: Arc<AtomicI32>) -> StatusBuilder {
+  let description = if config.motd.is_empty() {
+    let mut description = Chat::empty();
+    description.add("Bamboo").color(Color::BrightGreen);
+    description.add(" -- ").color(Color::Gray);
+    #[cfg(debug_assertions)]
+    description.add("Development mode").color(Color::Blue);
+    #[cfg(not(debug_assertions))]
+    description.add("Release mode").color(Color::Red);
+    description
+  } else {
+    Chat::from_legacy_with(&config.motd, '&')
+  };
+  let version_name = if config.version_name.is_empty() {
+    format!("1.8 - {}", ProtocolVersion::latest())
+  } else {
+    config.version_name.clone()
+  };
+  let max = config.max_players;
+  let sample = config.sample.clone();
+  Arc::new(move |icon, ver| JsonStatus {
+    version:     JsonVersion {
+      name:     version_name.clone(),
+      protocol: if ver == ProtocolVersion::Invalid {
+        ProtocolVersion::latest().id()
+      } else {
+        ver.id()
+      } as i32,
+    },
+    players:     JsonPlayers {
+      max,
+      online: online.load(Ordering::Relaxed),
+      // These are only names, so they all get the nil uuid, which clients won't
+      // try to look up.
+      sample: sample
+        .iter()
+        .map(|name| JsonPlayer { name: name.clone(), id: UUID::from_u128(0).as_dashed_str() })
+        .collect(),
+    },
+    description: description.clone(),
+    favicon:     icon,
+  })
+}
+
 const JAVA_LISTENER: Token = Token(0xffffffff);
 const BEDROCK_LISTENER: Token = Token(0xfffffffe);
 
@@ -83,12 +145,15 @@ pub struct Proxy {
   forwarding:     config::Forwarding,
   compression:    i32,
   conv:           Arc<TypeConverter>,
-  status_builder: Arc<dyn for<'a> Fn(&'a str, ProtocolVersion) -> JsonStatus<'a>>,
+  status_builder: StatusBuilder,
+  /// The number of clients that have logged in through this proxy.
+  online:         Arc<AtomicI32>,
 }
 
 impl Proxy {
   /// Creates a proxy with default settings.
   pub fn new(addr: SocketAddr, server_addr: SocketAddr) -> Self {
+    let online = Arc::new(AtomicI32::new(0));
     Proxy {
       icon: None,
       key: Arc::new(RsaPrivateKey::new(&mut OsRng, 1024).expect("failed to generate a key")),
@@ -98,28 +163,8 @@ impl Proxy {
       forwarding: config::Forwarding::default(),
       compression: 256,
       conv: Arc::new(TypeConverter::new()),
-      status_builder: Arc::new(|icon, ver| {
-        let mut description = Chat::empty();
-        description.add("Bamboo").color(Color::BrightGreen);
-        description.add(" -- ").color(Color::Gray);
-        #[cfg(debug_assertions)]
-        description.add("Development mode").color(Color::Blue);
-        #[cfg(not(debug_assertions))]
-        description.add("Release mode").color(Color::Red);
-        JsonStatus {
-          version: JsonVersion {
-            name:     format!("1.8 - {}", ProtocolVersion::latest()),
-            protocol: if ver == ProtocolVersion::Invalid {
-              ProtocolVersion::latest().id()
-            } else {
-              ver.id()
-            } as i32,
-          },
-          players: JsonPlayers { max: 0, online: 0, sample: vec![] },
-          description,
-          favicon: icon,
-        }
-      }),
+      status_builder: config_status_builder(&Config::default(), online.clone()),
+      online,
     }
     .with_encryption(true)
   }
@@ -129,8 +174,10 @@ impl Proxy {
       return Err(Error::Bungeecord("cannot enable forwarding and encryption"));
     }
 
+    let proxy = Self::new(config.address.parse()?, config.server.parse()?);
+    let status_builder = config_status_builder(&config, proxy.online.clone());
     Ok(
-      Self::new(config.address.parse()?, config.server.parse()?)
+      Proxy { status_builder, ..proxy }
         .with_encryption(config.encryption)
         .with_forwarding(config.forwarding)
         .with_compression(config.compression_thresh)
@@ -192,7 +239,8 @@ impl Proxy {
       self.forwarding.clone(),
       self.status_builder.clone(),
     )
-    .with_compression(self.compression);
+    .with_compression(self.compression)
+    .with_online_counter(self.online.clone());
     if let Some(icon) = &self.icon {
       conn.with_icon(icon)
     } else {
@@ -331,13 +379,20 @@ impl<'listener: 'b, 'b> TokenHandler<'listener, 'b> {
   pub fn handle_bool(&mut self, res: Result<bool>) {
     match res {
       Ok(false) => {}
-      Ok(true) => {
-        self.clients.remove(&self.token);
-      }
+      Ok(true) => self.remove(),
       Err(e) => self.handle_err(e),
     }
   }
 
+  /// Removes the client, and logs the packets we dropped from it.
+  fn remove(&mut self) {
+    if let Some(conn) = self.clients.remove(&self.token) {
+      if !conn.dropped_packets().is_empty() {
+        debug!("client {:?} sent unhandled packets: {:?}", self.token, conn.dropped_packets());
+      }
+    }
+  }
+
   /// Logs any errors that need to be logged, and removes the client if needed.
   fn handle_err(&mut self, e: Error) {
     let remove = match e.io_kind() {
@@ -352,7 +407,7 @@ impl<'listener: 'b, 'b> TokenHandler<'listener, 'b> {
       }
     };
     if remove {
-      self.clients.remove(&self.token);
+      self.remove();
     }
   }
 }
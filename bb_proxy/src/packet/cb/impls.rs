@@ -282,6 +282,29 @@ to_tcp!(CollectItem => (self, conn, ver) {
     })
   }
 });
+to_tcp!(Disconnect => (self, conn, _ver) {
+  gpacket!(Disconnect V8 { reason: self.reason.to_json() })
+});
+to_tcp!(EntityEffect => (self, conn, ver) {
+  if ver >= ProtocolVersion::V1_9 {
+    // 0x02 shows particles, and 0x04 shows the icon in the inventory.
+    gpacket!(EntityEffect V9 {
+      entity_id: self.eid,
+      effect_id: self.effect.id() as i8,
+      amplifier: self.amplifier as i8,
+      duration: self.duration,
+      flags: if self.particles { 0x06 } else { 0x04 },
+    })
+  } else {
+    gpacket!(EntityEffect V8 {
+      entity_id: self.eid,
+      effect_id: self.effect.id() as i8,
+      amplifier: self.amplifier as i8,
+      duration: self.duration,
+      hide_particles: !self.particles,
+    })
+  }
+});
 to_tcp!(EntityEquipment => (mut self, conn, ver) {
   use bb_common::net::cb::{ArmorSlot, EquipmentSlot};
 
@@ -486,9 +509,15 @@ to_tcp!(JoinGame => (self, conn, ver) {
 
     // List of worlds
     buf.write_varint(1);
-    buf.write_str("minecraft:overworld");
+    buf.write_str(self.dimension.key());
 
-    crate::registry::write_codec(&mut buf, ver, self.world_min_y, self.world_height);
+    crate::registry::write_codec(
+      &mut buf,
+      ver,
+      self.dimension,
+      self.world_min_y,
+      self.world_height,
+    );
 
     // Hashed world seed, used for biomes client side.
     buf.write_u64(0);
@@ -497,8 +526,7 @@ to_tcp!(JoinGame => (self, conn, ver) {
 
     buf.write_varint(self.view_distance.into());
     if ver >= ProtocolVersion::V1_18 {
-      // The simulation distance
-      buf.write_varint(self.view_distance.into());
+      buf.write_varint(self.simulation_distance.into());
     }
     buf.write_bool(self.reduced_debug_info);
     buf.write_bool(self.enable_respawn_screen);
@@ -509,7 +537,7 @@ to_tcp!(JoinGame => (self, conn, ver) {
       buf.write_option(&None, |_, _: &()| {});
     }
   } else if ver >= ProtocolVersion::V1_15_2 {
-    buf.write_i32(self.dimension.into());
+    buf.write_i32(self.dimension.id().into());
     // Hashed world seed, used for biomes
     buf.write_u64(0);
     // Max players (ignored)
@@ -520,7 +548,7 @@ to_tcp!(JoinGame => (self, conn, ver) {
     buf.write_bool(self.reduced_debug_info);
     buf.write_bool(self.enable_respawn_screen);
   } else if ver >= ProtocolVersion::V1_14_4 {
-    buf.write_i32(self.dimension.into());
+    buf.write_i32(self.dimension.id().into());
     // Max players (ignored)
     buf.write_u8(0);
     // World type
@@ -536,7 +564,7 @@ to_tcp!(JoinGame => (self, conn, ver) {
       entity_id:     self.eid,
       hardcore_mode: self.hardcore_mode,
       game_type:     self.game_mode.id(),
-      dimension:     self.dimension.into(),
+      dimension:     self.dimension.id().into(),
       difficulty:    self.difficulty.into(),
       max_players:   0,
       world_type:    self.level_type,
@@ -546,7 +574,7 @@ to_tcp!(JoinGame => (self, conn, ver) {
       player_id:     self.eid,
       hardcore_mode: self.hardcore_mode,
       game_type:     self.game_mode.id(),
-      dimension:     self.dimension.into(),
+      dimension:     self.dimension.id().into(),
       difficulty:    self.difficulty.into(),
       max_players:   0,
       world_type:    self.level_type,
@@ -775,7 +803,10 @@ to_tcp!(PlaySound => (self, conn, ver) {
 });
 to_tcp!(PluginMessage => (self, conn, ver) {
   // No length prefix for data, it is inferred from packet length.
-  if ver < ProtocolVersion::V1_14_4 {
+  if ver < ProtocolVersion::V1_13 {
+    let channel = crate::packet::channel_to_legacy(&self.channel);
+    gpacket!(CustomPayload V8 { channel, unknown: self.data, v_2: 0 })
+  } else if ver < ProtocolVersion::V1_14_4 {
     gpacket!(CustomPayload V8 { channel: self.channel, unknown: self.data, v_2: 0 })
   } else {
     gpacket!(CustomPayload V14 { channel: self.channel, unknown: self.data, v_2: 0 })
@@ -791,14 +822,38 @@ to_tcp!(RemoveEntities => (self, conn, ver) {
     gpacket!(DestroyEntities V8 { unknown: data })
   }
 });
+to_tcp!(RemoveEntityEffect => (self, conn, _ver) {
+  gpacket!(RemoveEntityEffect V8 { entity_id: self.eid, effect_id: self.effect.id() as i8 })
+});
+to_tcp!(ResourcePack => (self, conn, ver) {
+  if ver >= ProtocolVersion::V1_17_1 {
+    let mut data = vec![];
+    let mut buf = Buffer::new(&mut data);
+    buf.write_option(&self.prompt, |buf, msg| buf.write_str(&msg.to_json()));
+    gpacket!(ResourcePackSend V17 {
+      url:      self.url,
+      hash:     self.hash,
+      required: self.forced,
+      unknown:  data,
+    })
+  } else {
+    gpacket!(ResourcePackSend V8 { url: self.url, hash: self.hash })
+  }
+});
 to_tcp!(Respawn => (self, conn, ver) {
   if ver >= ProtocolVersion::V1_14 {
     let mut data = vec![];
     let mut buf = Buffer::new(&mut data);
-    buf.write_i32(self.dimension.into());
+    buf.write_i32(self.dimension.id().into());
     if ver >= ProtocolVersion::V1_16_5 {
-      crate::registry::write_single_dimension(&mut buf, ver, 0, 256);
-      buf.write_str("minecraft:overworld");
+      crate::registry::write_single_dimension(
+        &mut buf,
+        ver,
+        self.dimension,
+        self.world_min_y,
+        self.world_height,
+      );
+      buf.write_str(self.dimension.key());
     }
     if ver >= ProtocolVersion::V1_15_2 {
       // hashed seed, same as join game
@@ -819,7 +874,7 @@ to_tcp!(Respawn => (self, conn, ver) {
     })
   } else {
     gpacket!(Respawn V8 {
-      dimension_id: self.dimension.into(),
+      dimension_id: self.dimension.id().into(),
       difficulty:   self.difficulty.into(),
       game_type:    self.game_mode.id(),
       world_type:   self.level_type,
@@ -854,7 +909,9 @@ to_tcp!(ScoreboardObjective => (self, conn, ver) {
   match self.mode {
     ObjectiveAction::Create { value, ty } | ObjectiveAction::Update { value, ty } => {
       if ver <= ProtocolVersion::V1_12_2 {
-        buf.write_str(&value.to_codes());
+        // Older clients will disconnect if the display name is longer than 32
+        // characters.
+        buf.write_str(&value.to_codes().chars().take(32).collect::<String>());
       } else {
         buf.write_str(&value.to_json());
       }
@@ -915,8 +972,14 @@ to_tcp!(ScoreboardUpdate => (self, conn, ver) {
       ScoreboardAction::Create(score) => buf.write_varint(score),
       ScoreboardAction::Remove => {}
     }
+    // Entries are limited to 40 characters before 1.13.
+    let name = if ver < ProtocolVersion::V1_13 {
+      self.username.chars().take(40).collect()
+    } else {
+      self.username
+    };
     gpacket!(UpdateScore V8 {
-      name:      self.username,
+      name,
       objective: self.objective,
       action:    match self.action {
         ScoreboardAction::Create(_) => 0,
@@ -926,6 +989,13 @@ to_tcp!(ScoreboardUpdate => (self, conn, ver) {
     })
   }
 });
+to_tcp!(SetExperience => (self, conn, _ver) {
+  gpacket!(SetExperience V8 {
+    experience_bar: self.bar,
+    level: self.level,
+    total_experience: self.total,
+  })
+});
 to_tcp!(SetPosLook => (self, conn, ver) {
   let mut data = vec![];
   let mut buf = Buffer::new(&mut data);
@@ -1114,6 +1184,14 @@ to_tcp!(SpawnEntity => (self, conn, ver) {
     }
   }
 });
+to_tcp!(SpawnPosition => (self, conn, ver) {
+  if ver >= ProtocolVersion::V1_17_1 {
+    // The angle is only used for the direction the player faces when spawning.
+    gpacket!(SpawnPosition V17 { pos: self.pos, angle: 0.0 })
+  } else {
+    gpacket!(SpawnPosition V8 { spawn_block_pos: self.pos })
+  }
+});
 to_tcp!(SpawnPlayer => (self, conn, ver) {
   if ver >= ProtocolVersion::V1_15_2 {
     let spawn = gpacket!(SpawnPlayer V15 {
@@ -1201,6 +1279,21 @@ to_tcp_manual!(Tags => (self, conn, ver) {
 to_tcp!(Title => (self, conn, ver) {
   use bb_common::net::cb::TitleAction;
 
+  if let TitleAction::ActionBar(msg) = self.action {
+    // 1.17 split titles into multiple packets, and added a dedicated action bar
+    // packet. The title packet only supports action bars from 1.11 onwards.
+    // Before that, position 2 in the chat packet does the same thing.
+    if ver >= ProtocolVersion::V1_17_1 {
+      return Ok(smallvec![gpacket!(OverlayMessage V17 { message: msg.to_json() })]);
+    }
+    if ver < ProtocolVersion::V1_11 {
+      return packet::ChatMessage { msg, ty: 2 }.to_tcp(conn);
+    }
+    let mut data = vec![];
+    let mut buf = Buffer::new(&mut data);
+    buf.write_str(&msg.to_json());
+    return Ok(smallvec![gpacket!(Title V8 { ty: 2, unknown: data })]);
+  }
   if ver >= ProtocolVersion::V1_17_1 {
     match self.action {
       TitleAction::Title(chat) => gpacket!(Title V17 { title: chat.to_json() }),
@@ -1211,6 +1304,7 @@ to_tcp!(Title => (self, conn, ver) {
         fade_out_ticks: fade_out as i32,
       }),
       TitleAction::Clear(reset) => gpacket!(ClearTitle V17 { reset }),
+      TitleAction::ActionBar(_) => unreachable!(),
     }
   } else {
     let mut data = vec![];
@@ -1226,10 +1320,12 @@ to_tcp!(Title => (self, conn, ver) {
       _ => {}
     }
     gpacket!(Title V8 {
-      ty:      if ver >= ProtocolVersion::V1_12_2 {
+      // 1.11 added the action bar as id 2, which shifted everything after it.
+      ty:      if ver >= ProtocolVersion::V1_11 {
         match self.action {
           TitleAction::Title(_) => 0,
           TitleAction::Subtitle(_) => 1,
+          TitleAction::ActionBar(_) => 2,
           TitleAction::Times { .. } => 3,
           TitleAction::Clear(false) => 4,
           TitleAction::Clear(true) => 5,
@@ -1241,6 +1337,7 @@ to_tcp!(Title => (self, conn, ver) {
           TitleAction::Times { .. } => 2,
           TitleAction::Clear(false) => 3,
           TitleAction::Clear(true) => 4,
+          TitleAction::ActionBar(_) => unreachable!(),
         }
       },
       unknown: data,
@@ -1255,6 +1352,15 @@ to_tcp!(Teams => (self, conn, ver) {
   fn write_entities(buf: &mut Buffer<&mut Vec<u8>>, entities: &[String]) {
     buf.write_list(entities, |buf, n| buf.write_str(n.as_str()));
   }
+  /// Before 1.13, the prefix and postfix are limited to 16 characters, and the
+  /// client will disconnect if they are any longer.
+  fn truncate_team_text(ver: ProtocolVersion, text: String, len: usize) -> String {
+    if ver < ProtocolVersion::V1_13 {
+      text.chars().take(len).collect()
+    } else {
+      text
+    }
+  }
   fn write_info(ver: ProtocolVersion, buf: &mut Buffer<&mut Vec<u8>>, info: &TeamInfo) {
     if ver >= ProtocolVersion::V1_14_4 {
       buf.write_str(&info.display_name.to_json());
@@ -1270,7 +1376,7 @@ to_tcp!(Teams => (self, conn, ver) {
       });
       buf.write_str(match info.collisions {
         TeamRule::Always => "always",
-        TeamRule::ForOtherTeams => "pushrOtherTeams",
+        TeamRule::ForOtherTeams => "pushOtherTeams",
         TeamRule::ForOwnTeam => "pushOwnTeam",
         TeamRule::Never => "never",
       });
@@ -1281,11 +1387,11 @@ to_tcp!(Teams => (self, conn, ver) {
       buf.write_str(&info.display_name.to_codes());
       // Team colors are broken. This code makes titles match the functionality of
       // 1.14+ clients.
-      let mut prefix = info.prefix.to_codes();
+      let mut prefix = truncate_team_text(ver, info.prefix.to_codes(), 14);
       prefix.push(chat::CODE_SEP);
       prefix.push(info.color.code());
       buf.write_str(&prefix);
-      buf.write_str(&info.postfix.to_codes());
+      buf.write_str(&truncate_team_text(ver, info.postfix.to_codes(), 16));
       buf.write_u8(
         if info.friendly_fire { 0x01 } else { 0x00 }
           | if info.see_invis { 0x02 } else { 0x00 },
@@ -1298,7 +1404,7 @@ to_tcp!(Teams => (self, conn, ver) {
       });
       buf.write_str(match info.collisions {
         TeamRule::Always => "always",
-        TeamRule::ForOtherTeams => "pushrOtherTeams",
+        TeamRule::ForOtherTeams => "pushOtherTeams",
         TeamRule::ForOwnTeam => "pushOwnTeam",
         TeamRule::Never => "never",
       });
@@ -1309,11 +1415,11 @@ to_tcp!(Teams => (self, conn, ver) {
       buf.write_str(&info.display_name.to_codes());
       // Team colors are broken. This code makes titles match the functionality of
       // 1.14+ clients.
-      let mut prefix = info.prefix.to_codes();
+      let mut prefix = truncate_team_text(ver, info.prefix.to_codes(), 14);
       prefix.push(chat::CODE_SEP);
       prefix.push(info.color.code());
       buf.write_str(&prefix);
-      buf.write_str(&info.postfix.to_codes());
+      buf.write_str(&truncate_team_text(ver, info.postfix.to_codes(), 16));
       buf.write_u8(
         if info.friendly_fire { 0x01 } else { 0x00 }
           | if info.see_invis { 0x02 } else { 0x00 },
@@ -1478,3 +1584,18 @@ to_tcp!(WindowItem => (mut self, conn, ver) {
     })
   }
 });
+to_tcp!(WindowProperty => (self, conn, ver) {
+  if ver >= ProtocolVersion::V1_16_5 {
+    gpacket!(ScreenHandlerPropertyUpdate V16 {
+      sync_id:     self.wid.into(),
+      property_id: self.property.into(),
+      value:       self.value.into(),
+    })
+  } else {
+    gpacket!(WindowProperty V8 {
+      window_id: self.wid.into(),
+      var_index: self.property.into(),
+      var_value: self.value.into(),
+    })
+  }
+});
@@ -9,12 +9,16 @@ mod impls;
 #[derive(Debug, Clone)]
 pub enum WriteError {
   InvalidVer,
+  /// The proxy doesn't know how to convert this packet. This contains the
+  /// name of the packet, from [`Packet::name`].
+  Unsupported(&'static str),
 }
 
 impl fmt::Display for WriteError {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     match self {
       Self::InvalidVer => write!(f, "invalid version"),
+      Self::Unsupported(p) => write!(f, "cannot convert {p} into generated packet"),
     }
   }
 }
@@ -42,6 +46,8 @@ impl ToTcp for Packet {
       Packet::ChatMessage(p) => p.to_tcp(conn),
       Packet::CommandList(p) => p.to_tcp(conn),
       Packet::CollectItem(p) => p.to_tcp(conn),
+      Packet::Disconnect(p) => p.to_tcp(conn),
+      Packet::EntityEffect(p) => p.to_tcp(conn),
       Packet::EntityEquipment(p) => p.to_tcp(conn),
       Packet::EntityHeadLook(p) => p.to_tcp(conn),
       Packet::EntityLook(p) => p.to_tcp(conn),
@@ -60,13 +66,17 @@ impl ToTcp for Packet {
       Packet::PlaySound(p) => p.to_tcp(conn),
       Packet::PluginMessage(p) => p.to_tcp(conn),
       Packet::RemoveEntities(p) => p.to_tcp(conn),
+      Packet::RemoveEntityEffect(p) => p.to_tcp(conn),
+      Packet::ResourcePack(p) => p.to_tcp(conn),
       Packet::Respawn(p) => p.to_tcp(conn),
       Packet::ScoreboardDisplay(p) => p.to_tcp(conn),
       Packet::ScoreboardObjective(p) => p.to_tcp(conn),
       Packet::ScoreboardUpdate(p) => p.to_tcp(conn),
+      Packet::SetExperience(p) => p.to_tcp(conn),
       Packet::SetPosLook(p) => p.to_tcp(conn),
       Packet::SpawnEntity(p) => p.to_tcp(conn),
       Packet::SpawnPlayer(p) => p.to_tcp(conn),
+      Packet::SpawnPosition(p) => p.to_tcp(conn),
       Packet::Tags(p) => p.to_tcp(conn),
       Packet::Title(p) => p.to_tcp(conn),
       Packet::Teams(p) => p.to_tcp(conn),
@@ -76,7 +86,8 @@ impl ToTcp for Packet {
       Packet::WindowOpen(p) => p.to_tcp(conn),
       Packet::WindowItems(p) => p.to_tcp(conn),
       Packet::WindowItem(p) => p.to_tcp(conn),
-      _ => todo!("convert {:?} into generated packet", self),
+      Packet::WindowProperty(p) => p.to_tcp(conn),
+      _ => Err(WriteError::Unsupported(self.name())),
     }
   }
 }
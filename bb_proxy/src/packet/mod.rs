@@ -1,5 +1,6 @@
 use crate::gnet::cb::Packet;
 use bb_common::{
+  chunk::paletted::Section,
   math::ChunkPos,
   version::{BlockVersion, ProtocolVersion},
 };
@@ -54,15 +55,12 @@ pub fn multi_block_change(
   ver: ProtocolVersion,
   conv: &TypeConverter,
 ) -> Packet {
-  match ver.block() {
-    BlockVersion::V1_8 | BlockVersion::V1_9 | BlockVersion::V1_12 => {
-      v1_8::multi_block_change(pos, y, changes, ver, conv)
-    }
-    // ProtocolVersion::V1_13 => v1_13::serialize_chunk(pos, bit_map, &sections, conv),
-    // BlockVersion::V1_14 => v1_14::chunk(pos, full, bit_map, &sections, conv),
-    // ProtocolVersion::V1_15 => v1_15::serialize_chunk(pos, c),
-    // ProtocolVersion::V1_16 => v1_16::serialize_chunk(pos, c),
-    _ => todo!("multi block change on version {}", ver),
+  // The records didn't change from 1.8 until 1.16.2, where the packet was
+  // changed to cover a single chunk section.
+  if ver >= ProtocolVersion::V1_16_2 {
+    v1_16::multi_block_change(pos, y, changes, ver, conv)
+  } else {
+    v1_8::multi_block_change(pos, y, changes, ver, conv)
   }
 }
 
@@ -76,10 +74,25 @@ impl ChunkWithPos {
       .sections
       .iter()
       .enumerate()
-      .filter(|(_, section)| section.is_some())
+      .filter(|(_, section)| matches!(section, Some(s) if self.should_send(s)))
       .map(|(y, _)| 1 << y)
       .sum()
   }
+
+  /// Returns the sections that should be sent to 1.8-1.17 clients, from the
+  /// bottom of the world up. These match the bits in
+  /// [`old_bit_map`](Self::old_bit_map).
+  pub fn old_sections(&self) -> impl Iterator<Item = &Section> + '_ {
+    self.packet.sections.iter().flatten().filter(|s| self.should_send(s))
+  }
+
+  /// Returns true if the given section needs to be sent. Sections that only
+  /// contain air can be skipped in full chunks, as the client treats missing
+  /// sections as air. Partial chunks still need to send them, so that the
+  /// client replaces the old section.
+  fn should_send(&self, section: &Section) -> bool {
+    !self.packet.full || section.non_air_blocks() != 0
+  }
 }
 
 impl std::ops::Deref for ChunkWithPos {
@@ -87,3 +100,94 @@ impl std::ops::Deref for ChunkWithPos {
 
   fn deref(&self) -> &Self::Target { &self.packet }
 }
+
+/// Converts a namespaced plugin message channel into the channel name used by
+/// pre-1.13 clients. Channels without a legacy name are left unchanged.
+pub fn channel_to_legacy(channel: &str) -> String {
+  match channel {
+    "minecraft:brand" => "MC|Brand".into(),
+    "minecraft:register" => "REGISTER".into(),
+    "minecraft:unregister" => "UNREGISTER".into(),
+    "bungeecord:main" => "BungeeCord".into(),
+    _ => channel.into(),
+  }
+}
+
+/// Converts a plugin message channel from a pre-1.13 client into a namespaced
+/// channel. Unknown channels are passed through unchanged, as there is no way
+/// to know which namespace they belong to.
+pub fn channel_from_legacy(channel: &str) -> String {
+  match channel {
+    "MC|Brand" => "minecraft:brand".into(),
+    "REGISTER" => "minecraft:register".into(),
+    "UNREGISTER" => "minecraft:unregister".into(),
+    "BungeeCord" => "bungeecord:main".into(),
+    _ => channel.into(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{fuzz::VERSIONS, gnet::tcp};
+  use bb_common::util::Buffer;
+
+  #[test]
+  fn multi_block_change_versions() {
+    let conv = TypeConverter::new();
+    let pos = ChunkPos::new(-3, 5);
+    // Block id 1 at x 1, y 2, z 3, in section 4. This is how the server packs
+    // each change.
+    let change = 1 << 12 | 1 << 8 | 2 << 4 | 3;
+    for &ver in VERSIONS {
+      let p = multi_block_change(pos, 4, vec![change], ver, &conv);
+      // This panics if the packet doesn't exist on `ver`.
+      let id = p.tcp_id(ver);
+      let mut tcp = tcp::Packet::new(id as i32, ver);
+      p.to_tcp(&mut tcp);
+      let old_id = conv.block_to_old(1, ver.block()) as i32;
+
+      let mut buf = Buffer::new(tcp.serialize());
+      assert_eq!(buf.read_varint().unwrap(), id as i32);
+      if ver >= ProtocolVersion::V1_16_2 {
+        // 22 bits for X, 22 bits for Z, then 20 bits for Y, all signed.
+        let section = buf.read_u64().unwrap() as i64;
+        assert_eq!(section >> 42, -3, "{ver}");
+        assert_eq!(section << 22 >> 42, 5, "{ver}");
+        assert_eq!(section << 44 >> 44, 4, "{ver}");
+        assert!(!buf.read_bool().unwrap());
+        assert_eq!(buf.read_varint().unwrap(), 1);
+        // This is a varlong, but small values are encoded the same as a varint.
+        let record = buf.read_varint().unwrap();
+        assert_eq!(record >> 12, old_id, "{ver}");
+        assert_eq!(record >> 8 & 0xf, 1, "{ver}");
+        assert_eq!(record >> 4 & 0xf, 3, "{ver}");
+        assert_eq!(record & 0xf, 2, "{ver}");
+      } else {
+        assert_eq!(buf.read_i32().unwrap(), -3);
+        assert_eq!(buf.read_i32().unwrap(), 5);
+        assert_eq!(buf.read_varint().unwrap(), 1);
+        assert_eq!(buf.read_u8().unwrap(), 1 << 4 | 3);
+        assert_eq!(buf.read_u8().unwrap(), 4 * 16 + 2);
+        assert_eq!(buf.read_varint().unwrap(), old_id, "{ver}");
+      }
+    }
+  }
+
+  #[test]
+  fn legacy_channels() {
+    for (name, legacy) in [
+      ("minecraft:brand", "MC|Brand"),
+      ("minecraft:register", "REGISTER"),
+      ("minecraft:unregister", "UNREGISTER"),
+      ("bungeecord:main", "BungeeCord"),
+    ] {
+      assert_eq!(channel_to_legacy(name), legacy);
+      assert_eq!(channel_from_legacy(legacy), name);
+    }
+    // Channels without a legacy name are passed through both ways.
+    assert_eq!(channel_to_legacy("myplugin:data"), "myplugin:data");
+    assert_eq!(channel_from_legacy("myplugin:data"), "myplugin:data");
+    assert_eq!(channel_from_legacy("MyPlugin"), "MyPlugin");
+  }
+}
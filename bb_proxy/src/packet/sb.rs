@@ -8,11 +8,14 @@ use crate::{
 };
 use bb_common::{
   math::{FPos, Pos},
-  net::sb::{Button, ClickWindow, DigStatus, Packet, PlayerCommand, UseEntityAction},
+  net::sb::{
+    Button, ClickWindow, ClientStatus, DigStatus, Packet, PlayerCommand, ResourcePackStatus,
+    UseEntityAction,
+  },
   util::{Face, Hand},
   version::ProtocolVersion,
 };
-use std::{io, io::ErrorKind};
+use std::{fmt, io, io::ErrorKind};
 
 pub trait FromTcp<G> {
   fn from_tcp(p: G, ver: ProtocolVersion, conv: &TypeConverter) -> Result<Self>
@@ -27,10 +30,12 @@ impl FromTcp<GPacket> for Packet {
       GPacket::Chat(g) => Packet::from_tcp(g, ver, conv),
       GPacket::CommandExecution(g) => Packet::from_tcp(g, ver, conv),
       GPacket::ClickWindow(g) => Packet::from_tcp(g, ver, conv),
+      GPacket::ClientStatus(g) => Packet::from_tcp(g, ver, conv),
       GPacket::CloseWindow(g) => Packet::from_tcp(g, ver, conv),
       GPacket::CloseHandledScreen(g) => Packet::from_tcp(g, ver, conv),
       GPacket::ClickSlot(g) => Packet::from_tcp(g, ver, conv),
       GPacket::CreativeInventoryAction(g) => Packet::from_tcp(g, ver, conv),
+      GPacket::CustomPayload(g) => Packet::from_tcp(g, ver, conv),
       GPacket::HeldItemChange(g) => Packet::from_tcp(g, ver, conv),
       GPacket::KeepAlive(g) => Packet::from_tcp(g, ver, conv),
       GPacket::PlayerDig(g) => Packet::from_tcp(g, ver, conv),
@@ -48,6 +53,8 @@ impl FromTcp<GPacket> for Packet {
       GPacket::PlayerPosition(g) => Packet::from_tcp(g, ver, conv),
       GPacket::PlayerAbilities(g) => Packet::from_tcp(g, ver, conv),
       GPacket::UpdatePlayerAbilities(g) => Packet::from_tcp(g, ver, conv),
+      GPacket::RenameItem(g) => Packet::from_tcp(g, ver, conv),
+      GPacket::ResourcePackStatus(g) => Packet::from_tcp(g, ver, conv),
       gpacket => Err(Error::UnknownSB(Box::new(gpacket))),
     }
   }
@@ -80,7 +87,7 @@ macro_rules! from_tcp {
 
 from_tcp!(Animation, _ver, _conv, {
   V8(_g) => Packet::Animation { hand: Hand::Main },
-  V9(g) => Packet::Animation { hand: Hand::from_id(g.hand as u8) },
+  V9(g) => Packet::Animation { hand: hand(g.hand as u8)? },
 });
 from_tcp!(Chat, _ver, _conv, {
   V8(g) => Packet::Chat { msg: g.message },
@@ -104,8 +111,8 @@ from_tcp!(ClickWindow, ver, conv, {
       g.slot_id = -999;
     }
     Packet::ClickWindow {
-      wid:  g.window_id.try_into().unwrap(),
-      slot: g.slot_id.try_into().unwrap(),
+      wid:  num(g.window_id, "window id")?,
+      slot: num(g.slot_id, "slot")?,
       mode: click_window(g.mode, g.used_button)?,
     }
   },
@@ -115,17 +122,36 @@ from_tcp!(ClickWindow, ver, conv, {
       g.slot_id = -999;
     }
     Packet::ClickWindow {
-      wid:  g.window_id.try_into().unwrap(),
-      slot: g.slot_id.try_into().unwrap(),
+      wid:  num(g.window_id, "window id")?,
+      slot: num(g.slot_id, "slot")?,
       mode: click_window(g.mode, g.used_button)?,
     }
   },
 });
+// No length prefix for data, it is inferred from packet length.
+from_tcp!(CustomPayload, ver, _conv, {
+  V8(g) buf = g.unknown => {
+    if ver < ProtocolVersion::V1_13 && g.channel == "MC|ItemName" {
+      // Renaming an item in an anvil was a plugin message before 1.13.
+      Packet::NameItem { name: buf.read_str(32767)? }
+    } else {
+      Packet::PluginMessage {
+        channel: if ver < ProtocolVersion::V1_13 {
+          crate::packet::channel_from_legacy(&g.channel)
+        } else {
+          g.channel
+        },
+        data:    buf.read_all(),
+      }
+    }
+  },
+  V14(g) => Packet::PluginMessage { channel: g.channel, data: g.unknown },
+});
 from_tcp!(CloseWindow, _ver, _conv, {
-  V8(g) => Packet::WindowClose { wid: g.window_id.try_into().unwrap() },
+  V8(g) => Packet::WindowClose { wid: num(g.window_id, "window id")? },
 });
 from_tcp!(CloseHandledScreen, _ver, _conv, {
-  V16(g) => Packet::WindowClose { wid: g.sync_id.try_into().unwrap() },
+  V16(g) => Packet::WindowClose { wid: num(g.sync_id, "window id")? },
 });
 from_tcp!(ClickSlot, ver, conv, {
   V16(mut g) buf = g.unknown => {
@@ -139,8 +165,8 @@ from_tcp!(ClickSlot, ver, conv, {
       g.slot = -999;
     }
     Packet::ClickWindow {
-      wid:  g.sync_id.try_into().unwrap(),
-      slot: g.slot.try_into().unwrap(),
+      wid:  num(g.sync_id, "window id")?,
+      slot: num(g.slot, "slot")?,
       mode: click_window(g.action_type, g.button)?,
     }
   },
@@ -155,15 +181,15 @@ from_tcp!(ClickSlot, ver, conv, {
       g.slot = -999;
     }
     Packet::ClickWindow {
-      wid:  g.sync_id.try_into().unwrap(),
-      slot: g.slot.try_into().unwrap(),
+      wid:  num(g.sync_id, "window id")?,
+      slot: num(g.slot, "slot")?,
       mode: click_window(g.action_type, g.button)?,
     }
   },
 });
 from_tcp!(CreativeInventoryAction, ver, conv, {
   V8(g) buf = g.unknown => Packet::CreativeInventoryUpdate {
-    slot: g.slot_id.try_into().unwrap(),
+    slot: num(g.slot_id, "slot")?,
     item: buf.read_item(conv)?,
   },
 });
@@ -179,7 +205,7 @@ from_tcp!(PlayerDig, ver, _conv, {
     0 | 1 | 2 => Packet::BlockDig {
       pos:    g.position,
       status: DigStatus::from_id(g.status as u8),
-      face:   Face::from_id(buf.read_varint()? as u8),
+      face:   face(buf.read_varint()? as u8)?,
     },
     3 => Packet::ClickWindow { wid: u8::MAX, slot: 0, mode: ClickWindow::DropAll },
     4 => Packet::ClickWindow { wid: u8::MAX, slot: 0, mode: ClickWindow::Drop },
@@ -205,7 +231,7 @@ from_tcp!(PlayerBlockPlacement, ver, conv, {
       let cursor_z = buf.read_u8()?;
       Packet::BlockPlace {
         pos:    g.position,
-        face:   Face::from_id(g.placed_block_direction as u8),
+        face:   face(g.placed_block_direction as u8)?,
         hand:   Hand::Main,
         cursor: FPos::new(
           cursor_x as f64 / 16.0,
@@ -219,24 +245,24 @@ from_tcp!(PlayerBlockPlacement, ver, conv, {
 from_tcp!(PlayerInteractBlock, ver, _conv, {
   V9(g) => Packet::BlockPlace {
     pos:    g.position,
-    face:   Face::from_id(g.placed_block_direction as u8),
-    hand:   Hand::from_id(g.hand as u8),
+    face:   face(g.placed_block_direction as u8)?,
+    hand:   hand(g.hand as u8)?,
     cursor: FPos::new(g.facing_x.into(), g.facing_y.into(), g.facing_z.into()),
   },
   V11(g) => Packet::BlockPlace {
     pos:    g.position,
-    face:   Face::from_id(g.placed_block_direction as u8),
-    hand:   Hand::from_id(g.hand as u8),
+    face:   face(g.placed_block_direction as u8)?,
+    hand:   hand(g.hand as u8)?,
     cursor: FPos::new(g.facing_x.into(), g.facing_y.into(), g.facing_z.into()),
   },
   V14(g) buf = g.unknown => {
     let pos = buf.read_pos()?;
-    let face = Face::from_id(buf.read_varint()? as u8);
+    let face = face(buf.read_varint()? as u8)?;
     let cursor =
       FPos::new(buf.read_f32()?.into(), buf.read_f32()?.into(), buf.read_f32()?.into());
     let _in_head = buf.read_bool()?;
     let _sequence = if ver >= ProtocolVersion::V1_19 { Some(buf.read_varint()?) } else { None };
-    Packet::BlockPlace { hand: Hand::from_id(g.hand as u8), pos, face, cursor }
+    Packet::BlockPlace { hand: hand(g.hand as u8)?, pos, face, cursor }
   },
 });
 from_tcp!(UseEntity, ver, _conv, {
@@ -246,12 +272,12 @@ from_tcp!(UseEntity, ver, _conv, {
       0 => UseEntityAction::Interact(if ver == ProtocolVersion::V1_8 {
         Hand::Main
       } else {
-        Hand::from_id(buf.read_u8()?)
+        hand(buf.read_u8()?)?
       }),
       1 => UseEntityAction::Attack,
       2 => UseEntityAction::InteractAt(
         FPos::new(buf.read_f32()?.into(), buf.read_f32()?.into(), buf.read_f32()?.into()),
-        if ver == ProtocolVersion::V1_8 { Hand::Main } else { Hand::from_id(buf.read_u8()?) },
+        if ver == ProtocolVersion::V1_8 { Hand::Main } else { hand(buf.read_u8()?)? },
       ),
       _ => return Err(io::Error::new(ErrorKind::Other, "invalid use entity action").into()),
     },
@@ -263,17 +289,33 @@ from_tcp!(UseEntity, ver, _conv, {
   V17(g) buf = g.unknown => Packet::UseEntity {
     eid:      g.entity_id,
     action:   match g.v_2 {
-      0 => UseEntityAction::Interact(Hand::from_id(buf.read_u8()?)),
+      0 => UseEntityAction::Interact(hand(buf.read_u8()?)?),
       1 => UseEntityAction::Attack,
       2 => UseEntityAction::InteractAt(
         FPos::new(buf.read_f32()?.into(), buf.read_f32()?.into(), buf.read_f32()?.into()),
-        if ver == ProtocolVersion::V1_8 { Hand::Main } else { Hand::from_id(buf.read_u8()?) },
+        if ver == ProtocolVersion::V1_8 { Hand::Main } else { hand(buf.read_u8()?)? },
       ),
       _ => return Err(io::Error::new(ErrorKind::Other, "invalid use entity action").into()),
     },
     sneaking: Some(buf.read_bool()?),
   },
 });
+// 1.8-1.11 clients also send a status when opening their inventory. The
+// server doesn't need it, so it is dropped like any other unhandled packet.
+from_tcp!(ClientStatus, _ver, _conv, {
+  V8(g) => {
+    if g.status == 2 {
+      return Err(Error::UnknownSB(Box::new(GPacket::ClientStatus(gpacket::ClientStatus::V8(g)))));
+    }
+    Packet::ClientStatus {
+      status: match g.status {
+        0 => ClientStatus::Respawn,
+        1 => ClientStatus::RequestStats,
+        _ => return Err(io::Error::new(ErrorKind::Other, "invalid client status").into()),
+      },
+    }
+  },
+});
 from_tcp!(PlayerCommand, _ver, _conv, {
   V8(g) => Packet::PlayerCommand {
     command: match g.action {
@@ -287,9 +329,9 @@ from_tcp!(PlayerCommand, _ver, _conv, {
   },
 });
 from_tcp!(PlayerInteractItem, _ver, _conv, {
-  V9(g) => Packet::UseItem { hand: Hand::from_id(g.hand as u8) },
+  V9(g) => Packet::UseItem { hand: hand(g.hand as u8)? },
   // TODO: Use `g.sequence`.
-  V19(g) => Packet::UseItem { hand: Hand::from_id(g.hand as u8) },
+  V19(g) => Packet::UseItem { hand: hand(g.hand as u8)? },
 });
 from_tcp!(Player, _ver, _conv, {
   V8(g) => Packet::PlayerOnGround { on_ground: g.on_ground },
@@ -344,6 +386,16 @@ from_tcp!(UpdatePlayerAbilities, _ver, _conv, {
   V14(g) => Packet::Flying { flying: g.flying },
   V16(g) => Packet::Flying { flying: g.flying },
 });
+// Before 1.13, this was sent as the `MC|ItemName` plugin message, which is
+// handled in `CustomPayload`.
+from_tcp!(RenameItem, _ver, _conv, {
+  V14(g) => Packet::NameItem { name: g.name },
+});
+// 1.8 clients also send the hash of the pack, which we ignore.
+from_tcp!(ResourcePackStatus, _ver, _conv, {
+  V8(g) => Packet::ResourcePackStatus { status: resource_pack_status(g.status)? },
+  V10(g) => Packet::ResourcePackStatus { status: resource_pack_status(g.status)? },
+});
 
 fn click_window(mode: i32, bt: i32) -> Result<ClickWindow> {
   Ok(match mode {
@@ -352,7 +404,7 @@ fn click_window(mode: i32, bt: i32) -> Result<ClickWindow> {
     // Shift click
     1 => ClickWindow::ShiftClick(button(bt)?),
     // Number (puts the item in the given slot in the hotbar)
-    2 => ClickWindow::Number(bt.try_into().unwrap()),
+    2 => ClickWindow::Number(num(bt, "hotbar slot")?),
     // Middle click
     3 => ClickWindow::Click(Button::Middle),
     // Drop item
@@ -378,6 +430,27 @@ fn click_window(mode: i32, bt: i32) -> Result<ClickWindow> {
   })
 }
 
+fn hand(id: u8) -> Result<Hand> {
+  Ok(match id {
+    0 => Hand::Main,
+    1 => Hand::Off,
+    _ => return Err(io::Error::new(ErrorKind::Other, format!("invalid hand {id}")).into()),
+  })
+}
+
+fn face(id: u8) -> Result<Face> {
+  match id {
+    0..=5 => Ok(Face::from_id(id)),
+    _ => Err(io::Error::new(ErrorKind::Other, format!("invalid block face {id}")).into()),
+  }
+}
+
+/// Converts a number sent by the client, and returns an error if it doesn't
+/// fit in the type we need.
+fn num<T: TryFrom<U>, U: Copy + fmt::Display>(v: U, name: &str) -> Result<T> {
+  v.try_into().map_err(|_| io::Error::new(ErrorKind::Other, format!("invalid {name} {v}")).into())
+}
+
 fn button(bt: i32) -> Result<Button> {
   Ok(match bt {
     0 => Button::Left,
@@ -386,3 +459,17 @@ fn button(bt: i32) -> Result<Button> {
     _ => return Err(io::Error::new(ErrorKind::Other, format!("invalid button {bt}")).into()),
   })
 }
+
+fn resource_pack_status(status: i32) -> Result<ResourcePackStatus> {
+  Ok(match status {
+    0 => ResourcePackStatus::Loaded,
+    1 => ResourcePackStatus::Declined,
+    2 => ResourcePackStatus::Failed,
+    3 => ResourcePackStatus::Accepted,
+    _ => {
+      return Err(
+        io::Error::new(ErrorKind::Other, format!("invalid resource pack status {status}")).into(),
+      )
+    }
+  })
+}
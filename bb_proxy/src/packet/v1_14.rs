@@ -1,10 +1,6 @@
 use super::{ChunkWithPos, TypeConverter};
 use crate::gnet::cb::{packet, Packet};
-use bb_common::{
-  nbt::{Tag, NBT},
-  util::Buffer,
-  version::BlockVersion,
-};
+use bb_common::{util::Buffer, version::BlockVersion};
 
 // CHANGES (since 1.12.2):
 // No length is written for >8 bpb
@@ -18,7 +14,7 @@ pub fn chunk(chunk: ChunkWithPos, conv: &TypeConverter) -> Packet {
 
   let mut chunk_data = vec![];
   let mut chunk_buf = Buffer::new(&mut chunk_data);
-  for s in chunk.sections.iter().flatten() {
+  for s in chunk.old_sections() {
     chunk_buf.write_u16(s.non_air_blocks() as u16);
     chunk_buf.write_u8(s.data().bpe() as u8);
     if s.data().bpe() <= 8 {
@@ -39,9 +35,7 @@ pub fn chunk(chunk: ChunkWithPos, conv: &TypeConverter) -> Packet {
     }
   }
 
-  let heightmap = vec![];
-  let heightmap =
-    NBT::new("", Tag::new_compound(&[("MOTION_BLOCKING", Tag::LongArray(heightmap))]));
+  let heightmap = chunk.heightmaps.to_nbt(256, false);
 
   let mut data = Vec::with_capacity(chunk_buf.len());
   let mut buf = Buffer::new(&mut data);
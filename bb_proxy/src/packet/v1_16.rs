@@ -1,9 +1,9 @@
 use super::{ChunkWithPos, TypeConverter};
 use crate::gnet::cb::{packet, Packet};
 use bb_common::{
-  nbt::{Tag, NBT},
+  math::ChunkPos,
   util::Buffer,
-  version::BlockVersion,
+  version::{BlockVersion, ProtocolVersion},
 };
 
 // CHANGES:
@@ -14,7 +14,7 @@ pub fn chunk(chunk: ChunkWithPos, conv: &TypeConverter) -> Packet {
 
   let mut chunk_data = vec![];
   let mut chunk_buf = Buffer::new(&mut chunk_data);
-  for s in chunk.sections.iter().flatten() {
+  for s in chunk.old_sections() {
     chunk_buf.write_u16(s.non_air_blocks() as u16);
     chunk_buf.write_u8(s.data().bpe() as u8);
     if s.data().bpe() <= 8 {
@@ -38,9 +38,7 @@ pub fn chunk(chunk: ChunkWithPos, conv: &TypeConverter) -> Packet {
     }
   }
 
-  let heightmap = vec![];
-  let heightmap =
-    NBT::new("", Tag::new_compound(&[("MOTION_BLOCKING", Tag::LongArray(heightmap))]));
+  let heightmap = chunk.heightmaps.to_nbt(256, true);
 
   let mut data = Vec::with_capacity(chunk_buf.len());
   let mut buf = Buffer::new(&mut data);
@@ -59,3 +57,35 @@ pub fn chunk(chunk: ChunkWithPos, conv: &TypeConverter) -> Packet {
   }
   .into()
 }
+
+// CHANGES:
+// Multi block changes are now for a chunk section, instead of a chunk column.
+// The section position is packed into a long, and each record is a varlong.
+pub fn multi_block_change(
+  pos: ChunkPos,
+  y: i32,
+  changes: Vec<u64>,
+  ver: ProtocolVersion,
+  conv: &TypeConverter,
+) -> Packet {
+  let mut data = vec![];
+  let mut buf = Buffer::new(&mut data);
+  // 22 bits for X, 22 bits for Z, then 20 bits for Y.
+  buf.write_u64(
+    (pos.x() as u64 & 0x3fffff) << 42 | (pos.z() as u64 & 0x3fffff) << 20 | (y as u64 & 0xfffff),
+  );
+  // Don't skip light updates.
+  buf.write_bool(false);
+  buf.write_varint(changes.len() as i32);
+  for change in changes {
+    let id = (change >> 12) as u32;
+    let s_x = (change >> 8) & 0xf;
+    let s_y = (change >> 4) & 0xf;
+    let s_z = change & 0xf;
+    let old_id = conv.block_to_old(id, ver.block());
+
+    // Note that this is not the same order as the server uses.
+    buf.write_varlong(i64::from(old_id) << 12 | (s_x << 8 | s_z << 4 | s_y) as i64);
+  }
+  packet::MultiBlockChangeV16 { unknown: data }.into()
+}
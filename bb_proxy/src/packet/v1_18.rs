@@ -1,11 +1,6 @@
 use super::{ChunkWithPos, TypeConverter};
 use crate::gnet::cb::{packet, Packet};
-use bb_common::{
-  chunk::Chunk,
-  nbt::{Tag, NBT},
-  util::Buffer,
-  version::ProtocolVersion,
-};
+use bb_common::{util::Buffer, version::ProtocolVersion};
 
 // CHANGES:
 // - Bitmask was removed, and we now need to send empty sections.
@@ -25,7 +20,8 @@ pub fn chunk(chunk: ChunkWithPos, ver: ProtocolVersion, conv: &TypeConverter) ->
 
   // 1.18 requires all chunk sections to be sent
   for s in &chunk.sections {
-    if let Some(s) = s {
+    // Sections that only contain air use the same format as missing sections.
+    if let Some(s) = s.as_ref().filter(|s| s.non_air_blocks() != 0) {
       chunk_buf.write_u16(s.non_air_blocks() as u16);
 
       // Paletted container for chunk data
@@ -79,10 +75,9 @@ pub fn chunk(chunk: ChunkWithPos, ver: ProtocolVersion, conv: &TypeConverter) ->
     }
   }
 
-  let c = Chunk::from_sections(chunk.packet.sections, 15);
-  let heightmap = c.build_heightmap_new();
-  let heightmap =
-    NBT::new("", Tag::new_compound(&[("MOTION_BLOCKING", Tag::LongArray(heightmap))]));
+  // 1.18 clients need every section, so the world is as tall as the sections
+  // we sent.
+  let heightmap = chunk.heightmaps.to_nbt(chunk.sections.len() as u32 * 16, true);
 
   let mut data = Vec::with_capacity(chunk_buf.len());
   let mut buf = Buffer::new(&mut data);
@@ -12,7 +12,7 @@ pub fn chunk(chunk: ChunkWithPos, conv: &TypeConverter) -> Packet {
   let skylight = true; // Assume overworld
 
   // Don't send unload chunks when we really want an empty chunk.
-  let actual_sections = chunk.sections.iter().flatten().count();
+  let actual_sections = chunk.old_sections().count();
   let total_sections = if actual_sections == 0 { 1 } else { actual_sections };
 
   let data_len = total_sections * 16 * 16 * 16 * 2 // Chunk data
@@ -32,7 +32,7 @@ pub fn chunk(chunk: ChunkWithPos, conv: &TypeConverter) -> Packet {
     // We just want 4096 0_u16s for this section.
     chunk_buf.skip(16 * 16 * 16 * 2);
   } else {
-    for s in chunk.sections.iter().flatten() {
+    for s in chunk.old_sections() {
       for y in 0..16 {
         for z in 0..16 {
           for x in 0..16 {
@@ -8,11 +8,10 @@ pub fn chunk(chunk: ChunkWithPos, ver: ProtocolVersion, conv: &TypeConverter) ->
   let skylight = true; // Assume overworld
 
   let base = 16 * 16 / 2;
-  let mut chunk_data = Vec::with_capacity(
-    1024 + chunk.sections.iter().flatten().count() * (19 + 16 * 16 * 16 + 16 * base),
-  );
+  let mut chunk_data =
+    Vec::with_capacity(1024 + chunk.old_sections().count() * (19 + 16 * 16 * 16 + 16 * base));
   let mut chunk_buf = Buffer::new(&mut chunk_data);
-  for s in chunk.sections.iter().flatten() {
+  for s in chunk.old_sections() {
     chunk_buf.write_u8(s.data().bpe() as u8);
     chunk_buf.write_varint(s.palette().len() as i32);
     for g in s.palette() {
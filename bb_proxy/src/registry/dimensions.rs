@@ -1,3 +1,4 @@
+use bb_common::util::Dimension as DimensionType;
 use serde::Serialize;
 
 #[derive(Debug, Clone, Serialize)]
@@ -24,6 +25,16 @@ pub struct Dimension {
   monster_spawn_block_light_limit: i32,
 }
 
+/// Returns the dimension element for the given dimension type, using the min y
+/// and height from the world's config.
+pub fn for_type(ty: DimensionType, world_min_y: i32, world_height: u32) -> Dimension {
+  match ty {
+    DimensionType::Overworld => overworld(world_min_y, world_height),
+    DimensionType::Nether => nether(world_min_y, world_height),
+    DimensionType::End => end(world_min_y, world_height),
+  }
+}
+
 pub fn overworld(world_min_y: i32, world_height: u32) -> Dimension {
   Dimension {
     piglin_safe:          false,
@@ -47,3 +58,51 @@ pub fn overworld(world_min_y: i32, world_height: u32) -> Dimension {
     monster_spawn_block_light_limit: 7,
   }
 }
+
+pub fn nether(world_min_y: i32, world_height: u32) -> Dimension {
+  Dimension {
+    piglin_safe:          true,
+    natural:              false,
+    ambient_light:        0.1,
+    fixed_time:           18000,
+    infiniburn:           "#minecraft:infiniburn_nether".into(),
+    respawn_anchor_works: true,
+    has_skylight:         false,
+    bed_works:            false,
+    effects:              "minecraft:the_nether".into(),
+    has_raids:            false,
+    logical_height:       128,
+    coordinate_scale:     8.0,
+    ultrawarm:            true,
+    has_ceiling:          true,
+    min_y:                world_min_y,
+    height:               (world_height as i32 + 15) / 16 * 16,
+
+    monster_spawn_light_level:       11,
+    monster_spawn_block_light_limit: 15,
+  }
+}
+
+pub fn end(world_min_y: i32, world_height: u32) -> Dimension {
+  Dimension {
+    piglin_safe:          false,
+    natural:              false,
+    ambient_light:        0.0,
+    fixed_time:           6000,
+    infiniburn:           "#minecraft:infiniburn_end".into(),
+    respawn_anchor_works: false,
+    has_skylight:         false,
+    bed_works:            false,
+    effects:              "minecraft:the_end".into(),
+    has_raids:            true,
+    logical_height:       256,
+    coordinate_scale:     1.0,
+    ultrawarm:            false,
+    has_ceiling:          false,
+    min_y:                world_min_y,
+    height:               (world_height as i32 + 15) / 16 * 16,
+
+    monster_spawn_light_level:       7,
+    monster_spawn_block_light_limit: 0,
+  }
+}
@@ -1,4 +1,8 @@
-use bb_common::{nbt, util::Buffer, version::ProtocolVersion};
+use bb_common::{
+  nbt,
+  util::{Buffer, Dimension},
+  version::ProtocolVersion,
+};
 use serde::Serialize;
 
 mod biomes;
@@ -30,37 +34,47 @@ struct CodecItem<T> {
   element: T,
 }
 
+/// Writes the dimension element for `dimension`. This is used in the respawn
+/// packet.
 pub fn write_single_dimension<T>(
   out: &mut Buffer<T>,
   _ver: ProtocolVersion,
+  dimension: Dimension,
   world_min_y: i32,
   world_height: u32,
 ) where
   std::io::Cursor<T>: std::io::Write,
 {
-  let dimension = dimensions::overworld(world_min_y, world_height);
-  out.write_buf(&nbt::to_nbt("", &dimension).unwrap().serialize());
+  let dim = dimensions::for_type(dimension, world_min_y, world_height);
+  out.write_buf(&nbt::to_nbt("", &dim).unwrap().serialize());
 }
 
+/// Writes the full registry codec, which contains every dimension type, and
+/// then the current dimension and world name.
 pub fn write_codec<T>(
   out: &mut Buffer<T>,
   ver: ProtocolVersion,
+  dimension: Dimension,
   world_min_y: i32,
   world_height: u32,
 ) where
   std::io::Cursor<T>: std::io::Write,
 {
-  let dimension = dimensions::overworld(world_min_y, world_height);
-  let dimension_tag = nbt::to_nbt("", &dimension).unwrap();
+  let dimension_tag =
+    nbt::to_nbt("", &dimensions::for_type(dimension, world_min_y, world_height)).unwrap();
 
   let info = LoginInfo {
     dimensions: Codec {
       ty:    "minecraft:dimension_type".into(),
-      value: vec![CodecItem {
-        name:    "minecraft:overworld".into(),
-        id:      0,
-        element: dimension,
-      }],
+      value: [Dimension::Overworld, Dimension::Nether, Dimension::End]
+        .into_iter()
+        .enumerate()
+        .map(|(id, dim)| CodecItem {
+          name:    dim.key().into(),
+          id:      id as i32,
+          element: dimensions::for_type(dim, world_min_y, world_height),
+        })
+        .collect(),
     },
     biomes:     Codec { ty: "minecraft:worldgen/biome".into(), value: biomes::all() },
     chat:       Codec { ty: "minecraft:chat_type".into(), value: chat_type::all() },
@@ -71,13 +85,13 @@ pub fn write_codec<T>(
   out.write_buf(&nbt::to_nbt("", &info).unwrap().serialize());
   if ver >= ProtocolVersion::V1_19 {
     // Current dimension type (key in dimension codec)
-    out.write_str("minecraft:overworld");
+    out.write_str(dimension.key());
     // Current world
-    out.write_str("minecraft:overworld");
+    out.write_str(dimension.key());
   } else {
     // World codec (included in dimension)
     out.write_buf(&dimension_tag.serialize());
     // Current world
-    out.write_str("minecraft:overworld");
+    out.write_str(dimension.key());
   }
 }
@@ -6,12 +6,13 @@ use aes::{
 };
 use bb_common::{util, util::Buffer, version::ProtocolVersion};
 use cfb8::Cfb8;
-use miniz_oxide::{deflate::compress_to_vec_zlib, inflate::decompress_to_vec_zlib};
+use miniz_oxide::{deflate::compress_to_vec_zlib, inflate::decompress_to_vec_zlib_with_limit};
 use mio::net::TcpStream;
 use std::{
   collections::VecDeque,
   fmt, io,
   io::{ErrorKind, Read, Write},
+  net::SocketAddr,
 };
 
 /// The largest size that an uncompressed or compressed packet can be. Only used
@@ -60,6 +61,100 @@ impl JavaStream {
   }
 }
 
+/// Compresses a packet, when compression is enabled. `bytes` is the packet id
+/// and data. Packets larger than the threshold are compressed, and the rest
+/// are written with an uncompressed length of 0. The returned packet doesn't
+/// include the total length prefix. This is the opposite of [`decompress`].
+pub(crate) fn compress(bytes: Vec<u8>, compression: i32) -> Vec<u8> {
+  let mut out = vec![];
+  let mut buf = Buffer::new(&mut out);
+  // as usize won't wrap here, because `compression >= 0`
+  if bytes.len() > compression as usize {
+    buf.write_varint(bytes.len() as i32);
+    buf.write_buf(&compress_to_vec_zlib(&bytes, 1));
+  } else {
+    buf.write_varint(0);
+    buf.write_buf(&bytes);
+  }
+  out
+}
+
+/// Decompresses a packet, when compression is enabled. `data` is the whole
+/// packet, without the total length prefix. This returns an error if the
+/// uncompressed length is too large, if it is below the compression threshold,
+/// or if it doesn't match the length of the decompressed data.
+pub(crate) fn decompress(mut data: Vec<u8>, compression: i32) -> io::Result<Vec<u8>> {
+  let mut buf = Buffer::new(&mut data);
+  let uncompressed_length =
+    buf.read_varint().map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+  if uncompressed_length < 0 || uncompressed_length as usize > MAX_PACKET_SIZE {
+    // Packet is too long! We want to kick this client now.
+    return Err(io::Error::new(ErrorKind::InvalidData, "uncompressed packet too long"));
+  }
+  if uncompressed_length == 0 {
+    return Ok(buf.read_all());
+  }
+  if uncompressed_length < compression {
+    return Err(io::Error::new(
+      ErrorKind::InvalidData,
+      format!(
+        "compressed packet of length {uncompressed_length} is below the threshold {compression}"
+      ),
+    ));
+  }
+  let uncompressed_length = uncompressed_length as usize;
+  // The limit means a zip bomb will stop as soon as it passes the declared
+  // length, instead of filling up memory.
+  let decompressed = decompress_to_vec_zlib_with_limit(&buf.read_all(), uncompressed_length)
+    .map_err(|e| io::Error::new(ErrorKind::InvalidData, format!("invalid zlib data: {:?}", e)))?;
+  if decompressed.len() != uncompressed_length {
+    return Err(io::Error::new(
+      ErrorKind::InvalidData,
+      format!(
+        "decompressed packet is {} bytes, but should be {uncompressed_length} bytes",
+        decompressed.len()
+      ),
+    ));
+  }
+  Ok(decompressed)
+}
+
+/// Removes a single length prefixed packet from the front of `recv`. This
+/// returns `None` if `recv` doesn't contain a whole packet yet, and an error if
+/// the length is invalid or too large. The returned packet doesn't include the
+/// length prefix, and hasn't been decompressed.
+pub(crate) fn read_frame(recv: &mut VecDeque<u8>) -> io::Result<Option<Vec<u8>>> {
+  let mut bytes = [0; 5];
+  let end = recv.len().min(5);
+  for (i, b) in recv.range(0..end).enumerate() {
+    bytes[i] = *b;
+  }
+  let (len, read) = util::read_varint(&bytes);
+  // Varint that is more than 5 bytes long.
+  if read < 0 {
+    return Err(io::Error::new(ErrorKind::InvalidData, "invalid varint"));
+  }
+  let read = read as usize;
+  // Incomplete varint
+  if read == 0 {
+    return Ok(None);
+  }
+  // Now that we have a valid varint, we make sure the packet isn't too large.
+  if len < 0 || len as usize > MAX_PACKET_SIZE {
+    // Packet is too long! We want to kick this client now.
+    return Err(io::Error::new(ErrorKind::InvalidData, "packet too long"));
+  }
+  let len = len as usize;
+  // Incomplete packet, but a valid length
+  if recv.len() < len + read {
+    return Ok(None);
+  }
+
+  // Now that we know we have a valid packet, we pop the whole packet
+  recv.drain(0..read);
+  Ok(Some(recv.drain(0..len).collect()))
+}
+
 impl PacketStream for JavaStream {
   fn enable_encryption(&mut self, secret: &[u8; 16]) {
     self.read_cipher = Some(Cfb8::new_from_slices(secret, secret).unwrap());
@@ -84,100 +179,35 @@ impl PacketStream for JavaStream {
     Ok(())
   }
   fn read(&mut self, ver: ProtocolVersion) -> Result<Option<tcp::Packet>> {
-    let mut bytes = [0; 5];
-    let end = self.recv.len().min(5);
-    for (i, b) in self.recv.range(0..end).enumerate() {
-      bytes[i] = *b;
-    }
-    let (len, read) = util::read_varint(&bytes);
-    // Varint that is more than 5 bytes long.
-    if read < 0 {
-      return Err(io::Error::new(ErrorKind::InvalidData, "invalid varint").into());
-    }
-    let read = read as usize;
-    // Incomplete varint
-    if read == 0 {
-      return Ok(None);
-    }
-    // Now that we have a valid varint, we make sure the packet isn't too large.
-    if len < 0 || len as usize > MAX_PACKET_SIZE {
-      // Packet is too long! We want to kick this client now.
-      return Err(io::Error::new(ErrorKind::InvalidData, "packet too long").into());
-    }
-    let len = len as usize;
-    // Incomplete packet, but a valid length
-    if self.recv.len() < len + read {
-      return Ok(None);
-    }
-
-    // Now that we know we have a valid packet, we pop the whole packet
-    self.recv.drain(0..read);
-    let mut vec = Vec::with_capacity(len);
-    for v in self.recv.drain(0..len) {
-      vec.push(v);
-    }
+    let vec = match read_frame(&mut self.recv)? {
+      Some(v) => v,
+      None => return Ok(None),
+    };
     // And parse it
     if self.compression >= 0 {
-      let mut buf = Buffer::new(&mut vec);
-      let uncompressed_length = buf.read_varint()?;
-      if uncompressed_length < 0 || uncompressed_length as usize > MAX_PACKET_SIZE {
-        // Packet is too long! We want to kick this client now.
-        return Err(io::Error::new(ErrorKind::InvalidData, "uncompressed packet too long").into());
-      }
-      if uncompressed_length == 0 {
-        Ok(Some(tcp::Packet::from_buf(buf.read_all(), ver)?))
-      } else {
-        let decompressed = decompress_to_vec_zlib(&buf.read_all()).map_err(|e| {
-          io::Error::new(ErrorKind::InvalidData, format!("invalid zlib data: {:?}", e))
-        })?;
-        Ok(Some(tcp::Packet::from_buf(decompressed, ver)?))
-      }
+      Ok(Some(tcp::Packet::from_buf(decompress(vec, self.compression)?, ver)?))
     } else {
       Ok(Some(tcp::Packet::from_buf(vec, ver)?))
     }
   }
 
   fn write(&mut self, p: tcp::Packet) {
-    // This is the packet, including it's id
+    // This is the packet, including it's id. Packets are always encoded with
+    // this stream's threshold, no matter where they came from.
     let mut bytes = p.serialize();
-
-    // Either the uncompressed length, or the total and uncompressed length.
-    let mut data = vec![];
-    let mut buf = Buffer::new(&mut data);
-
     if self.compression >= 0 {
-      // as usize won't wrap here, because `self.compression >= 0`
-      if bytes.len() > self.compression as usize {
-        let uncompressed_length = bytes.len();
-        let mut compressed = compress_to_vec_zlib(&bytes, 1);
-
-        // See how many bytes the uncompressed_length varint takes up
-        let mut uncompressed_length_data = vec![];
-        let mut uncompressed_length_buf = Buffer::new(&mut uncompressed_length_data);
-        uncompressed_length_buf.write_varint(uncompressed_length as i32);
-
-        // This is the total length of the packet.
-        let total_length = uncompressed_length_buf.len() + compressed.len();
-        buf.write_varint(total_length as i32);
-        buf.write_varint(uncompressed_length as i32);
-        self.write_data(&mut buf);
-        self.write_data(&mut compressed);
-      } else {
-        // The 1 is for the zero uncompressed_length
-        buf.write_varint(bytes.len() as i32 + 1);
-        buf.write_varint(0);
-        self.write_data(&mut buf);
-        self.write_data(&mut bytes);
-      }
-    } else {
-      // Uncompressed packets just have the length prefixed.
-      buf.write_varint(bytes.len() as i32);
-      self.write_data(&mut buf);
-      self.write_data(&mut bytes);
+      bytes = compress(bytes, self.compression);
     }
+
+    let mut prefix = vec![];
+    let mut buf = Buffer::new(&mut prefix);
+    buf.write_varint(bytes.len() as i32);
+    self.write_data(&mut prefix);
+    self.write_data(&mut bytes);
   }
 
   fn needs_flush(&self) -> bool { !self.outgoing.is_empty() }
+  fn peer_addr(&self) -> io::Result<SocketAddr> { self.stream.peer_addr() }
 
   fn flush(&mut self) -> Result<()> {
     if self.outgoing.is_empty() {
@@ -188,3 +218,62 @@ impl PacketStream for JavaStream {
     Ok(())
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn compressed(uncompressed_length: i32, data: &[u8]) -> Vec<u8> {
+    let mut out = vec![];
+    let mut buf = Buffer::new(&mut out);
+    buf.write_varint(uncompressed_length);
+    buf.write_buf(&compress_to_vec_zlib(data, 1));
+    out
+  }
+
+  #[test]
+  fn decompress_packets() {
+    let data = vec![5; 300];
+    assert_eq!(decompress(compressed(300, &data), 256).unwrap(), data);
+
+    // Uncompressed packets have a length of 0.
+    let mut packet = vec![0];
+    packet.extend([1, 2, 3]);
+    assert_eq!(decompress(packet, 256).unwrap(), vec![1, 2, 3]);
+
+    // Below the threshold
+    assert!(decompress(compressed(300, &data), 512).is_err());
+    // Declared length is too short
+    assert!(decompress(compressed(299, &data), 256).is_err());
+    // Declared length is too long
+    assert!(decompress(compressed(301, &data), 256).is_err());
+    // Declared length is larger than the max packet size
+    let bomb = vec![0; MAX_PACKET_SIZE + 1];
+    assert!(decompress(compressed(MAX_PACKET_SIZE as i32 + 1, &bomb), 256).is_err());
+    assert!(decompress(compressed(-1, &data), 256).is_err());
+    // Invalid zlib data
+    let mut packet = vec![];
+    Buffer::new(&mut packet).write_varint(300);
+    packet.extend([1, 2, 3]);
+    assert!(decompress(packet, 256).is_err());
+  }
+
+  #[test]
+  fn reencode_packets() {
+    let small = vec![1, 2, 3];
+    let large = vec![5; 300];
+    // Packets at or below the threshold are not compressed.
+    assert_eq!(compress(small.clone(), 256), vec![0, 1, 2, 3]);
+    assert_eq!(compress(small.clone(), 0)[0], 3);
+
+    for data in [small, large] {
+      for thresh in [0, 256, 512] {
+        assert_eq!(decompress(compress(data.clone(), thresh), thresh).unwrap(), data);
+      }
+      // A packet decoded at one threshold can be encoded at any other, which is
+      // what happens when a player's packets go through the proxy.
+      let decoded = decompress(compress(data.clone(), 0), 0).unwrap();
+      assert_eq!(decompress(compress(decoded, 512), 512).unwrap(), data);
+    }
+  }
+}
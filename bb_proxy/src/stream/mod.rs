@@ -3,6 +3,7 @@ pub mod java;
 
 use crate::{gnet::tcp, Result};
 use bb_common::version::ProtocolVersion;
+use std::{io, net::SocketAddr};
 
 pub trait PacketStream {
   // Config functions
@@ -44,4 +45,7 @@ pub trait PacketStream {
   /// stream still needs to be flushed. You should poll for `Interest::WRITABLE`
   /// and try again.
   fn flush(&mut self) -> Result<()> { Ok(()) }
+
+  /// Returns the address of the client on the other end of this stream.
+  fn peer_addr(&self) -> io::Result<SocketAddr>;
 }
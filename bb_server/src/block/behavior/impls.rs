@@ -47,6 +47,70 @@ impl Behavior for Falling {
   }
 }
 
+pub struct Crop {
+  /// The maximum value of the `age` property.
+  pub max_age: u32,
+}
+impl Behavior for Crop {
+  fn random_tick(&self, world: &Arc<World>, block: Block) {
+    // Crops need light to grow, and only grow on some random ticks.
+    if world.get_light(block.pos.add_y(1)).unwrap_or(0) < 9 || !world.random_chance(1, 3) {
+      return;
+    }
+    let age = block.ty.prop("age").int();
+    if age < self.max_age {
+      let _ = world.set_block(block.pos, block.ty.with("age", age + 1));
+    }
+  }
+}
+
+pub struct Sapling {
+  pub log:    Kind,
+  pub leaves: Kind,
+}
+impl Behavior for Sapling {
+  fn random_tick(&self, world: &Arc<World>, block: Block) {
+    if world.get_light(block.pos.add_y(1)).unwrap_or(0) < 9 || !world.random_chance(1, 7) {
+      return;
+    }
+    // Saplings take two growth steps. The first one only sets the stage.
+    if block.ty.prop("stage").int() == 0 {
+      let _ = world.set_block(block.pos, block.ty.with("stage", 1_u32));
+      return;
+    }
+    let pos = block.pos;
+    let _ = world.set_kind(pos, self.log);
+    // The trunk is placed first, so that the leaves don't replace it.
+    for y in 1..=5 {
+      place_tree_block(world, pos.add_y(y), self.log);
+    }
+    for (min, max) in
+      [(Pos::new(-2, 3, -2), Pos::new(2, 4, 2)), (Pos::new(-1, 5, -1), Pos::new(1, 6, 1))]
+    {
+      for y in min.y..=max.y {
+        for z in min.z..=max.z {
+          for x in min.x..=max.x {
+            place_tree_block(world, pos + Pos::new(x, y, z), self.leaves);
+          }
+        }
+      }
+    }
+  }
+}
+
+/// Places a block of a growing tree. Trees only grow into air or replaceable
+/// blocks, so that they don't destroy anything built around them.
+fn place_tree_block(world: &Arc<World>, pos: Pos, kind: Kind) {
+  let replaceable = match world.get_kind(pos) {
+    Ok(Kind::Air) => true,
+    Ok(k) => world.block_converter().get(k).material.is_replaceable(),
+    Err(_) => false,
+  };
+  if replaceable {
+    let _ = world.set_kind(pos, kind);
+  }
+}
+
 pub struct CraftingTable;
 impl Behavior for CraftingTable {
   fn interact(&self, _: Block, player: &Arc<Player>) -> EventFlow {
@@ -132,6 +196,7 @@ impl TileEntity for ChestTE {
     self.write(w)
   }
   fn as_any(&self) -> &dyn Any { self }
+  fn clone_te(&self) -> Arc<dyn TileEntity> { Arc::new(ChestTE { inv: self.inv.deep_clone() }) }
 }
 
 pub struct Trapdoor;
@@ -174,6 +239,262 @@ impl Behavior for Door {
   }
 }
 
+/// Places a lever or button, which can be on the floor, ceiling, or a wall.
+fn place_attached<'a>(data: &'a Data, click: BlockClick) -> Type<'a> {
+  let ty = data.default_type();
+  match click.face {
+    Face::Top => ty.with("face", "floor").with("facing", click.dir.as_horz_face().as_str()),
+    Face::Bottom => ty.with("face", "ceiling").with("facing", click.dir.as_horz_face().as_str()),
+    face => ty.with("face", "wall").with("facing", face.as_str()),
+  }
+}
+/// Returns the side of a lever or button that is touching the block it is
+/// placed on.
+fn attached_face(ty: Type) -> Face {
+  match ty.prop("face").as_enum() {
+    "floor" => Face::Bottom,
+    "ceiling" => Face::Top,
+    _ => Face::from(ty.prop("facing").as_enum()).opposite(),
+  }
+}
+/// The power output of a lever or button. These weakly power everything
+/// around them, and strongly power the block they are placed on.
+fn switch_power(ty: Type, face: Face, strong: bool) -> u8 {
+  if ty.prop("powered").bool() && (!strong || face == attached_face(ty)) {
+    15
+  } else {
+    0
+  }
+}
+
+pub struct Lever;
+impl Behavior for Lever {
+  fn place<'a>(&self, data: &'a Data, _: Pos, click: BlockClick) -> Type<'a> {
+    place_attached(data, click)
+  }
+  fn interact(&self, mut block: Block, _: &Arc<Player>) -> EventFlow {
+    block.set(block.ty.with("powered", !block.ty.prop("powered").bool()));
+    block.world.queue_redstone_updates_around(block.pos);
+    Handled
+  }
+  fn weak_power(&self, block: Block, face: Face) -> u8 { switch_power(block.ty, face, false) }
+  fn strong_power(&self, block: Block, face: Face) -> u8 { switch_power(block.ty, face, true) }
+}
+
+pub struct Button {
+  /// The number of ticks this button stays pressed for.
+  pub ticks: u32,
+}
+impl Behavior for Button {
+  fn place<'a>(&self, data: &'a Data, _: Pos, click: BlockClick) -> Type<'a> {
+    place_attached(data, click)
+  }
+  fn interact(&self, mut block: Block, _: &Arc<Player>) -> EventFlow {
+    if !block.ty.prop("powered").bool() {
+      block.set(block.ty.with("powered", true));
+      block.world.queue_redstone_updates_around(block.pos);
+      block.world.schedule_tick(block.pos, self.ticks, 0);
+    }
+    Handled
+  }
+  fn scheduled_tick(&self, world: &Arc<World>, block: Block) {
+    if block.ty.prop("powered").bool() {
+      let _ = world.set_block(block.pos, block.ty.with("powered", false));
+      world.queue_redstone_updates_around(block.pos);
+    }
+  }
+  fn weak_power(&self, block: Block, face: Face) -> u8 { switch_power(block.ty, face, false) }
+  fn strong_power(&self, block: Block, face: Face) -> u8 { switch_power(block.ty, face, true) }
+}
+
+pub struct RedstoneBlock;
+impl Behavior for RedstoneBlock {
+  fn update_place(&self, world: &Arc<World>, block: Block) {
+    world.queue_redstone_updates_around(block.pos);
+  }
+  fn weak_power(&self, _: Block, _: Face) -> u8 { 15 }
+}
+
+pub struct RedstoneLamp;
+impl Behavior for RedstoneLamp {
+  fn update_place(&self, world: &Arc<World>, block: Block) {
+    world.queue_redstone_update(block.pos);
+  }
+  fn update(&self, world: &Arc<World>, block: Block, _: Block, _: Block) {
+    world.queue_redstone_update(block.pos);
+  }
+  fn redstone_update(&self, world: &Arc<World>, block: Block) {
+    let lit = world.received_power(block.pos) > 0;
+    if block.ty.prop("lit").bool() != lit {
+      let _ = world.set_block(block.pos, block.ty.with("lit", lit));
+    }
+  }
+}
+
+pub struct RedstoneTorch;
+impl RedstoneTorch {
+  /// Returns the side of the torch that is touching the block it is placed on.
+  fn attached_face(ty: Type) -> Face {
+    if ty.kind() == Kind::RedstoneWallTorch {
+      Face::from(ty.prop("facing").as_enum()).opposite()
+    } else {
+      Face::Bottom
+    }
+  }
+  /// Returns true if the block this torch is placed on is powered.
+  fn should_be_lit(world: &Arc<World>, block: Block) -> bool {
+    let face = Self::attached_face(block.ty);
+    world.power(block.pos + face, face.opposite()) == 0
+  }
+}
+impl Behavior for RedstoneTorch {
+  fn update_place(&self, world: &Arc<World>, block: Block) {
+    world.queue_redstone_update(block.pos);
+    world.queue_redstone_updates_around(block.pos);
+  }
+  fn update(&self, world: &Arc<World>, block: Block, _: Block, _: Block) {
+    world.queue_redstone_update(block.pos);
+  }
+  fn redstone_update(&self, world: &Arc<World>, block: Block) {
+    // Torches take 2 ticks to change.
+    if block.ty.prop("lit").bool() != Self::should_be_lit(world, block) {
+      world.schedule_tick(block.pos, 2, 0);
+    }
+  }
+  fn scheduled_tick(&self, world: &Arc<World>, block: Block) {
+    let lit = Self::should_be_lit(world, block);
+    if block.ty.prop("lit").bool() != lit {
+      let _ = world.set_block(block.pos, block.ty.with("lit", lit));
+      world.queue_redstone_updates_around(block.pos);
+    }
+  }
+  fn weak_power(&self, block: Block, face: Face) -> u8 {
+    if block.ty.prop("lit").bool() && face != Self::attached_face(block.ty) {
+      15
+    } else {
+      0
+    }
+  }
+  fn strong_power(&self, block: Block, face: Face) -> u8 {
+    if block.ty.prop("lit").bool() && face == Face::Top {
+      15
+    } else {
+      0
+    }
+  }
+}
+
+pub struct RedstoneWire;
+impl RedstoneWire {
+  /// Returns true if dust should connect to the given block.
+  fn connects_to(world: &World, kind: Kind) -> bool {
+    matches!(
+      kind,
+      Kind::RedstoneWire
+        | Kind::RedstoneTorch
+        | Kind::RedstoneWallTorch
+        | Kind::RedstoneBlock
+        | Kind::Lever
+        | Kind::Repeater
+        | Kind::Comparator
+        | Kind::Observer
+        | Kind::Target
+    ) || world.block_converter().get(kind).tags.contains(&"buttons")
+  }
+  /// Returns the connection on the given side of the dust at `pos`. This is
+  /// `"side"`, `"up"`, or `"none"`.
+  fn connection(world: &World, pos: Pos, face: Face) -> &'static str {
+    let side = pos + face;
+    if world.get_kind(side).map(|k| Self::connects_to(world, k)).unwrap_or(false) {
+      return "side";
+    }
+    if !world.is_conductor(pos.add_y(1)) && world.get_kind(side.add_y(1)) == Ok(Kind::RedstoneWire)
+    {
+      return "up";
+    }
+    if !world.is_conductor(side) && world.get_kind(side.add_y(-1)) == Ok(Kind::RedstoneWire) {
+      return "side";
+    }
+    "none"
+  }
+  /// Returns the power of any dust that can transfer power into the dust at
+  /// `pos`.
+  fn max_wire_power(world: &World, pos: Pos) -> u8 {
+    let power = |p: Pos| match world.get_block(p) {
+      Ok(ty) if ty.kind() == Kind::RedstoneWire => ty.prop("power").int() as u8,
+      _ => 0,
+    };
+    let mut max = 0;
+    for face in Face::SIDES {
+      let side = pos + face;
+      max = max.max(power(side));
+      if !world.is_conductor(pos.add_y(1)) {
+        max = max.max(power(side.add_y(1)));
+      }
+      if !world.is_conductor(side) {
+        max = max.max(power(side.add_y(-1)));
+      }
+    }
+    max
+  }
+}
+impl Behavior for RedstoneWire {
+  fn update_place(&self, world: &Arc<World>, block: Block) {
+    world.queue_redstone_update(block.pos);
+    world.queue_redstone_updates_around(block.pos);
+  }
+  fn update(&self, world: &Arc<World>, block: Block, _: Block, _: Block) {
+    world.queue_redstone_update(block.pos);
+  }
+  fn redstone_update(&self, world: &Arc<World>, block: Block) {
+    let mut ty = block.ty;
+    let power = world
+      .received_power_no_wire(block.pos)
+      .max(Self::max_wire_power(world, block.pos).saturating_sub(1));
+    ty.set_prop("power", power as u32);
+
+    let mut connections = Face::SIDES.map(|f| Self::connection(world, block.pos, f));
+    // Dust with a single connection is a line, so it extends to the opposite side.
+    if connections.iter().filter(|c| **c != "none").count() == 1 {
+      for i in 0..4 {
+        if connections[i] != "none" {
+          connections[i ^ 1] = "side";
+        }
+      }
+    }
+    for (face, conn) in Face::SIDES.iter().zip(connections) {
+      ty.set_prop(face.as_str(), conn);
+    }
+
+    if ty != block.ty {
+      let _ = world.set_block(block.pos, ty);
+      if power as u32 != block.ty.prop("power").int() {
+        world.queue_redstone_updates_around(block.pos);
+      }
+    }
+  }
+  fn weak_power(&self, block: Block, face: Face) -> u8 {
+    let power = block.ty.prop("power").int() as u8;
+    match face {
+      // Dust always powers the block it is on.
+      Face::Bottom => power,
+      Face::Top => 0,
+      // If the dust isn't connected to anything, it powers all sides. Otherwise, it
+      // only powers the sides it is pointing into.
+      face => {
+        if Face::SIDES.iter().all(|f| block.ty.prop(f.as_str()) == "none")
+          || block.ty.prop(face.as_str()) != "none"
+        {
+          power
+        } else {
+          0
+        }
+      }
+    }
+  }
+  fn strong_power(&self, block: Block, face: Face) -> u8 { self.weak_power(block, face) }
+}
+
 pub struct Slab;
 /// Note: block place is handled by [`crate::item::behavior::impls::Slab`].
 impl Behavior for Slab {
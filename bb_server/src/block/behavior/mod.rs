@@ -6,7 +6,10 @@ use crate::{
   player::{BlockClick, Player},
   world::World,
 };
-use bb_common::math::{FPos, Pos};
+use bb_common::{
+  math::{FPos, Pos},
+  util::Face,
+};
 use bb_transfer::{MessageReader, MessageWriter};
 use std::{any::Any, sync::Arc};
 
@@ -55,8 +58,45 @@ pub trait Behavior: Send + Sync {
     None
   }
 
-  /// Called when a player right clicks on this block. If this returns `true`,
-  /// the event was handled, and a block should not be placed.
+  /// Called when a tick scheduled with
+  /// [`World::schedule_tick`](crate::world::World::schedule_tick) is ready.
+  ///
+  /// This should handle things like buttons unpressing.
+  fn scheduled_tick(&self, world: &Arc<World>, block: Block) { let _ = (world, block); }
+
+  /// Called when this block is picked for a random tick. Every tick, a few
+  /// random blocks are picked from each chunk section within simulation
+  /// distance of a player.
+  ///
+  /// This should handle things like crops growing.
+  fn random_tick(&self, world: &Arc<World>, block: Block) { let _ = (world, block); }
+
+  /// Returns the weak redstone power this block outputs through `face`. `face`
+  /// is the side of this block that the receiving block is on.
+  ///
+  /// Weak power will power any redstone component next to this block, and
+  /// will make solid blocks weakly powered. Weakly powered blocks will power
+  /// components such as lamps, but will not power redstone dust.
+  fn weak_power(&self, block: Block, face: Face) -> u8 {
+    let _ = (block, face);
+    0
+  }
+  /// Returns the strong redstone power this block outputs through `face`. This
+  /// only matters when the block on `face` is solid, in which case that block
+  /// will be strongly powered, and will power everything next to it
+  /// (including redstone dust).
+  fn strong_power(&self, block: Block, face: Face) -> u8 {
+    let _ = (block, face);
+    0
+  }
+  /// Called when a redstone update has been queued for this block, with
+  /// [`World::queue_redstone_update`]. This should recalculate any power
+  /// levels, and update the block if needed.
+  fn redstone_update(&self, world: &Arc<World>, block: Block) { let _ = (world, block); }
+
+  /// Called when a player right clicks on this block. If this returns
+  /// [`Handled`](EventFlow::Handled), the interaction was handled, and the
+  /// item in the player's hand will not be used.
   fn interact(&self, block: Block, player: &Arc<Player>) -> EventFlow {
     let _ = (block, player);
     EventFlow::Continue
@@ -98,6 +138,9 @@ impl Behavior for DefaultBehavior {}
 pub trait TileEntity: Any + Send + Sync {
   fn save(&self, w: &mut MessageWriter<&mut Vec<u8>>) -> Result<(), bb_transfer::WriteError>;
   fn as_any(&self) -> &dyn Any;
+  /// Returns a deep copy of this tile entity. Changes to this tile entity
+  /// should not show up in the returned one, and the other way around.
+  fn clone_te(&self) -> Arc<dyn TileEntity>;
 }
 
 #[derive(Default)]
@@ -125,15 +168,34 @@ impl BehaviorList {
       *wood*Log => impls::Log;
       Stripped*wood*Log => impls::Log;
 
-      *wood*Trapdoor | WarpedTrapdoor => impls::Trapdoor;
+      *wood*Trapdoor | CrimsonTrapdoor | WarpedTrapdoor => impls::Trapdoor;
       *wood*Door | WarpedDoor => impls::Door;
 
       *wood*Slab | StoneSlab | SmoothStoneSlab => impls::Slab;
 
       Sand | RedSand | Gravel => impls::Falling;
 
+      *wood*Button | CrimsonButton | WarpedButton => impls::Button { ticks: 30 };
+      StoneButton | PolishedBlackstoneButton => impls::Button { ticks: 20 };
+      Lever => impls::Lever;
+
+      RedstoneWire => impls::RedstoneWire;
+      RedstoneTorch | RedstoneWallTorch => impls::RedstoneTorch;
+      RedstoneBlock => impls::RedstoneBlock;
+      RedstoneLamp => impls::RedstoneLamp;
+
       CraftingTable => impls::CraftingTable;
 
+      Wheat | Carrots | Potatoes => impls::Crop { max_age: 7 };
+      Beetroots => impls::Crop { max_age: 3 };
+
+      OakSapling => impls::Sapling { log: Kind::OakLog, leaves: Kind::OakLeaves };
+      BirchSapling => impls::Sapling { log: Kind::BirchLog, leaves: Kind::BirchLeaves };
+      SpruceSapling => impls::Sapling { log: Kind::SpruceLog, leaves: Kind::SpruceLeaves };
+      DarkOakSapling => impls::Sapling { log: Kind::DarkOakLog, leaves: Kind::DarkOakLeaves };
+      AcaciaSapling => impls::Sapling { log: Kind::AcaciaLog, leaves: Kind::AcaciaLeaves };
+      JungleSapling => impls::Sapling { log: Kind::JungleLog, leaves: Kind::JungleLeaves };
+
       *color*Bed => impls::Bed;
 
       Chest => impls::Chest;
@@ -19,14 +19,40 @@ impl BlockLightChunk {
   pub fn new() -> Self { BlockLightChunk { data: LightChunk::new() } }
 
   pub fn update(&mut self, chunk: &BlockData, pos: RelPos) {
-    let directions = [Face::Top, Face::Bottom, Face::North, Face::South, Face::East, Face::West];
     let emitted = chunk.wm().block_converter().get(chunk.get_kind(pos).unwrap()).emit_light;
-    let mut queue = vec![(pos, emitted)];
+    self.propagate(chunk, vec![(pos, emitted)]);
+  }
+  /// Recalculates the block light for the entire chunk. This clears all
+  /// existing light, and then propagates light from every block that emits
+  /// light. This is very slow, so [`update`](Self::update) should be used for
+  /// single block changes.
+  pub fn update_all(&mut self, chunk: &BlockData) {
+    self.data = LightChunk::new();
+    let conv = chunk.wm().block_converter();
+    let mut queue = vec![];
+    for y in 0..256 {
+      for z in 0..16 {
+        for x in 0..16 {
+          let pos = RelPos::new(x, y, z);
+          let emitted = conv.get(chunk.get_kind(pos).unwrap()).emit_light;
+          if emitted > 0 {
+            queue.push((pos, emitted));
+          }
+        }
+      }
+    }
+    self.propagate(chunk, queue);
+  }
+
+  /// Spreads light outwards from all the sources in `queue`, one level at a
+  /// time.
+  fn propagate(&mut self, chunk: &BlockData, mut queue: Vec<(RelPos, u8)>) {
+    let directions = [Face::Top, Face::Bottom, Face::North, Face::South, Face::East, Face::West];
     let mut other_queue = vec![];
     while !queue.is_empty() {
       for &(source, emitted) in &queue {
-        if emitted > self.data.get_light(pos) {
-          self.data.set_light(pos, emitted);
+        if emitted > self.data.get_light(source) {
+          self.data.set_light(source, emitted);
         }
         for dir in directions {
           let new_pos = match source.checked_add(dir) {
@@ -52,19 +78,50 @@ impl BlockLightChunk {
       std::mem::swap(&mut queue, &mut other_queue);
     }
   }
-  pub fn update_all(&mut self, chunk: &BlockData) {
-    let _ = chunk;
-    // TODO
-  }
 }
 
 impl SkyLightChunk {
   pub fn new() -> Self { SkyLightChunk { data: LightChunk::new() } }
 
   pub fn update(&mut self, chunk: &BlockData, pos: RelPos) {
-    let directions = [Face::Top, Face::Bottom, Face::North, Face::South, Face::East, Face::West];
     let level = self.data.get_light(pos);
-    let mut queue = vec![(pos, level)];
+    self.propagate(chunk, vec![(pos, level)]);
+  }
+  /// Recalculates the sky light for the entire chunk. Each column is lit with
+  /// a level of 15 from the top of the world down to the first opaque block,
+  /// and then that light is spread sideways and into any caves. Transparent
+  /// blocks (like leaves or glass) reduce the light by their `filter_light`.
+  pub fn update_all(&mut self, chunk: &BlockData) {
+    self.data = LightChunk::new();
+    let conv = chunk.wm().block_converter();
+    let mut queue = vec![];
+    for z in 0..16 {
+      for x in 0..16 {
+        let mut level = 15_u8;
+        for y in (0..256).rev() {
+          let pos = RelPos::new(x, y, z);
+          let kind = chunk.get_kind(pos).unwrap();
+          let data = conv.get(kind);
+          if kind != block::Kind::Air && !data.transparent {
+            break;
+          }
+          level = level.saturating_sub(data.filter_light);
+          if level == 0 {
+            break;
+          }
+          self.data.set_light(pos, level);
+          queue.push((pos, level));
+        }
+      }
+    }
+    self.propagate(chunk, queue);
+  }
+
+  /// Spreads light outwards from all the sources in `queue`, one level at a
+  /// time. Full sky light travels straight down without getting dimmer, and
+  /// drops by one level in every other direction.
+  fn propagate(&mut self, chunk: &BlockData, mut queue: Vec<(RelPos, u8)>) {
+    let directions = [Face::Top, Face::Bottom, Face::North, Face::South, Face::East, Face::West];
     let mut other_queue = vec![];
     while !queue.is_empty() {
       for &(source, level) in &queue {
@@ -79,13 +136,14 @@ impl SkyLightChunk {
           if new_pos.y() < 0 || new_pos.y() > 255 {
             continue;
           }
-          let data = chunk.wm().block_converter().get(chunk.get_kind(new_pos).unwrap());
-          if data.transparent {
-            if dir == Face::Bottom {
-              other_queue.push((new_pos, level));
-            } else if self.data.get_light(new_pos) < level - 1 {
-              self.data.set_light(new_pos, level - 1);
-              other_queue.push((new_pos, level));
+          let kind = chunk.get_kind(new_pos).unwrap();
+          let data = chunk.wm().block_converter().get(kind);
+          if kind == block::Kind::Air || data.transparent {
+            let falloff = if dir == Face::Bottom && level == 15 { 0 } else { 1 };
+            let new_level = level.saturating_sub(falloff + data.filter_light);
+            if self.data.get_light(new_pos) < new_level {
+              self.data.set_light(new_pos, new_level);
+              other_queue.push((new_pos, new_level));
             }
           }
         }
@@ -94,8 +152,4 @@ impl SkyLightChunk {
       std::mem::swap(&mut queue, &mut other_queue);
     }
   }
-  pub fn update_all(&mut self, chunk: &BlockData) {
-    let _ = chunk;
-    // TODO
-  }
 }
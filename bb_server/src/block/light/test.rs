@@ -1,4 +1,4 @@
-use super::BlockLightChunk;
+use super::{BlockLightChunk, SkyLightChunk};
 use crate::{
   block,
   world::{BlockData, WorldManager},
@@ -301,3 +301,38 @@ fn remove_light() {
   assert_plane_matches(&mut light, &mut expected);
 }
 */
+
+#[test]
+fn update_all() {
+  let wm = Arc::new(WorldManager::new(false));
+
+  #[rustfmt::skip]
+  let (chunk, _) = chunk_from_str(wm.clone(), &[
+    &[
+      "    ###    ",
+      "    #T#    ",
+    ],
+  ]);
+
+  let mut single = BlockLightChunk::new();
+  single.update(&chunk, RelPos::new(5, 1, 0));
+
+  let mut all = BlockLightChunk::new();
+  all.data.set_light(RelPos::new(0, 10, 0), 5);
+  all.update_all(&chunk);
+  assert_eq!(all, single);
+}
+
+#[test]
+fn sky_update_all() {
+  let wm = Arc::new(WorldManager::new(false));
+  let mut chunk = BlockData::new(wm, 256, 0);
+  chunk.set_kind(RelPos::new(0, 10, 0), block::Kind::Stone).unwrap();
+
+  let mut light = SkyLightChunk::new();
+  light.update_all(&chunk);
+  assert_eq!(light.data.get_light(RelPos::new(0, 11, 0)), 15);
+  assert_eq!(light.data.get_light(RelPos::new(0, 10, 0)), 0);
+  assert_eq!(light.data.get_light(RelPos::new(0, 9, 0)), 14);
+  assert_eq!(light.data.get_light(RelPos::new(5, 0, 5)), 15);
+}
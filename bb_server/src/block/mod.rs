@@ -13,7 +13,9 @@ pub use behavior::{Behavior, BlockDrops, TileEntity};
 pub use custom::{CustomBlockBuilder, CustomData, CustomKind, CustomProp, CustomPropValue};
 pub use material::Material;
 pub use store::TypeStore;
-pub use ty::{Data, ItemDrop, Kind, Prop, PropKind, PropValue, PropValueStore, Type};
+pub use ty::{
+  BoundingBoxKind, Data, ItemDrop, Kind, Prop, PropKind, PropValue, PropValueStore, Type,
+};
 pub use version::TypeConverter;
 
 use crate::world::World;
@@ -1,5 +1,5 @@
 use super::{ty::STATE_PROPS_LEN, Kind, Prop, PropKind, PropValue};
-use std::{collections::HashMap, fmt};
+use std::fmt;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TypeStore {
@@ -86,12 +86,9 @@ impl TypeStore {
   pub fn prop_at(&self, name: &str) -> Option<&Prop> {
     self.props.iter().find(|prop| prop.name == name)
   }
-  pub fn props(&self) -> HashMap<String, String> {
-    self
-      .props
-      .iter()
-      .enumerate()
-      .map(|(i, prop)| (prop.name.into(), prop.kind.name_at(self.state_props[i])))
-      .collect()
+  /// Iterates through all the properties of this block, along with their
+  /// current values. See [`Type::props`](super::Type::props).
+  pub fn props(&self) -> impl Iterator<Item = (&'static str, PropValue<'static>)> + '_ {
+    self.props.iter().zip(self.state_props).map(|(p, id)| (p.name, p.from_id(id)))
   }
 }
@@ -1,5 +1,5 @@
 use super::{CustomKind, Material, TypeStore};
-use std::{collections::HashMap, error::Error, fmt, str::FromStr};
+use std::{error::Error, fmt, str::FromStr};
 
 pub(super) const STATE_PROPS_LEN: usize = 8;
 
@@ -73,47 +73,46 @@ impl Type<'_> {
     }
     self.state + id
   }
-  pub fn prop(&self, name: &str) -> PropValue<'_> {
+  /// Returns the index of the given property in `props` and `state_props`.
+  /// There are at most [`STATE_PROPS_LEN`] properties, so this is a short
+  /// search through the generated property table.
+  fn prop_index(&self, name: &str) -> Option<usize> {
+    self.props.iter().position(|p| p.name == name)
+  }
+
+  /// Returns the value of the given property.
+  ///
+  /// # Panics
+  /// If this block doesn't have that property. Use
+  /// [`try_prop`](Self::try_prop) to handle that case.
+  pub fn prop(&self, name: &str) -> PropValue<'static> {
     self.try_prop(name).unwrap_or_else(|e| panic!("{e}"))
   }
-  pub fn try_prop(&self, name: &str) -> Result<PropValue<'_>, PropError> {
-    let mut idx = None;
-    for (i, p) in self.props.iter().enumerate() {
-      if p.name == name {
-        idx = Some(i);
-        break;
-      }
-    }
-    if let Some(idx) = idx {
-      let state = self.state_props[idx];
-      Ok(match self.props[idx].kind {
-        PropKind::Bool => match state {
-          0 => PropValue::Bool(true),
-          _ => PropValue::Bool(false),
-        },
-        PropKind::Enum(values) => PropValue::Enum(values[state as usize]),
-        PropKind::Int { min, max } => PropValue::Int((state + min).min(max)),
-      })
-    } else {
-      Err(PropError { name: name.into(), props: self.props.to_vec() })
+  /// Returns the value of the given property, or an error if this block
+  /// doesn't have that property.
+  pub fn try_prop(&self, name: &str) -> Result<PropValue<'static>, PropError> {
+    match self.prop_index(name) {
+      Some(idx) => Ok(self.props[idx].from_id(self.state_props[idx])),
+      None => Err(PropError { name: name.into(), props: self.props.to_vec() }),
     }
   }
+  /// Sets the given property.
+  ///
+  /// # Panics
+  /// If this block doesn't have that property, or the value is invalid for
+  /// that property. Use [`try_set_prop`](Self::try_set_prop) to handle those
+  /// cases.
   pub fn set_prop<'a>(&mut self, name: &str, val: impl Into<PropValue<'a>>) {
     self.try_set_prop(name, val).unwrap_or_else(|e| panic!("{e}"))
   }
+  /// Sets the given property. Returns an error if this block doesn't have that
+  /// property, or if the value is invalid for that property.
   pub fn try_set_prop<'a>(
     &mut self,
     name: &str,
     val: impl Into<PropValue<'a>>,
   ) -> Result<(), SetPropError<'a>> {
-    let mut idx = None;
-    for (i, p) in self.props.iter().enumerate() {
-      if p.name == name {
-        idx = Some(i);
-        break;
-      }
-    }
-    if let Some(idx) = idx {
+    if let Some(idx) = self.prop_index(name) {
       let val = val.into();
       if val.is(&self.props[idx].kind) {
         self.state_props[idx] = val.id(&self.props[idx].kind);
@@ -128,28 +127,53 @@ impl Type<'_> {
     }
     Ok(())
   }
-  pub fn with<'a>(mut self, name: &str, val: impl Into<PropValue<'a>>) -> Self {
+  /// Sets the given property, and returns the modified type. This is the same
+  /// as [`with_prop`](Self::with_prop).
+  pub fn with<'a>(self, name: &str, val: impl Into<PropValue<'a>>) -> Self {
+    self.with_prop(name, val)
+  }
+  /// Sets the given property, and returns the modified type. This panics in
+  /// the same cases as [`set_prop`](Self::set_prop).
+  pub fn with_prop<'a>(mut self, name: &str, val: impl Into<PropValue<'a>>) -> Self {
     self.set_prop(name, val);
     self
   }
+  /// Sets the given property, and returns the modified type. This fails in
+  /// the same cases as [`try_set_prop`](Self::try_set_prop).
+  pub fn try_with_prop<'a>(
+    mut self,
+    name: &str,
+    val: impl Into<PropValue<'a>>,
+  ) -> Result<Self, SetPropError<'a>> {
+    self.try_set_prop(name, val)?;
+    Ok(self)
+  }
+
+  /// Returns true if this block can be waterlogged. This is any block with a
+  /// `waterlogged` property, like stairs, slabs, and fences.
+  pub fn can_waterlog(&self) -> bool { self.prop_index("waterlogged").is_some() }
+  /// Returns true if this block is waterlogged. Waterlogged blocks render with
+  /// water inside them, and leave water behind when they are broken.
+  pub fn is_waterlogged(&self) -> bool {
+    matches!(self.try_prop("waterlogged"), Ok(PropValue::Bool(true)))
+  }
 
+  /// Returns the definition of the given property, if this block has it.
   pub fn prop_at(&self, name: &str) -> Option<&Prop> {
-    self.props.iter().find(|prop| prop.name == name)
+    self.prop_index(name).map(|idx| &self.props[idx])
   }
-  pub fn props(&self) -> HashMap<String, String> {
-    self
-      .props
-      .iter()
-      .enumerate()
-      .map(|(i, prop)| (prop.name.into(), prop.kind.name_at(self.state_props[i])))
-      .collect()
+  /// Iterates through all the properties of this block, along with their
+  /// current values. These are in the same order as the generated data, which
+  /// is not sorted by name.
+  pub fn props(&self) -> impl Iterator<Item = (&'static str, PropValue<'static>)> + '_ {
+    self.props.iter().zip(self.state_props).map(|(p, id)| (p.name, p.from_id(id)))
   }
 }
 impl fmt::Display for Type<'_> {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     write!(f, "{}", self.kind().to_str())?;
-    let mut all_props: Vec<_> = self.props().into_iter().collect();
-    all_props.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    let mut all_props: Vec<_> = self.props().collect();
+    all_props.sort_unstable_by_key(|(key, _)| *key);
     if !all_props.is_empty() {
       write!(f, "[")?;
       for (i, (key, val)) in all_props.iter().enumerate() {
@@ -349,6 +373,16 @@ impl PropValue<'_> {
   }
 }
 
+impl fmt::Display for PropValue<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::Bool(v) => write!(f, "{v}"),
+      Self::Enum(v) => write!(f, "{v}"),
+      Self::Int(v) => write!(f, "{v}"),
+    }
+  }
+}
+
 impl From<bool> for PropValue<'_> {
   fn from(v: bool) -> Self { PropValue::Bool(v) }
 }
@@ -472,4 +506,29 @@ mod tests {
                              | waterlogged (false -> 1)
     */
   }
+
+  #[test]
+  fn test_props() {
+    let conv = TypeConverter::new();
+    let ty = conv.get(Kind::OakLeaves).default_type().with_prop("distance", 3);
+
+    let mut props: Vec<_> = ty.props().collect();
+    props.sort_unstable_by_key(|(key, _)| *key);
+    assert_eq!(
+      props,
+      [
+        ("distance", PropValue::Int(3)),
+        ("persistent", PropValue::Bool(false)),
+        ("waterlogged", PropValue::Bool(false)),
+      ]
+    );
+    assert_eq!(ty.to_string(), "oak_leaves[distance=3,persistent=false,waterlogged=false]");
+
+    assert_eq!(ty.prop("distance"), 3);
+    assert!(ty.try_prop("facing").is_err());
+    assert!(matches!(ty.try_with_prop("facing", "north"), Err(SetPropError::Missing(_))));
+    assert!(matches!(ty.try_with_prop("distance", 8), Err(SetPropError::WrongType(..))));
+    assert!(matches!(ty.try_with_prop("distance", true), Err(SetPropError::WrongType(..))));
+    assert_eq!(ty.try_with_prop("distance", 7).unwrap().prop("distance"), 7);
+  }
 }
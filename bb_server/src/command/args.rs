@@ -0,0 +1,130 @@
+use super::{parse::wrap_degrees, Arg, EntitySelector};
+use crate::item;
+use bb_common::math::{FPos, Pos};
+use std::ops::Deref;
+
+/// The parsed arguments of a command. This derefs to a slice of [`Arg`]s, so
+/// arguments can still be accessed by index. However, the getters on this type
+/// are preferred, as they look up arguments by the name passed to
+/// [`add_arg`](super::Command::add_arg), and they won't panic if the argument
+/// is missing or has a different type.
+///
+/// The first argument is always the name of the command. Literals are named by
+/// their text, so `/fill rect` would have a literal named `rect`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Args {
+  args:  Vec<Arg>,
+  names: Vec<String>,
+}
+
+macro_rules! getter_copy {
+  ($name:ident, $enum:ident, $ty:ty) => {
+    #[doc = concat!("Returns the `", stringify!($enum), "` argument with the given name.")]
+    /// This returns `None` if the argument wasn't parsed, or if it has a
+    /// different type.
+    pub fn $name(&self, name: &str) -> Option<$ty> {
+      match self.get_arg(name)? {
+        Arg::$enum(v) => Some(*v),
+        _ => None,
+      }
+    }
+  };
+}
+
+impl Args {
+  /// Creates an empty list of arguments.
+  pub fn new() -> Self { Args::default() }
+
+  /// Adds an argument to the end of this list.
+  pub fn push(&mut self, name: impl Into<String>, arg: Arg) {
+    self.names.push(name.into());
+    self.args.push(arg);
+  }
+  /// Adds all of the arguments in `other` to the end of this list.
+  pub fn extend(&mut self, other: Args) {
+    self.names.extend(other.names);
+    self.args.extend(other.args);
+  }
+
+  /// Returns the argument with the given name. If there are multiple arguments
+  /// with the same name, this returns the first one.
+  pub fn get_arg(&self, name: &str) -> Option<&Arg> {
+    self.names.iter().position(|n| n == name).map(|i| &self.args[i])
+  }
+  /// Returns true if an argument with the given name was parsed. This is
+  /// mostly useful for literals and optional arguments.
+  pub fn has(&self, name: &str) -> bool { self.names.iter().any(|n| n == name) }
+
+  getter_copy!(get_bool, Bool, bool);
+  getter_copy!(get_int, Int, i32);
+  getter_copy!(get_float, Float, f32);
+  getter_copy!(get_double, Double, f64);
+
+  /// Returns the `String` argument with the given name. This returns `None`
+  /// if the argument wasn't parsed, or if it has a different type.
+  pub fn get_string(&self, name: &str) -> Option<&str> {
+    match self.get_arg(name)? {
+      Arg::String(v) => Some(v),
+      _ => None,
+    }
+  }
+
+  /// Returns the `BlockPos` argument with the given name. This returns `None`
+  /// if the argument wasn't parsed, or if it has a different type.
+  pub fn get_block_pos(&self, name: &str) -> Option<Pos> {
+    match self.get_arg(name)? {
+      Arg::BlockPos(pos, _) => Some(*pos),
+      _ => None,
+    }
+  }
+  /// Returns the `Vec3` argument with the given name. This returns `None` if
+  /// the argument wasn't parsed, or if it has a different type.
+  pub fn get_vec3(&self, name: &str) -> Option<FPos> {
+    match self.get_arg(name)? {
+      Arg::Vec3(pos, _) => Some(*pos),
+      _ => None,
+    }
+  }
+  /// Returns the `Rotation` argument with the given name, as a yaw and pitch.
+  /// Any relative values are applied to `base`, which is also a yaw and pitch.
+  /// The pitch is clamped to -90..90. This returns `None` if the argument
+  /// wasn't parsed, or if it has a different type.
+  pub fn get_rotation(&self, name: &str, base: (f32, f32)) -> Option<(f32, f32)> {
+    match self.get_arg(name)? {
+      Arg::Rotation { yaw, pitch, yaw_rel, pitch_rel } => {
+        let yaw = if *yaw_rel { wrap_degrees(base.0 + yaw) } else { *yaw };
+        let pitch = if *pitch_rel { base.1 + pitch } else { *pitch };
+        Some((yaw, pitch.clamp(-90.0, 90.0)))
+      }
+      _ => None,
+    }
+  }
+  /// Returns the `Entity` argument with the given name. This returns `None`
+  /// if the argument wasn't parsed, or if it has a different type.
+  pub fn get_entity(&self, name: &str) -> Option<&EntitySelector> {
+    match self.get_arg(name)? {
+      Arg::Entity(v) => Some(v),
+      _ => None,
+    }
+  }
+
+  /// Returns the `ItemStack` argument with the given name. This returns
+  /// `None` if the argument wasn't parsed, or if it has a different type.
+  pub fn get_item_stack(&self, name: &str) -> Option<&item::Stack> {
+    match self.get_arg(name)? {
+      Arg::ItemStack(v) => Some(v),
+      _ => None,
+    }
+  }
+
+  /// Returns the names of all the arguments, in order.
+  pub fn names(&self) -> &[String] { &self.names }
+  /// Returns the arguments, without their names.
+  pub fn into_vec(self) -> Vec<Arg> { self.args }
+}
+
+impl Deref for Args {
+  type Target = [Arg];
+
+  fn deref(&self) -> &[Arg] { &self.args }
+}
@@ -6,8 +6,8 @@ use crate::{
   world::{EntitiesIter, EntitiesMapRef},
 };
 use bb_common::{
-  math::{ChunkPos, Pos},
-  nbt::NBT,
+  math::{ChunkPos, FPos, Pos},
+  nbt::{Tag, NBT},
   util::{
     chat::{Chat, Color},
     UUID,
@@ -27,6 +27,18 @@ pub enum StringType {
   Greedy,
 }
 
+/// How a single coordinate of a [`Arg::BlockPos`] or [`Arg::Vec3`] was written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordKind {
+  /// A plain number, like `10`.
+  Absolute,
+  /// Relative to the sender's position, like `~10`.
+  Relative,
+  /// Relative to where the sender is looking, like `^10`. This is either used
+  /// for all three axes, or none of them.
+  Local,
+}
+
 /// This is a command argument parser. All of the information for this comes
 /// from [wiki.vg](https://wiki.vg/Command_Data). They have a great collection
 /// of data for all of this stuff, and this entire server wouldn't be possible
@@ -126,8 +138,11 @@ pub enum Parser {
   NbtTag,
   /// Represents a full nbt tag.
   NbtCompoundTag,
-  /// Represents a time duration.
-  Time,
+  /// Represents a time duration. This is a number, with an optional unit of
+  /// `t` (ticks), `s` (seconds), or `d` (days). It is parsed into an
+  /// [`Arg::Int`], which is the number of ticks. Any time less than `min`
+  /// ticks is invalid. Vanilla uses a `min` of 0.
+  Time { min: i32 },
 
   // Forge only types:
   /// A forge mod id
@@ -153,6 +168,29 @@ pub enum EntitySelector {
   Random(HashMap<String, String>),
 }
 
+/// An item predicate, used in commands like `/clear`. This matches a single
+/// item, or an item tag like `#minecraft:logs`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItemPredicate {
+  /// Matches the given item. If the NBT is present, it was included in the
+  /// command, but it is not used when matching yet.
+  Item(item::Type, Option<Tag>),
+  /// Matches any item in the given tag. This is the name of the tag, without
+  /// the leading `#`.
+  Tag(String),
+}
+
+impl ItemPredicate {
+  /// Returns true if the given stack matches this predicate. Item tags are not
+  /// loaded on the server, so tags never match anything.
+  pub fn matches(&self, stack: &item::Stack) -> bool {
+    match self {
+      ItemPredicate::Item(ty, _) => stack.item() == *ty,
+      ItemPredicate::Tag(_) => false,
+    }
+  }
+}
+
 /// This is the result of a parsed command. It contains all the values from
 /// Parser, but also contains the data that each argument contains.
 ///
@@ -172,15 +210,19 @@ pub enum Arg {
   Entity(EntitySelector),
   ScoreHolder(String),
   GameProfile(EntitySelector),
-  BlockPos(Pos),
+  /// A block position, resolved against the sender. This also stores how each
+  /// axis was written.
+  BlockPos(Pos, [CoordKind; 3]),
   ColumnPos(ChunkPos),
-  Vec3(f64, f64, f64),
+  /// An exact position, resolved against the sender. This also stores how each
+  /// axis was written.
+  Vec3(FPos, [CoordKind; 3]),
   Vec2(f64, f64),
   // A block kind, with state info, and optional nbt
   BlockState(block::Kind, HashMap<String, String>, Option<NBT>),
   BlockPredicate(block::Kind),
   ItemStack(item::Stack),
-  ItemPredicate(item::Type),
+  ItemPredicate(ItemPredicate),
   Color(Color),
   Component(Chat),
   Message(String),
@@ -190,10 +232,23 @@ pub enum Arg {
   ObjectiveCriteria(String),
   Operation(String),
   Particle(String), // TODO: Particles
-  Rotation(f32, f32),
+  /// A yaw and pitch. If either value is relative (written with a `~`), then
+  /// that value is an offset from the rotation of whatever this is applied
+  /// to.
+  Rotation {
+    yaw:       f32,
+    pitch:     f32,
+    yaw_rel:   bool,
+    pitch_rel: bool,
+  },
   Angle(f32),
   ScoreboardSlot(String),
-  Swizzle(f64, f64, f64),
+  /// A set of axes, like `xz`. Each axis can only be listed once.
+  Swizzle {
+    x: bool,
+    y: bool,
+    z: bool,
+  },
   Team(String),
   /// A name for an inventory slot. Unclear on what is valid. Parsed as a string
   /// for now.
@@ -210,13 +265,16 @@ pub enum Arg {
     min: f64,
     max: f64,
   },
+  /// A range like `3..9`, `5..` or `..5`. At least one bound is present, and
+  /// `min <= max` if both are present. A single number sets both bounds.
   IntRange {
-    min: i32,
-    max: i32,
+    min: Option<i32>,
+    max: Option<i32>,
   },
+  /// The same as [`IntRange`](Self::IntRange), but the bounds can be decimals.
   FloatRange {
-    min: f64,
-    max: f64,
+    min: Option<f64>,
+    max: Option<f64>,
   },
   /// Represents a item enchantment. Parsed as a string.
   ItemEnchantment(String),
@@ -252,7 +310,12 @@ impl Arg {
   unwrapper_copy!(double, Double, f64);
   unwrapper_copy!(float, Float, f32);
   unwrapper_copy!(int, Int, i32);
-  unwrapper_copy!(pos, BlockPos, Pos);
+  pub fn pos(&self) -> Pos {
+    match self {
+      Arg::BlockPos(v, _) => *v,
+      _ => panic!("arg is a {:?}, not a pos", self),
+    }
+  }
   pub fn lit(&self) -> &str {
     match self {
       Arg::Literal(v) => v,
@@ -288,10 +351,31 @@ impl Arg {
 pub enum EntityIter<'a> {
   /// A player
   Player(Option<Arc<Player>>),
-  /// All entities, with the given restrictions
-  Entities { props: HashMap<String, String>, iter: EntitiesIter<'a> },
-  /// All players, with the given restrictions
-  Players { props: HashMap<String, String>, iter: EntitiesIter<'a> },
+  /// All entities, with the given restrictions. `left` is the number of
+  /// entities left before the `limit` is reached.
+  Entities { props: HashMap<String, String>, iter: EntitiesIter<'a>, left: Option<usize> },
+  /// All players, with the given restrictions. `left` is the number of players
+  /// left before the `limit` is reached.
+  Players { props: HashMap<String, String>, iter: EntitiesIter<'a>, left: Option<usize> },
+}
+
+/// Returns the `limit` argument of a selector, if it was set.
+fn selector_limit(props: &HashMap<String, String>) -> Option<usize> {
+  props.get("limit")?.parse().ok()
+}
+
+/// Returns `true` if `ent` matches the `type` argument of a selector. This can
+/// be negated with a `!`, like `type=!zombie`.
+fn matches_type(props: &HashMap<String, String>, ent: &EntityRef) -> bool {
+  let ty = match props.get("type") {
+    Some(ty) => ty,
+    None => return true,
+  };
+  let (negate, name) = match ty.strip_prefix('!') {
+    Some(name) => (true, name),
+    None => (false, ty.as_str()),
+  };
+  (entity::Type::from_name(name) == Some(ent.ty())) != negate
 }
 
 impl EntitySelector {
@@ -311,8 +395,12 @@ impl EntitySelector {
         }
         EntityIter::Player(None)
       }
-      EntitySelector::Entities(props) => EntityIter::Entities { props, iter: entities.iter() },
-      EntitySelector::Players(props) => EntityIter::Players { props, iter: entities.iter() },
+      EntitySelector::Entities(props) => {
+        EntityIter::Entities { left: selector_limit(&props), props, iter: entities.iter() }
+      }
+      EntitySelector::Players(props) => {
+        EntityIter::Players { left: selector_limit(&props), props, iter: entities.iter() }
+      }
       EntitySelector::Runner => EntityIter::Player(runner.cloned()),
       EntitySelector::Closest(_props) => {
         // TODO: Filter this by props
@@ -345,22 +433,24 @@ impl<'a> Iterator for EntityIter<'a> {
   type Item = EntityRef<'a>;
 
   fn next(&mut self) -> Option<Self::Item> {
-    match self {
-      EntityIter::Player(p) => p.take().map(EntityRef::Player),
-      EntityIter::Entities { props: _, iter } => {
-        // TODO: Read props, and filter this iterator based on this properties.
-        iter.next()
+    let (props, iter, left, only_players) = match self {
+      EntityIter::Player(p) => return p.take().map(EntityRef::Player),
+      EntityIter::Entities { props, iter, left } => (props, iter, left, false),
+      EntityIter::Players { props, iter, left } => (props, iter, left, true),
+    };
+    // TODO: Filter by the rest of the props, like `distance`.
+    if *left == Some(0) {
+      return None;
+    }
+    loop {
+      let ent = iter.next()?;
+      if (only_players && ent.as_player().is_none()) || !matches_type(props, &ent) {
+        continue;
       }
-      EntityIter::Players { props: _, iter } => {
-        // TODO: Read props, and filter this iterator based on this properties.
-        loop {
-          let ent = iter.next()?;
-          if ent.as_player().is_none() {
-            continue;
-          }
-          break Some(ent);
-        }
+      if let Some(left) = left {
+        *left -= 1;
       }
+      break Some(ent);
     }
   }
 }
@@ -17,6 +17,11 @@
 //! separated by spaces. However, they also support relative coordinates: `~10`
 //! means 10 blocks up/right/forward of your current position. See the
 //! [`Parser`] type for details on the various parsers.
+//!
+//! When the command is run, the handler is given the parsed [`Args`]. These
+//! can be looked up by name, so the above command could use
+//! `args.get_block_pos("first")`.
+mod args;
 mod enums;
 pub mod parse;
 pub mod reader;
@@ -26,7 +31,8 @@ mod serialize;
 #[cfg(feature = "wasm_plugins")]
 mod ffi;
 
-pub use enums::{Arg, EntitySelector, Parser, StringType};
+pub use args::Args;
+pub use enums::{Arg, CoordKind, EntitySelector, ItemPredicate, Parser, StringType};
 use parse::{ChildError, Span};
 pub use parse::{ErrorKind, ParseError, Tokenizer};
 pub use sender::{CommandSender, ErrorFormat};
@@ -37,7 +43,7 @@ use parking_lot::Mutex;
 use reader::CommandReader;
 use std::{collections::HashMap, sync::Arc};
 
-type Handler = Box<dyn Fn(&Arc<WorldManager>, Option<&Arc<Player>>, Vec<Arg>) + Send + Sync>;
+type Handler = Box<dyn Fn(&Arc<WorldManager>, Option<&Arc<Player>>, Args) + Send + Sync>;
 
 /// All of the commands on a server. This is a table of all the commands that
 /// the clients can run. It handles serializing these commands to packets, and
@@ -45,7 +51,7 @@ type Handler = Box<dyn Fn(&Arc<WorldManager>, Option<&Arc<Player>>, Vec<Arg>) +
 /// parsing that needs to be done for callbacks to work.
 #[derive(Default)]
 pub struct CommandTree {
-  commands: Mutex<HashMap<String, (Command, Handler)>>,
+  commands: Mutex<HashMap<String, (Command, u8, Handler)>>,
 }
 
 impl CommandTree {
@@ -55,11 +61,25 @@ impl CommandTree {
   /// Adds a new command to the tree. Any new players that join will be able to
   /// execute this command. This will also update the `/help` output, and
   /// include the command syntax/description.
+  ///
+  /// Players who are already online will be able to run this command, but
+  /// their client won't autocomplete it until
+  /// [`Player::send_commands`](Player::send_commands) is called.
   pub fn add<F>(&self, c: Command, handler: F)
   where
-    F: (Fn(&Arc<WorldManager>, Option<&Arc<Player>>, Vec<Arg>)) + Send + Sync + 'static,
+    F: (Fn(&Arc<WorldManager>, Option<&Arc<Player>>, Args)) + Send + Sync + 'static,
   {
-    self.commands.lock().insert(c.name().into(), (c, Box::new(handler)));
+    self.add_op(c, 0, handler);
+  }
+  /// Adds a new command to the tree, which can only be run by senders with an
+  /// op level of at least `op_level`. Vanilla uses level 2 for commands that
+  /// change the world, like `/tp`, and level 4 for commands that manage the
+  /// server, like `/stop`. See [`CommandSender::op_level`].
+  pub fn add_op<F>(&self, c: Command, op_level: u8, handler: F)
+  where
+    F: (Fn(&Arc<WorldManager>, Option<&Arc<Player>>, Args)) + Send + Sync + 'static,
+  {
+    self.commands.lock().insert(c.name().into(), (c, op_level, Box::new(handler)));
   }
   /// Called whenever a command should be executed. This can also be used to act
   /// like a player sent a command, even if they didn't. The text passed in
@@ -71,7 +91,7 @@ impl CommandTree {
       Ok(v) => v,
       Err(_) => return,
     };
-    let (command, handler) = match &commands.get(&command_name) {
+    let (command, op_level, handler) = match &commands.get(&command_name) {
       Some(v) => v,
       None => {
         let mut msg = Chat::empty();
@@ -82,7 +102,13 @@ impl CommandTree {
         return;
       }
     };
-    let args = match command.parse(text, sender) {
+    if sender.op_level() < *op_level {
+      let mut msg = Chat::empty();
+      msg.add("You do not have permission to use this command").color(Color::Red);
+      sender.send_message(msg);
+      return;
+    }
+    let args = match command.parse_args(text, sender) {
       Ok(v) => v,
       Err(e) => {
         let format = sender.error_format();
@@ -98,7 +124,7 @@ impl CommandTree {
           args:   args
             .iter()
             .map(|arg| {
-              types::command::sl_from_arg(arg.clone(), player.world(), Some(player)).into()
+              types::command::sl_from_arg(arg.clone(), &player.world(), Some(player)).into()
             })
             .collect(),
         })
@@ -152,6 +178,12 @@ impl Command {
   fn lit(name: String) -> Self {
     Command { name, ty: NodeType::Literal, children: vec![], optional: false }
   }
+  /// Creates a new optional literal node. Use
+  /// [`add_lit_opt`](Self::add_lit_opt) if you want to add an optional literal
+  /// node to the current command.
+  fn lit_opt(name: String) -> Self {
+    Command { name, ty: NodeType::Literal, children: vec![], optional: true }
+  }
   /// Creates a new argument node. Use [`add_arg`](Self::add_arg) if you want to
   /// add an argument node to the current command.
   fn arg(name: String, parser: Parser) -> Self {
@@ -201,6 +233,14 @@ impl Command {
     let index = self.children.len() - 1;
     self.children.get_mut(index).unwrap()
   }
+  /// Adds an optional literal. This is the same as [`add_lit`](Self::add_lit),
+  /// but allows the command to be parsed without this literal. If there are
+  /// multiple optional literals, at most one of them will be parsed.
+  pub fn add_lit_opt(&mut self, name: &str) -> &mut Command {
+    self.children.push(Command::lit_opt(name.into()));
+    let index = self.children.len() - 1;
+    self.children.get_mut(index).unwrap()
+  }
   /// Adds a new argument to this command. Unlike literal arguments, the name
   /// does not matter here. It will only be show to the client when they are
   /// autocompleting the command. Other than that, it is never used. The
@@ -225,6 +265,15 @@ impl Command {
   /// a slash at the start. If anything went wrong during parsing, a ParseError
   /// will be returned. Otherwise, a list of fields will be returned.
   pub fn parse<S>(&self, text: &str, sender: &S) -> Result<Vec<Arg>, ParseError>
+  where
+    S: CommandSender,
+  {
+    self.parse_args(text, sender).map(Args::into_vec)
+  }
+
+  /// Parses the given text, and returns the arguments along with their names.
+  /// See [`Args`] for details.
+  pub fn parse_args<S>(&self, text: &str, sender: &S) -> Result<Args, ParseError>
   where
     S: CommandSender,
   {
@@ -234,24 +283,21 @@ impl Command {
   /// If this fails, it returns the number of levels deep it was. This is so
   /// that branching commands can choose the deepest error to provide feedback
   /// for.
-  fn parse_inner<S>(
-    &self,
-    tokens: &mut Tokenizer,
-    sender: &S,
-  ) -> Result<Vec<Arg>, (ParseError, usize)>
+  fn parse_inner<S>(&self, tokens: &mut Tokenizer, sender: &S) -> Result<Args, (ParseError, usize)>
   where
     S: CommandSender,
   {
     // If we are optional, and there aren't any characters remaining, we just
     // return an empty list. This will signify to the caller that everything worked.
     if self.optional && tokens.peek().is_none() {
-      return Ok(vec![]);
+      return Ok(Args::new());
     }
     let arg = self.parse_arg(tokens, sender).map_err(|e| (e, 1))?;
     // if self.children.is_empty() && index < text.len() {
     //   return Err(ParseError::Trailing(text[index..].into()));
     // }
-    let mut out = vec![arg];
+    let mut out = Args::new();
+    out.push(self.name.clone(), arg);
     let mut deepest_error = 0;
     let mut errors = vec![];
     for c in &self.children {
@@ -358,8 +404,8 @@ impl Command {
 #[cfg(test)]
 pub mod tests {
   use super::*;
-  use crate::block;
-  use bb_common::math::Pos;
+  use crate::{block, entity, world::World};
+  use bb_common::math::{FPos, Pos};
   use std::collections::HashMap;
 
   pub struct NoneSender {}
@@ -402,11 +448,151 @@ pub mod tests {
       v,
       vec![
         Arg::Literal("fill".into()),
-        Arg::BlockPos(Pos::new(20, 20, 20)),
-        Arg::BlockPos(Pos::new(10, 30, 10)),
+        Arg::BlockPos(Pos::new(20, 20, 20), [CoordKind::Absolute; 3]),
+        Arg::BlockPos(Pos::new(10, 30, 10), [CoordKind::Absolute; 3]),
         Arg::BlockState(block::Kind::Stone, HashMap::new(), None),
       ]
     );
     Ok(())
   }
+
+  #[test]
+  fn op_level() {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct GuestSender {
+      messages: u32,
+    }
+    impl CommandSender for GuestSender {
+      fn block_pos(&self) -> Option<Pos> { None }
+      fn op_level(&self) -> u8 { 0 }
+      fn send_message(&mut self, _: Chat) { self.messages += 1; }
+      fn error_format(&self) -> ErrorFormat { ErrorFormat::Minecraft }
+    }
+
+    let wm = Arc::new(WorldManager::new(false));
+    let runs = Arc::new(AtomicU32::new(0));
+    let tree = CommandTree::new();
+    let r = runs.clone();
+    tree.add_op(Command::new("stop"), 4, move |_, _, _| {
+      r.fetch_add(1, Ordering::SeqCst);
+    });
+
+    let mut guest = GuestSender { messages: 0 };
+    tree.execute(&wm, &mut guest, "stop");
+    assert_eq!(runs.load(Ordering::SeqCst), 0);
+    assert_eq!(guest.messages, 1);
+
+    tree.execute(&wm, &mut NoneSender {}, "stop");
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+  }
+
+  #[test]
+  fn parse_named() -> Result<(), ParseError> {
+    let mut c = Command::new("fill");
+    c.add_lit("circle")
+      .add_arg("center", Parser::BlockPos)
+      .add_arg("radius", Parser::Float { min: Some(0.0), max: None })
+      .add_arg_opt("hollow", Parser::Bool);
+    let args = c.parse_args("fill circle 1 2 3 5.5", &NoneSender {})?;
+    assert!(args.has("circle"));
+    assert_eq!(args.get_block_pos("center"), Some(Pos::new(1, 2, 3)));
+    assert_eq!(args.get_float("radius"), Some(5.5));
+    // Wrong types and missing arguments don't panic.
+    assert_eq!(args.get_int("radius"), None);
+    assert_eq!(args.get_bool("hollow"), None);
+    assert_eq!(args.names(), ["fill", "circle", "center", "radius"]);
+    // Arguments can still be accessed by index.
+    assert_eq!(args[3], Arg::Float(5.5));
+
+    let args = c.parse_args("fill circle 1 2 3 5.5 true", &NoneSender {})?;
+    assert_eq!(args.get_bool("hollow"), Some(true));
+    Ok(())
+  }
+
+  #[test]
+  fn parse_optional_lit() -> Result<(), ParseError> {
+    let mut c = Command::new("fill");
+    let block = c
+      .add_arg("min", Parser::BlockPos)
+      .add_arg("max", Parser::BlockPos)
+      .add_arg("block", Parser::BlockState);
+    block.add_lit_opt("replace");
+    block.add_lit_opt("keep");
+    let args = c.parse_args("fill 1 2 3 4 5 6 stone", &NoneSender {})?;
+    assert_eq!(args.names(), ["fill", "min", "max", "block"]);
+    let args = c.parse_args("fill 1 2 3 4 5 6 stone keep", &NoneSender {})?;
+    assert!(args.has("keep"));
+    assert!(!args.has("replace"));
+    assert!(c.parse_args("fill 1 2 3 4 5 6 stone other", &NoneSender {}).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn serialize() {
+    use bb_common::net::cb::{packet::CommandList, CommandType};
+
+    let tree = CommandTree::new();
+    let mut c = Command::new("tp");
+    c.add_arg("pos", Parser::BlockPos).add_arg_opt("rotation", Parser::Rotation);
+    tree.add(c, |_, _, _| {});
+    let mut c = Command::new("fly");
+    c.add_lit("on");
+    tree.add(c, |_, _, _| {});
+
+    let CommandList { nodes, root } = tree.serialize(0);
+    // Children are written before their parents, and commands are sorted by name.
+    let names: Vec<&str> = nodes.iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(names, ["on", "fly", "rotation", "pos", "tp", ""]);
+    assert_eq!(root, 5);
+
+    let root = &nodes[5];
+    assert_eq!(root.ty, CommandType::Root);
+    assert!(!root.executable);
+    assert_eq!(root.children, [1, 4]);
+
+    // `fly` needs a literal, but `tp <pos>` can be run without a rotation.
+    assert!(!nodes[1].executable);
+    assert!(nodes[0].executable);
+    assert!(nodes[3].executable);
+    assert_eq!(nodes[3].ty, CommandType::Argument);
+    assert_eq!(nodes[3].parser, "minecraft:block_pos");
+    assert_eq!(nodes[2].parser, "minecraft:rotation");
+    assert_eq!(nodes[4].ty, CommandType::Literal);
+    assert_eq!(nodes[4].children, [3]);
+
+    // Op-only commands are only sent to players who can run them.
+    tree.add_op(Command::new("stop"), 4, |_, _, _| {});
+    let names = |op_level| -> Vec<String> {
+      tree.serialize(op_level).nodes.into_iter().map(|n| n.name).collect()
+    };
+    assert!(!names(0).contains(&"stop".into()));
+    assert!(!names(3).contains(&"stop".into()));
+    assert!(names(4).contains(&"stop".into()));
+  }
+
+  #[test]
+  fn selector_props() {
+    let world = World::new_test();
+    let zombies: Vec<i32> =
+      (0..3).map(|_| world.summon(entity::Type::Zombie, FPos::new(0.0, 100.0, 0.0))).collect();
+    let pig = world.summon(entity::Type::Pig, FPos::new(0.0, 100.0, 0.0));
+    let select = |text: &str| -> Vec<i32> {
+      let arg = Parser::Entity { single: false, only_players: false }
+        .parse(&mut Tokenizer::new(text), &NoneSender {})
+        .unwrap();
+      let entities = world.entities();
+      let mut eids: Vec<i32> = arg.entity().iter(&entities, None).map(|e| e.eid()).collect();
+      eids.sort_unstable();
+      eids
+    };
+    assert_eq!(select("@e").len(), 4);
+    assert_eq!(select("@e[limit=1]").len(), 1);
+    assert_eq!(select("@e[type=zombie]"), zombies);
+    assert_eq!(select("@e[type=minecraft:pig]"), vec![pig]);
+    assert_eq!(select("@e[type=!zombie]"), vec![pig]);
+    assert_eq!(select("@e[type=zombie,limit=2]").len(), 2);
+    assert!(select("@e[type=player]").is_empty());
+    assert!(select("@a").is_empty());
+  }
 }
@@ -4,9 +4,15 @@ mod token;
 pub use err::{ChildError, ErrorKind, ParseError, Result};
 pub use token::{Span, Tokenizer, Word};
 
-use super::{enums::EntitySelector, Arg, CommandSender, Parser, StringType};
+use super::{
+  enums::EntitySelector, Arg, CommandSender, CoordKind, ItemPredicate, Parser, StringType,
+};
 use crate::{block, entity, item};
-use bb_common::math::Pos;
+use bb_common::{
+  math::{FPos, Pos},
+  nbt::{Tag, NBT},
+  util::UUID,
+};
 use std::{collections::HashMap, fmt::Display, str::FromStr};
 
 pub fn parse_num<T>(w: &Word, min: &Option<T>, max: &Option<T>) -> Result<T>
@@ -35,6 +41,206 @@ where
   Ok(num)
 }
 
+/// Parses a range, like `3..9`. Either bound can be left out (`5..` or `..5`),
+/// but not both. A single number (`5`) is a range that only contains that
+/// number. This is the syntax used by entity selectors, such as
+/// `@e[distance=..5]`.
+pub fn parse_range<T>(w: &Word) -> Result<(Option<T>, Option<T>)>
+where
+  T: PartialOrd + FromStr + Copy + Into<f64>,
+{
+  let parse = |s: &str| -> Result<Option<T>> {
+    if s.is_empty() {
+      return Ok(None);
+    }
+    let num: T = s.parse().map_err(|_| w.expected("a range (3..9, 5.., ..5 or 5)"))?;
+    // Float ranges would otherwise accept `nan` and `inf`.
+    if num.into().is_finite() {
+      Ok(Some(num))
+    } else {
+      Err(w.expected("a finite number"))
+    }
+  };
+  let (min, max) = match w.find("..") {
+    Some(idx) => (parse(&w[..idx])?, parse(&w[idx + 2..])?),
+    None => {
+      let num = parse(w)?;
+      (num, num)
+    }
+  };
+  match (min, max) {
+    (None, None) => Err(w.expected("a range (3..9, 5.., ..5 or 5)")),
+    (Some(min), Some(max)) if min > max => {
+      Err(ParseError::new(w.pos(), ErrorKind::Range(min.into(), None, Some(max.into()))))
+    }
+    _ => Ok((min, max)),
+  }
+}
+
+/// Parses an angle in degrees. If the word starts with `~`, the angle is
+/// relative to `base`. If `base` is `None`, relative angles are invalid.
+fn parse_angle(w: &mut Word, base: Option<f32>) -> Result<f32> {
+  if let Some(rel) = w.strip_prefix('~') {
+    let base = base.ok_or_else(|| w.expected("an absolute angle"))?;
+    if rel.is_empty() {
+      return Ok(base);
+    }
+    let rel = rel.to_string();
+    w.set_text(rel);
+    Ok(base + parse_finite(w)?)
+  } else {
+    parse_finite(w)
+  }
+}
+
+/// Parses a single coordinate of a position, and returns how it was written.
+/// For relative (`~`) and local (`^`) coordinates, this returns the offset,
+/// which is zero if there is no number after the prefix. If `int` is set,
+/// absolute coordinates must be whole numbers. If `center` is set, whole
+/// numbers are moved to the middle of the block, so `/tp 10 64 10` puts the
+/// player at `10.5 64 10.5`.
+fn parse_coord(w: &mut Word, int: bool, center: bool) -> Result<(f64, CoordKind)> {
+  let kind = match w.chars().next() {
+    Some('~') => CoordKind::Relative,
+    Some('^') => CoordKind::Local,
+    _ => CoordKind::Absolute,
+  };
+  if kind != CoordKind::Absolute {
+    let rel = w[1..].to_string();
+    w.set_text(rel);
+    if w.is_empty() {
+      return Ok((0.0, kind));
+    }
+    return Ok((parse_finite(w)?, kind));
+  }
+  if int {
+    return Ok((parse_num::<i32>(w, &None, &None)?.into(), kind));
+  }
+  let num: f64 = parse_finite(w)?;
+  if center && !w.contains('.') {
+    Ok((num + 0.5, kind))
+  } else {
+    Ok((num, kind))
+  }
+}
+
+/// Parses the three coordinates of a position, and resolves any relative or
+/// local coordinates against the sender. If `block` is set, absolute
+/// coordinates must be whole numbers. Relative coordinates are always added to
+/// the sender's exact position, so block positions should be floored
+/// afterwards, like vanilla. This returns the position, how each axis was
+/// written, and the word for the Y axis.
+fn parse_coords<S>(
+  tokens: &mut Tokenizer,
+  sender: &S,
+  block: bool,
+) -> Result<(FPos, [CoordKind; 3], Word)>
+where
+  S: CommandSender,
+{
+  let mut w_x = tokens.read_spaced_text()?;
+  let (x, x_kind) = parse_coord(&mut w_x, block, !block)?;
+  let mut w_y = tokens.read_spaced_text()?;
+  let (y, y_kind) = parse_coord(&mut w_y, block, false)?;
+  let mut w_z = tokens.read_spaced_text()?;
+  let (z, z_kind) = parse_coord(&mut w_z, block, !block)?;
+
+  // Just like vanilla, local coordinates cannot be mixed with world coordinates.
+  let local = x_kind == CoordKind::Local;
+  for (w, kind) in [(&w_y, y_kind), (&w_z, z_kind)] {
+    if (kind == CoordKind::Local) != local {
+      return Err(w.expected("`^` on either all or none of the axes"));
+    }
+  }
+  let pos = if local {
+    match (sender.pos(), sender.rotation()) {
+      (Some(pos), Some((yaw, pitch))) => pos + local_offset(yaw, pitch, x, y, z),
+      _ => return Err(w_x.expected("an absolute position")),
+    }
+  } else {
+    // Senders that only have a block position fall back to that.
+    let base = sender.pos().or_else(|| sender.block_pos().map(FPos::from));
+    let resolve = |w: &Word, kind: CoordKind, value: f64, base: Option<f64>| match kind {
+      CoordKind::Relative => {
+        base.map(|base| base + value).ok_or_else(|| w.expected("an absolute position"))
+      }
+      _ => Ok(value),
+    };
+    FPos::new(
+      resolve(&w_x, x_kind, x, base.map(|p| p.x()))?,
+      resolve(&w_y, y_kind, y, base.map(|p| p.y()))?,
+      resolve(&w_z, z_kind, z, base.map(|p| p.z()))?,
+    )
+  };
+  Ok((pos, [x_kind, y_kind, z_kind], w_y))
+}
+
+/// Converts a local offset (`^left ^up ^forward`) into a world offset, for
+/// something looking in the given direction. This matches vanilla, so the up
+/// axis tilts along with the pitch.
+fn local_offset(yaw: f32, pitch: f32, left: f64, up: f64, forward: f64) -> FPos {
+  let yaw = f64::from(yaw + 90.0).to_radians();
+  let pitch = f64::from(-pitch).to_radians();
+  let up_pitch = pitch + std::f64::consts::FRAC_PI_2;
+  let f = (yaw.cos() * pitch.cos(), pitch.sin(), yaw.sin() * pitch.cos());
+  let u = (yaw.cos() * up_pitch.cos(), up_pitch.sin(), yaw.sin() * up_pitch.cos());
+  // The left axis is `up x forward`.
+  let l = (u.1 * f.2 - u.2 * f.1, u.2 * f.0 - u.0 * f.2, u.0 * f.1 - u.1 * f.0);
+  FPos::new(
+    f.0 * forward + u.0 * up + l.0 * left,
+    f.1 * forward + u.1 * up + l.1 * left,
+    f.2 * forward + u.2 * up + l.2 * left,
+  )
+}
+
+/// Parses a float, and makes sure it isn't infinite or NaN.
+fn parse_finite<T>(w: &Word) -> Result<T>
+where
+  T: num::Float + FromStr + Display,
+{
+  let num: T = parse_num(w, &None, &None)?;
+  if num.is_finite() {
+    Ok(num)
+  } else {
+    Err(w.expected("a finite number"))
+  }
+}
+
+/// Parses an item id, like `diamond` or `minecraft:diamond`.
+fn parse_item(w: &Word) -> Result<item::Type> {
+  item::Type::from_name(w).ok_or_else(|| w.invalid())
+}
+
+/// Parses a UUID, either in the hyphenated 8-4-4-4-12 form, or as 32 hex
+/// digits with no hyphens.
+fn parse_uuid(w: &Word) -> Result<UUID> {
+  let valid = match w.len() {
+    32 => w.chars().all(|c| c.is_ascii_hexdigit()),
+    36 => w.char_indices().all(|(i, c)| match i {
+      8 | 13 | 18 | 23 => c == '-',
+      _ => c.is_ascii_hexdigit(),
+    }),
+    _ => false,
+  };
+  if !valid {
+    return Err(w.expected("a valid UUID"));
+  }
+  let hex: String = w.chars().filter(|&c| c != '-').collect();
+  UUID::from_str(&hex).map_err(|_| w.expected("a valid UUID"))
+}
+
+/// Wraps the given angle into the range -180..180.
+pub(super) fn wrap_degrees(angle: f32) -> f32 {
+  let angle = angle % 360.0;
+  if angle >= 180.0 {
+    angle - 360.0
+  } else if angle < -180.0 {
+    angle + 360.0
+  } else {
+    angle
+  }
+}
+
 impl Parser {
   pub fn parse<S>(&self, tokens: &mut Tokenizer, sender: &S) -> Result<Arg>
   where
@@ -67,48 +273,99 @@ impl Parser {
         Ok(Arg::Int(num))
       }
       Self::String(StringType::Word) => Ok(Arg::String(tokens.read_spaced_word()?.to_string())),
+      Self::String(StringType::Quotable) => Ok(Arg::String(tokens.read_quotable()?.to_string())),
       Self::BlockPos => {
-        if let Some(pos) = sender.block_pos() {
-          let mut w_x = tokens.read_spaced_text()?;
-          let x_rel = w_x.starts_with('~');
-          if x_rel {
-            w_x.set_text(w_x[1..].to_string());
-          }
-          let x = if x_rel && w_x.is_empty() { 0 } else { parse_num(&w_x, &None, &None)? };
-
-          let mut w_y = tokens.read_spaced_text()?;
-          let y_rel = w_y.starts_with('~');
-          if y_rel {
-            w_y.set_text(w_y[1..].to_string());
-          }
-          let y = if y_rel && w_y.is_empty() { 0 } else { parse_num(&w_y, &None, &None)? };
-
-          let mut w_z = tokens.read_spaced_text()?;
-          let z_rel = w_z.starts_with('~');
-          if z_rel {
-            w_z.set_text(w_z[1..].to_string());
-          }
-          let z = if z_rel && w_z.is_empty() { 0 } else { parse_num(&w_z, &None, &None)? };
-
-          let out = Pos::new(
-            if x_rel { pos.x() + x } else { x },
-            if y_rel { pos.y() + y } else { y },
-            if z_rel { pos.z() + z } else { z },
-          );
-          if out.y < 0 || out.y > 255 {
-            Err(w_y.expected("a position inside the world"))
-          } else {
-            Ok(Arg::BlockPos(out))
+        let (pos, kinds, w_y) = parse_coords(tokens, sender, true)?;
+        let pos = pos.block();
+        if pos.y < 0 || pos.y > 255 {
+          Err(w_y.expected("a position inside the world"))
+        } else {
+          Ok(Arg::BlockPos(pos, kinds))
+        }
+      }
+      Self::Vec3 => {
+        let (pos, kinds, _) = parse_coords(tokens, sender, false)?;
+        Ok(Arg::Vec3(pos, kinds))
+      }
+      Self::Rotation => {
+        // Relative values are left as offsets, as they are usually relative to the
+        // entity being rotated, not the sender.
+        let mut w_yaw = tokens.read_spaced_text()?;
+        let yaw_rel = w_yaw.starts_with('~');
+        let yaw = parse_angle(&mut w_yaw, Some(0.0))?;
+        let mut w_pitch = tokens.read_spaced_text()?;
+        let pitch_rel = w_pitch.starts_with('~');
+        let pitch = parse_angle(&mut w_pitch, Some(0.0))?;
+        if !pitch_rel && !(-90.0..=90.0).contains(&pitch) {
+          return Err(w_pitch.expected("a pitch between -90 and 90"));
+        }
+        let yaw = if yaw_rel { yaw } else { wrap_degrees(yaw) };
+        Ok(Arg::Rotation { yaw, pitch, yaw_rel, pitch_rel })
+      }
+      Self::Angle => {
+        let mut w = tokens.read_spaced_text()?;
+        let angle = parse_angle(&mut w, sender.rotation().map(|(yaw, _)| yaw))?;
+        Ok(Arg::Angle(wrap_degrees(angle)))
+      }
+      Self::Swizzle => {
+        let w = tokens.read_spaced_text()?;
+        if w.is_empty() {
+          return Err(w.expected("an axis (x, y or z)"));
+        }
+        let (mut x, mut y, mut z) = (false, false, false);
+        for (i, c) in w.char_indices() {
+          let axis = match c {
+            'x' => &mut x,
+            'y' => &mut y,
+            'z' => &mut z,
+            _ => return Err(w.expected("only x, y or z")),
+          };
+          if *axis {
+            let pos = w.pos().start + i;
+            return Err(ParseError::new(
+              Span::single(pos),
+              ErrorKind::Expected("each axis only once".into()),
+            ));
           }
+          *axis = true;
+        }
+        Ok(Arg::Swizzle { x, y, z })
+      }
+      Self::IntRange => {
+        let w = tokens.read_spaced_text()?;
+        let (min, max) = parse_range(&w)?;
+        Ok(Arg::IntRange { min, max })
+      }
+      Self::FloatRange => {
+        let w = tokens.read_spaced_text()?;
+        let (min, max) = parse_range(&w)?;
+        Ok(Arg::FloatRange { min, max })
+      }
+      Self::Uuid => {
+        let w = tokens.read_spaced_text()?;
+        Ok(Arg::Uuid(parse_uuid(&w)?))
+      }
+      Self::Time { min } => {
+        let mut w = tokens.read_spaced_text()?;
+        let ticks_per_unit = match w.chars().last() {
+          Some('t') => 1.0,
+          Some('s') => 20.0,
+          Some('d') => 24000.0,
+          Some(c) if c.is_ascii_alphabetic() => return Err(w.expected("a valid unit (t, s or d)")),
+          _ => 1.0,
+        };
+        if w.ends_with(|c: char| c.is_ascii_alphabetic()) {
+          let num = w[..w.len() - 1].to_string();
+          w.set_text(num);
+        }
+        let num: f64 = parse_finite(&w)?;
+        let ticks = (num * ticks_per_unit).round();
+        if ticks < f64::from(*min) {
+          Err(w.expected(format!("a time of at least {min} ticks")))
+        } else if ticks > f64::from(i32::MAX) {
+          Err(w.expected("a shorter time"))
         } else {
-          let w = tokens.read_spaced_text()?;
-          let x = parse_num(&w, &None, &None)?;
-          let w = tokens.read_spaced_text()?;
-          let y = parse_num(&w, &None, &None)?;
-          let w = tokens.read_spaced_text()?;
-          let z = parse_num(&w, &None, &None)?;
-
-          Ok(Arg::BlockPos(Pos::new(x, y, z)))
+          Ok(Arg::Int(ticks as i32))
         }
       }
       Self::BlockState => {
@@ -120,12 +377,53 @@ impl Parser {
         ))
       }
       Self::ItemStack => {
-        let w = tokens.read_spaced_word()?;
-        Ok(Arg::ItemStack(item::Stack::new(item::Type::from_str(&w).map_err(|_| w.invalid())?)))
+        let w = tokens.read_resource()?;
+        let mut stack = item::Stack::new(parse_item(&w)?);
+        if tokens.peek() == Some('{') {
+          let (nbt, span) = tokens.read_snbt()?;
+          stack = stack
+            .with_nbt(&nbt)
+            .map_err(|_| ParseError::new(span, ErrorKind::Expected("valid item data".into())))?;
+        }
+        tokens.read_space()?;
+        Ok(Arg::ItemStack(stack))
+      }
+      Self::ItemPredicate => {
+        let w = tokens.read_resource()?;
+        let pred = match w.strip_prefix('#') {
+          Some(tag) if !tag.is_empty() => ItemPredicate::Tag(tag.to_string()),
+          Some(_) => return Err(w.expected("a tag name")),
+          None => {
+            let ty = parse_item(&w)?;
+            let nbt = if tokens.peek() == Some('{') { Some(tokens.read_snbt()?.0) } else { None };
+            ItemPredicate::Item(ty, nbt)
+          }
+        };
+        tokens.read_space()?;
+        Ok(Arg::ItemPredicate(pred))
       }
       Self::EntitySummon => {
-        let w = tokens.read_spaced_word()?;
-        Ok(Arg::EntitySummon(entity::Type::from_str(&w).map_err(|_| w.invalid())?))
+        let w = tokens.read_resource()?;
+        let ty = match entity::Type::from_name(&w) {
+          // Players can't be summoned.
+          Some(entity::Type::Player) | None => return Err(w.expected("a valid entity type")),
+          Some(ty) => ty,
+        };
+        tokens.read_space()?;
+        Ok(Arg::EntitySummon(ty))
+      }
+      Self::NbtTag => {
+        let (tag, _) = tokens.read_snbt()?;
+        tokens.read_space()?;
+        Ok(Arg::NbtTag(NBT::new("", tag)))
+      }
+      Self::NbtCompoundTag => {
+        let (tag, span) = tokens.read_snbt()?;
+        if !matches!(tag, Tag::Compound(_)) {
+          return Err(ParseError::new(span, ErrorKind::Expected("a compound tag".into())));
+        }
+        tokens.read_space()?;
+        Ok(Arg::NbtCompoundTag(NBT::new("", tag)))
       }
       Self::Entity { single, only_players } => {
         let word = tokens.read_spaced_text()?;
@@ -133,29 +431,44 @@ impl Parser {
           // Add one for the `@` character
           let mut tokens = Tokenizer::new_with_pos(text, word.pos().start + 1);
           let selector = tokens.read_word()?;
-          if *only_players && matches!(selector.as_str(), "e" | "r") {
-            return Err(selector.expected("a valid selector (@a, @p or @s)"));
-          }
-          if *single && matches!(selector.as_str(), "a") {
-            return Err(selector.expected("a valid selector (@p or @s)"));
-          }
           if !matches!(selector.as_str(), "p" | "r" | "a" | "e" | "s") {
             return Err(selector.expected("a valid selector"));
           }
           let mut args = HashMap::new();
           if !tokens.is_empty() {
             tokens.expect("[")?;
-            loop {
+            while tokens.peek() != Some(']') {
               let key = tokens.read_word()?;
               tokens.expect("=")?;
-              let val = tokens.read_word()?;
+              // Values are kept as text, so that things like `distance=..5` and
+              // `type=!player` can be handled when the selector is used.
+              let val = tokens.read_until(&[',', ']']);
+              if val.is_empty() {
+                return Err(val.expected("a value"));
+              }
               args.insert(key.to_string(), val.to_string());
-              if tokens.peek() == Some(']') {
-                break;
-              } else {
+              if tokens.peek() != Some(']') {
                 tokens.expect(",")?;
               }
             }
+            tokens.expect("]")?;
+            if !tokens.is_empty() {
+              return Err(ParseError::new(
+                Span::new(tokens.pos(), word.pos().end),
+                ErrorKind::Trailing,
+              ));
+            }
+          }
+          // `@e` can still be used with these flags, as long as the arguments
+          // limit it to one entity, or to players.
+          let one = args.get("limit").map_or(false, |v| v == "1");
+          let players =
+            args.get("type").map_or(false, |v| v == "player" || v == "minecraft:player");
+          if *only_players && selector == "e" && !players {
+            return Err(selector.expected("a valid selector (@a, @p, @r or @s)"));
+          }
+          if *single && matches!(selector.as_str(), "a" | "e") && !one {
+            return Err(selector.expected("a valid selector (@p, @r or @s)"));
           }
           match selector.as_str() {
             "p" => EntitySelector::Closest(args),
@@ -167,6 +480,11 @@ impl Parser {
           }
         } else {
           // A username
+          let valid = (1..=16).contains(&word.len())
+            && word.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+          if !valid {
+            return Err(word.expected("a username or selector"));
+          }
           EntitySelector::Name(word.to_string())
         }))
       }
@@ -350,7 +668,7 @@ impl Parser {
       Self::Uuid => "a UUID",
       Self::NbtTag => "a partial NBT tag",
       Self::NbtCompoundTag => "a complete NBT tag",
-      Self::Time => "a time duration (10 (ticks), 0.5d (days), 3s (seconds))",
+      Self::Time { .. } => "a time duration (10 (ticks), 0.5d (days), 3s (seconds))",
       Self::Modid => "a mod id",
       Self::Enum => "an enum added by Forge",
     }
@@ -360,7 +678,8 @@ impl Parser {
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::command::tests::NoneSender;
+  use crate::command::{tests::NoneSender, ErrorFormat};
+  use bb_common::util::Chat;
 
   #[test]
   fn parse_types() -> Result<()> {
@@ -482,4 +801,487 @@ mod tests {
     // Parser::Enum => (),
     Ok(())
   }
+
+  struct LookingSender {}
+
+  impl CommandSender for LookingSender {
+    fn block_pos(&self) -> Option<Pos> { None }
+    fn pos(&self) -> Option<FPos> { Some(FPos::new(1.5, 64.0, -3.25)) }
+    fn rotation(&self) -> Option<(f32, f32)> { Some((170.0, 10.0)) }
+    fn send_message(&mut self, _: Chat) {}
+    fn error_format(&self) -> ErrorFormat { ErrorFormat::Minecraft }
+  }
+
+  #[test]
+  fn parse_rotation() -> Result<()> {
+    let parse = |p: Parser, text: &str| p.parse(&mut Tokenizer::new(text), &NoneSender {});
+    let abs = |yaw, pitch| Arg::Rotation { yaw, pitch, yaw_rel: false, pitch_rel: false };
+    assert_eq!(parse(Parser::Rotation, "90 -45")?, abs(90.0, -45.0));
+    assert_eq!(parse(Parser::Rotation, "270 0")?, abs(-90.0, 0.0));
+    assert_eq!(parse(Parser::Angle, "45.5")?, Arg::Angle(45.5));
+    assert_eq!(parse(Parser::Angle, "180")?, Arg::Angle(-180.0));
+    assert_eq!(parse(Parser::Angle, "-540")?, Arg::Angle(-180.0));
+
+    // Relative rotations are kept as offsets, so they don't depend on the sender.
+    assert_eq!(
+      parse(Parser::Rotation, "~ ~-.5")?,
+      Arg::Rotation { yaw: 0.0, pitch: -0.5, yaw_rel: true, pitch_rel: true }
+    );
+    assert_eq!(
+      parse(Parser::Rotation, "~200 5")?,
+      Arg::Rotation { yaw: 200.0, pitch: 5.0, yaw_rel: true, pitch_rel: false }
+    );
+    // A relative pitch can be out of range, as it gets clamped once it is applied.
+    assert_eq!(
+      parse(Parser::Rotation, "0 ~120")?,
+      Arg::Rotation { yaw: 0.0, pitch: 120.0, yaw_rel: false, pitch_rel: true }
+    );
+    // Following arguments should still parse.
+    let mut tokens = Tokenizer::new("~10 ~ 5");
+    assert_eq!(
+      Parser::Rotation.parse(&mut tokens, &NoneSender {})?,
+      Arg::Rotation { yaw: 10.0, pitch: 0.0, yaw_rel: true, pitch_rel: true }
+    );
+    assert_eq!(tokens.pos(), 6);
+    let looking = |p: Parser, text: &str| p.parse(&mut Tokenizer::new(text), &LookingSender {});
+    assert_eq!(looking(Parser::Angle, "~-10")?, Arg::Angle(160.0));
+
+    assert_eq!(
+      parse(Parser::Rotation, "0 91"),
+      Err(ParseError::new(
+        Span::new(2, 4),
+        ErrorKind::Expected("a pitch between -90 and 90".into())
+      ))
+    );
+    assert_eq!(
+      parse(Parser::Angle, "north"),
+      Err(ParseError::new(Span::new(0, 5), ErrorKind::Expected("a number".into())))
+    );
+    assert_eq!(
+      parse(Parser::Angle, "inf"),
+      Err(ParseError::new(Span::new(0, 3), ErrorKind::Expected("a finite number".into())))
+    );
+    assert_eq!(parse(Parser::Rotation, "10").unwrap_err().kind(), &ErrorKind::EOF);
+    Ok(())
+  }
+
+  #[test]
+  fn parse_swizzle() -> Result<()> {
+    let parse = |text: &str| Parser::Swizzle.parse(&mut Tokenizer::new(text), &NoneSender {});
+    assert_eq!(parse("y")?, Arg::Swizzle { x: false, y: true, z: false });
+    assert_eq!(parse("zx")?, Arg::Swizzle { x: true, y: false, z: true });
+    assert_eq!(parse("xyz")?, Arg::Swizzle { x: true, y: true, z: true });
+
+    // The parser should stop at the space, so that the next argument can be read.
+    let mut tokens = Tokenizer::new("xz 5");
+    assert_eq!(
+      Parser::Swizzle.parse(&mut tokens, &NoneSender {})?,
+      Arg::Swizzle { x: true, y: false, z: true }
+    );
+    assert_eq!(tokens.pos(), 3);
+    assert_eq!(
+      Parser::Int { min: None, max: None }.parse(&mut tokens, &NoneSender {})?,
+      Arg::Int(5)
+    );
+
+    assert_eq!(
+      parse("xyx"),
+      Err(ParseError::new(Span::single(2), ErrorKind::Expected("each axis only once".into())))
+    );
+    assert_eq!(
+      parse("xw"),
+      Err(ParseError::new(Span::new(0, 2), ErrorKind::Expected("only x, y or z".into())))
+    );
+    assert_eq!(
+      parse(" x"),
+      Err(ParseError::new(Span::new(0, 0), ErrorKind::Expected("an axis (x, y or z)".into())))
+    );
+    assert_eq!(parse("").unwrap_err().kind(), &ErrorKind::EOF);
+    Ok(())
+  }
+
+  #[test]
+  fn parse_uuid_arg() -> Result<()> {
+    let parse = |text: &str| Parser::Uuid.parse(&mut Tokenizer::new(text), &NoneSender {});
+    let uuid = Arg::Uuid(UUID::from_u128(0x0123456789abcdef0011223344556677));
+    assert_eq!(parse("01234567-89ab-cdef-0011-223344556677")?, uuid);
+    assert_eq!(parse("0123456789ABCDEF0011223344556677")?, uuid);
+
+    let mut tokens = Tokenizer::new("01234567-89ab-cdef-0011-223344556677 5");
+    assert_eq!(Parser::Uuid.parse(&mut tokens, &NoneSender {})?, uuid);
+    assert_eq!(tokens.pos(), 37);
+
+    let invalid = |start, end| {
+      Err(ParseError::new(Span::new(start, end), ErrorKind::Expected("a valid UUID".into())))
+    };
+    // Wrong length, misplaced hyphens, and non-hex digits.
+    assert_eq!(parse("0123456789abcdef"), invalid(0, 16));
+    assert_eq!(parse("0123456-789ab-cdef-0011-223344556677"), invalid(0, 36));
+    assert_eq!(parse("0123456789abcdef001122334455667g"), invalid(0, 32));
+    // `u128::from_str_radix` would accept a leading `+`.
+    assert_eq!(parse("+123456789abcdef0011223344556677"), invalid(0, 32));
+    assert_eq!(parse("").unwrap_err().kind(), &ErrorKind::EOF);
+    Ok(())
+  }
+
+  #[test]
+  fn parse_ranges() -> Result<()> {
+    let int = |text: &str| Parser::IntRange.parse(&mut Tokenizer::new(text), &NoneSender {});
+    let float = |text: &str| Parser::FloatRange.parse(&mut Tokenizer::new(text), &NoneSender {});
+    assert_eq!(int("5")?, Arg::IntRange { min: Some(5), max: Some(5) });
+    assert_eq!(int("5..")?, Arg::IntRange { min: Some(5), max: None });
+    assert_eq!(int("..-5")?, Arg::IntRange { min: None, max: Some(-5) });
+    assert_eq!(int("3..9")?, Arg::IntRange { min: Some(3), max: Some(9) });
+    assert_eq!(float("1.5..2")?, Arg::FloatRange { min: Some(1.5), max: Some(2.0) });
+    assert_eq!(float(".5..")?, Arg::FloatRange { min: Some(0.5), max: None });
+
+    let expected = |end| {
+      Err(ParseError::new(
+        Span::new(0, end),
+        ErrorKind::Expected("a range (3..9, 5.., ..5 or 5)".into()),
+      ))
+    };
+    assert_eq!(int(".."), expected(2));
+    assert_eq!(int("1.5..2"), expected(6));
+    assert_eq!(int("3...9"), expected(5));
+    assert_eq!(
+      int("9..3"),
+      Err(ParseError::new(Span::new(0, 4), ErrorKind::Range(9.0, None, Some(3.0))))
+    );
+    for text in ["nan..", "..inf", "-inf..0", "NaN"] {
+      assert_eq!(
+        float(text),
+        Err(ParseError::new(
+          Span::new(0, text.len()),
+          ErrorKind::Expected("a finite number".into())
+        ))
+      );
+    }
+
+    let mut tokens = Tokenizer::new("..5 3");
+    assert_eq!(
+      Parser::IntRange.parse(&mut tokens, &NoneSender {})?,
+      Arg::IntRange { min: None, max: Some(5) }
+    );
+    assert_eq!(
+      Parser::IntRange.parse(&mut tokens, &NoneSender {})?,
+      Arg::IntRange { min: Some(3), max: Some(3) }
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn parse_entity() -> Result<()> {
+    let parse = |single: bool, only_players: bool, text: &str| {
+      Parser::Entity { single, only_players }.parse(&mut Tokenizer::new(text), &NoneSender {})
+    };
+    let args = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+      pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    };
+    assert_eq!(parse(false, false, "@s")?, Arg::Entity(EntitySelector::Runner));
+    assert_eq!(parse(false, false, "Notch")?, Arg::Entity(EntitySelector::Name("Notch".into())));
+    assert_eq!(
+      parse(false, false, "@e[type=!player,distance=..5]")?,
+      Arg::Entity(EntitySelector::Entities(args(&[("type", "!player"), ("distance", "..5")])))
+    );
+    assert_eq!(parse(false, false, "@a[]")?, Arg::Entity(EntitySelector::Players(args(&[]))));
+
+    // `@r` only picks players, and `@e` is fine if it is limited.
+    assert_eq!(parse(true, true, "@r")?, Arg::Entity(EntitySelector::Random(args(&[]))));
+    assert_eq!(
+      parse(true, false, "@e[limit=1]")?,
+      Arg::Entity(EntitySelector::Entities(args(&[("limit", "1")])))
+    );
+    assert_eq!(
+      parse(false, true, "@e[type=player]")?,
+      Arg::Entity(EntitySelector::Entities(args(&[("type", "player")])))
+    );
+    assert_eq!(
+      parse(false, true, "@e"),
+      Err(ParseError::new(
+        Span::new(1, 2),
+        ErrorKind::Expected("a valid selector (@a, @p, @r or @s)".into())
+      ))
+    );
+    assert_eq!(
+      parse(true, false, "@a[limit=2]"),
+      Err(ParseError::new(
+        Span::new(1, 2),
+        ErrorKind::Expected("a valid selector (@p, @r or @s)".into())
+      ))
+    );
+
+    assert_eq!(
+      parse(false, false, "@x"),
+      Err(ParseError::new(Span::new(1, 2), ErrorKind::Expected("a valid selector".into())))
+    );
+    assert_eq!(
+      parse(false, false, "@e[type=]"),
+      Err(ParseError::new(Span::new(8, 8), ErrorKind::Expected("a value".into())))
+    );
+    assert_eq!(
+      parse(false, false, "@e[limit=1]x"),
+      Err(ParseError::new(Span::new(11, 12), ErrorKind::Trailing))
+    );
+    assert_eq!(
+      parse(false, false, "not-a-name"),
+      Err(ParseError::new(Span::new(0, 10), ErrorKind::Expected("a username or selector".into())))
+    );
+    // The selector stops at the space, so the next argument can be read.
+    let mut tokens = Tokenizer::new("@p[distance=3..9] 5");
+    Parser::Entity { single: true, only_players: true }.parse(&mut tokens, &NoneSender {})?;
+    assert_eq!(tokens.pos(), 18);
+    Ok(())
+  }
+
+  #[test]
+  fn parse_vec3() -> Result<()> {
+    use CoordKind::*;
+    let parse = |text: &str| Parser::Vec3.parse(&mut Tokenizer::new(text), &NoneSender {});
+    let vec3 = |x, y, z, kinds| Arg::Vec3(FPos::new(x, y, z), kinds);
+    assert_eq!(parse("1.5 64 -3.25")?, vec3(1.5, 64.0, -3.25, [Absolute; 3]));
+    // Whole numbers are centered on the block, except for Y.
+    assert_eq!(parse("10 64 -10")?, vec3(10.5, 64.0, -9.5, [Absolute; 3]));
+    assert_eq!(
+      parse("~ 64 0"),
+      Err(ParseError::new(Span::new(0, 1), ErrorKind::Expected("an absolute position".into())))
+    );
+    assert_eq!(
+      parse("0 nan 0"),
+      Err(ParseError::new(Span::new(2, 5), ErrorKind::Expected("a finite number".into())))
+    );
+    assert_eq!(parse("1 2").unwrap_err().kind(), &ErrorKind::EOF);
+
+    let looking = |text: &str| Parser::Vec3.parse(&mut Tokenizer::new(text), &LookingSender {});
+    assert_eq!(looking("~ ~ ~")?, vec3(1.5, 64.0, -3.25, [Relative; 3]));
+    assert_eq!(looking("~1 ~-0.5 5")?, vec3(2.5, 63.5, 5.5, [Relative, Relative, Absolute]));
+
+    assert_eq!(
+      looking("^ ^1 ~"),
+      Err(ParseError::new(
+        Span::new(5, 6),
+        ErrorKind::Expected("`^` on either all or none of the axes".into())
+      ))
+    );
+    assert_eq!(
+      looking("1 ^ ^"),
+      Err(ParseError::new(
+        Span::new(2, 3),
+        ErrorKind::Expected("`^` on either all or none of the axes".into())
+      ))
+    );
+    assert_eq!(
+      parse("^ ^ ^1"),
+      Err(ParseError::new(Span::new(0, 1), ErrorKind::Expected("an absolute position".into())))
+    );
+    Ok(())
+  }
+
+  struct FacingSender {
+    yaw:   f32,
+    pitch: f32,
+  }
+
+  impl CommandSender for FacingSender {
+    fn block_pos(&self) -> Option<Pos> { Some(Pos::new(0, 64, 0)) }
+    fn pos(&self) -> Option<FPos> { Some(FPos::new(0.5, 64.0, 0.5)) }
+    fn rotation(&self) -> Option<(f32, f32)> { Some((self.yaw, self.pitch)) }
+    fn send_message(&mut self, _: Chat) {}
+    fn error_format(&self) -> ErrorFormat { ErrorFormat::Minecraft }
+  }
+
+  #[test]
+  fn parse_local_coords() -> Result<()> {
+    let parse = |yaw: f32, pitch: f32, text: &str| -> Result<FPos> {
+      match Parser::Vec3.parse(&mut Tokenizer::new(text), &FacingSender { yaw, pitch })? {
+        Arg::Vec3(pos, kinds) => {
+          assert_eq!(kinds, [CoordKind::Local; 3]);
+          Ok(pos)
+        }
+        arg => panic!("expected a Vec3, got {arg:?}"),
+      }
+    };
+    let assert_near = |actual: FPos, expected: FPos| {
+      assert!(actual.dist(expected) < 1e-9, "expected {expected}, got {actual}");
+    };
+    // Facing south (+Z), so left is east (+X).
+    assert_near(parse(0.0, 0.0, "^ ^ ^5")?, FPos::new(0.5, 64.0, 5.5));
+    assert_near(parse(0.0, 0.0, "^2 ^1 ^")?, FPos::new(2.5, 65.0, 0.5));
+    // Facing east (+X), so left is north (-Z).
+    assert_near(parse(-90.0, 0.0, "^1 ^ ^3")?, FPos::new(3.5, 64.0, -0.5));
+    // Looking straight down, forward is down, and up is south.
+    assert_near(parse(0.0, 90.0, "^ ^1 ^2")?, FPos::new(0.5, 62.0, 1.5));
+    Ok(())
+  }
+
+  #[test]
+  fn parse_block_pos() -> Result<()> {
+    use CoordKind::*;
+    let parse = |text: &str| {
+      Parser::BlockPos.parse(&mut Tokenizer::new(text), &FacingSender { yaw: 0.0, pitch: 0.0 })
+    };
+    assert_eq!(parse("1 2 3")?, Arg::BlockPos(Pos::new(1, 2, 3), [Absolute; 3]));
+    // The offset is added to the exact position (0.5, 64, 0.5), and then floored.
+    assert_eq!(parse("~1 ~ ~-2.5")?, Arg::BlockPos(Pos::new(1, 64, -2), [Relative; 3]));
+    assert_eq!(parse("~-1 ~ ~0.4")?, Arg::BlockPos(Pos::new(-1, 64, 0), [Relative; 3]));
+    assert_eq!(parse("^ ^ ^3.5")?, Arg::BlockPos(Pos::new(0, 64, 4), [Local; 3]));
+    assert_eq!(
+      parse("1.5 2 3"),
+      Err(ParseError::new(Span::new(0, 3), ErrorKind::Expected("a number".into())))
+    );
+    assert_eq!(
+      parse("~ ~300 ~"),
+      Err(ParseError::new(
+        Span::new(2, 6),
+        ErrorKind::Expected("a position inside the world".into())
+      ))
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn parse_time() -> Result<()> {
+    let parse =
+      |min: i32, text: &str| Parser::Time { min }.parse(&mut Tokenizer::new(text), &NoneSender {});
+    assert_eq!(parse(0, "10")?, Arg::Int(10));
+    assert_eq!(parse(0, "10t")?, Arg::Int(10));
+    assert_eq!(parse(0, "5s")?, Arg::Int(100));
+    assert_eq!(parse(0, "0.5s")?, Arg::Int(10));
+    assert_eq!(parse(0, "2d")?, Arg::Int(48000));
+    assert_eq!(parse(0, "0.5d")?, Arg::Int(12000));
+
+    assert_eq!(
+      parse(0, "-5s"),
+      Err(ParseError::new(
+        Span::new(0, 3),
+        ErrorKind::Expected("a time of at least 0 ticks".into())
+      ))
+    );
+    assert_eq!(
+      parse(20, "10t"),
+      Err(ParseError::new(
+        Span::new(0, 3),
+        ErrorKind::Expected("a time of at least 20 ticks".into())
+      ))
+    );
+    assert_eq!(
+      parse(0, "5m"),
+      Err(ParseError::new(Span::new(0, 2), ErrorKind::Expected("a valid unit (t, s or d)".into())))
+    );
+    assert_eq!(
+      parse(0, "s"),
+      Err(ParseError::new(Span::new(0, 1), ErrorKind::Expected("a number".into())))
+    );
+    assert_eq!(
+      parse(0, "nant"),
+      Err(ParseError::new(Span::new(0, 4), ErrorKind::Expected("a finite number".into())))
+    );
+    assert_eq!(
+      parse(0, "infs"),
+      Err(ParseError::new(Span::new(0, 4), ErrorKind::Expected("a finite number".into())))
+    );
+    assert_eq!(parse(0, "").unwrap_err().kind(), &ErrorKind::EOF);
+    Ok(())
+  }
+
+  #[test]
+  fn parse_summon() -> Result<()> {
+    let parse = |p: Parser, text: &str| p.parse(&mut Tokenizer::new(text), &NoneSender {});
+    assert_eq!(parse(Parser::EntitySummon, "zombie")?, Arg::EntitySummon(entity::Type::Zombie));
+    assert_eq!(
+      parse(Parser::EntitySummon, "minecraft:falling_block ~ ~ ~")?,
+      Arg::EntitySummon(entity::Type::FallingBlock)
+    );
+    assert_eq!(
+      parse(Parser::EntitySummon, "player"),
+      Err(ParseError::new(Span::new(0, 6), ErrorKind::Expected("a valid entity type".into())))
+    );
+    assert_eq!(
+      parse(Parser::EntitySummon, "minecraft:not_an_entity"),
+      Err(ParseError::new(Span::new(0, 23), ErrorKind::Expected("a valid entity type".into())))
+    );
+
+    assert_eq!(
+      parse(Parser::NbtCompoundTag, "{CustomName: '\"Bob\"'}")?,
+      Arg::NbtCompoundTag(NBT::new(
+        "",
+        Tag::new_compound(&[("CustomName", Tag::String("\"Bob\"".into()))])
+      ))
+    );
+    assert_eq!(
+      parse(Parser::NbtCompoundTag, "[1, 2]"),
+      Err(ParseError::new(Span::new(0, 6), ErrorKind::Expected("a compound tag".into())))
+    );
+    assert_eq!(parse(Parser::NbtTag, "5b")?, Arg::NbtTag(NBT::new("", Tag::Byte(5))));
+    Ok(())
+  }
+
+  #[test]
+  fn parse_item_stack() -> Result<()> {
+    let parse = |p: Parser, text: &str| p.parse(&mut Tokenizer::new(text), &NoneSender {});
+    assert_eq!(
+      parse(Parser::ItemStack, "stone")?,
+      Arg::ItemStack(item::Stack::new(item::Type::Stone))
+    );
+    assert_eq!(
+      parse(Parser::ItemStack, "minecraft:stone 5")?,
+      Arg::ItemStack(item::Stack::new(item::Type::Stone))
+    );
+    let mut named = item::Stack::new(item::Type::DiamondSword);
+    named.data_mut().display.name = Some(Chat::new("Sword"));
+    named.data_mut().unbreakable = true;
+    assert_eq!(
+      parse(Parser::ItemStack, r#"diamond_sword{display:{Name:'Sword'},Unbreakable:1b}"#)?,
+      Arg::ItemStack(named)
+    );
+
+    assert_eq!(
+      parse(Parser::ItemStack, "minecraft:not_an_item"),
+      Err(ParseError::new(Span::new(0, 21), ErrorKind::Invalid))
+    );
+    assert_eq!(
+      parse(Parser::ItemStack, "stone{a:"),
+      Err(ParseError::new(Span::single(8), ErrorKind::Expected("a value".into())))
+    );
+    assert_eq!(
+      parse(Parser::ItemStack, "stone{display:5}"),
+      Err(ParseError::new(Span::new(5, 16), ErrorKind::Expected("valid item data".into())))
+    );
+    assert_eq!(
+      parse(Parser::ItemStack, "stone{}x"),
+      Err(ParseError::new(Span::single(7), ErrorKind::Expected("a space".into())))
+    );
+    assert_eq!(parse(Parser::ItemStack, "").unwrap_err().kind(), &ErrorKind::EOF);
+    Ok(())
+  }
+
+  #[test]
+  fn parse_item_predicate() -> Result<()> {
+    let parse = |text: &str| Parser::ItemPredicate.parse(&mut Tokenizer::new(text), &NoneSender {});
+    assert_eq!(parse("stone")?, Arg::ItemPredicate(ItemPredicate::Item(item::Type::Stone, None)));
+    assert_eq!(
+      parse("minecraft:stone{a:1}")?,
+      Arg::ItemPredicate(ItemPredicate::Item(
+        item::Type::Stone,
+        Some(bb_common::nbt::Tag::new_compound(&[("a", bb_common::nbt::Tag::Int(1))]))
+      ))
+    );
+    assert_eq!(
+      parse("#minecraft:logs")?,
+      Arg::ItemPredicate(ItemPredicate::Tag("minecraft:logs".into()))
+    );
+    assert_eq!(
+      parse("#"),
+      Err(ParseError::new(Span::new(0, 1), ErrorKind::Expected("a tag name".into())))
+    );
+    assert_eq!(parse("stones"), Err(ParseError::new(Span::new(0, 6), ErrorKind::Invalid)));
+
+    assert!(
+      ItemPredicate::Item(item::Type::Stone, None).matches(&item::Stack::new(item::Type::Stone))
+    );
+    assert!(
+      !ItemPredicate::Item(item::Type::Stone, None).matches(&item::Stack::new(item::Type::Dirt))
+    );
+    Ok(())
+  }
 }
@@ -1,4 +1,5 @@
 use super::{ErrorKind, ParseError, Result};
+use bb_common::nbt::Tag;
 use std::{iter::Peekable, ops::Deref, str::Chars};
 
 #[derive(Clone)]
@@ -93,6 +94,20 @@ impl<'a> Tokenizer<'a> {
     }
   }
 
+  /// Reads characters until one of `ends` is found, or until the end of the
+  /// text. The end character is not consumed. This can return an empty word.
+  pub fn read_until(&mut self, ends: &[char]) -> Word {
+    let mut text = String::new();
+    let start = self.pos;
+    while let Some(c) = self.peek_char() {
+      if ends.contains(&c) {
+        break;
+      }
+      text.push(self.next_char().unwrap());
+    }
+    Word { text, pos: Span { start, end: self.pos } }
+  }
+
   /// Reads a single word. This must be terminated by a space. Any non-alphabet
   /// characters are considered invalid.
   pub fn read_spaced_word(&mut self) -> Result<Word> {
@@ -157,6 +172,116 @@ impl<'a> Tokenizer<'a> {
     Ok(Word { pos, text })
   }
 
+  /// Reads a quotable phrase. If the next character is a quote (`"` or `'`),
+  /// this reads until the matching closing quote, and handles `\\` and `\"`
+  /// escapes. Otherwise, this is the same as
+  /// [`read_spaced_word`](Self::read_spaced_word).
+  ///
+  /// A quote that is never closed, or a backslash at the end of the text, will
+  /// produce an EOF error. An unknown escape sequence will produce an error
+  /// spanning the backslash and the escaped character.
+  pub fn read_quotable(&mut self) -> Result<Word> {
+    let quote = match self.peek_char() {
+      Some(c @ ('"' | '\'')) => c,
+      _ => return self.read_spaced_word(),
+    };
+    let start = self.pos;
+    self.next_char().unwrap();
+    let mut text = String::new();
+    loop {
+      match self.next_char() {
+        Some('\\') => {
+          let escape_start = self.pos - 1;
+          match self.next_char() {
+            Some(c) if c == '\\' || c == quote => text.push(c),
+            Some(_) => {
+              return Err(ParseError::new(
+                Span::new(escape_start, self.pos),
+                ErrorKind::Expected(format!("a valid escape sequence (`\\\\` or `\\{quote}`)")),
+              ))
+            }
+            None => return Err(ParseError::new(Span::single(self.pos), ErrorKind::EOF)),
+          }
+        }
+        Some(c) if c == quote => break,
+        Some(c) => text.push(c),
+        None => return Err(ParseError::new(Span::single(self.pos), ErrorKind::EOF)),
+      }
+    }
+    let end = self.pos;
+    match self.peek_char() {
+      Some(c) if c.is_whitespace() => {
+        self.next_char().unwrap();
+      }
+      Some(_) => {
+        return Err(ParseError::new(Span::single(self.pos), ErrorKind::Expected("a space".into())))
+      }
+      None => {}
+    }
+    Ok(Word { text, pos: Span::new(start, end) })
+  }
+
+  /// Reads a resource location, like `minecraft:stone` or `#minecraft:logs`.
+  /// Unlike [`read_spaced_word`](Self::read_spaced_word), this stops at the
+  /// first character that can't be in a resource location, so that things like
+  /// NBT can directly follow it. This doesn't consume any whitespace.
+  pub fn read_resource(&mut self) -> Result<Word> {
+    let start = self.pos;
+    let mut text = String::new();
+    while let Some(c) = self.peek_char() {
+      if !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | ':' | '#')) {
+        break;
+      }
+      text.push(self.next_char().unwrap());
+    }
+    if text.is_empty() {
+      match self.peek_char() {
+        Some(_) => Err(ParseError::new(
+          Span::single(start),
+          ErrorKind::Expected("a resource location".into()),
+        )),
+        None => Err(ParseError::new(Span::single(start), ErrorKind::EOF)),
+      }
+    } else {
+      Ok(Word { text, pos: Span::new(start, self.pos) })
+    }
+  }
+
+  /// Reads a stringified NBT tag, like `{display:{Name:'"Sword"'}}`. Returns
+  /// the tag, and the span of text that it covered. This doesn't consume any
+  /// whitespace after the tag.
+  pub fn read_snbt(&mut self) -> Result<(Tag, Span)> {
+    let start = self.pos;
+    let rest: String = self.chars.clone().collect();
+    match Tag::from_snbt_prefix(&rest) {
+      Ok((tag, len)) => {
+        while self.pos < start + len {
+          self.next_char();
+        }
+        Ok((tag, Span::new(start, self.pos)))
+      }
+      Err(e) => {
+        Err(ParseError::new(Span::single(start + e.pos), ErrorKind::Expected(e.expected.into())))
+      }
+    }
+  }
+
+  /// Consumes a single space. This is used after reading something that
+  /// doesn't consume whitespace, like [`read_snbt`](Self::read_snbt). If
+  /// there is no more text, this does nothing.
+  pub fn read_space(&mut self) -> Result<()> {
+    match self.peek_char() {
+      Some(c) if c.is_whitespace() => {
+        self.next_char();
+        Ok(())
+      }
+      Some(_) => {
+        Err(ParseError::new(Span::single(self.pos), ErrorKind::Expected("a space".into())))
+      }
+      None => Ok(()),
+    }
+  }
+
   /// Checks for trailing characters. If there are any unread characters, this
   /// will return an error.
   pub fn check_trailing(&mut self) -> Result<()> {
@@ -238,4 +363,41 @@ mod tests {
     assert_eq!(tok.read_spaced_word().unwrap_err().kind(), &ErrorKind::EOF);
     assert!(tok.check_trailing().is_ok());
   }
+
+  #[test]
+  fn quotable() {
+    let mut tok = Tokenizer::new(r#""big \"gaming\"" word 'it\'s \\'"#);
+    assert_eq!(
+      tok.read_quotable(),
+      Ok(Word { text: r#"big "gaming""#.into(), pos: Span { start: 0, end: 16 } })
+    );
+    assert_eq!(
+      tok.read_quotable(),
+      Ok(Word { text: "word".into(), pos: Span { start: 17, end: 21 } })
+    );
+    assert_eq!(
+      tok.read_quotable(),
+      Ok(Word { text: r#"it's \"#.into(), pos: Span { start: 22, end: 32 } })
+    );
+    assert!(tok.check_trailing().is_ok());
+  }
+
+  #[test]
+  fn quotable_errors() {
+    let mut tok = Tokenizer::new(r#""unterminated"#);
+    let err = tok.read_quotable().unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::EOF);
+    assert_eq!(err.pos(), Span::single(13));
+
+    let mut tok = Tokenizer::new(r#""trailing\"#);
+    let err = tok.read_quotable().unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::EOF);
+    assert_eq!(err.pos(), Span::single(10));
+
+    let mut tok = Tokenizer::new(r#""bad \n escape""#);
+    assert_eq!(tok.read_quotable().unwrap_err().pos(), Span::new(5, 7));
+
+    let mut tok = Tokenizer::new(r#""no"space"#);
+    assert_eq!(tok.read_quotable().unwrap_err().pos(), Span::single(4));
+  }
 }
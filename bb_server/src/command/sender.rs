@@ -1,5 +1,8 @@
 use crate::player::Player;
-use bb_common::{math::Pos, util::Chat};
+use bb_common::{
+  math::{FPos, Pos},
+  util::Chat,
+};
 use std::sync::Arc;
 
 pub enum ErrorFormat {
@@ -19,6 +22,23 @@ pub trait CommandSender {
   /// coordinates will not be available to this sender.
   fn block_pos(&self) -> Option<Pos>;
 
+  /// If this command sender has a position in the world, this should return
+  /// the exact position. If this returns None, relative coordinates will not
+  /// be available when parsing a [`Vec3`](super::Parser::Vec3), and local
+  /// (`^`) coordinates will not be available at all.
+  fn pos(&self) -> Option<FPos> { None }
+
+  /// If this command sender is looking in a direction, this should return the
+  /// yaw and pitch, in that order. If this returns None, relative angles and
+  /// local (`^`) coordinates will not be available to this sender.
+  fn rotation(&self) -> Option<(f32, f32)> { None }
+
+  /// Returns the op level of this sender, from 0 to 4. Commands added with
+  /// [`CommandTree::add_op`](super::CommandTree::add_op) can only be run by
+  /// senders with a high enough level. This defaults to 4, as the console and
+  /// rcon can run every command.
+  fn op_level(&self) -> u8 { 4 }
+
   /// If this is a player, returns the player.
   fn as_player(&self) -> Option<&Arc<Player>> { None }
 
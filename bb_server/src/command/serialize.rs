@@ -9,19 +9,27 @@ use bb_common::{
 
 impl CommandTree {
   /// Serializes the entire command tree. This will be called any time a player
-  /// joins.
-  pub fn serialize(&self) -> cb::packet::CommandList {
+  /// joins. See [`Player::send_commands`](crate::player::Player::send_commands)
+  /// to resend the tree after it has changed.
+  ///
+  /// Only commands that can be run with the given `op_level` are included, so
+  /// players won't see commands they don't have permission to use.
+  ///
+  /// Commands are sorted by name, so the same tree will always produce the same
+  /// packet.
+  pub fn serialize(&self, op_level: u8) -> cb::packet::CommandList {
     // This is a reverse-order list of all the nodes. The highest level node (the
     // root node) will be last.
     let mut nodes = vec![];
 
     let commands = self.commands.lock();
-    let c = Command {
-      name:     "".into(),
-      ty:       NodeType::Root,
-      children: commands.values().map(|(command, _)| command.clone()).collect(),
-      optional: false,
-    };
+    let mut children: Vec<Command> = commands
+      .values()
+      .filter(|(_, level, _)| op_level >= *level)
+      .map(|(command, _, _)| command.clone())
+      .collect();
+    children.sort_by(|a, b| a.name.cmp(&b.name));
+    let c = Command { name: "".into(), ty: NodeType::Root, children, optional: false };
     c.write_nodes(&mut nodes);
 
     cb::packet::CommandList { root: nodes.len() as u32 - 1, nodes }
@@ -37,7 +45,9 @@ impl Command {
     let children = self.children.iter().map(|c| c.write_nodes(nodes)).collect();
     nodes.push(CommandNode {
       ty: self.ty.as_ty(),
-      executable: self.children.is_empty() || self.children.iter().any(|c| c.optional),
+      // The root node can never be executed on its own.
+      executable: !matches!(self.ty, NodeType::Root)
+        && (self.children.is_empty() || self.children.iter().any(|c| c.optional)),
       children,
       redirect: None,
       name: self.name.clone(),
@@ -119,7 +129,7 @@ impl Parser {
       Self::Uuid               => "minecraft:uuid",
       Self::NbtTag             => "minecraft:nbt_tag",
       Self::NbtCompoundTag     => "minecraft:nbt_compound_tag",
-      Self::Time               => "minecraft:time",
+      Self::Time { .. }        => "minecraft:time",
       Self::Modid              => "forge:modid",
       Self::Enum               => "forge:enum",
     }
@@ -28,6 +28,13 @@ pub struct Config {
   #[default(10)]
   pub view_distance: u32,
 
+  /// The default simulation distance. Entities are only ticked in chunks
+  /// within this many chunks of a player, and freeze everywhere else. This is
+  /// capped at the view distance. Clients older than 1.18 don't have a
+  /// simulation distance, so for them this is the same as the view distance.
+  #[default(10)]
+  pub simulation_distance: u32,
+
   /// Whenever a player joins, they will be put into this gamemode. This can
   /// be overriden with plugins, but without any plugins, this will be the
   /// gamemode of all the clients.
@@ -57,19 +64,43 @@ pub struct Config {
   #[default(true)]
   pub leave_messages: bool,
 
+  /// The usernames of players who are ops. These players are given op level
+  /// 4, which lets them run every command. Everyone else has op level 0, and
+  /// can only run commands that aren't limited to ops.
+  pub ops: Vec<String>,
+
   /// The path for the vanilla data directory. If not found, an error will be
   /// logged, and there will be no crafting recipes.
   #[default("data/".into())]
   pub data_path: String,
 
+  /// The maximum number of packets a single client can send per second.
+  /// Movement packets are combined before this is checked, so only the latest
+  /// movement in each batch counts. Packets over this limit are dropped, and
+  /// clients that send more than double this limit are disconnected. Set to 0
+  /// to disable the limit.
+  #[default(500)]
+  pub max_packets_per_second: u32,
+
   /// Toggle debug info in the player list.
   #[default(true)]
   pub debug_playerlist: bool,
 
+  /// The number of ticks run every second. Vanilla runs at 20 ticks per
+  /// second. Everything timed in ticks (entities, players, redstone, random
+  /// ticks, etc.) will speed up or slow down if this is changed, so this can
+  /// be used for slow motion, or to speed up tests. Must be at least 1.
+  #[default(20)]
+  pub tick_rate: u32,
+
   /// Configs for rcon. This is a protocol used by vanilla to allow a remote
   /// to execute commands on the server.
   pub rcon: RconConfig,
 
+  /// Configs for the metrics endpoint. This is an HTTP server which exports
+  /// metrics in the Prometheus text format.
+  pub metrics: MetricsConfig,
+
   /// Configs for world generation/loading.
   pub world: WorldConfig,
 }
@@ -92,6 +123,17 @@ pub struct RconConfig {
   pub password: String,
 }
 
+#[derive(Clone, Debug, Config, Default, PartialEq)]
+pub struct MetricsConfig {
+  /// If set, metrics will be served at `http://<addr>/metrics`. There is no
+  /// authentication, so don't open this to the internet.
+  #[default(false)]
+  pub enabled: bool,
+  /// The address the metrics server listens on.
+  #[default("127.0.0.1:9225".into())]
+  pub addr:    String,
+}
+
 #[derive(Clone, Debug, Config, Default, PartialEq)]
 pub struct WorldConfig {
   /// If set, the world cannot be modified. This can be used in minigame
@@ -123,6 +165,21 @@ pub struct WorldConfig {
   #[default(0)]
   pub min_y:  i32,
 
+  /// The number of blocks picked for a random tick in each chunk section
+  /// every tick. Random ticks make crops grow, among other things. Set to 0
+  /// to disable random ticks. This is the default value of the
+  /// `randomTickSpeed` gamerule. Values above 4096 are clamped to 4096.
+  #[default(3)]
+  pub random_tick_speed: u32,
+
+  /// The maximum number of blocks that can be changed by a single `/fill`
+  /// command. Larger fills will be rejected.
+  #[default(32768)]
+  pub max_fill_volume: u64,
+
+  /// The default gamerules. These can be changed ingame with `/gamerule`.
+  pub gamerules: GameRulesConfig,
+
   /// Vanilla world loading settings
   pub vanilla: VanillaConfig,
 
@@ -130,6 +187,26 @@ pub struct WorldConfig {
   pub schematic: SchematicConfig,
 }
 
+#[derive(Clone, Debug, Config, Default, PartialEq)]
+pub struct GameRulesConfig {
+  /// If set, the time of day will advance every tick.
+  #[default(true)]
+  pub do_daylight_cycle:    bool,
+  /// If set, players will respawn right away, without seeing the death screen.
+  /// This only works on 1.15+ clients.
+  #[default(false)]
+  pub do_immediate_respawn: bool,
+  /// If set, players won't drop their items when they die.
+  #[default(false)]
+  pub keep_inventory:       bool,
+  /// If set, mobs and explosions can break blocks.
+  #[default(true)]
+  pub mob_griefing:         bool,
+  /// If set, the debug screen will hide coordinates and other info.
+  #[default(false)]
+  pub reduced_debug_info:   bool,
+}
+
 #[derive(Clone, Debug, Config, Default, PartialEq)]
 pub struct VanillaConfig {
   /// If set, then the world will be a void world, and a vanilla world will
@@ -0,0 +1,49 @@
+use super::{Behavior, EntityData, EntityPos, ShouldDespawn};
+use crate::world::World;
+use bb_common::net::cb;
+use std::sync::Arc;
+
+pub struct ExpOrb {
+  amount: i32,
+  age:    u32,
+}
+
+impl Default for ExpOrb {
+  fn default() -> Self { ExpOrb::new(1) }
+}
+
+impl ExpOrb {
+  /// Creates an exp orb which holds `amount` experience.
+  pub fn new(amount: i32) -> Self { ExpOrb { amount, age: 0 } }
+}
+
+impl Behavior for ExpOrb {
+  fn exp_count(&self) -> i32 { self.amount }
+
+  fn gravity(&self) -> f64 { 0.03 }
+  fn horizontal_drag(&self) -> f64 { 0.98 }
+
+  fn tick(&mut self, _: &Arc<World>, ent: &EntityData, p: &mut EntityPos) -> ShouldDespawn {
+    self.apply_physics(p);
+    self.age += 1;
+
+    if self.age >= 10 {
+      let chunk = p.aabb.pos.block().chunk();
+      for player in ent.world.read().players().iter().in_view(chunk) {
+        if player.alive() && player.pos().dist_squared(p.aabb.pos) < 1.5_f64.powi(2) {
+          player.add_experience(self.amount);
+          let collect = cb::packet::CollectItem {
+            item_eid:   ent.eid(),
+            player_eid: player.eid(),
+            amount:     1,
+          };
+          for other in player.world().players().iter().in_view(chunk) {
+            other.send(collect.clone());
+          }
+          return ShouldDespawn(true);
+        }
+      }
+    }
+    ShouldDespawn(false)
+  }
+}
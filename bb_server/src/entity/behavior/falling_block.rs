@@ -7,21 +7,10 @@ use std::sync::Arc;
 pub struct FallingBlock;
 
 impl Behavior for FallingBlock {
-  fn tick(&mut self, world: &Arc<World>, ent: &EntityData, p: &mut EntityPos) -> ShouldDespawn {
-    let vel = p.vel;
-    p.aabb.pos += vel;
-    // 9.8 m/s ~= 0.5 m/tick. However, minecraft go brrr, and gravity is actually
-    // 0.03 b/tick for projectiles, 0.04 b/tick for items, and 0.08 b/tick for
-    // living entities.
-    if !p.grounded {
-      p.vel.y -= 0.04;
-    }
-    p.vel.y *= 0.98;
+  fn gravity(&self) -> f64 { 0.04 }
 
-    // This is multiplied by the 'slipperiness' of the block the entity is standing
-    // on.
-    p.vel.x *= 0.91;
-    p.vel.z *= 0.91;
+  fn tick(&mut self, world: &Arc<World>, ent: &EntityData, p: &mut EntityPos) -> ShouldDespawn {
+    self.apply_physics(p);
 
     if p.grounded {
       let ty = world
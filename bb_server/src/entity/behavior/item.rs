@@ -9,19 +9,11 @@ pub struct ItemBehavior {
 }
 
 impl Behavior for ItemBehavior {
+  fn gravity(&self) -> f64 { 0.04 }
+  fn horizontal_drag(&self) -> f64 { 0.98 }
+
   fn tick(&mut self, _: &Arc<World>, ent: &EntityData, p: &mut EntityPos) -> ShouldDespawn {
-    let vel = p.vel;
-    p.aabb.pos += vel;
-    // This is for items.
-    p.vel.x *= 0.98;
-    p.vel.y *= 0.98;
-    p.vel.z *= 0.98;
-    if p.grounded {
-      p.vel.x *= 0.6;
-      p.vel.z *= 0.6;
-    } else {
-      p.vel.y -= 0.04;
-    }
+    self.apply_physics(p);
     self.age += 1;
 
     if self.age >= 10 {
@@ -1,13 +1,15 @@
+mod exp_orb;
 mod falling_block;
 mod item;
-mod snowball;
+mod projectile;
 
+pub use exp_orb::ExpOrb;
 pub use falling_block::FallingBlock;
 pub use item::ItemBehavior;
-pub use snowball::SnowballBehavior;
+pub use projectile::Projectile;
 
 use super::{EntityData, EntityPos, Type};
-use crate::world::World;
+use crate::{math::Vec3, player::Player, world::World};
 use std::sync::Arc;
 
 /// A wrapper type, to make it clear that `true` means an entity should be
@@ -15,6 +17,16 @@ use std::sync::Arc;
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct ShouldDespawn(pub bool);
 
+/// Damage that an entity wants to deal to another entity. See
+/// [`Behavior::take_hit`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Hit {
+  /// The entity to damage.
+  pub eid:       i32,
+  pub amount:    f32,
+  pub knockback: Vec3,
+}
+
 pub trait Behavior {
   /// The maximum health of this entity.
   fn max_health(&self) -> f32 { 20.0 }
@@ -28,26 +40,71 @@ pub trait Behavior {
   /// killed.
   fn exp_count(&self) -> i32 { 1 }
 
-  /// Any extra functionality needed. Called every tick, after movement and
-  /// collision checks have been completed.
-  fn tick(&mut self, world: &Arc<World>, ent: &EntityData, p: &mut EntityPos) -> ShouldDespawn {
-    let _ = (world, ent);
+  /// The amount that this entity accelerates downwards every tick, in
+  /// blocks/tick^2.
+  ///
+  /// 9.8 m/s ~= 0.5 m/tick. However, minecraft go brrr, and gravity is actually
+  /// 0.03 b/tick for projectiles, 0.04 b/tick for items, and 0.08 b/tick for
+  /// living entities.
+  fn gravity(&self) -> f64 { 0.08 }
+
+  /// The vertical velocity is multiplied by this every tick.
+  fn drag(&self) -> f64 { 0.98 }
+
+  /// The horizontal velocity is multiplied by this every tick.
+  fn horizontal_drag(&self) -> f64 { 0.91 }
+
+  /// When on the ground, the horizontal velocity is also multiplied by this
+  /// every tick.
+  ///
+  /// TODO: This should be the 'slipperiness' of the block the entity is
+  /// standing on.
+  fn ground_friction(&self) -> f64 { 0.6 }
+
+  /// Moves the entity by its velocity, and then applies gravity and drag. This
+  /// is called by the default [`tick`](Self::tick).
+  fn apply_physics(&self, p: &mut EntityPos) {
     let vel = p.vel;
     p.aabb.pos += vel;
-    // 9.8 m/s ~= 0.5 m/tick. However, minecraft go brrr, and gravity is actually
-    // 0.03 b/tick for projectiles, 0.04 b/tick for items, and 0.08 b/tick for
-    // living entities.
     if !p.grounded {
-      p.vel.y -= 0.08;
+      p.vel.y -= self.gravity();
     }
-    p.vel.y *= 0.98;
+    p.vel.y *= self.drag();
+    let mut horz = self.horizontal_drag();
+    if p.grounded {
+      horz *= self.ground_friction();
+    }
+    p.vel.x *= horz;
+    p.vel.z *= horz;
+  }
 
-    // This is multiplied by the 'slipperiness' of the block the entity is standing
-    // on.
-    p.vel.x *= 0.91;
-    p.vel.z *= 0.91;
+  /// Any extra functionality needed. Called every tick, after movement and
+  /// collision checks have been completed.
+  fn tick(&mut self, world: &Arc<World>, ent: &EntityData, p: &mut EntityPos) -> ShouldDespawn {
+    let _ = (world, ent);
+    self.apply_physics(p);
     ShouldDespawn(false)
   }
+
+  /// Returns the damage this entity wants to deal to another entity, if any.
+  /// This is called right after [`tick`](Self::tick), and the damage is
+  /// applied once this behavior has been unlocked.
+  ///
+  /// Damaging another entity from within `tick` can deadlock, if that entity
+  /// is also trying to damage this one.
+  fn take_hit(&mut self) -> Option<Hit> { None }
+
+  /// Called when something tries to damage this entity, before the damage is
+  /// applied. Returning `false` will cancel the damage.
+  fn damage(&mut self, world: &Arc<World>, ent: &EntityData, amount: f32, knockback: Vec3) -> bool {
+    let _ = (world, ent, amount, knockback);
+    true
+  }
+
+  /// Called when a player right clicks on this entity.
+  fn interact(&mut self, world: &Arc<World>, ent: &EntityData, player: &Arc<Player>) {
+    let _ = (world, ent, player);
+  }
 }
 
 /// Default functionality for entities. Mostly used when an entity hasn't been
@@ -58,9 +115,46 @@ impl Behavior for DefaultBehavior {}
 
 pub fn for_entity(ty: Type) -> Box<dyn Behavior + Send> {
   match ty {
-    Type::Snowball => Box::new(SnowballBehavior::default()),
+    Type::Snowball | Type::Egg | Type::EnderPearl | Type::Arrow | Type::SpectralArrow => {
+      Box::new(Projectile::for_type(ty))
+    }
     Type::Item => Box::new(ItemBehavior::default()),
+    Type::ExperienceOrb => Box::new(ExpOrb::default()),
     Type::FallingBlock => Box::new(FallingBlock::default()),
     _ => Box::new(DefaultBehavior::default()),
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::math::Vec3;
+  use bb_common::math::FPos;
+
+  fn fall(b: &dyn Behavior, ticks: u32) -> Vec<f64> {
+    let mut p = EntityPos::new(FPos::new(0.0, 100.0, 0.0), Vec3::new(0.25, 0.25, 0.25));
+    (0..ticks)
+      .map(|_| {
+        b.apply_physics(&mut p);
+        p.aabb.pos.y()
+      })
+      .collect()
+  }
+
+  #[test]
+  fn fall_curves() {
+    let item = fall(&ItemBehavior::default(), 20);
+    let arrow = fall(&Projectile::for_type(Type::Arrow), 20);
+    let zombie = fall(&DefaultBehavior, 20);
+
+    // Everything should fall, and never go up.
+    for curve in [&item, &arrow, &zombie] {
+      assert!(curve.windows(2).all(|w| w[1] <= w[0]));
+      assert!(*curve.last().unwrap() < 100.0);
+    }
+    // Arrows have more gravity and less drag than items, and living entities
+    // have the most gravity.
+    assert!(item.last() > arrow.last());
+    assert!(arrow.last() > zombie.last());
+  }
+}
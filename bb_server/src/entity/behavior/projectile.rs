@@ -0,0 +1,121 @@
+use super::{Behavior, EntityData, EntityPos, Hit, ShouldDespawn};
+use crate::{
+  entity,
+  entity::{Entity, EntityRef},
+  math::{Vec3, AABB},
+  world::World,
+};
+use std::sync::Arc;
+
+/// The number of ticks an arrow will stay stuck in a block before despawning.
+const STUCK_DESPAWN_TICKS: u32 = 1200;
+
+/// Arrows, snowballs, and anything else that gets thrown or shot. These move in
+/// a straight line (with a bit of gravity), and check for collisions with
+/// blocks and entities along the way.
+pub struct Projectile {
+  /// The amount of damage dealt to any entity this hits.
+  pub damage:  f32,
+  /// The gravity of this projectile, in blocks/tick^2.
+  pub gravity: f64,
+  /// If true, this will get stuck in any block it hits, like an arrow.
+  /// Otherwise, this will despawn as soon as it hits a block.
+  pub sticks:  bool,
+  /// The entity that launched this projectile. This entity will never be hit.
+  pub shooter: Option<i32>,
+  /// Set once this projectile is stuck in a block. This is the number of ticks
+  /// since it got stuck.
+  stuck:       Option<u32>,
+  /// Set when this projectile hits an entity. This is applied after the tick,
+  /// see [`Behavior::take_hit`].
+  hit:         Option<Hit>,
+}
+
+impl Projectile {
+  pub fn new(damage: f32, gravity: f64, sticks: bool) -> Self {
+    Projectile { damage, gravity, sticks, shooter: None, stuck: None, hit: None }
+  }
+  /// Returns the default projectile behavior for the given entity type.
+  pub fn for_type(ty: entity::Type) -> Self {
+    match ty {
+      entity::Type::Arrow | entity::Type::SpectralArrow => Projectile::new(2.0, 0.05, true),
+      _ => Projectile::new(0.0, 0.03, false),
+    }
+  }
+  /// Sets the entity that shot this projectile.
+  pub fn with_shooter(mut self, eid: i32) -> Self {
+    self.shooter = Some(eid);
+    self
+  }
+}
+
+impl Behavior for Projectile {
+  fn gravity(&self) -> f64 { self.gravity }
+  fn drag(&self) -> f64 { 0.99 }
+  fn horizontal_drag(&self) -> f64 { 0.99 }
+  // Projectiles never sit on the ground, they get stuck or despawn instead.
+  fn ground_friction(&self) -> f64 { 1.0 }
+
+  fn take_hit(&mut self) -> Option<Hit> { self.hit.take() }
+
+  fn tick(&mut self, world: &Arc<World>, ent: &EntityData, p: &mut EntityPos) -> ShouldDespawn {
+    if let Some(ticks) = &mut self.stuck {
+      *ticks += 1;
+      return ShouldDespawn(*ticks >= STUCK_DESPAWN_TICKS);
+    }
+
+    let start = p.aabb.pos;
+    let vel = p.vel;
+    let len = vel.len();
+    if len > 0.0 {
+      let dir = vel / len;
+      let block = world.raycast_block_dist(start, dir, len);
+      let max_dist = block.map(|(_, _, dist)| dist).unwrap_or(len);
+
+      // Other entities (including projectiles) are ticked on other threads. So
+      // we collect them first, and only lock one of them at a time after that.
+      // The damage itself is applied in `take_hit`, after our behavior is
+      // unlocked.
+      let others: Vec<Entity> = world
+        .entities()
+        .iter_values()
+        .filter(|(&eid, _)| eid != ent.eid() && Some(eid) != self.shooter)
+        .map(|(_, e)| e.clone())
+        .collect();
+      // Find the closest entity that is in front of any block we would hit.
+      let mut closest: Option<(f64, EntityRef)> = None;
+      for other in &others {
+        let other = match other.as_entity_ref(world) {
+          Some(other) => other,
+          None => continue,
+        };
+        let aabb = match &other {
+          EntityRef::Entity(e) => e.pos.lock().aabb,
+          EntityRef::Player(p) => AABB::new(p.pos(), Vec3::new(0.6, 1.8, 0.6)),
+        };
+        if let Some((dist, _)) = aabb.ray_intersection(start, dir) {
+          if dist <= max_dist && closest.as_ref().map(|(d, _)| dist < *d).unwrap_or(true) {
+            closest = Some((dist, other));
+          }
+        }
+      }
+      if let Some((_, other)) = closest {
+        self.hit =
+          Some(Hit { eid: other.eid(), amount: self.damage, knockback: dir * 0.4 });
+        return ShouldDespawn(true);
+      }
+      if let Some((_, _, dist)) = block {
+        if !self.sticks {
+          return ShouldDespawn(true);
+        }
+        p.aabb.pos = start + dir * dist;
+        p.vel = Vec3::new(0.0, 0.0, 0.0);
+        self.stuck = Some(0);
+        return ShouldDespawn(false);
+      }
+    }
+
+    self.apply_physics(p);
+    ShouldDespawn(false)
+  }
+}
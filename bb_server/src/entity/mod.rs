@@ -5,11 +5,12 @@ pub use ty::{Data, Type};
 pub use version::TypeConverter;
 
 use crate::{
+  block,
   math::{Vec3, AABB},
   player::Player,
   world::World,
 };
-use bb_common::{math::FPos, metadata::Metadata, util::UUID};
+use bb_common::{math::FPos, metadata::Metadata, net::cb, util::UUID, version::ProtocolVersion};
 use parking_lot::{Mutex, MutexGuard, RwLock};
 use std::sync::Arc;
 
@@ -24,6 +25,10 @@ pub struct EntityPos {
   /// This is determined by the server, so it will always be accurate, unlike
   /// the player grounded field, which is sent to use from the client.
   pub grounded: bool,
+  /// Set at the start of every tick, based on the block the entity is in.
+  pub in_water: bool,
+  /// Set at the start of every tick, based on the block the entity is in.
+  pub in_lava:  bool,
 
   pub yaw:   f32,
   pub pitch: f32,
@@ -35,6 +40,8 @@ impl EntityPos {
       aabb:     AABB::new(pos, size),
       vel:      Vec3::new(0.0, 0.0, 0.0),
       grounded: false,
+      in_water: false,
+      in_lava:  false,
       yaw:      0.0,
       pitch:    0.0,
     }
@@ -88,6 +95,11 @@ pub struct EntityData {
 
   /// Entity metadata
   meta: Mutex<Metadata>,
+  /// The number of ticks this entity will be on fire for. If this is 0, the
+  /// entity is not on fire.
+  fire: Mutex<u32>,
+  /// The number of ticks this entity has existed for.
+  age:  Mutex<u32>,
 
   /// An extra int. Used for item frames and falling blocks. Appears to only be
   /// used when it changes the rendering of the base entity model.
@@ -152,6 +164,112 @@ impl EntityRef<'_> {
     }
   }
 
+  /// Returns this entity's pitch and yaw, in that order. This matches
+  /// [`Player::look`].
+  pub fn look(&self) -> (f32, f32) {
+    match self {
+      Self::Entity(e) => {
+        let p = e.pos.lock();
+        (p.pitch, p.yaw)
+      }
+      Self::Player(p) => p.look(),
+    }
+  }
+
+  /// Moves this entity to the given position, and sets its yaw and pitch.
+  /// This does not check for collisions. For players, this is the same as
+  /// [`Player::teleport`].
+  ///
+  /// Players who could see the old position, but not the new one, will see
+  /// the entity despawn. Players who can only see the new position will see it
+  /// spawn.
+  pub fn teleport(&self, pos: FPos, yaw: f32, pitch: f32) {
+    match self {
+      Self::Entity(e) => {
+        let old = {
+          let mut p = e.pos.lock();
+          let old = p.aabb.pos;
+          p.aabb.pos = pos;
+          p.yaw = yaw;
+          p.pitch = pitch;
+          old
+        };
+        let w = e.world.read().clone();
+        for p in w.players().iter() {
+          match (p.in_view(old.chunk()), p.in_view(pos.chunk())) {
+            (true, true) => {
+              let (yaw, pitch) = if p.ver() == ProtocolVersion::V1_8 {
+                ((yaw / 360.0 * 256.0).round() as i8, (pitch / 360.0 * 256.0).round() as i8)
+              } else {
+                (yaw as i8, pitch as i8)
+              };
+              p.send(cb::packet::EntityPos {
+                eid: e.eid,
+                x: pos.x(),
+                y: pos.y(),
+                z: pos.z(),
+                yaw,
+                pitch,
+                on_ground: false,
+              });
+            }
+            (true, false) => p.send(cb::packet::RemoveEntities { eids: vec![e.eid] }),
+            (false, true) => w.send_entity_spawn(p, self),
+            (false, false) => {}
+          }
+        }
+      }
+      Self::Player(p) => p.teleport(pos, yaw, pitch),
+    }
+  }
+
+  /// Moves this entity into `world`, and then teleports it to the given
+  /// position. If the entity is already in `world`, this is the same as
+  /// [`teleport`](Self::teleport). Players will see a loading screen while
+  /// the new world is sent (see [`WorldManager::move_player`]).
+  ///
+  /// [`WorldManager::move_player`]: crate::world::WorldManager::move_player
+  pub fn teleport_to_world(&self, world: &Arc<World>, pos: FPos, yaw: f32, pitch: f32) {
+    match self {
+      Self::Entity(e) => {
+        let old_world = e.world.read().clone();
+        if Arc::ptr_eq(&old_world, world) {
+          self.teleport(pos, yaw, pitch);
+          return;
+        }
+        let ent = match old_world.take_entity(e.eid) {
+          Some(ent) => ent,
+          None => return,
+        };
+        let old = e.pos.lock().aabb.pos;
+        for p in old_world.players().iter().in_view(old.chunk()) {
+          p.send(cb::packet::RemoveEntities { eids: vec![e.eid] });
+        }
+        {
+          let mut p = e.pos.lock();
+          p.aabb.pos = pos;
+          p.yaw = yaw;
+          p.pitch = pitch;
+        }
+        *e.world.write() = world.clone();
+        world.add_entity(e.eid, ent);
+        for p in world.players().iter().in_view(pos.chunk()) {
+          world.send_entity_spawn(p, self);
+        }
+      }
+      Self::Player(p) => {
+        if !Arc::ptr_eq(&p.world(), world) {
+          let wm = world.world_manager();
+          let index = wm.worlds().iter().position(|w| Arc::ptr_eq(w, world));
+          if let Some(index) = index {
+            wm.move_player(p, index);
+          }
+        }
+        p.teleport(pos, yaw, pitch);
+      }
+    }
+  }
+
   /// Returns the unique id for this entity.
   pub fn eid(&self) -> i32 {
     match self {
@@ -237,11 +355,47 @@ impl EntityRef<'_> {
   /// false for dead or invulnerable entities.
   pub fn damage(&self, amount: f32, blockable: bool, knockback: Vec3) -> bool {
     match self {
-      Self::Entity(_) => true, // TODO
+      Self::Entity(e) => e.damage(amount, knockback),
       Self::Player(p) => p.damage(amount, blockable, knockback),
     }
   }
 
+  /// Called when `player` right clicks on this entity. This does nothing for
+  /// players.
+  pub fn interact(&self, player: &Arc<Player>) {
+    match self {
+      Self::Entity(e) => e.behavior.lock().interact(&*e.world.read(), e, player),
+      Self::Player(_) => {}
+    }
+  }
+
+  /// Returns `true` if this entity is standing in water. For players, this
+  /// checks the block at their feet.
+  pub fn is_in_water(&self) -> bool {
+    match self {
+      Self::Entity(e) => e.is_in_water(),
+      Self::Player(p) => p.world().get_kind(p.pos().block()) == Ok(block::Kind::Water),
+    }
+  }
+
+  /// Returns `true` if this entity is on fire.
+  pub fn is_on_fire(&self) -> bool {
+    match self {
+      Self::Entity(e) => e.is_on_fire(),
+      Self::Player(p) => p.is_on_fire(),
+    }
+  }
+
+  /// Sets this entity on fire for the given number of ticks. If `ticks` is 0,
+  /// the fire will be put out. This will update the on fire flag for all nearby
+  /// players.
+  pub fn set_on_fire(&self, ticks: u32) {
+    match self {
+      Self::Entity(e) => e.set_on_fire(ticks),
+      Self::Player(p) => p.set_on_fire(ticks),
+    }
+  }
+
   /// Returns `true` if this is a player.
   pub fn is_player(&self) -> bool { matches!(self, Self::Player(_)) }
 
@@ -265,6 +419,8 @@ impl EntityData {
       world: RwLock::new(world),
       behavior: Mutex::new(behavior),
       meta: Mutex::new(meta),
+      fire: Mutex::new(0),
+      age: Mutex::new(0),
       data,
     }
   }
@@ -288,16 +444,132 @@ impl EntityData {
       world: RwLock::new(world),
       behavior: Mutex::new(Box::new(behavior)),
       meta: Mutex::new(meta),
+      fire: Mutex::new(0),
+      age: Mutex::new(0),
       data,
     }
   }
 
   pub fn data(&self) -> i32 { self.data }
   pub fn fpos(&self) -> FPos { self.pos.lock().aabb.pos }
+  pub fn vel(&self) -> Vec3 { self.pos.lock().vel }
   pub fn health(&self) -> f32 { *self.health.lock() }
   pub fn eid(&self) -> i32 { self.eid }
   pub fn metadata(&self) -> MutexGuard<'_, Metadata> { self.meta.lock() }
+  pub fn is_in_water(&self) -> bool { self.pos.lock().in_water }
+  pub fn is_on_fire(&self) -> bool { *self.fire.lock() > 0 }
+
+  /// Sets this entity on fire for the given number of ticks. If `ticks` is 0,
+  /// the fire will be put out.
+  pub fn set_on_fire(&self, ticks: u32) {
+    let was_on_fire = {
+      let mut fire = self.fire.lock();
+      let was_on_fire = *fire > 0;
+      *fire = ticks;
+      was_on_fire
+    };
+    if was_on_fire != (ticks > 0) {
+      self.send_status_byte(ticks > 0);
+    }
+  }
+
+  /// Sets the on fire flag in this entity's status byte, and sends the new
+  /// status byte to all nearby players. The other flags are left unchanged.
+  fn send_status_byte(&self, on_fire: bool) {
+    let status = {
+      let mut meta = self.meta.lock();
+      let mut status = meta.get_byte(0);
+      if on_fire {
+        status |= 0x01;
+      } else {
+        status &= !0x01;
+      }
+      meta.set_byte(0, status as i8);
+      status
+    };
+    let mut meta = Metadata::new();
+    meta.set_byte(0, status as i8);
+    self.world.read().send_entity_meta(self.fpos().chunk(), self.eid, self.ty, meta);
+  }
+
+  /// Passes damage to this entity's behavior, and then applies the damage and
+  /// knockback if the behavior allowed it. Returns `false` if the behavior
+  /// cancelled the damage. If this kills the entity, it will despawn on its
+  /// next tick.
+  fn damage(&self, amount: f32, knockback: Vec3) -> bool {
+    let world = self.world.read().clone();
+    if !self.behavior.lock().damage(&world, self, amount, knockback) {
+      return false;
+    }
+    self.damage_direct(amount);
+    let (chunk, vel) = {
+      let mut p = self.pos.lock();
+      p.vel += knockback;
+      (p.aabb.pos.chunk(), p.vel)
+    };
+    world.send_entity_vel(chunk, self.eid, vel);
+    true
+  }
+
+  /// Deals damage to this entity directly, ignoring armor. Returns `true` if
+  /// the entity should despawn.
+  fn damage_direct(&self, amount: f32) -> bool {
+    let mut health = self.health.lock();
+    *health -= amount;
+    self.behavior.lock().should_despawn(*health).0
+  }
+
+  /// Samples the block this entity is in, and updates `in_water` and
+  /// `in_lava`.
+  fn update_fluid(&self, w: &World, p: &mut EntityPos) {
+    let kind = w.get_kind(p.aabb.pos.block()).unwrap_or(block::Kind::Air);
+    p.in_water = kind == block::Kind::Water;
+    p.in_lava = kind == block::Kind::Lava;
+  }
+
+  /// Applies buoyancy and drag from the fluid this entity is in, and updates
+  /// fire. Returns `true` if the entity should despawn.
+  fn tick_fluid(&self, p: &mut EntityPos) -> bool {
+    let age = {
+      let mut age = self.age.lock();
+      *age += 1;
+      *age
+    };
+    if p.in_water {
+      // Water mostly cancels out gravity, and slows everything down a lot.
+      p.vel = p.vel * 0.8;
+      p.vel.y += 0.02;
+      if self.is_on_fire() {
+        self.set_on_fire(0);
+      }
+    } else if p.in_lava {
+      p.vel = p.vel * 0.5;
+      p.vel.y += 0.02;
+      // Vanilla sets entities on fire for 15 seconds when they touch lava.
+      self.set_on_fire(300);
+      if age % 10 == 0 && self.damage_direct(4.0) {
+        return true;
+      }
+    }
+    let mut fire = self.fire.lock();
+    if *fire > 0 {
+      *fire -= 1;
+      let fire_ticks = *fire;
+      drop(fire);
+      if fire_ticks == 0 {
+        self.send_status_byte(false);
+      } else if !p.in_lava && age % 20 == 0 && self.damage_direct(1.0) {
+        return true;
+      }
+    }
+    false
+  }
+
   fn tick(&self) -> bool {
+    // Entities can be killed by other entities between ticks.
+    if self.behavior.lock().should_despawn(self.health()).0 {
+      return true;
+    }
     // We don't actually have a race condition here, unless tick() is called at the
     // same time from multiple places (which would be a Bad Thing). Because we can't
     // modify `self.pos` from anywhere else (simply because the functions don't
@@ -306,7 +578,24 @@ impl EntityData {
     let mut p = *self.pos.lock();
     let old = p.aabb;
     let old_vel = p.vel;
-    if self.behavior.lock().tick(&*self.world.read(), self, &mut p).0 {
+    self.update_fluid(&self.world.read(), &mut p);
+    let (despawn, hit) = {
+      let mut behavior = self.behavior.lock();
+      (behavior.tick(&*self.world.read(), self, &mut p), behavior.take_hit())
+    };
+    // Our behavior must be unlocked here, as the entity we are damaging might be
+    // trying to damage us at the same time.
+    if let Some(hit) = hit {
+      let world = self.world.read().clone();
+      let other = world.entities().get_cloned(hit.eid);
+      if let Some(other) = other.as_ref().and_then(|e| e.as_entity_ref(&world)) {
+        other.damage(hit.amount, true, hit.knockback);
+      }
+    }
+    if despawn.0 {
+      return true;
+    }
+    if self.tick_fluid(&mut p) {
       return true;
     }
     let w = self.world.read();
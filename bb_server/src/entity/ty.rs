@@ -146,6 +146,12 @@ impl Type {
   /// `None`. This differs from items and blocks, as those both have defaults
   /// (air). There is no 'air' like entity, so we need to return an Option here.
   pub fn from_u32(v: u32) -> Option<Type> { num::FromPrimitive::from_u32(v) }
+  /// Returns the entity with the given name. The `minecraft:` namespace is
+  /// optional, so `zombie` and `minecraft:zombie` are both valid. Returns
+  /// `None` if there is no entity with that name.
+  pub fn from_name(name: &str) -> Option<Type> {
+    Type::from_str(name.strip_prefix("minecraft:").unwrap_or(name)).ok()
+  }
 }
 
 #[derive(Debug)]
@@ -299,3 +305,22 @@ impl Type {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_names() {
+    crate::util::check_names(
+      Type::COUNT,
+      |id| Type::from_u32(id).unwrap(),
+      Type::to_str,
+      Type::from_name,
+    );
+    assert_eq!(Type::from_name("zombie"), Some(Type::Zombie));
+    assert_eq!(Type::from_name("minecraft:falling_block"), Some(Type::FallingBlock));
+    assert_eq!(Type::from_name("not_an_entity"), None);
+    assert_eq!(Type::from_name("other:zombie"), None);
+  }
+}
@@ -50,6 +50,9 @@ impl TypeConverter {
   /// Returns any data about this item. Includes things like max stack size,
   /// display name, etc.
   pub fn get_data(&self, entity: Type) -> &Data { &self.types[entity.id() as usize] }
+  /// Returns the entity with the given name, like `zombie` or
+  /// `minecraft:zombie`. This is the same as [`Type::from_name`].
+  pub fn get_by_name(&self, name: &str) -> Option<Type> { Type::from_name(name) }
 }
 
 // TODO: Don't include versioning data, as we don't really need it.
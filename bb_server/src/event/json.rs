@@ -54,6 +54,10 @@ impl From<JsonPos> for Pos {
 }
 impl From<block::TypeStore> for JsonBlock {
   fn from(ty: block::TypeStore) -> Self {
-    JsonBlock { kind: ty.kind().to_str().into(), id: ty.id(), props: ty.props() }
+    JsonBlock {
+      kind:  ty.kind().to_str().into(),
+      id:    ty.id(),
+      props: ty.props().map(|(k, v)| (k.into(), v.to_string())).collect(),
+    }
   }
 }
@@ -42,13 +42,15 @@ impl Events<'_> {
     // self.req(player, ServerRequest::Interact { hand, click });
     let stack = player.lock_inventory().main_hand().clone();
     try_event!(self.player_request(Interact { player: player.clone(), slot: 36 }));
-    try_event!(self.wm.item_behaviors().call(stack.item(), |i| i.interact(click)));
+    // Interacting with a block (opening a door, pressing a button, etc) takes
+    // priority over using the item in the player's hand.
     if let Click::Block(click) = click {
       try_event!(self
         .wm
         .block_behaviors()
         .call(click.block.kind(), |b| b.interact(click.block, player)));
     }
+    try_event!(self.wm.item_behaviors().call(stack.item(), |i| i.interact(click)));
     Continue
   }
 
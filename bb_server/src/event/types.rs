@@ -2,7 +2,7 @@ use super::json::*;
 use crate::{block, item::Stack, math::Vec3, player::Player, plugin::IntoPanda};
 use bb_common::{
   math::{ChunkPos, FPos, Pos},
-  net::sb::ClickWindow,
+  net::sb::{ClickWindow, ResourcePackStatus},
   util::GameMode,
 };
 use panda::{
@@ -259,6 +259,19 @@ event! {
     old_pos: FPos,
     new_pos: FPos,
   },
+  /// Called when a player closes a window opened with `show_inventory`.
+  WindowClose: "window_close" {},
+  /// Called when a player types into the text box of an anvil window.
+  NameItem: "name_item" {
+    /// The new contents of the text box.
+    name: String,
+  },
+  /// Called when a player responds to a resource pack sent with
+  /// `set_resource_pack`.
+  ResourcePackResponse: "resource_pack_status" {
+    /// The status of the resource pack download.
+    status: ResourcePackStatus,
+  },
 }
 event! {
   /// An event from the server to the plugin. This is very similar to
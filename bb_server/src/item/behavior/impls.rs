@@ -19,8 +19,8 @@ impl Behavior for DebugStick {
   #[allow(clippy::collapsible_else_if)]
   fn break_block(&self, click: BlockClick) -> EventFlow {
     let mut block = click.block;
-    let mut all_props: Vec<_> = block.ty.props().into_iter().collect();
-    all_props.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    let mut all_props: Vec<_> = block.ty.props().collect();
+    all_props.sort_unstable_by_key(|(key, _)| *key);
     let reverse = click.player.is_crouching();
     for (key, _) in all_props {
       let prop = block.ty.prop_at(&key).unwrap();
@@ -56,6 +56,23 @@ pub struct Bucket(pub Option<block::Kind>);
 impl Behavior for Bucket {
   fn interact(&self, click: Click) -> EventFlow {
     if let Some((pos, res)) = click.do_raycast(5.0, true) {
+      // Water buckets fill waterloggable blocks, and empty buckets drain them.
+      let hit = (pos + res.axis / -2.0).block();
+      let world = click.player().world();
+      if let Ok(ty) = world.get_block(hit) {
+        let ty = ty.ty();
+        let waterlog = match self.0 {
+          Some(block::Kind::Water) => Some(true),
+          None => Some(false),
+          _ => None,
+        };
+        if let Some(waterlog) = waterlog {
+          if ty.can_waterlog() && ty.is_waterlogged() != waterlog {
+            let _ = world.set_block(hit, ty.with_prop("waterlogged", waterlog));
+            return Handled;
+          }
+        }
+      }
       let pos = (pos + res.axis / 2.0).block();
       let _ = match self.0 {
         Some(block) => click.player().world().set_kind(pos, block),
@@ -69,17 +86,28 @@ impl Behavior for Bucket {
 pub struct Snowball;
 impl Behavior for Snowball {
   fn interact(&self, click: Click) -> EventFlow {
-    let eid = click.player().world().summon(entity::Type::Snowball, click.player().view_pos());
-
-    // If the entity doesn't exist, it already despawned, so we do nothing if
-    // it isn't in the world.
-    if let Some(ent) = click.player().world().entities().get(eid) {
-      ent.set_vel(click.dir() * 1.5);
-    }
+    let p = click.player();
+    p.world().spawn_projectile(
+      entity::Type::Snowball,
+      p.view_pos(),
+      click.dir() * 1.5,
+      Some(p.eid()),
+    );
     Continue
   }
 }
 
+/// TODO: Bows should charge up while right click is held, and fire once it is
+/// released. For now, we just fire a full power arrow right away.
+pub struct Bow;
+impl Behavior for Bow {
+  fn interact(&self, click: Click) -> EventFlow {
+    let p = click.player();
+    p.world().spawn_projectile(entity::Type::Arrow, p.view_pos(), click.dir() * 3.0, Some(p.eid()));
+    Handled
+  }
+}
+
 pub struct Torch {
   pub normal: block::Kind,
   pub wall:   block::Kind,
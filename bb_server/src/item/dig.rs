@@ -66,6 +66,8 @@ impl Type {
 }
 
 impl Tool {
+  /// Returns the type of this tool.
+  pub fn ty(&self) -> ToolType { self.ty }
   pub fn does_mine(&self, block: &block::Data) -> bool {
     if !self.ty.does_mine(block.material) {
       return false;
@@ -0,0 +1,150 @@
+//! Implements [`Stack::add_damage`]
+
+use super::{dig::ToolType, Stack, Type};
+
+impl Type {
+  /// Returns the number of times this item can be used before it breaks, or
+  /// `None` if this item doesn't have durability.
+  pub fn max_durability(&self) -> Option<u32> {
+    // Armor durability is a multiple of these, depending on the material.
+    const HELMET: u32 = 11;
+    const CHESTPLATE: u32 = 16;
+    const LEGGINGS: u32 = 15;
+    const BOOTS: u32 = 13;
+    Some(match self {
+      Type::WoodenSword | Type::WoodenPickaxe | Type::WoodenAxe => 59,
+      Type::WoodenShovel | Type::WoodenHoe => 59,
+      Type::StoneSword | Type::StonePickaxe | Type::StoneAxe => 131,
+      Type::StoneShovel | Type::StoneHoe => 131,
+      Type::IronSword | Type::IronPickaxe | Type::IronAxe => 250,
+      Type::IronShovel | Type::IronHoe => 250,
+      Type::GoldenSword | Type::GoldenPickaxe | Type::GoldenAxe => 32,
+      Type::GoldenShovel | Type::GoldenHoe => 32,
+      Type::DiamondSword | Type::DiamondPickaxe | Type::DiamondAxe => 1561,
+      Type::DiamondShovel | Type::DiamondHoe => 1561,
+      Type::NetheriteSword | Type::NetheritePickaxe | Type::NetheriteAxe => 2031,
+      Type::NetheriteShovel | Type::NetheriteHoe => 2031,
+
+      Type::LeatherHelmet => HELMET * 5,
+      Type::LeatherChestplate => CHESTPLATE * 5,
+      Type::LeatherLeggings => LEGGINGS * 5,
+      Type::LeatherBoots => BOOTS * 5,
+      Type::ChainmailHelmet | Type::IronHelmet => HELMET * 15,
+      Type::ChainmailChestplate | Type::IronChestplate => CHESTPLATE * 15,
+      Type::ChainmailLeggings | Type::IronLeggings => LEGGINGS * 15,
+      Type::ChainmailBoots | Type::IronBoots => BOOTS * 15,
+      Type::GoldenHelmet => HELMET * 7,
+      Type::GoldenChestplate => CHESTPLATE * 7,
+      Type::GoldenLeggings => LEGGINGS * 7,
+      Type::GoldenBoots => BOOTS * 7,
+      Type::DiamondHelmet => HELMET * 33,
+      Type::DiamondChestplate => CHESTPLATE * 33,
+      Type::DiamondLeggings => LEGGINGS * 33,
+      Type::DiamondBoots => BOOTS * 33,
+      Type::NetheriteHelmet => HELMET * 37,
+      Type::NetheriteChestplate => CHESTPLATE * 37,
+      Type::NetheriteLeggings => LEGGINGS * 37,
+      Type::NetheriteBoots => BOOTS * 37,
+      Type::TurtleHelmet => HELMET * 25,
+
+      Type::Bow => 384,
+      Type::Crossbow => 465,
+      Type::Trident => 250,
+      Type::Shield => 336,
+      Type::Elytra => 432,
+      Type::FishingRod => 64,
+      Type::FlintAndSteel => 64,
+      Type::Shears => 238,
+      Type::CarrotOnAStick => 25,
+      Type::WarpedFungusOnAStick => 100,
+      _ => return None,
+    })
+  }
+
+  /// Returns the durability used when breaking a block with this item. Swords
+  /// take 2 damage, other tools take 1, and everything else takes none.
+  pub fn break_wear(&self) -> u32 {
+    match self.tool() {
+      Some(tool) if tool.ty() == ToolType::Sword => 2,
+      Some(_) => 1,
+      None => 0,
+    }
+  }
+  /// Returns the durability used when attacking an entity with this item.
+  /// Swords take 1 damage, other tools take 2, and everything else takes none.
+  pub fn attack_wear(&self) -> u32 {
+    match self.tool() {
+      Some(tool) if tool.ty() == ToolType::Sword => 1,
+      Some(_) => 2,
+      None => 0,
+    }
+  }
+}
+
+impl Stack {
+  /// Returns the amount of durability this item has lost.
+  pub fn damage(&self) -> u32 { self.data.damage }
+  /// Sets the amount of durability this item has lost. This doesn't break the
+  /// item, even if it is past the max durability.
+  pub fn set_damage(&mut self, damage: u32) { self.data.damage = damage; }
+  /// Sets the damage of this item, and returns the modified self.
+  pub fn with_damage(mut self, damage: u32) -> Self {
+    self.set_damage(damage);
+    self
+  }
+
+  /// Returns true if this item loses durability when it is used. This is false
+  /// for items without durability, and for unbreakable items.
+  pub fn is_damageable(&self) -> bool {
+    self.item().max_durability().is_some() && !self.data.unbreakable
+  }
+
+  /// Damages this item by `amount`. If the damage goes past the max
+  /// durability, the item breaks, and this stack is replaced with air.
+  ///
+  /// Returns `true` if the item broke. If the item isn't
+  /// [damageable](Self::is_damageable), this does nothing.
+  pub fn add_damage(&mut self, amount: u32) -> bool {
+    let max = match self.item().max_durability() {
+      Some(max) if !self.data.unbreakable => max,
+      _ => return false,
+    };
+    self.data.damage = self.data.damage.saturating_add(amount);
+    if self.data.damage >= max {
+      *self = Stack::empty();
+      true
+    } else {
+      false
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_durability() {
+    let mut stack = Stack::new(Type::WoodenPickaxe);
+    assert!(stack.is_damageable());
+    assert!(!stack.add_damage(58));
+    assert_eq!(stack.damage(), 58);
+    assert!(stack.add_damage(1));
+    assert!(stack.is_empty());
+
+    let mut stack = Stack::new(Type::DiamondSword);
+    stack.data_mut().unbreakable = true;
+    assert!(!stack.is_damageable());
+    assert!(!stack.add_damage(10_000));
+    assert_eq!(stack.damage(), 0);
+
+    let mut stack = Stack::new(Type::Stone);
+    assert!(!stack.add_damage(1));
+    assert_eq!(stack, Stack::new(Type::Stone));
+
+    assert_eq!(Type::DiamondSword.break_wear(), 2);
+    assert_eq!(Type::DiamondSword.attack_wear(), 1);
+    assert_eq!(Type::IronAxe.attack_wear(), 2);
+    assert_eq!(Type::Stone.attack_wear(), 0);
+  }
+}
@@ -46,26 +46,58 @@ impl<const N: usize> Inventory<N> {
   /// number of remaining items in the stack. If the inventory has enough space,
   /// this will return 0.
   pub fn add(&mut self, stack: &Stack) -> u8 {
-    let mut remaining = stack.amount();
-    for it in self.items_mut().iter_mut() {
+    self.add_stack(stack.clone()).map(|s| s.amount()).unwrap_or(0)
+  }
+
+  /// Adds the given stack to this inventory. This will first merge the stack
+  /// into any existing stacks of the same item, and then fill empty slots.
+  /// Stack sizes are limited by [`Stack::max_amount`].
+  ///
+  /// Returns the items that didn't fit, or `None` if the entire stack was
+  /// added.
+  pub fn add_stack(&mut self, mut stack: Stack) -> Option<Stack> {
+    if stack.is_empty() {
+      return None;
+    }
+    let max = stack.max_amount();
+    for it in self.items.iter_mut() {
+      if !it.is_empty() && it.can_merge(&stack) && it.amount() < max {
+        let moved = (max - it.amount()).min(stack.amount());
+        it.set_amount(it.amount() + moved);
+        stack.set_amount(stack.amount() - moved);
+        if stack.is_empty() {
+          return None;
+        }
+      }
+    }
+    for it in self.items.iter_mut() {
       if it.is_empty() {
-        *it = stack.clone().with_amount(remaining);
-        remaining = 0;
-      } else if it.item() == stack.item() {
-        let amount_possible = 64 - it.amount();
-        if amount_possible > remaining {
-          *it = stack.clone().with_amount(it.amount() + remaining);
-          remaining = 0;
-        } else {
-          *it = stack.clone().with_amount(64);
-          remaining -= amount_possible;
+        let moved = max.min(stack.amount());
+        *it = stack.clone().with_amount(moved);
+        stack.set_amount(stack.amount() - moved);
+        if stack.is_empty() {
+          return None;
         }
       }
-      if remaining == 0 {
-        break;
+    }
+    Some(stack)
+  }
+
+  /// Moves the stack at `index` into `other`, using
+  /// [`add_stack`](Self::add_stack). Anything that doesn't fit in `other` will
+  /// be left at `index`. This is what a shift-click does.
+  ///
+  /// Returns `true` if the entire stack was moved.
+  #[track_caller]
+  pub fn transfer_to<const M: usize>(&mut self, other: &mut Inventory<M>, index: u32) -> bool {
+    let stack = self.replace(index, Stack::EMPTY);
+    match other.add_stack(stack) {
+      Some(remaining) => {
+        self.set(index, remaining);
+        false
       }
+      None => true,
     }
-    remaining
   }
 }
 
@@ -82,15 +114,15 @@ pub struct SingleInventory<const N: usize> {
 #[derive(Debug)]
 pub struct WrappedInventory<const N: usize> {
   pub(crate) inv:     Inventory<N>,
-  // Everyone who has this inventory open.
-  pub(crate) viewers: HashMap<UUID, ConnSender>,
-  pub(crate) wid:     u8,
+  // Everyone who has this inventory open, along with the window id they have
+  // it open as.
+  pub(crate) viewers: HashMap<UUID, (ConnSender, u8)>,
   pub(crate) offset:  u32,
 }
 
 impl<const N: usize> From<Inventory<N>> for WrappedInventory<N> {
   fn from(inv: Inventory<N>) -> WrappedInventory<N> {
-    WrappedInventory { inv, viewers: HashMap::new(), wid: 1, offset: 0 }
+    WrappedInventory { inv, viewers: HashMap::new(), offset: 0 }
   }
 }
 
@@ -176,36 +208,11 @@ impl<const N: usize> SingleInventory<N> {
   /// number of remaining items in the stack. If the inventory has enough space,
   /// this will return 0.
   pub fn add(&mut self, stack: &Stack) -> u8 {
-    // Local `sync` impl, which doesn't use the internal inventory. This is needed
-    // because we iterate through the inventory with `items_mut`, so the inventory
-    // is mutably borrowed for the entire loop.
-    let sync = |index: u32, item: &Stack| {
-      self.conn.send(cb::packet::WindowItem {
-        wid:  self.wid,
-        slot: (index + self.offset) as i32,
-        item: item.to_item(),
-      });
-    };
-    let mut remaining = stack.amount();
-    for (i, it) in self.inv.items_mut().iter_mut().enumerate() {
-      let i = i as u32;
-      if it.is_empty() {
-        *it = stack.clone().with_amount(remaining);
-        sync(i, it);
-        remaining = 0;
-      } else if it.item() == stack.item() {
-        let amount_possible = 64 - it.amount();
-        if amount_possible > remaining {
-          *it = stack.clone().with_amount(it.amount() + remaining);
-          remaining = 0;
-        } else {
-          *it = stack.clone().with_amount(64);
-          remaining -= amount_possible;
-        }
-        sync(i, it);
-      }
-      if remaining == 0 {
-        break;
+    let before = self.inv.clone();
+    let remaining = self.inv.add_stack(stack.clone()).map(|s| s.amount()).unwrap_or(0);
+    for (i, (old, new)) in before.items().iter().zip(self.inv.items()).enumerate() {
+      if old != new {
+        self.sync_raw(i as u32);
       }
     }
     remaining
@@ -219,9 +226,9 @@ impl<const N: usize> SingleInventory<N> {
       let i = i as u32;
       if it.is_empty() {
         self.sync_raw(i);
-        remaining = 0;
-      } else if it.item() == stack.item() {
-        let amount_possible = 64 - it.amount();
+        remaining = remaining.saturating_sub(stack.max_amount());
+      } else if it.can_merge(stack) {
+        let amount_possible = stack.max_amount().saturating_sub(it.amount());
         if amount_possible > remaining {
           remaining = 0;
         } else {
@@ -241,16 +248,17 @@ impl<const N: usize> SingleInventory<N> {
 }
 
 impl<const N: usize> WrappedInventory<N> {
-  pub fn new(wid: u8, offset: u32) -> Self {
-    WrappedInventory { inv: Inventory::new(), viewers: HashMap::new(), wid, offset }
+  pub fn new(offset: u32) -> Self {
+    WrappedInventory { inv: Inventory::new(), viewers: HashMap::new(), offset }
   }
 
   /// Adds the player with `id` to the list of players that are viewing this
-  /// inventory.
-  pub fn open(&mut self, id: UUID, conn: ConnSender) {
-    // If we `open` twice for the same player, this should do nothing, as the same
-    // id will always map to the same ConnSender.
-    self.viewers.insert(id, conn);
+  /// inventory. `wid` is the window id that this player has the inventory open
+  /// as.
+  pub fn open(&mut self, id: UUID, conn: ConnSender, wid: u8) {
+    // If we `open` twice for the same player, this will just update the window id,
+    // as the same id will always map to the same ConnSender.
+    self.viewers.insert(id, (conn, wid));
   }
   /// Removes the player from the list of players that has this inventory open.
   ///
@@ -311,9 +319,9 @@ impl<const N: usize> WrappedInventory<N> {
   /// Syncs the item at the given slot with the client.
   #[track_caller]
   pub fn sync_raw(&self, index: u32) {
-    for conn in self.viewers.values() {
+    for (conn, wid) in self.viewers.values() {
       conn.send(cb::packet::WindowItem {
-        wid:  self.wid,
+        wid:  *wid,
         slot: (index + self.offset) as i32,
         item: self.get_raw(index).unwrap().to_item(),
       });
@@ -323,9 +331,9 @@ impl<const N: usize> WrappedInventory<N> {
   /// index, to get the actual inventory offset.
   #[track_caller]
   pub(crate) fn sync(&self, index: u32) {
-    for conn in self.viewers.values() {
+    for (conn, wid) in self.viewers.values() {
       conn.send(cb::packet::WindowItem {
-        wid:  self.wid,
+        wid:  *wid,
         slot: index as i32,
         item: self.get(index).unwrap().to_item(),
       });
@@ -340,38 +348,11 @@ impl<const N: usize> WrappedInventory<N> {
   /// number of remaining items in the stack. If the inventory has enough space,
   /// this will return 0.
   pub fn add(&mut self, stack: &Stack) -> u8 {
-    // Local `sync` impl, which doesn't use the internal inventory. This is needed
-    // because we iterate through the inventory with `items_mut`, so the inventory
-    // is mutably borrowed for the entire loop.
-    let sync = |index: u32, item: &Stack| {
-      for conn in self.viewers.values() {
-        conn.send(cb::packet::WindowItem {
-          wid:  self.wid,
-          slot: (index + self.offset) as i32,
-          item: item.to_item(),
-        });
-      }
-    };
-    let mut remaining = stack.amount();
-    for (i, it) in self.inv.items_mut().iter_mut().enumerate() {
-      let i = i as u32;
-      if it.is_empty() {
-        *it = stack.clone().with_amount(remaining);
-        sync(i, it);
-        remaining = 0;
-      } else if it.item() == stack.item() {
-        let amount_possible = 64 - it.amount();
-        if amount_possible > remaining {
-          *it = stack.clone().with_amount(it.amount() + remaining);
-          remaining = 0;
-        } else {
-          *it = stack.clone().with_amount(64);
-          remaining -= amount_possible;
-        }
-        sync(i, it);
-      }
-      if remaining == 0 {
-        break;
+    let before = self.inv.clone();
+    let remaining = self.inv.add_stack(stack.clone()).map(|s| s.amount()).unwrap_or(0);
+    for (i, (old, new)) in before.items().iter().zip(self.inv.items()).enumerate() {
+      if old != new {
+        self.sync_raw(i as u32);
       }
     }
     remaining
@@ -385,9 +366,9 @@ impl<const N: usize> WrappedInventory<N> {
       let i = i as u32;
       if it.is_empty() {
         self.sync_raw(i);
-        remaining = 0;
-      } else if it.item() == stack.item() {
-        let amount_possible = 64 - it.amount();
+        remaining = remaining.saturating_sub(stack.max_amount());
+      } else if it.can_merge(stack) {
+        let amount_possible = stack.max_amount().saturating_sub(it.amount());
         if amount_possible > remaining {
           remaining = 0;
         } else {
@@ -405,3 +386,75 @@ impl<const N: usize> WrappedInventory<N> {
   /// Returns the size of this inventory.
   pub const fn size(&self) -> u32 { self.inv.size() }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::item::Type;
+
+  #[test]
+  fn add_stack() {
+    let mut inv = Inventory::<3>::new();
+    assert_eq!(inv.add_stack(Stack::new(Type::Stone).with_amount(40)), None);
+    assert_eq!(inv.add_stack(Stack::new(Type::Dirt).with_amount(10)), None);
+    // This should fill up the first stack, and then overflow into the last slot.
+    assert_eq!(inv.add_stack(Stack::new(Type::Stone).with_amount(40)), None);
+    assert_eq!(inv.get(0), Some(&Stack::new(Type::Stone).with_amount(64)));
+    assert_eq!(inv.get(1), Some(&Stack::new(Type::Dirt).with_amount(10)));
+    assert_eq!(inv.get(2), Some(&Stack::new(Type::Stone).with_amount(16)));
+
+    // Partial merge into the existing stacks, with the rest overflowing.
+    assert_eq!(
+      inv.add_stack(Stack::new(Type::Dirt).with_amount(60)),
+      Some(Stack::new(Type::Dirt).with_amount(6))
+    );
+    assert_eq!(inv.get(1), Some(&Stack::new(Type::Dirt).with_amount(64)));
+
+    // The inventory is full, so nothing should change.
+    assert_eq!(
+      inv.add_stack(Stack::new(Type::Stone).with_amount(64)),
+      Some(Stack::new(Type::Stone).with_amount(16))
+    );
+    assert_eq!(
+      inv.add_stack(Stack::new(Type::Cobblestone).with_amount(5)),
+      Some(Stack::new(Type::Cobblestone).with_amount(5))
+    );
+    assert_eq!(inv.add_stack(Stack::EMPTY), None);
+  }
+
+  #[test]
+  fn add_stack_size() {
+    let mut inv = Inventory::<3>::new();
+    assert_eq!(
+      inv.add_stack(Stack::new(Type::Snowball).with_amount(40)),
+      Some(Stack::new(Type::Snowball).with_amount(8))
+    );
+    assert_eq!(inv.get(0), Some(&Stack::new(Type::Snowball).with_amount(16)));
+    assert_eq!(inv.get(2), Some(&Stack::new(Type::Snowball).with_amount(16)));
+
+    let mut inv = Inventory::<2>::new();
+    assert_eq!(
+      inv.add_stack(Stack::new(Type::DiamondSword).with_amount(3)),
+      Some(Stack::new(Type::DiamondSword))
+    );
+  }
+
+  #[test]
+  fn transfer_to() {
+    let mut a = Inventory::<2>::new();
+    let mut b = Inventory::<2>::new();
+    a.set(0, Stack::new(Type::Stone).with_amount(50));
+    b.set(0, Stack::new(Type::Stone).with_amount(60));
+    b.set(1, Stack::new(Type::Dirt));
+
+    // Only 4 items fit into `b`.
+    assert!(!a.transfer_to(&mut b, 0));
+    assert_eq!(a.get(0), Some(&Stack::new(Type::Stone).with_amount(46)));
+    assert_eq!(b.get(0), Some(&Stack::new(Type::Stone).with_amount(64)));
+
+    b.set(1, Stack::EMPTY);
+    assert!(a.transfer_to(&mut b, 0));
+    assert_eq!(a.get(0), Some(&Stack::EMPTY));
+    assert_eq!(b.get(1), Some(&Stack::new(Type::Stone).with_amount(46)));
+  }
+}
@@ -1,6 +1,7 @@
 mod attack;
 mod behavior;
 mod dig;
+mod durability;
 mod inventory;
 mod shared;
 mod stack;
@@ -2,7 +2,7 @@ use super::{Inventory, WrappedInventory};
 use crate::item::Stack;
 use bb_transfer::{MessageRead, MessageReader, MessageWrite, MessageWriter, ReadError, WriteError};
 use parking_lot::{Mutex, MutexGuard};
-use std::{io, sync::Arc};
+use std::{collections::HashMap, io, sync::Arc};
 
 #[derive(Debug, Clone)]
 pub struct SharedInventory<const N: usize> {
@@ -20,7 +20,7 @@ impl<const N: usize> Default for SharedInventory<N> {
 impl<const N: usize> MessageRead<'_> for SharedInventory<N> {
   fn read(r: &mut MessageReader) -> Result<Self, ReadError> {
     let items = r.read_list::<Stack>()?;
-    let mut inv = WrappedInventory::new(1, 0);
+    let mut inv = WrappedInventory::new(0);
     assert_eq!(items.len(), N);
     for (i, it) in items.enumerate() {
       inv.set(i as u32, it?);
@@ -38,7 +38,20 @@ impl<const N: usize> MessageWrite for SharedInventory<N> {
 impl<const N: usize> SharedInventory<N> {
   pub fn new() -> Self {
     // TODO: Offset should be passedin
-    SharedInventory { inv: Arc::new(Mutex::new(WrappedInventory::new(1, 0))) }
+    SharedInventory { inv: Arc::new(Mutex::new(WrappedInventory::new(0))) }
   }
   pub fn lock(&self) -> MutexGuard<WrappedInventory<N>> { self.inv.lock() }
+  /// Copies the items in this inventory into a new inventory. Unlike
+  /// [`clone`](Clone::clone), changes to the returned inventory won't show up
+  /// in this one. The new inventory has no viewers.
+  pub fn deep_clone(&self) -> Self {
+    let inv = self.lock();
+    SharedInventory {
+      inv: Arc::new(Mutex::new(WrappedInventory {
+        inv:     inv.inv.clone(),
+        viewers: HashMap::new(),
+        offset:  inv.offset,
+      })),
+    }
+  }
 }
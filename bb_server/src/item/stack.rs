@@ -1,11 +1,14 @@
 use super::Type;
 use crate::enchantment;
-use bb_common::util::{Item, ItemData};
+use bb_common::{
+  nbt::{Tag, WrongTag},
+  util::{Chat, Item, ItemData},
+};
 use bb_transfer::{
   MessageRead, MessageReader, MessageWrite, MessageWriter, ReadError, StructRead, StructReader,
   WriteError,
 };
-use std::num::NonZeroU8;
+use std::{num::NonZeroU8, str::FromStr};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Stack {
@@ -86,6 +89,15 @@ impl Stack {
   }
   /// Returns the item that is in this item stack.
   pub fn item(&self) -> Type { self.item }
+  /// Returns the maximum amount of items that can be in this stack. This
+  /// depends on the item type.
+  pub fn max_amount(&self) -> u8 { self.item.data().stack_size() }
+  /// Returns true if `other` can be merged into this stack. This is true if
+  /// they have the same item and the same data. This does not check the
+  /// amount in either stack.
+  pub fn can_merge(&self, other: &Stack) -> bool {
+    self.item == other.item && self.data == other.data
+  }
 
   /// Returns true if this item stack is considered "empty". This is true
   /// whenever the type is Air, or the count is zero.
@@ -103,6 +115,58 @@ impl Stack {
     self
   }
 
+  /// Reads the given item NBT into this stack's data. This handles the display
+  /// name and lore, enchantments, damage, and the unbreakable tag. Any other
+  /// tags are ignored. This is the NBT used in commands, like `/give`.
+  ///
+  /// Returns an error if any of those tags have the wrong type.
+  pub fn with_nbt(mut self, nbt: &Tag) -> Result<Self, WrongTag> {
+    fn chat(tag: &Tag) -> Result<Chat, WrongTag> {
+      let text = tag.string()?;
+      Ok(Chat::from_json(text).unwrap_or_else(|_| Chat::new(text)))
+    }
+    fn int(tag: &Tag) -> Result<i32, WrongTag> {
+      match tag {
+        Tag::Byte(v) => Ok((*v).into()),
+        Tag::Short(v) => Ok((*v).into()),
+        _ => tag.int(),
+      }
+    }
+    let nbt = nbt.compound()?;
+    if let Some(display) = nbt.inner.get("display") {
+      let display = display.compound()?;
+      if let Some(name) = display.inner.get("Name") {
+        self.data.display.name = Some(chat(name)?);
+      }
+      if let Some(lore) = display.inner.get("Lore") {
+        self.data.display.lore = lore.list()?.iter().map(chat).collect::<Result<_, _>>()?;
+      }
+    }
+    if let Some(enchantments) = nbt.inner.get("Enchantments") {
+      for tag in enchantments.list()? {
+        let ench = tag.compound()?;
+        let (id, level) = match (ench.inner.get("id"), ench.inner.get("lvl")) {
+          (Some(id), Some(level)) => (id.string()?, int(level)?),
+          _ => return Err(WrongTag::new(tag.clone())),
+        };
+        let ty = match enchantment::Type::from_str(id.strip_prefix("minecraft:").unwrap_or(id)) {
+          Ok(ty) => ty,
+          Err(_) => return Err(WrongTag::new(tag.clone())),
+        };
+        if let Some(level) = NonZeroU8::new(level.clamp(0, 255) as u8) {
+          self.data.enchantments_mut().insert(ty.id(), level);
+        }
+      }
+    }
+    if let Some(unbreakable) = nbt.inner.get("Unbreakable") {
+      self.data.unbreakable = int(unbreakable)? != 0;
+    }
+    if let Some(damage) = nbt.inner.get("Damage") {
+      self.data.damage = int(damage)?.max(0) as u32;
+    }
+    Ok(self)
+  }
+
   /// Returns the level of enchantment, or `0` if the stack doesn't have that
   /// enchantment.
   pub fn enchantment(&self, ty: enchantment::Type) -> u8 {
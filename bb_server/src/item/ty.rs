@@ -10,6 +10,8 @@ pub struct Data {
   ty:   Type,
   name: &'static str,
   id:   u32,
+
+  stack_size: u32,
 }
 
 impl Data {
@@ -20,6 +22,10 @@ impl Data {
   pub fn id(&self) -> u32 { self.id }
   /// Returns the name of this item. This is something like `minecraft:stone`.
   pub fn name(&self) -> &'static str { self.name }
+  /// Returns the maximum number of items that can be in a single stack of
+  /// this item. This is 64 for most items, 16 for things like snowballs, and 1
+  /// for tools and armor.
+  pub fn stack_size(&self) -> u8 { self.stack_size as u8 }
 }
 
 #[derive(Debug)]
@@ -43,10 +49,35 @@ impl Type {
   /// Converts the given number to a block kind. If the number is invalid, this
   /// returns Kind::Air.
   pub fn from_u32(id: u32) -> Self { FromPrimitive::from_u32(id).unwrap_or(Type::Air) }
+  /// Returns the data for this item. This is the same as
+  /// [`TypeConverter::get_data`](super::TypeConverter::get_data), but doesn't
+  /// need a converter, as item data is not version specific.
+  pub fn data(self) -> &'static Data { &generate_kinds()[self.id() as usize] }
+  /// Returns the item with the given name. The `minecraft:` namespace is
+  /// optional, so `stone` and `minecraft:stone` are both valid. Returns `None`
+  /// if there is no item with that name.
+  pub fn from_name(name: &str) -> Option<Self> {
+    Type::from_str(name.strip_prefix("minecraft:").unwrap_or(name)).ok()
+  }
 }
 
 #[cfg(test)]
 mod tests {
+  use super::*;
+
+  #[test]
+  fn test_names() {
+    crate::util::check_names(Type::COUNT, Type::from_u32, Type::to_str, Type::from_name);
+    for id in 0..Type::COUNT {
+      let ty = Type::from_u32(id);
+      assert_eq!(ty.id(), id);
+      assert_eq!(ty.data().name(), ty.to_str());
+    }
+    assert_eq!(Type::from_name("diamond_sword"), Some(Type::DiamondSword));
+    assert_eq!(Type::from_name("minecraft:stone"), Some(Type::Stone));
+    assert_eq!(Type::from_name("not_an_item"), None);
+    assert_eq!(Type::from_name("other:stone"), None);
+  }
 
   #[test]
   fn test_blocks() {
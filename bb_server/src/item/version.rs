@@ -54,6 +54,12 @@ impl TypeConverter {
   /// Returns any data about this item. Includes things like max stack size,
   /// display name, etc.
   pub fn get_data(&self, item: Type) -> &Data { &self.types[item.id() as usize] }
+  /// Returns the data for the item with the given name, like `stone` or
+  /// `minecraft:stone`. This is the same as calling [`Type::from_name`], and
+  /// then [`get_data`](Self::get_data).
+  pub fn get_by_name(&self, name: &str) -> Option<&Data> {
+    Type::from_name(name).map(|ty| self.get_data(ty))
+  }
 }
 
 /// This is the conversion table for a single old version of the game and the
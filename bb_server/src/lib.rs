@@ -17,6 +17,7 @@ pub mod entity;
 pub mod event;
 pub mod item;
 pub mod math;
+pub mod metrics;
 pub mod net;
 pub mod particle;
 pub mod player;
@@ -1,7 +1,8 @@
 #[macro_use]
 extern crate log;
 
-use bb_server::{net::ConnectionManager, rcon::RCon, world::WorldManager};
+use bb_common::util::Dimension;
+use bb_server::{metrics::Metrics, net::ConnectionManager, rcon::RCon, world::WorldManager};
 use clap::Parser;
 use std::{sync::Arc, thread};
 
@@ -96,22 +97,32 @@ fn main() {
   let wm = Arc::new(WorldManager::new_with_config(config));
   wm.stop_on_ctrlc();
   let world = wm.new_world();
-  wm.add_world(world);
+  // This is the first world, so the name can't be taken yet.
+  wm.add_world(world, "world", Dimension::Overworld).unwrap();
   wm.load_plugins();
   wm.default_world().init();
 
   if let Some(mut rcon) = RCon::new(wm.clone()) {
     thread::spawn(move || rcon.run());
   }
+  if let Some(mut metrics) = Metrics::new(wm.clone()) {
+    thread::spawn(move || metrics.run());
+  }
 
   let w = wm.clone();
   thread::spawn(|| w.run());
 
-  let mut conn = ConnectionManager::new(wm);
+  let mut conn = ConnectionManager::new(wm.clone());
+  let w = wm.clone();
+  thread::spawn(move || {
+    info!("listening on {}", addr);
+    match conn.run(addr) {
+      Ok(_) => {}
+      Err(e) => error!("error in connection: {e}"),
+    };
+    w.request_stop();
+  });
 
-  info!("listening on {}", addr);
-  match conn.run(addr) {
-    Ok(_) => {}
-    Err(e) => error!("error in connection: {e}"),
-  };
+  // Signals and `/stop` only set a flag, so the actual shutdown happens here.
+  wm.wait_for_stop();
 }
@@ -1,5 +1,5 @@
 use super::Vec3;
-use bb_common::math::FPos;
+use bb_common::{math::FPos, util::Face};
 
 #[derive(Debug, Clone, Copy)]
 pub struct AABB {
@@ -142,6 +142,52 @@ impl AABB {
   /// Returns the minimum position of this bounding box. Can be used to move the
   /// box around.
   pub fn pos_mut(&mut self) -> &mut FPos { &mut self.pos }
+
+  /// Checks if a ray starting at `origin` going in the direction `dir` hits
+  /// this bounding box. If it does, this returns the distance along the ray
+  /// (in multiples of `dir`) and the face that the ray entered through.
+  ///
+  /// If `origin` is inside this box, the distance will be zero, and the face
+  /// will be the one opposite to `dir`.
+  pub fn ray_intersection(&self, origin: FPos, dir: Vec3) -> Option<(f64, Face)> {
+    if self.size.x == 0.0 || self.size.y == 0.0 || self.size.z == 0.0 {
+      return None;
+    }
+    let back = Vec3::new(-dir.x, -dir.y, -dir.z).as_face();
+    let mut enter = f64::NEG_INFINITY;
+    let mut exit = f64::INFINITY;
+    let mut face = back;
+    for (o, d, min, max, faces) in [
+      (origin.x(), dir.x, self.min_x(), self.max_x(), (Face::West, Face::East)),
+      (origin.y(), dir.y, self.min_y(), self.max_y(), (Face::Bottom, Face::Top)),
+      (origin.z(), dir.z, self.min_z(), self.max_z(), (Face::North, Face::South)),
+    ] {
+      if d == 0.0 {
+        // Parallel to this axis, so we must already be within the slab.
+        if o < min || o > max {
+          return None;
+        }
+        continue;
+      }
+      let (t_min, t_max, near) = if d > 0.0 {
+        ((min - o) / d, (max - o) / d, faces.0)
+      } else {
+        ((max - o) / d, (min - o) / d, faces.1)
+      };
+      if t_min > enter {
+        enter = t_min;
+        face = near;
+      }
+      exit = exit.min(t_max);
+    }
+    if enter > exit || exit < 0.0 {
+      None
+    } else if enter < 0.0 {
+      Some((0.0, back))
+    } else {
+      Some((enter, face))
+    }
+  }
 }
 
 fn in_range(val: (f64, f64), range: (f64, f64)) -> bool {
@@ -211,4 +257,28 @@ mod tests {
     assert_eq!(res.factor, 0.5);
     assert_eq!(b.pos, FPos::new(4.0, 0.0, 1.0));
   }
+
+  #[test]
+  fn ray_intersection() {
+    // A full block at 0, 0, 0
+    let b = AABB::new(FPos::new(0.5, 0.0, 0.5), Vec3::new(1.0, 1.0, 1.0));
+
+    let res = b.ray_intersection(FPos::new(-2.0, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0));
+    assert_eq!(res, Some((2.0, Face::West)));
+    let res = b.ray_intersection(FPos::new(0.5, 3.0, 0.5), Vec3::new(0.0, -1.0, 0.0));
+    assert_eq!(res, Some((2.0, Face::Top)));
+    let res = b.ray_intersection(FPos::new(0.5, 0.5, 4.0), Vec3::new(0.0, 0.0, -2.0));
+    assert_eq!(res, Some((1.5, Face::South)));
+
+    // Misses the block entirely
+    assert_eq!(b.ray_intersection(FPos::new(-2.0, 1.5, 0.5), Vec3::new(1.0, 0.0, 0.0)), None);
+    // Pointing away from the block
+    assert_eq!(b.ray_intersection(FPos::new(-2.0, 0.5, 0.5), Vec3::new(-1.0, 0.0, 0.0)), None);
+
+    // A bottom slab only gets hit from above if the ray goes low enough.
+    let slab = AABB::new(FPos::new(0.5, 0.0, 0.5), Vec3::new(1.0, 0.5, 1.0));
+    let res = slab.ray_intersection(FPos::new(0.5, 2.0, 0.5), Vec3::new(0.0, -1.0, 0.0));
+    assert_eq!(res, Some((1.5, Face::Top)));
+    assert_eq!(slab.ray_intersection(FPos::new(-1.0, 0.75, 0.5), Vec3::new(1.0, 0.0, 0.0)), None);
+  }
 }
@@ -1,6 +1,6 @@
 use super::{CollisionResult, AABB};
 use bb_common::{math::FPos, util::Face};
-use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Vec3 {
@@ -17,6 +17,17 @@ pub struct Vec2 {
 
 impl Vec3 {
   pub fn new(x: f64, y: f64, z: f64) -> Self { Vec3 { x, y, z } }
+  /// Returns a unit vector pointing in the direction of the given yaw and
+  /// pitch. These are in degrees, and use the same axis as minecraft: a yaw of
+  /// 0 faces south (+Z), and a pitch of 90 faces straight down.
+  pub fn from_yaw_pitch(yaw: f32, pitch: f32) -> Self {
+    let yaw = f64::from(yaw).to_radians();
+    let pitch = f64::from(pitch).to_radians();
+    let m = pitch.cos();
+    // The coordinate system of minecraft means that we need to do this hell to get
+    // the axis to line up correctly.
+    Vec3::new(-yaw.sin() * m, -pitch.sin(), yaw.cos() * m)
+  }
   /// Returns the velocity in the packet format. This is `self.x * 8000`,
   /// because self.x is in blocks/tick.
   pub fn fixed_x(&self) -> i16 { (self.x * 8000.0) as i16 }
@@ -34,6 +45,28 @@ impl Vec3 {
   /// [`len_squared`](Self::len_squared).
   pub fn len(&self) -> f64 { self.len_squared().sqrt() }
 
+  /// Returns a vector in the same direction as this one, with a length of 1.
+  /// If this vector has a length of 0, this returns a zero vector.
+  pub fn normalize(&self) -> Vec3 {
+    let len = self.len();
+    if len == 0.0 {
+      Vec3::new(0.0, 0.0, 0.0)
+    } else {
+      *self / len
+    }
+  }
+  /// Returns the dot product of `self` and `other`.
+  pub fn dot(&self, other: Vec3) -> f64 { self.x * other.x + self.y * other.y + self.z * other.z }
+  /// Returns the cross product of `self` and `other`. This is perpendicular to
+  /// both vectors.
+  pub fn cross(&self, other: Vec3) -> Vec3 {
+    Vec3::new(
+      self.y * other.z - self.z * other.y,
+      self.z * other.x - self.x * other.z,
+      self.x * other.y - self.y * other.x,
+    )
+  }
+
   pub fn as_face(&self) -> Face {
     let xa = self.x.abs();
     let ya = self.y.abs();
@@ -130,6 +163,11 @@ impl Div<f64> for Vec3 {
 
   fn div(self, fac: f64) -> Vec3 { Vec3 { x: self.x / fac, y: self.y / fac, z: self.z / fac } }
 }
+impl Neg for Vec3 {
+  type Output = Vec3;
+
+  fn neg(self) -> Vec3 { Vec3 { x: -self.x, y: -self.y, z: -self.z } }
+}
 
 impl Vec3 {
   /// Moves this `vec3` in the given direction, and make sure that it doesn't
@@ -233,3 +271,40 @@ impl AddAssign<Vec3> for FPos {
     self.z += other.z;
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn assert_close(a: Vec3, b: Vec3) {
+    assert!((a - b).len() < 1e-9, "expected {b:?}, got {a:?}");
+  }
+
+  #[test]
+  fn test_products() {
+    let a = Vec3::new(1.0, 2.0, 3.0);
+    let b = Vec3::new(4.0, 5.0, 6.0);
+    assert_eq!(a.dot(b), 32.0);
+    assert_eq!(a.cross(b), Vec3::new(-3.0, 6.0, -3.0));
+    assert_eq!(a.cross(b).dot(a), 0.0);
+    assert_eq!(-a, Vec3::new(-1.0, -2.0, -3.0));
+  }
+
+  #[test]
+  fn test_normalize() {
+    assert_eq!(Vec3::new(0.0, 3.0, 0.0).normalize(), Vec3::new(0.0, 1.0, 0.0));
+    assert_eq!(Vec3::new(0.0, 0.0, 0.0).normalize(), Vec3::new(0.0, 0.0, 0.0));
+    assert!((Vec3::new(3.0, -4.0, 12.0).normalize().len() - 1.0).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_from_yaw_pitch() {
+    assert_close(Vec3::from_yaw_pitch(0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+    assert_close(Vec3::from_yaw_pitch(90.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+    assert_close(Vec3::from_yaw_pitch(180.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+    assert_close(Vec3::from_yaw_pitch(-90.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+    assert_close(Vec3::from_yaw_pitch(0.0, 90.0), Vec3::new(0.0, -1.0, 0.0));
+    assert_close(Vec3::from_yaw_pitch(0.0, -90.0), Vec3::new(0.0, 1.0, 0.0));
+    assert!((Vec3::from_yaw_pitch(33.0, 41.0).len() - 1.0).abs() < 1e-9);
+  }
+}
@@ -0,0 +1,202 @@
+//! A tiny HTTP server, which exports metrics in the Prometheus text format.
+//! This is enabled with the `metrics` section of the config.
+//!
+//! This only ever responds with the metrics page, so any request to the
+//! configured address will get the same response.
+
+use crate::world::WorldManager;
+use std::{
+  fmt::Write as _,
+  fs, io,
+  io::{Read, Write},
+  net::{SocketAddr, TcpListener, TcpStream},
+  sync::Arc,
+  time::Duration,
+};
+
+/// The largest request we will read. Anything past this is ignored.
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+
+pub struct Metrics {
+  addr: SocketAddr,
+  wm:   Arc<WorldManager>,
+}
+
+impl Metrics {
+  /// Creates a new metrics server. This returns `None` if metrics are disabled
+  /// in the config, or if the address is invalid.
+  pub fn new(wm: Arc<WorldManager>) -> Option<Self> {
+    let config = &wm.config().metrics;
+    if !config.enabled {
+      return None;
+    }
+    let addr = match config.addr.parse() {
+      Ok(a) => a,
+      Err(e) => {
+        error!("invalid metrics address: {e}");
+        return None;
+      }
+    };
+
+    Some(Metrics { addr, wm })
+  }
+
+  /// Listens for requests. This blocks forever, so it should be called on
+  /// another thread.
+  pub fn run(&mut self) {
+    let listener = match TcpListener::bind(self.addr) {
+      Ok(l) => l,
+      Err(e) => {
+        error!("couldn't bind to metrics addr {}: {}", self.addr, e);
+        return;
+      }
+    };
+    info!("metrics listening on {}", self.addr);
+
+    for stream in listener.incoming() {
+      let res = match stream {
+        Ok(stream) => self.handle(stream),
+        Err(e) => Err(e),
+      };
+      if let Err(e) = res {
+        warn!("error while serving metrics: {e}");
+      }
+    }
+  }
+
+  fn handle(&self, mut stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    // We don't care what the request is, but we need to read all of it before
+    // responding, or some clients will see the connection reset.
+    let mut req = vec![];
+    let mut buf = [0; 1024];
+    while !req.windows(4).any(|w| w == b"\r\n\r\n") && req.len() < MAX_REQUEST_SIZE {
+      let n = stream.read(&mut buf)?;
+      if n == 0 {
+        break;
+      }
+      req.extend_from_slice(&buf[..n]);
+    }
+    let body = render(&self.wm);
+    write!(
+      stream,
+      "HTTP/1.1 200 OK\r\n\
+       Content-Type: text/plain; version=0.0.4\r\n\
+       Content-Length: {}\r\n\
+       Connection: close\r\n\
+       \r\n\
+       {body}",
+      body.len()
+    )?;
+    stream.flush()
+  }
+}
+
+/// Renders all of the metrics for the given world manager, in the Prometheus
+/// text format.
+pub fn render(wm: &WorldManager) -> String {
+  let mut out = String::new();
+  let worlds = wm.worlds();
+
+  header(&mut out, "bamboo_players", "gauge", "The number of players online.");
+  writeln!(out, "bamboo_players {}", wm.all_players().len()).unwrap();
+
+  header(&mut out, "bamboo_world_players", "gauge", "The number of players in each world.");
+  for w in worlds.iter() {
+    writeln!(out, "bamboo_world_players{{world={}}} {}", label(w.name()), w.players().len())
+      .unwrap();
+  }
+  header(&mut out, "bamboo_world_loaded_chunks", "gauge", "The number of chunks in memory.");
+  for w in worlds.iter() {
+    writeln!(out, "bamboo_world_loaded_chunks{{world={}}} {}", label(w.name()), w.loaded_chunks())
+      .unwrap();
+  }
+  header(&mut out, "bamboo_world_entities", "gauge", "The number of entities, including players.");
+  for w in worlds.iter() {
+    let entities = w.entities().iter_values().len();
+    writeln!(out, "bamboo_world_entities{{world={}}} {entities}", label(w.name())).unwrap();
+  }
+
+  header(&mut out, "bamboo_world_ticks_total", "counter", "The number of ticks run.");
+  for w in worlds.iter() {
+    writeln!(out, "bamboo_world_ticks_total{{world={}}} {}", label(w.name()), w.tick()).unwrap();
+  }
+  header(
+    &mut out,
+    "bamboo_world_tick_seconds_total",
+    "counter",
+    "The time spent running ticks. Divide by the number of ticks for the average tick time.",
+  );
+  for w in worlds.iter() {
+    let time = w.total_tick_time().as_secs_f64();
+    writeln!(out, "bamboo_world_tick_seconds_total{{world={}}} {time}", label(w.name())).unwrap();
+  }
+  header(&mut out, "bamboo_world_tps", "gauge", "The number of ticks run in the last second.");
+  for w in worlds.iter() {
+    writeln!(out, "bamboo_world_tps{{world={}}} {}", label(w.name()), w.tps()).unwrap();
+  }
+  header(&mut out, "bamboo_world_last_tick_seconds", "gauge", "The time the last tick took.");
+  for w in worlds.iter() {
+    let time = w.last_tick_time().as_secs_f64();
+    writeln!(out, "bamboo_world_last_tick_seconds{{world={}}} {time}", label(w.name())).unwrap();
+  }
+
+  if let Some(rss) = resident_memory() {
+    header(&mut out, "bamboo_resident_memory_bytes", "gauge", "The memory used by the server.");
+    writeln!(out, "bamboo_resident_memory_bytes {rss}").unwrap();
+  }
+  out
+}
+
+fn header(out: &mut String, name: &str, ty: &str, help: &str) {
+  writeln!(out, "# HELP {name} {help}").unwrap();
+  writeln!(out, "# TYPE {name} {ty}").unwrap();
+}
+
+/// Quotes and escapes a label value.
+fn label(value: &str) -> String {
+  let mut out = String::with_capacity(value.len() + 2);
+  out.push('"');
+  for c in value.chars() {
+    match c {
+      '\\' => out.push_str("\\\\"),
+      '"' => out.push_str("\\\""),
+      '\n' => out.push_str("\\n"),
+      c => out.push(c),
+    }
+  }
+  out.push('"');
+  out
+}
+
+/// Returns the resident set size of this process, in bytes. This only works on
+/// Linux, and returns `None` everywhere else.
+fn resident_memory() -> Option<u64> {
+  let status = fs::read_to_string("/proc/self/status").ok()?;
+  let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+  // This looks like `VmRSS:    1234 kB`.
+  let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+  Some(kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn render_metrics() {
+    let wm = Arc::new(WorldManager::new(false));
+    wm.add_world_no_tick(wm.new_world());
+    let out = render(&wm);
+    assert!(out.contains("\nbamboo_players 0\n"), "{out}");
+    assert!(out.contains("\nbamboo_world_players{world=\"world\"} 0\n"), "{out}");
+    assert!(out.contains("\nbamboo_world_ticks_total{world=\"world\"} 0\n"), "{out}");
+    assert!(out.contains("# TYPE bamboo_world_tick_seconds_total counter\n"), "{out}");
+  }
+
+  #[test]
+  fn escape_labels() {
+    assert_eq!(label("world"), r#""world""#);
+    assert_eq!(label("a \"b\"\\\n"), r#""a \"b\"\\\n""#);
+  }
+}
@@ -0,0 +1,123 @@
+use bb_common::{math::FPos, net::sb};
+use std::time::Instant;
+
+/// A token bucket, used to limit how many packets a single connection can
+/// send. The bucket holds up to one second of packets, and refills at
+/// `rate` packets per second.
+#[derive(Debug)]
+pub struct RateLimiter {
+  rate:    f64,
+  tokens:  f64,
+  last:    Instant,
+  // The number of packets dropped since the bucket was last full.
+  dropped: u32,
+}
+
+/// The result of [`RateLimiter::check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+  /// The packet should be handled.
+  Allow,
+  /// The client is sending too many packets, and this packet should be
+  /// dropped.
+  Drop,
+  /// The client has sent more than twice the limit, and should be
+  /// disconnected.
+  Disconnect,
+}
+
+impl RateLimiter {
+  /// Creates a rate limiter that allows `rate` packets per second. If `rate` is
+  /// 0, every packet is allowed.
+  pub fn new(rate: u32) -> Self {
+    RateLimiter { rate: rate.into(), tokens: rate.into(), last: Instant::now(), dropped: 0 }
+  }
+
+  /// Checks if another packet is allowed to be received right now.
+  pub fn check(&mut self) -> Limit { self.check_at(Instant::now()) }
+
+  /// Checks if another packet is allowed to be received at the time `now`.
+  pub fn check_at(&mut self, now: Instant) -> Limit {
+    if self.rate == 0.0 {
+      return Limit::Allow;
+    }
+    let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+    self.last = now;
+    self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+    if self.tokens >= self.rate {
+      self.dropped = 0;
+    }
+    if self.tokens >= 1.0 {
+      self.tokens -= 1.0;
+      Limit::Allow
+    } else {
+      self.dropped += 1;
+      if f64::from(self.dropped) > self.rate {
+        Limit::Disconnect
+      } else {
+        Limit::Drop
+      }
+    }
+  }
+}
+
+/// Removes all movement packets that are overridden by a later packet in
+/// `packets`. The player only moves once per tick, so only the latest position
+/// and look matter. A `PlayerPosLook` that is partially overridden will be
+/// replaced with just the part that is still needed.
+///
+/// The positions from any removed packets are returned, in the order they were
+/// received. These are all before the position that was kept, and they need to
+/// be added to the player's path, so that the whole path can still be
+/// validated on the next tick.
+pub fn coalesce_movement(packets: &mut Vec<sb::Packet>) -> Vec<FPos> {
+  let mut seen_pos = false;
+  let mut seen_look = false;
+  let mut seen_ground = false;
+  let mut out = Vec::with_capacity(packets.len());
+  let mut skipped = vec![];
+  for p in packets.drain(..).rev() {
+    let p = match p {
+      sb::Packet::PlayerOnGround { .. } if seen_ground => continue,
+      sb::Packet::PlayerPos { x, y, z, .. } if seen_pos => {
+        skipped.push(FPos::new(x, y, z));
+        continue;
+      }
+      sb::Packet::PlayerLook { .. } if seen_look => continue,
+      sb::Packet::PlayerPosLook { x, y, z, .. } if seen_pos && seen_look => {
+        skipped.push(FPos::new(x, y, z));
+        continue;
+      }
+      sb::Packet::PlayerPosLook { x, y, z, on_ground, .. } if seen_look => {
+        sb::Packet::PlayerPos { x, y, z, on_ground }
+      }
+      sb::Packet::PlayerPosLook { x, y, z, yaw, pitch, on_ground } if seen_pos => {
+        skipped.push(FPos::new(x, y, z));
+        sb::Packet::PlayerLook { yaw, pitch, on_ground }
+      }
+      p => p,
+    };
+    match p {
+      sb::Packet::PlayerOnGround { .. } => seen_ground = true,
+      sb::Packet::PlayerPos { .. } => {
+        seen_pos = true;
+        seen_ground = true;
+      }
+      sb::Packet::PlayerLook { .. } => {
+        seen_look = true;
+        seen_ground = true;
+      }
+      sb::Packet::PlayerPosLook { .. } => {
+        seen_pos = true;
+        seen_look = true;
+        seen_ground = true;
+      }
+      _ => {}
+    }
+    out.push(p);
+  }
+  out.reverse();
+  *packets = out;
+  skipped.reverse();
+  skipped
+}
@@ -1,5 +1,6 @@
 use crate::{player::Player, world::WorldManager};
 use bb_common::{
+  math::FPos,
   net::{cb, sb},
   util::{JoinInfo, ThreadPool},
   version::ProtocolVersion,
@@ -7,7 +8,8 @@ use bb_common::{
 use bb_transfer::{
   InvalidReadError, MessageRead, MessageReader, MessageWrite, MessageWriter, ReadError,
 };
-use crossbeam_channel::{Receiver, Sender, TryRecvError};
+use crossbeam_channel::{Receiver, Sender, TryRecvError, TrySendError};
+use limit::{Limit, RateLimiter};
 use mio::{
   event::Event,
   net::{TcpListener, TcpStream},
@@ -26,6 +28,7 @@ use std::{
   },
 };
 
+mod limit;
 pub mod packet;
 pub(crate) mod serialize;
 
@@ -33,9 +36,21 @@ pub(crate) mod serialize;
 mod tests;
 
 pub struct Connection {
-  stream: TcpStream,
-  ver:    Option<ProtocolVersion>,
-  closed: AtomicBool,
+  stream:      TcpStream,
+  // The address of the proxy on the other end of `stream`.
+  addr:        SocketAddr,
+  // Set once the proxy sends the login packet.
+  ver:         Option<ProtocolVersion>,
+  // The address of the client connected to the proxy. This is sent by the
+  // proxy in the login packet, so it will be `None` until then. It will also be
+  // `None` for older proxies that don't send it.
+  client_addr: Option<SocketAddr>,
+  closed:      AtomicBool,
+  // Limits how many packets the client can send per second.
+  limiter:     RateLimiter,
+  // Positions from movement packets that were removed when coalescing. These
+  // still need to be added to the player's path. See `take_path`.
+  path:        Vec<FPos>,
 
   /// Sending on this will send a packet to the client.
   tx:    Sender<cb::Packet>,
@@ -70,7 +85,13 @@ pub struct NewConn {
 
 impl fmt::Debug for Connection {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    f.debug_struct("Connection").field("closed", &self.closed).finish()
+    f.debug_struct("Connection")
+      .field("addr", &self.addr)
+      .field("ver", &self.ver)
+      .field("client_addr", &self.client_addr)
+      .field("closed", &self.closed)
+      .field("limiter", &self.limiter)
+      .finish()
   }
 }
 
@@ -107,22 +128,59 @@ impl ConnSender {
       self.waker.wake().unwrap();
     }
   }
+  /// Returns `true` once the networking thread has picked up every packet sent
+  /// on this connection, and tried to write it to the proxy. This is used on
+  /// shutdown, so that the last packets aren't lost when the process exits.
+  pub fn is_flushed(&self) -> bool { self.tx.is_empty() }
+  /// Sends all of the given packets to the client. This is the same as calling
+  /// [`send`](Self::send) for each packet, but the networking thread is only
+  /// woken up once, after all the packets have been queued. If the queue fills
+  /// up, the networking thread is woken up early, so that it can drain the
+  /// queue while we wait.
+  pub fn send_all(&self, packets: Vec<cb::Packet>) {
+    if packets.is_empty() {
+      return;
+    }
+    for p in packets {
+      match self.tx.try_send(p) {
+        Ok(()) => {}
+        Err(TrySendError::Full(p)) => {
+          self.wake_network();
+          if self.tx.send(p).is_err() {
+            return;
+          }
+        }
+        Err(TrySendError::Disconnected(_)) => return,
+      }
+    }
+    self.wake_network();
+  }
+  fn wake_network(&self) {
+    self.wake.send(WakeEvent::Clientbound(self.tok)).unwrap();
+    self.waker.wake().unwrap();
+  }
 }
 
 impl Connection {
   pub(crate) fn new(
     stream: TcpStream,
+    addr: SocketAddr,
     wake: Sender<WakeEvent>,
     waker: Arc<Waker>,
     tok: Token,
+    packet_limit: u32,
   ) -> Self {
     // For a 10 chunk render distance, we need to send 441 packets at once. So a
     // limit of 512 means we don't block very much.
     let (tx, rx) = crossbeam_channel::bounded(512);
     Connection {
       stream,
+      addr,
       ver: None,
+      client_addr: None,
       closed: false.into(),
+      limiter: RateLimiter::new(packet_limit),
+      path: vec![],
       tx,
       rx,
       wake,
@@ -162,6 +220,19 @@ impl Connection {
       self.waker.wake().unwrap();
     }
   }
+  /// Sends all of the given packets to the client. See
+  /// [`ConnSender::send_all`].
+  pub fn send_all(&self, packets: Vec<cb::Packet>) { self.sender().send_all(packets) }
+
+  /// Returns the address of the proxy on the other end of this connection.
+  pub fn addr(&self) -> SocketAddr { self.addr }
+  /// Returns the protocol version of the client. This will be `None` until the
+  /// proxy has sent the login packet.
+  pub fn ver(&self) -> Option<ProtocolVersion> { self.ver }
+  /// Returns the address of the client connected to the proxy. This will be
+  /// `None` until the proxy has sent the login packet, or if the proxy didn't
+  /// send an address.
+  pub fn client_addr(&self) -> Option<SocketAddr> { self.client_addr }
 
   /// If this returns Ok(true) or an error, the connection should be closed.
   /// Ok(false) is normal operation. This will never return Err(WouldBlock).
@@ -174,20 +245,51 @@ impl Connection {
     let mut out = vec![];
     loop {
       let n = match self.stream.read(&mut self.garbage) {
-        Ok(0) => return Ok((true, None, out)),
+        Ok(0) => return Ok((true, None, self.limit(out)?)),
         Ok(n) => n,
-        Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok((false, None, out)),
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+          return Ok((false, None, self.limit(out)?))
+        }
         Err(e) => return Err(e),
       };
       self.incoming.extend_from_slice(&self.garbage[..n]);
       let (new_conn, packets) = self.read_incoming()?;
       if new_conn.is_some() {
-        return Ok((false, new_conn, packets));
+        return Ok((false, new_conn, self.limit(packets)?));
       }
       out.extend(packets);
     }
   }
 
+  /// Coalesces movement packets, and then drops any packets that go over the
+  /// rate limit. If the client is sending far too many packets, this returns
+  /// an error, which will disconnect them.
+  fn limit(&mut self, mut packets: Vec<sb::Packet>) -> io::Result<Vec<sb::Packet>> {
+    let skipped = limit::coalesce_movement(&mut packets);
+    self.path.extend(skipped);
+    let mut out = Vec::with_capacity(packets.len());
+    for p in packets {
+      match self.limiter.check() {
+        Limit::Allow => out.push(p),
+        Limit::Drop => {}
+        Limit::Disconnect => {
+          return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("client at {:?} sent too many packets", self.client_addr),
+          ))
+        }
+      }
+    }
+    Ok(out)
+  }
+
+  /// Returns the positions of any movement packets that were removed by the
+  /// last [`read`](Self::read). These must be passed to
+  /// [`Player::add_path`](Player::add_path) before the returned packets are
+  /// handled, so that the anti-cheat still sees every position the client
+  /// moved through.
+  pub fn take_path(&mut self) -> Vec<FPos> { std::mem::take(&mut self.path) }
+
   fn try_send(&mut self) -> io::Result<()> {
     loop {
       match self.rx.try_recv() {
@@ -278,6 +380,14 @@ impl Connection {
                 ));
               }
               self.ver = Some(ProtocolVersion::from(info.ver as i32));
+              self.client_addr = info.addr;
+              debug!(
+                "proxy {} logged in {} from {:?} on {}",
+                self.addr,
+                info.username,
+                info.addr,
+                self.ver.unwrap()
+              );
               // We rely on the caller to set the player using this value.
               return Ok((Some(NewConn { sender: self.sender(), info }), out));
             }
@@ -369,6 +479,7 @@ impl ConnectionManager {
     let mut next_token = 0;
 
     let (tx, rx) = crossbeam_channel::bounded(1024);
+    let packet_limit = self.wm.config().max_packets_per_second;
 
     let write_pool = ThreadPool::auto("network writer", || State {
       wm:    self.wm.clone(),
@@ -393,7 +504,7 @@ impl ConnectionManager {
           LISTEN => loop {
             // Received an event for the TCP server socket, which
             // indicates we can accept an connection.
-            let (mut conn, _addr) = match listen.accept() {
+            let (mut conn, addr) = match listen.accept() {
               Ok(v) => v,
               Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
                 // If we get a `WouldBlock` error we know our
@@ -415,7 +526,14 @@ impl ConnectionManager {
 
             self.connections.write().insert(
               token,
-              ConnPlayer::new(Connection::new(conn, tx.clone(), waker.clone(), token)),
+              ConnPlayer::new(Connection::new(
+                conn,
+                addr,
+                tx.clone(),
+                waker.clone(),
+                token,
+                packet_limit,
+              )),
             );
           },
           WAKE => {
@@ -448,6 +566,21 @@ impl ConnectionManager {
     }
   }
 
+  /// Handles all the packets from a single [`Connection::read`]. `path` should
+  /// come from [`Connection::take_path`], and is added to the player before
+  /// any packets are handled.
+  fn handle_packets(
+    wm: &Arc<WorldManager>,
+    player: &Arc<Player>,
+    path: &[FPos],
+    packets: Vec<sb::Packet>,
+  ) {
+    player.add_path(path);
+    for p in packets {
+      packet::handle(wm, player, p);
+    }
+  }
+
   /// Logs a disconnect, and if the player is present, it removes them.
   fn handle_disconnect(player: &Option<Arc<Player>>) {
     if let Some(p) = player {
@@ -501,12 +634,17 @@ impl ConnectionManager {
         if let Some(player) = rl.get(&token) {
           // Make sure we drop conn! We can get a deadlock if we call `packet::handle`
           // when this is locked.
-          let (disconnect, new_conn, packets) = match player.conn.lock().read() {
-            Ok(v) => v,
-            // Something else went wrong.
-            Err(e) => {
-              Self::handle_error(e, &player.player);
-              return true;
+          let (disconnect, new_conn, packets, path) = {
+            let mut conn = player.conn.lock();
+            match conn.read() {
+              Ok((disconnect, new_conn, packets)) => {
+                (disconnect, new_conn, packets, conn.take_path())
+              }
+              // Something else went wrong.
+              Err(e) => {
+                Self::handle_error(e, &player.player);
+                return true;
+              }
             }
           };
           if disconnect {
@@ -515,12 +653,11 @@ impl ConnectionManager {
           }
           // Don't drop our read lock yet, as we need to use the player we got from it.
           if let Some(player) = &player.player {
-            if packets.is_empty() {
+            let done = packets.is_empty();
+            Self::handle_packets(wm, player, &path, packets);
+            if done {
               break;
             }
-            for p in packets {
-              packet::handle(wm, player, p);
-            }
           } else {
             drop(rl);
             // The player must be created after we drop the `conn.lock()`, so that sending
@@ -534,9 +671,7 @@ impl ConnectionManager {
               }
               let rl = c.read();
               if let Some(player) = rl.get(&token) {
-                for p in packets {
-                  packet::handle(wm, player.player.as_ref().unwrap(), p);
-                }
+                Self::handle_packets(wm, player.player.as_ref().unwrap(), &path, packets);
               }
             }
           }
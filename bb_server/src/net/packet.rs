@@ -9,7 +9,7 @@ use bb_common::{
   math::FPos,
   net::{cb, sb},
   util::{
-    chat::{Chat, Color, HoverEvent},
+    chat::{Chat, Color},
     GameMode,
   },
 };
@@ -64,16 +64,14 @@ pub(crate) fn handle(wm: &Arc<WorldManager>, mut player: &Arc<Player>, p: sb::Pa
         {
           return;
         }
-        let mut msg = Chat::empty();
-        msg.add("<");
-        msg.add(player.username()).color(Color::BrightGreen).on_hover(HoverEvent::ShowText(
-          format!("wow it is almost like {} sent this message", player.username()),
-        ));
-        msg.add("> ");
-        msg.add(text);
-        wm.broadcast(msg);
+        player.world().broadcast(chat_message(player, text));
       }
     }
+    // Spectators can't break blocks. A modified client could still send digs, so
+    // the block is synced in case the client broke it on its side.
+    sb::Packet::BlockDig { pos, .. } if player.game_mode() == GameMode::Spectator => {
+      player.sync_block_at(pos);
+    }
     sb::Packet::BlockDig { pos, status, face } => {
       // If the world is locked then we need to sync this block.
       if player.world().is_locked() {
@@ -86,12 +84,13 @@ pub(crate) fn handle(wm: &Arc<WorldManager>, mut player: &Arc<Player>, p: sb::Pa
             sb::DigStatus::Finish => player.finish_digging(pos),
           },
           GameMode::Creative => {
-            if let Ok(looking_at) = player.world().get_block(pos) {
+            let world = player.world();
+            if let Ok(looking_at) = world.get_block(pos) {
               let click = BlockClick {
                 player,
                 face,
                 dir: player.look_as_vec(),
-                block: Block::new(player.world(), pos, looking_at.ty()),
+                block: Block::new(&world, pos, looking_at.ty()),
                 cursor: FPos::new(0.0, 0.0, 0.0),
               };
               let inv = player.lock_inventory();
@@ -115,22 +114,27 @@ pub(crate) fn handle(wm: &Arc<WorldManager>, mut player: &Arc<Player>, p: sb::Pa
               player.sync_block_at(pos);
             }
           }
-          // TODO: Not sure if the sync is needed, but it won't hurt much.
-          GameMode::Adventure => {
+          // TODO: Not sure if the sync is needed, but it won't hurt much. Spectators are
+          // handled above.
+          GameMode::Adventure | GameMode::Spectator => {
             player.sync_block_at(pos);
           }
-          // We will just ignore block digs from spectators, as they won't show any updates client
-          // side.
-          GameMode::Spectator => {}
         }
       }
     }
     sb::Packet::CreativeInventoryUpdate { slot, item } => {
+      // The client has already updated this slot, so we don't need to send it back.
       if slot >= 0 {
-        player.lock_inventory().set(slot.into(), item.into());
+        player.lock_inventory().set_no_sync(slot.into(), item.into());
       }
     }
     sb::Packet::ClickWindow { wid, mut slot, mode } => {
+      // The client might send a click for a window that we have already replaced,
+      // so we just resync their inventory in that case.
+      if wid != 0 && wid != u8::MAX && player.lock_inventory().window_id() != Some(wid) {
+        player.lock_inventory().sync_all();
+        return;
+      }
       if wid == u8::MAX {
         slot = i16::from(player.lock_inventory().selected_index()) + 36;
       }
@@ -148,6 +152,13 @@ pub(crate) fn handle(wm: &Arc<WorldManager>, mut player: &Arc<Player>, p: sb::Pa
     sb::Packet::ChangeHeldItem { slot } => {
       player.lock_inventory().set_selected(slot);
     }
+    // Spectators can't use items, place blocks, or attack.
+    sb::Packet::UseItem { .. } | sb::Packet::UseEntity { .. }
+      if player.game_mode() == GameMode::Spectator => {}
+    sb::Packet::BlockPlace { pos, face, .. } if player.game_mode() == GameMode::Spectator => {
+      player.sync_block_at(pos);
+      player.sync_block_at(pos + face);
+    }
     sb::Packet::UseItem { hand } => {
       wm.events().interact(
         player,
@@ -165,13 +176,14 @@ pub(crate) fn handle(wm: &Arc<WorldManager>, mut player: &Arc<Player>, p: sb::Pa
       };
       */
 
-      match player.world().get_block(pos) {
+      let world = player.world();
+      match world.get_block(pos) {
         Ok(looking_at) => {
           let click = BlockClick {
             player,
             face,
             dir: player.look_as_vec(),
-            block: Block::new(player.world(), pos, looking_at.ty()),
+            block: Block::new(&world, pos, looking_at.ty()),
             cursor,
           };
           if !player.is_crouching() {
@@ -248,15 +260,48 @@ pub(crate) fn handle(wm: &Arc<WorldManager>, mut player: &Arc<Player>, p: sb::Pa
       if let Some(ent) = player.world().entities().get(eid) {
         match action {
           sb::UseEntityAction::Attack => player.attack(ent),
-          _ => warn!("todo: action {action:?}"),
+          sb::UseEntityAction::Interact(_) => ent.interact(player),
+          // Clients send an `Interact` along with every `InteractAt`, so we only handle
+          // one of them.
+          sb::UseEntityAction::InteractAt(..) => {}
         }
       }
     }
-    sb::Packet::WindowClose { wid: _ } => player.lock_inventory().close_window(),
+    sb::Packet::NameItem { name } => {
+      wm.events().player_event(event::NameItem { player: player.clone(), name });
+    }
+    sb::Packet::PluginMessage { channel, data } => {
+      player.world().handle_plugin_message(player, &channel, &data);
+    }
+    sb::Packet::ResourcePackStatus { status } => player.handle_resource_pack_status(status),
+    sb::Packet::ClientStatus { status } => match status {
+      // Ignore respawns from players that are still alive.
+      sb::ClientStatus::Respawn if !player.alive() => player.respawn(),
+      sb::ClientStatus::Respawn => {}
+      sb::ClientStatus::RequestStats => {}
+    },
+    sb::Packet::WindowClose { wid } => {
+      let mut inv = player.lock_inventory();
+      // Ignore closes for windows that have already been replaced.
+      if inv.window_id() == Some(wid) && inv.close_window() {
+        drop(inv);
+        wm.events().player_event(event::WindowClose { player: player.clone() });
+      }
+    }
     _ => warn!("unknown packet: {:?}", p),
   }
 }
 
+/// Formats a chat message sent by `player`, in the form `<name> text`.
+pub(crate) fn chat_message(player: &Player, text: String) -> Chat {
+  let mut msg = Chat::empty();
+  msg.add("<");
+  msg.add(player.username()).color(Color::BrightGreen);
+  msg.add("> ");
+  msg.add(text);
+  msg
+}
+
 #[allow(unused)]
 fn use_item(player: &Arc<Player>, _hand: i32) {
   // TODO: Offhand
@@ -1,17 +1,38 @@
-use super::WakeEvent;
+use super::{
+  limit,
+  limit::{Limit, RateLimiter},
+  Connection, ConnectionManager, WakeEvent,
+};
 use crate::{
+  block::Kind,
+  command::{CommandSender, ErrorFormat},
+  entity,
+  item::{self, Stack},
+  math::Vec3,
   net::{packet, ConnSender},
   player::Player,
   world::WorldManager,
 };
 use bb_common::{
-  math::FPos,
+  math::{FPos, Pos},
+  metadata::Metadata,
   net::{cb, sb},
-  util::{JoinInfo, JoinMode, UUID},
+  util::{
+    chat::{Chat, Color},
+    Effect, Face, GameMode, Hand, JoinInfo, JoinMode, UUID,
+  },
   version::ProtocolVersion,
 };
+use bb_transfer::{MessageWrite, MessageWriter};
 use crossbeam_channel::Receiver;
-use std::sync::Arc;
+use parking_lot::Mutex;
+use std::{
+  io::Write,
+  net::{TcpListener, TcpStream},
+  sync::Arc,
+  thread,
+  time::{Duration, Instant},
+};
 
 pub struct TestHandler {
   rx:      Receiver<cb::Packet>,
@@ -41,11 +62,25 @@ impl TestHandler {
       username: "macmv".into(),
       uuid:     UUID::from_u128(0),
       ver:      ProtocolVersion::V1_8.id(),
+      addr:     None,
     };
     let player = wm.new_player(sender, info);
     TestHandler { rx, wake_rx, wm, player }
   }
   pub fn handle(&self, p: sb::Packet) { packet::handle(&self.wm, &self.player, p); }
+  /// Runs the given command as this player, with op level 4. The command
+  /// should not start with a `/`.
+  pub fn run_op_command(&self, text: &str) {
+    struct OpSender<'a>(&'a Arc<Player>);
+    impl CommandSender for OpSender<'_> {
+      fn block_pos(&self) -> Option<Pos> { Some(self.0.pos().block()) }
+      fn op_level(&self) -> u8 { 4 }
+      fn as_player(&self) -> Option<&Arc<Player>> { Some(self.0) }
+      fn send_message(&mut self, msg: Chat) { self.0.send_message(msg) }
+      fn error_format(&self) -> ErrorFormat { ErrorFormat::Minecraft }
+    }
+    self.player.world().commands().execute(&self.wm, &mut OpSender(&self.player), text);
+  }
   pub fn player(&self) -> &Arc<Player> { &self.player }
   pub fn clear(&self) {
     while let Ok(_) = self.rx.try_recv() {}
@@ -59,8 +94,6 @@ impl TestHandler {
       panic!("got packets, but expected none");
     }
   }
-  // This is a useful but unused function.
-  #[allow(unused)]
   pub fn assert_sent(&self, expected_packets: &[cb::Packet]) {
     let mut actual_packets = vec![];
     while let Ok(p) = self.rx.try_recv() {
@@ -116,3 +149,593 @@ fn test_move_packets() {
     handler.assert_empty();
   }
 }
+
+/// Encodes a single length-prefixed message, like the proxy sends.
+fn frame(f: impl FnOnce(&mut MessageWriter<&mut Vec<u8>>)) -> Vec<u8> {
+  let mut data = vec![];
+  f(&mut MessageWriter::new(&mut data));
+  let mut out = vec![];
+  MessageWriter::new(&mut out).write_u32(data.len() as u32).unwrap();
+  out.extend(data);
+  out
+}
+
+#[test]
+fn test_move_path() {
+  let handler = TestHandler::new();
+  let pos = handler.player().pos();
+
+  // Movement packets are coalesced when they are read, so we need to send these
+  // through a real connection.
+  let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+  let mut proxy = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+  let (stream, addr) = listener.accept().unwrap();
+  stream.set_nonblocking(true).unwrap();
+  let poll = mio::Poll::new().unwrap();
+  let waker = Arc::new(mio::Waker::new(poll.registry(), mio::Token(0xfffffffe)).unwrap());
+  let (wake, _wake_rx) = crossbeam_channel::unbounded();
+  let mut conn =
+    Connection::new(mio::net::TcpStream::from_std(stream), addr, wake, waker, mio::Token(0), 0);
+  let login = frame(|m| {
+    m.write(&JoinInfo {
+      mode:     JoinMode::New,
+      username: "macmv".into(),
+      uuid:     UUID::from_u128(0),
+      ver:      ProtocolVersion::V1_8.id(),
+      addr:     None,
+    })
+    .unwrap();
+  });
+  proxy.write_all(&login).unwrap();
+  for _ in 0..100 {
+    if conn.read().unwrap().1.is_some() {
+      break;
+    }
+    thread::sleep(Duration::from_millis(10));
+  }
+  assert!(conn.ver().is_some());
+
+  // The endpoint is close to where the player started, but the path there is too
+  // long, so this move should be rejected. Only the last position survives
+  // coalescing, so the first one must still end up in the player's path. These
+  // are sent in one write, so that they are read (and coalesced) together.
+  let mut moves = vec![];
+  for dy in [8.0, 0.5] {
+    moves.extend(frame(|m| {
+      sb::Packet::PlayerPos {
+        x:         pos.x(),
+        y:         pos.y() + dy,
+        z:         pos.z(),
+        on_ground: true,
+      }
+      .write(m)
+      .unwrap();
+    }));
+  }
+  proxy.write_all(&moves).unwrap();
+  let mut packets = vec![];
+  for _ in 0..100 {
+    let (_, _, p) = conn.read().unwrap();
+    packets.extend(p);
+    if !packets.is_empty() {
+      break;
+    }
+    thread::sleep(Duration::from_millis(10));
+  }
+  assert_eq!(packets.len(), 1);
+  ConnectionManager::handle_packets(&handler.wm, handler.player(), &conn.take_path(), packets);
+  handler.player().tick();
+  assert_eq!(handler.player().lock_pos().curr, pos);
+  handler.assert_sent(&[cb::packet::SetPosLook {
+    pos,
+    yaw: 0.0,
+    pitch: 0.0,
+    flags: 0,
+    teleport_id: 0,
+    should_dismount: false,
+  }
+  .into()]);
+
+  // The path was cleared, so a normal move on the next tick is fine.
+  handler.handle(sb::Packet::PlayerPos {
+    x:         pos.x(),
+    y:         pos.y() + 0.5,
+    z:         pos.z(),
+    on_ground: true,
+  });
+  handler.player().tick();
+  assert_eq!(handler.player().lock_pos().curr, pos + FPos::new(0.0, 0.5, 0.0));
+}
+
+#[test]
+fn test_chat() {
+  let handler = TestHandler::new();
+  handler.handle(sb::Packet::Chat { msg: "hello".into() });
+  handler.assert_sent(&[cb::packet::ChatMessage {
+    msg: packet::chat_message(handler.player(), "hello".into()),
+    ty:  0,
+  }
+  .into()]);
+
+  // Unknown commands should send an error back, and not broadcast anything.
+  handler.handle(sb::Packet::Chat { msg: "/not_a_command".into() });
+  let mut err = Chat::empty();
+  err.add("");
+  err.add("Unknown command: ").color(Color::Red);
+  err.add("not_a_command");
+  handler.assert_sent(&[cb::packet::ChatMessage { msg: err, ty: 0 }.into()]);
+}
+
+#[test]
+fn test_send_all() {
+  let handler = TestHandler::new();
+  let packets: Vec<cb::Packet> = vec![
+    cb::packet::KeepAlive { id: 1 }.into(),
+    cb::packet::KeepAlive { id: 2 }.into(),
+    cb::packet::KeepAlive { id: 3 }.into(),
+  ];
+  handler.player().send_all(packets.clone());
+  // Only one wake event is sent for the whole batch.
+  assert_eq!(handler.wake_rx.len(), 1);
+  handler.assert_sent(&packets);
+
+  handler.player().send_all(vec![]);
+  assert!(handler.wake_rx.is_empty());
+  handler.assert_empty();
+}
+
+#[test]
+fn test_world_command() {
+  let handler = TestHandler::new();
+  let other = handler.wm.add_world_no_tick(handler.wm.new_world());
+  other.init();
+  assert!(!Arc::ptr_eq(&handler.player().world(), &other));
+  handler.run_op_command("world 1");
+  assert!(Arc::ptr_eq(&handler.player().world(), &other));
+  handler.clear();
+
+  // Invalid worlds don't move the player.
+  handler.run_op_command("world 5");
+  assert!(Arc::ptr_eq(&handler.player().world(), &other));
+  handler.assert_sent(&[cb::packet::ChatMessage {
+    msg: Chat::new("cannot move to world 5"),
+    ty:  0,
+  }
+  .into()]);
+}
+
+#[test]
+fn test_send_all_full_queue() {
+  let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+  let _proxy = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+  let (stream, addr) = listener.accept().unwrap();
+  stream.set_nonblocking(true).unwrap();
+  let poll = mio::Poll::new().unwrap();
+  let waker = Arc::new(mio::Waker::new(poll.registry(), mio::Token(0xfffffffe)).unwrap());
+  let (wake, wake_rx) = crossbeam_channel::unbounded();
+  let conn =
+    Connection::new(mio::net::TcpStream::from_std(stream), addr, wake, waker, mio::Token(0), 0);
+  // This acts like the networking thread, which only drains the queue after
+  // being woken up.
+  let rx = conn.rx.clone();
+  let net = thread::spawn(move || {
+    let mut received = 0;
+    while wake_rx.recv_timeout(Duration::from_secs(5)).is_ok() {
+      while rx.try_recv().is_ok() {
+        received += 1;
+      }
+      if received == 1000 {
+        break;
+      }
+    }
+    received
+  });
+  // This is larger than the 512 packet queue, so the sender must wake up the
+  // networking thread before the batch is done.
+  conn.sender().send_all((0..1000).map(|id| cb::packet::KeepAlive { id }.into()).collect());
+  assert_eq!(net.join().unwrap(), 1000);
+}
+
+#[test]
+fn test_scoreboard_objectives() {
+  let handler = TestHandler::new();
+  let player = handler.player();
+  handler.clear();
+
+  // This name is used by the sidebar lines, so it can't be taken.
+  assert!(!player.lock_scoreboard().create_objective("scoreboard", Chat::new("Lines")));
+  handler.assert_empty();
+
+  assert!(player.lock_scoreboard().create_objective("kills", Chat::new("Kills")));
+  handler.assert_sent(&[cb::packet::ScoreboardObjective {
+    objective: "kills".into(),
+    mode:      cb::ObjectiveAction::Create {
+      value: Chat::new("Kills"),
+      ty:    cb::ObjectiveType::Integer,
+    },
+  }
+  .into()]);
+
+  player.lock_scoreboard().set_score("kills", "macmv", 3);
+  assert_eq!(player.lock_scoreboard().score("kills", "macmv"), Some(3));
+  handler.assert_sent(&[cb::packet::ScoreboardUpdate {
+    username:  "macmv".into(),
+    objective: "kills".into(),
+    action:    cb::ScoreboardAction::Create(3),
+  }
+  .into()]);
+
+  // Setting the same score again doesn't send anything.
+  player.lock_scoreboard().set_score("kills", "macmv", 3);
+  handler.assert_empty();
+  // Scores can't be set on the reserved objective either.
+  player.lock_scoreboard().set_score("scoreboard", "macmv", 3);
+  handler.assert_empty();
+}
+
+#[test]
+fn test_player_fire() {
+  let handler = TestHandler::new();
+  let player = handler.player();
+  player.set_game_mode(GameMode::Survival);
+  handler.clear();
+  let status = |status| {
+    let mut meta = Metadata::new();
+    meta.set_byte(0, status);
+    // Players think they are EID 1, so that is what they get sent.
+    cb::packet::EntityMetadata { eid: 1, ty: entity::Type::Player.id(), meta }.into()
+  };
+
+  player.set_on_fire(40);
+  assert!(player.is_on_fire());
+  handler.assert_sent(&[status(0x01)]);
+  // Setting the fire again doesn't change the flag, so nothing is sent.
+  player.set_on_fire(40);
+  handler.assert_empty();
+
+  for _ in 0..20 {
+    player.tick();
+  }
+  assert!(player.is_on_fire());
+  assert!(player.health() < 20.0);
+
+  for _ in 0..20 {
+    player.tick();
+  }
+  assert!(!player.is_on_fire());
+  assert!(handler.rx.try_iter().any(|p| p == status(0x00)));
+}
+
+#[test]
+fn test_friendly_fire() {
+  let handler = TestHandler::new();
+  let poll = mio::Poll::new().unwrap();
+  let (_rx, _wake_rx, sender) = ConnSender::mock(&poll);
+  let other = handler.wm.new_player(
+    sender,
+    JoinInfo {
+      mode:     JoinMode::New,
+      username: "other".into(),
+      uuid:     UUID::from_u128(1),
+      ver:      ProtocolVersion::V1_8.id(),
+      addr:     None,
+    },
+  );
+  other.set_game_mode(GameMode::Survival);
+  let team = handler.wm.create_team("red".into()).unwrap();
+  team.lock().add(handler.player());
+  team.lock().add(&other);
+  let attack = || {
+    handler.handle(sb::Packet::UseEntity {
+      eid:      other.eid(),
+      action:   sb::UseEntityAction::Attack,
+      sneaking: None,
+    })
+  };
+
+  team.lock().set_friendly_fire(false);
+  attack();
+  assert_eq!(other.health(), 20.0);
+
+  team.lock().set_friendly_fire(true);
+  attack();
+  assert!(other.health() < 20.0);
+}
+
+#[test]
+fn test_raycast() {
+  let handler = TestHandler::new();
+  let world = handler.player().world();
+  world.set_kind(Pos::new(3, 200, 0), Kind::Stone).unwrap();
+  let origin = FPos::new(0.5, 200.5, 0.5);
+  let dir = Vec3::new(1.0, 0.0, 0.0);
+  assert_eq!(world.raycast_block(origin, dir, 10.0).map(|(pos, _)| pos), Some(Pos::new(3, 200, 0)));
+  assert_eq!(world.raycast_block(origin, dir, 1.0), None);
+
+  // These would never stop stepping through the air.
+  let up = Vec3::new(0.0, 1.0, 0.0);
+  assert_eq!(world.raycast_block(origin, up, f64::INFINITY), None);
+  assert_eq!(world.raycast_block(origin, up, f64::NAN), None);
+  assert_eq!(world.raycast_block(origin, Vec3::new(f64::NAN, 0.0, 0.0), 10.0), None);
+  assert_eq!(world.raycast_block(FPos::new(f64::NAN, 200.0, 0.0), up, 10.0), None);
+}
+
+#[test]
+fn test_entity_damage() {
+  let handler = TestHandler::new();
+  let world = handler.player().world();
+  let eid = world.summon(entity::Type::Zombie, FPos::new(0.0, 100.0, 0.0));
+  let ent = || world.entities().get_ent(eid).unwrap().clone();
+  let vel = ent().vel();
+
+  let knockback = Vec3::new(0.4, 0.4, 0.0);
+  assert!(world.entities().get(eid).unwrap().damage(5.0, true, knockback));
+  assert_eq!(ent().health(), 15.0);
+  assert_eq!(ent().vel(), vel + knockback);
+  assert!(!world.entities().get(eid).unwrap().should_despawn());
+
+  world.entities().get(eid).unwrap().damage(15.0, true, Vec3::new(0.0, 0.0, 0.0));
+  assert!(world.entities().get(eid).unwrap().should_despawn());
+}
+
+#[test]
+fn test_entity_fire() {
+  let handler = TestHandler::new();
+  let world = handler.player().world();
+  let eid = world.summon(entity::Type::Zombie, FPos::new(0.0, 100.0, 0.0));
+  let ent = world.entities().get_ent(eid).unwrap().clone();
+  // Invisible, which must be kept when the fire flag changes.
+  ent.metadata().set_byte(0, 0x20);
+
+  ent.set_on_fire(20);
+  assert!(ent.is_on_fire());
+  assert_eq!(ent.metadata().get_byte(0), 0x21);
+
+  ent.set_on_fire(0);
+  assert!(!ent.is_on_fire());
+  assert_eq!(ent.metadata().get_byte(0), 0x20);
+}
+
+#[test]
+fn test_plugin_channels() {
+  let handler = TestHandler::new();
+  let plugin_message = |channel: &str, data: &[u8]| sb::Packet::PluginMessage {
+    channel: channel.into(),
+    data:    data.into(),
+  };
+
+  // Handlers can register other channels while they are running.
+  let received = Arc::new(Mutex::new(vec![]));
+  let r = received.clone();
+  handler.player().world().register_channel("test:first", move |player, _| {
+    let r = r.clone();
+    player.world().register_channel("test:second", move |_, data| r.lock().extend_from_slice(data));
+  });
+  handler.handle(plugin_message("test:second", &[1]));
+  handler.handle(plugin_message("test:first", &[]));
+  handler.handle(plugin_message("test:second", &[2, 3]));
+  assert_eq!(*received.lock(), vec![2, 3]);
+
+  handler.handle(plugin_message("minecraft:register", b"test:a\0test:b"));
+  assert!(handler.player().listens_on("test:a"));
+  assert!(handler.player().listens_on("test:b"));
+  handler.handle(plugin_message("minecraft:unregister", b"test:a"));
+  assert!(!handler.player().listens_on("test:a"));
+  assert!(handler.player().listens_on("test:b"));
+}
+
+#[test]
+fn test_coalesce_movement() {
+  let pos = |x| sb::Packet::PlayerPos { x, y: 0.0, z: 0.0, on_ground: true };
+  let look = |yaw| sb::Packet::PlayerLook { yaw, pitch: 0.0, on_ground: true };
+  let mut packets = vec![
+    pos(1.0),
+    sb::Packet::ChangeHeldItem { slot: 1 },
+    pos(2.0),
+    sb::Packet::PlayerPosLook {
+      x:         3.0,
+      y:         0.0,
+      z:         0.0,
+      yaw:       3.0,
+      pitch:     0.0,
+      on_ground: true,
+    },
+    pos(4.0),
+  ];
+  let skipped = limit::coalesce_movement(&mut packets);
+  // Serverbound packets don't implement `PartialEq`, so we compare them using
+  // their debug output.
+  assert_eq!(
+    format!("{packets:?}"),
+    format!("{:?}", vec![sb::Packet::ChangeHeldItem { slot: 1 }, look(3.0), pos(4.0)])
+  );
+  // The removed positions are kept, so that the path can still be validated.
+  assert_eq!(
+    skipped,
+    [FPos::new(1.0, 0.0, 0.0), FPos::new(2.0, 0.0, 0.0), FPos::new(3.0, 0.0, 0.0)]
+  );
+
+  let mut packets = vec![look(1.0), pos(1.0), look(2.0)];
+  assert!(limit::coalesce_movement(&mut packets).is_empty());
+  assert_eq!(format!("{packets:?}"), format!("{:?}", vec![pos(1.0), look(2.0)]));
+}
+
+#[test]
+fn test_rate_limit() {
+  let mut limiter = RateLimiter::new(10);
+  let now = Instant::now();
+  for _ in 0..10 {
+    assert_eq!(limiter.check_at(now), Limit::Allow);
+  }
+  for _ in 0..10 {
+    assert_eq!(limiter.check_at(now), Limit::Drop);
+  }
+  assert_eq!(limiter.check_at(now), Limit::Disconnect);
+
+  // After a second, the bucket is full again.
+  let mut limiter = RateLimiter::new(10);
+  for _ in 0..15 {
+    limiter.check_at(now);
+  }
+  assert_eq!(limiter.check_at(now + Duration::from_secs(1)), Limit::Allow);
+
+  // A limit of 0 allows everything.
+  let mut limiter = RateLimiter::new(0);
+  for _ in 0..1000 {
+    assert_eq!(limiter.check_at(now), Limit::Allow);
+  }
+}
+
+#[test]
+fn test_respawn() {
+  let handler = TestHandler::new();
+  let player = handler.player();
+  let spawn = FPos::new(5.0, 100.0, 5.0);
+  player.set_spawn_point(spawn);
+
+  // Players that are still alive cannot respawn.
+  handler.handle(sb::Packet::ClientStatus { status: sb::ClientStatus::Respawn });
+  handler.assert_empty();
+
+  player.set_health(0.0);
+  handler.handle(sb::Packet::ClientStatus { status: sb::ClientStatus::Respawn });
+  assert_eq!(player.health(), 20.0);
+  assert_eq!(player.pos(), player.world().safe_spawn(spawn));
+}
+
+#[test]
+fn test_effects() {
+  let handler = TestHandler::new();
+  let player = handler.player();
+  // Players think they are EID 1, so that is what they get sent.
+  let eid = 1;
+
+  player.add_effect(Effect::Speed, 1, 2, true);
+  handler.assert_sent(&[cb::packet::EntityEffect {
+    eid,
+    effect: Effect::Speed,
+    amplifier: 1,
+    duration: 2,
+    particles: true,
+  }
+  .into()]);
+  assert!((player.speed_multiplier() - 1.4).abs() < 1e-9);
+
+  player.tick();
+  assert_eq!(player.effect(Effect::Speed).map(|e| e.duration), Some(1));
+  player.tick();
+  assert_eq!(player.effect(Effect::Speed), None);
+  assert!(handler
+    .rx
+    .try_iter()
+    .any(|p| p == cb::packet::RemoveEntityEffect { eid, effect: Effect::Speed }.into()));
+  assert!(!player.remove_effect(Effect::Speed));
+
+  // Poison should never kill the player.
+  player.set_health(1.0);
+  player.add_effect(Effect::Poison, 5, 100, false);
+  for _ in 0..100 {
+    player.tick();
+  }
+  assert_eq!(player.health(), 1.0);
+}
+
+#[test]
+fn test_inventory_set() {
+  let handler = TestHandler::new();
+  let player = handler.player();
+  let poll = mio::Poll::new().unwrap();
+  let (other_rx, _wake_rx, sender) = ConnSender::mock(&poll);
+  let _other = handler.wm.new_player(
+    sender,
+    JoinInfo {
+      mode:     JoinMode::New,
+      username: "other".into(),
+      uuid:     UUID::from_u128(1),
+      ver:      ProtocolVersion::V1_8.id(),
+      addr:     None,
+    },
+  );
+  player.set_game_mode(GameMode::Creative);
+  handler.clear();
+  while other_rx.try_recv().is_ok() {}
+  let stack = Stack::new(item::Type::Stone);
+
+  // Invalid slots are ignored.
+  player.lock_inventory().set(1000, stack.clone());
+  player.lock_inventory().set_no_sync(-5, stack.clone());
+  handler.assert_empty();
+  assert!(other_rx.is_empty());
+
+  // The client already knows about creative updates, so they aren't echoed back.
+  // Slot 36 is the main hand, so other players still see the new item.
+  handler.handle(sb::Packet::CreativeInventoryUpdate { slot: 36, item: stack.to_item() });
+  handler.assert_empty();
+  assert!(other_rx.try_iter().any(|p| p
+    == cb::packet::EntityEquipment {
+      eid:  player.eid(),
+      slot: cb::EquipmentSlot::Hand(Hand::Main),
+      item: stack.to_item(),
+    }
+    .into()));
+  assert_eq!(player.lock_inventory().main_hand(), &stack);
+
+  // `set` sends the slot back to the client.
+  player.lock_inventory().set(37, stack.clone());
+  assert!(handler
+    .rx
+    .try_iter()
+    .any(|p| matches!(p, cb::Packet::WindowItem(w) if w.slot == 37 && w.item == stack.to_item())));
+}
+
+#[test]
+fn test_effects_on_spawn() {
+  let handler = TestHandler::new();
+  let poll = mio::Poll::new().unwrap();
+  let (_rx, _wake_rx, sender) = ConnSender::mock(&poll);
+  let other = handler.wm.new_player(
+    sender,
+    JoinInfo {
+      mode:     JoinMode::New,
+      username: "other".into(),
+      uuid:     UUID::from_u128(1),
+      ver:      ProtocolVersion::V1_8.id(),
+      addr:     None,
+    },
+  );
+  other.add_effect(Effect::Speed, 0, 100, true);
+  handler.clear();
+
+  // Viewers that spawn `other` later still need to see its effects.
+  other.spawn_for(handler.player());
+  let packets: Vec<_> = handler.rx.try_iter().collect();
+  assert!(packets.contains(&other.spawn_packet().into()));
+  assert!(packets.contains(
+    &cb::packet::EntityEffect {
+      eid:       other.eid(),
+      effect:    Effect::Speed,
+      amplifier: 0,
+      duration:  100,
+      particles: true,
+    }
+    .into()
+  ));
+}
+
+#[test]
+fn test_spectator_dig() {
+  let handler = TestHandler::new();
+  let player = handler.player();
+  let pos = Pos::new(0, 100, 0);
+  player.world().set_kind(pos, Kind::Stone).unwrap();
+  player.set_game_mode(GameMode::Spectator);
+  handler.clear();
+
+  handler.handle(sb::Packet::BlockDig { pos, status: sb::DigStatus::Start, face: Face::Top });
+  assert_eq!(player.world().get_kind(pos), Ok(Kind::Stone));
+  // The block is sent back, in case the client broke it on its side.
+  assert!(handler
+    .rx
+    .try_iter()
+    .any(|packet| matches!(packet, cb::Packet::BlockUpdate(p) if p.pos == pos)));
+}
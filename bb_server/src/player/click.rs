@@ -88,8 +88,16 @@ impl Player {
     inv: &mut PlayerInventory,
     clicked_pos: Pos,
     placed_pos: Pos,
-    ty: block::Type,
+    mut ty: block::Type,
   ) {
+    // Placing a waterloggable block into a water source fills it with water.
+    if ty.can_waterlog() {
+      if let Ok(old) = self.world().get_block(placed_pos) {
+        if old.kind() == block::Kind::Water && old.prop("level") == 0 {
+          ty.set_prop("waterlogged", true);
+        }
+      }
+    }
     if self
       .world()
       .events()
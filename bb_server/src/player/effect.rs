@@ -0,0 +1,145 @@
+use super::Player;
+use crate::math::Vec3;
+use bb_common::{net::cb, util::Effect};
+use std::sync::Arc;
+
+/// A status effect that is currently applied to a player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActiveEffect {
+  /// The kind of effect.
+  pub effect:    Effect,
+  /// The level of this effect, minus 1. So an amplifier of 0 is speed I, and
+  /// an amplifier of 1 is speed II.
+  pub amplifier: u8,
+  /// The number of ticks until this effect expires.
+  pub duration:  u32,
+  /// If false, this effect won't show any particles.
+  pub particles: bool,
+}
+
+impl ActiveEffect {
+  /// Returns true if this effect should apply its periodic effect this tick,
+  /// given how often it applies at amplifier 0. This matches vanilla, where
+  /// higher levels apply twice as often for each level.
+  fn should_apply(&self, interval: u32) -> bool {
+    let interval = interval.checked_shr(self.amplifier.into()).unwrap_or(0);
+    interval == 0 || self.duration % interval == 0
+  }
+}
+
+impl Player {
+  /// Sends an effect packet about this player to everyone in view, including
+  /// this player.
+  fn send_effect_packet<P: Into<cb::Packet>>(&self, packet: impl Fn(i32) -> P) {
+    // Players think they are EID 1.
+    self.send(packet(1));
+    self.send_to_in_view(packet(self.eid));
+  }
+
+  /// Spawns this player for `viewer`, and sends all of this player's active
+  /// effects with it. This should be used whenever this player comes into
+  /// view of another player.
+  pub(crate) fn spawn_for(&self, viewer: &Player) {
+    viewer.send(self.spawn_packet());
+    self.send_effects_to(viewer);
+  }
+
+  /// Sends all of this player's active effects to `viewer`.
+  pub(crate) fn send_effects_to(&self, viewer: &Player) {
+    for e in self.effects.lock().iter() {
+      viewer.send(cb::packet::EntityEffect {
+        eid:       self.eid,
+        effect:    e.effect,
+        amplifier: e.amplifier,
+        duration:  e.duration.try_into().unwrap_or(i32::MAX),
+        particles: e.particles,
+      });
+    }
+  }
+
+  /// Adds the given effect to this player. If the player already has this
+  /// effect, it will be replaced. `duration` is in ticks.
+  pub fn add_effect(&self, effect: Effect, amplifier: u8, duration: u32, particles: bool) {
+    {
+      let mut effects = self.effects.lock();
+      effects.retain(|e| e.effect != effect);
+      effects.push(ActiveEffect { effect, amplifier, duration, particles });
+    }
+    self.send_effect_packet(|eid| cb::packet::EntityEffect {
+      eid,
+      effect,
+      amplifier,
+      duration: duration.try_into().unwrap_or(i32::MAX),
+      particles,
+    });
+  }
+  /// Removes the given effect from this player. Returns `true` if the player
+  /// had this effect.
+  pub fn remove_effect(&self, effect: Effect) -> bool {
+    let removed = {
+      let mut effects = self.effects.lock();
+      let len = effects.len();
+      effects.retain(|e| e.effect != effect);
+      effects.len() != len
+    };
+    if removed {
+      self.send_effect_packet(|eid| cb::packet::RemoveEntityEffect { eid, effect });
+    }
+    removed
+  }
+  /// Returns the given effect, if this player has it.
+  pub fn effect(&self, effect: Effect) -> Option<ActiveEffect> {
+    self.effects.lock().iter().find(|e| e.effect == effect).copied()
+  }
+  /// Returns all of the effects on this player.
+  pub fn effects(&self) -> Vec<ActiveEffect> { self.effects.lock().clone() }
+
+  /// Returns how much faster this player can walk with their current effects.
+  /// This is 1 without any effects, and is never below 0.
+  pub fn speed_multiplier(&self) -> f64 {
+    let mut mult = 1.0;
+    for e in self.effects.lock().iter() {
+      match e.effect {
+        Effect::Speed => mult += 0.2 * (f64::from(e.amplifier) + 1.0),
+        Effect::Slowness => mult -= 0.15 * (f64::from(e.amplifier) + 1.0),
+        _ => {}
+      }
+    }
+    f64::max(mult, 0.0)
+  }
+
+  /// Counts down all the effects on this player, and applies regeneration and
+  /// poison. This must be called before the player's health is locked.
+  pub(super) fn tick_effects(self: &Arc<Self>) {
+    let mut expired = vec![];
+    let mut heal = false;
+    let mut poison = false;
+    {
+      let mut effects = self.effects.lock();
+      for e in effects.iter_mut() {
+        match e.effect {
+          Effect::Regeneration => heal |= e.should_apply(50),
+          Effect::Poison => poison |= e.should_apply(25),
+          _ => {}
+        }
+        e.duration = e.duration.saturating_sub(1);
+        if e.duration == 0 {
+          expired.push(e.effect);
+        }
+      }
+      effects.retain(|e| e.duration > 0);
+    }
+    for effect in expired {
+      self.send_effect_packet(|eid| cb::packet::RemoveEntityEffect { eid, effect });
+    }
+
+    let health = self.health();
+    if heal && health > 0.0 && health < 20.0 {
+      self.set_health((health + 1.0).min(20.0));
+    }
+    // Poison never kills the player.
+    if poison && self.health() > 1.0 {
+      self.damage(1.0, false, Vec3::new(0.0, 0.0, 0.0));
+    }
+  }
+}
@@ -0,0 +1,63 @@
+//! The level curve for player experience. These match vanilla, so the bar on
+//! the client lines up with the levels we send.
+
+/// Returns the total amount of experience needed to reach the given level.
+/// This saturates at `i32::MAX` for very large levels.
+pub fn total_for_level(level: i32) -> i32 {
+  let l = i64::from(level.max(0));
+  let total = if l <= 16 {
+    l * l + 6 * l
+  } else if l <= 31 {
+    (5 * l * l - 81 * l) / 2 + 360
+  } else {
+    (9 * l * l - 325 * l) / 2 + 2220
+  };
+  total.min(i32::MAX.into()) as i32
+}
+
+/// Returns the level for the given amount of total experience, and the
+/// progress towards the next level, from 0 to 1.
+pub fn level_for_total(total: i32) -> (i32, f32) {
+  let total = total.max(0);
+  let mut level = 0;
+  // Once the curve saturates, there are no more levels to reach.
+  while total_for_level(level + 1) <= total && total_for_level(level + 1) < i32::MAX {
+    level += 1;
+  }
+  let start = total_for_level(level);
+  let end = total_for_level(level + 1);
+  (level, (total - start) as f32 / (end - start) as f32)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_total_for_level() {
+    assert_eq!(total_for_level(0), 0);
+    assert_eq!(total_for_level(1), 7);
+    assert_eq!(total_for_level(16), 352);
+    assert_eq!(total_for_level(17), 394);
+    assert_eq!(total_for_level(30), 1395);
+    assert_eq!(total_for_level(31), 1507);
+    assert_eq!(total_for_level(32), 1628);
+    assert_eq!(total_for_level(100), 30970);
+    assert_eq!(total_for_level(1_000_000), i32::MAX);
+  }
+
+  #[test]
+  fn test_level_for_total() {
+    assert_eq!(level_for_total(0), (0, 0.0));
+    assert_eq!(level_for_total(-5), (0, 0.0));
+    assert_eq!(level_for_total(7), (1, 0.0));
+    assert_eq!(level_for_total(16), (2, 0.0));
+    assert_eq!(level_for_total(373), (16, 0.5));
+    assert!(level_for_total(i32::MAX).1 <= 1.0);
+    assert_eq!(level_for_total(394), (17, 0.0));
+    for level in [0, 5, 16, 17, 31, 32, 50] {
+      assert_eq!(level_for_total(total_for_level(level)).0, level);
+      assert_eq!(level_for_total(total_for_level(level + 1) - 1).0, level);
+    }
+  }
+}
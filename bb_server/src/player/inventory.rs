@@ -30,6 +30,9 @@ pub struct PlayerInventory {
   selected_index: u8,
   // Open window and held item
   window:         Option<Window>,
+  // The id of the open window. If no window is open, this is the id of the last
+  // window that was opened.
+  wid:            u8,
   // Held item. Always present, as survival inventories don't count as windows.
   held:           Stack,
 
@@ -59,6 +62,7 @@ impl PlayerInventory {
       off_hand:       SingleInventory::new(conn, 0, 45),
       selected_index: 0,
       window:         None,
+      wid:            0,
       held:           Stack::empty(),
       drag_slots:     vec![],
       drag_bt:        None,
@@ -66,27 +70,43 @@ impl PlayerInventory {
     }
   }
 
-  pub fn open_window(&mut self, win: Window) {
-    assert!(self.window.is_none());
+  /// Opens the given window, and returns the window id it was assigned. If
+  /// another window is already open, it will be closed first. This does not
+  /// send any packets to the client, so it should only be called through
+  /// [`Player::show_inventory`].
+  pub fn open_window(&mut self, win: Window) -> u8 {
+    self.close_window();
+    // Window ids are in the range `1..=100`, as the vanilla client expects. 0 is
+    // the player's own inventory.
+    self.wid = self.wid % 100 + 1;
     // Assume chest-like for now.
     self.main.offset = win.size();
-    self.main.wid = 1;
+    self.main.wid = self.wid;
     self.hotbar.offset = win.size() + self.main.size();
-    self.hotbar.wid = 1;
+    self.hotbar.wid = self.wid;
     let p = self.player.upgrade().unwrap();
-    win.open(p.uuid, &p.conn);
+    win.open(p.uuid, &p.conn, self.wid);
     self.window = Some(win);
+    self.wid
   }
-  pub fn close_window(&mut self) {
-    if let Some(win) = self.window.take() {
+  /// Closes the open window. Returns `true` if there was a window open.
+  pub fn close_window(&mut self) -> bool {
+    let was_open = if let Some(win) = self.window.take() {
       let p = self.player.upgrade().unwrap();
       win.close(p.uuid);
-    }
+      true
+    } else {
+      false
+    };
     self.main.offset = 9;
     self.main.wid = 0;
     self.hotbar.offset = 36;
     self.hotbar.wid = 0;
+    was_open
   }
+  /// Returns the id of the open window, or `None` if the player only has their
+  /// own inventory open.
+  pub fn window_id(&self) -> Option<u8> { self.window.as_ref().map(|_| self.wid) }
 
   /// Gives an item to the player. Returns the number of remaining items in the
   /// stack.
@@ -216,9 +236,21 @@ impl PlayerInventory {
   /// on if a window is open, the actual slot being accessed may change. Use
   /// [`main`](Self::main) or [`win`](Self::win) to access the main inventory or
   /// the open window directly.
+  ///
+  /// If `index` is not a valid slot, this does nothing.
   pub fn set(&mut self, index: i32, stack: Stack) {
-    self.access(index, |it| *it = stack);
-    self.sync(index);
+    if self.access(index, |it| *it = stack).is_some() {
+      self.sync(index);
+    }
+  }
+  /// Sets the item in the inventory, without sending the slot back to this
+  /// player. Other players will still see the new item if it is in the main
+  /// hand. This should only be used when the client already knows about the
+  /// change, such as when they set a slot in creative mode.
+  pub(crate) fn set_no_sync(&mut self, index: i32, stack: Stack) {
+    if self.access(index, |it| *it = stack).is_some() {
+      self.sync_equipment(index);
+    }
   }
 
   /// Sends an inventory update to the client. This is more efficient than
@@ -255,20 +287,14 @@ impl PlayerInventory {
       items.push(it.to_item());
     }
     let held = self.held.to_item();
-    self.main.conn.send(cb::packet::WindowItems { wid: 1, items, held });
+    let wid = if self.window.is_some() { self.wid } else { 0 };
+    self.main.conn.send(cb::packet::WindowItems { wid, items, held });
   }
   /// Sends an item update for the given slot. This shouldn't every be needed,
   /// as functions like [`set`](Self::set) and [`replace`](Self::replace) will
   /// call this for you.
   pub fn sync(&self, index: i32) {
-    if index == self.selected_index as i32 + 36 {
-      let p = self.player.upgrade().unwrap();
-      p.send_to_in_view(cb::packet::EntityEquipment {
-        eid:  p.eid(),
-        slot: cb::EquipmentSlot::Hand(Hand::Main),
-        item: self.get(index).unwrap().to_item(),
-      });
-    }
+    self.sync_equipment(index);
     if index == -999 {
       self.main.conn.send(cb::packet::WindowItem {
         wid:  u8::MAX,
@@ -303,6 +329,19 @@ impl PlayerInventory {
     }
   }
 
+  /// Sends the item in the main hand to everyone in view, if `index` is the
+  /// selected hotbar slot.
+  fn sync_equipment(&self, index: i32) {
+    if index == self.selected_index as i32 + 36 {
+      let p = self.player.upgrade().unwrap();
+      p.send_to_in_view(cb::packet::EntityEquipment {
+        eid:  p.eid(),
+        slot: cb::EquipmentSlot::Hand(Hand::Main),
+        item: self.get(index).unwrap().to_item(),
+      });
+    }
+  }
+
   /// Handles an inventory move operation.
   pub fn click_window(&mut self, slot: i32, click: ClickWindow, allow: bool) {
     info!("handling click at slot {slot} {click:?}");
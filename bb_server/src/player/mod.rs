@@ -13,14 +13,19 @@ use crate::{
 use bb_common::{
   math::{ChunkPos, FPos, Pos},
   metadata::{Metadata, Pose},
-  net::{cb, sb::PlayerCommand},
-  util::{Chat, GameMode, JoinInfo, SwitchMode, UUID},
+  net::{
+    cb,
+    sb::{PlayerCommand, ResourcePackStatus},
+  },
+  util::{chat::Color, Chat, Face, GameMode, Hand, JoinInfo, SwitchMode, UUID},
   version::ProtocolVersion,
 };
-use parking_lot::{Mutex, MutexGuard};
-use std::{collections::HashSet, f64::consts, fmt, net::SocketAddr, sync::Arc, time::Instant};
+use parking_lot::{Mutex, MutexGuard, RwLock};
+use std::{collections::HashSet, fmt, net::SocketAddr, sync::Arc, time::Instant};
 
 mod click;
+mod effect;
+mod exp;
 mod inventory;
 mod scoreboard;
 mod team;
@@ -28,6 +33,7 @@ mod tick;
 pub mod window;
 
 pub use click::{AirClick, BlockClick, Click};
+pub use effect::ActiveEffect;
 pub use inventory::PlayerInventory;
 pub use scoreboard::Scoreboard;
 pub use team::Team;
@@ -59,6 +65,12 @@ pub(crate) struct PlayerPosition {
   // is received. It is also used to set x,y,z on the next tick.
   pub(crate) next: FPos,
 
+  // Every position received since the last tick, in order. This includes the
+  // positions from packets that were coalesced away (see `add_path`). Used to
+  // validate the whole path the player took this tick, instead of just the
+  // endpoint.
+  path: Vec<FPos>,
+
   // Set after the player teleports. Only cleared once the player moves to this position.
   teleport_to: Option<FPos>,
 
@@ -118,10 +130,14 @@ pub struct Player {
   uuid:          UUID,
   conn:          ConnSender,
   ver:           ProtocolVersion,
-  world:         Arc<World>,
+  addr:          Option<SocketAddr>,
+  world:         RwLock<Arc<World>>,
   view_distance: u32,
+  sim_distance:  u32,
 
   game_mode: Mutex<GameMode>,
+  // If set, this player is hidden from everyone else. See `set_vanished`.
+  vanished:  Mutex<bool>,
 
   inv:        Mutex<PlayerInventory>,
   scoreboard: Mutex<Scoreboard>,
@@ -131,6 +147,26 @@ pub struct Player {
 
   health: Mutex<PlayerHealth>,
   food:   Mutex<PlayerFood>,
+  // The total amount of experience this player has. The level and bar are
+  // derived from this.
+  exp:    Mutex<i32>,
+
+  effects: Mutex<Vec<ActiveEffect>>,
+  // The number of ticks this player will be on fire for. If this is 0, the
+  // player is not on fire.
+  fire:    Mutex<u32>,
+
+  // Set when the player sleeps in a bed, for example. If `None`, the player
+  // respawns at the world spawn.
+  spawn_point: Mutex<Option<FPos>>,
+
+  // If the last resource pack we sent was forced, this is `true`.
+  resource_pack_forced: Mutex<bool>,
+  // The last status the client sent about their resource pack.
+  resource_pack_status: Mutex<Option<ResourcePackStatus>>,
+  // The plugin message channels this client has registered with
+  // `minecraft:register`.
+  client_channels:      Mutex<HashSet<String>>,
 
   // Not very efficient, but required, as we generate chunks in the background. Because chunk
   // generation is slow, we need to do it over multiple ticks. If the player moves into a chunk,
@@ -147,10 +183,13 @@ impl fmt::Debug for Player {
       .field("uuid", &self.uuid)
       .field("ver", &self.ver)
       .field("view_distance", &self.view_distance)
+      .field("sim_distance", &self.sim_distance)
       .field("inv", &self.inv)
       .field("scoreboard", &self.scoreboard)
       .field("pos", &self.pos)
       .field("health", &self.health)
+      .field("exp", &self.exp)
+      .field("effects", &self.effects)
       .finish()
   }
 }
@@ -179,12 +218,16 @@ impl PlayerAbilities {
     }
   }
   /// Updates the abilities based on the given gamemode. This will set
-  /// `invulnerable`, `instant_break`, and `flying_allowed`. The other fields
-  /// will not be modified.
+  /// `invulnerable`, `instant_break`, and `flying_allowed`. Spectators are
+  /// always flying, so this will also set `flying` for spectator mode. The
+  /// other fields will not be modified.
   pub fn set_from_game_mode(&mut self, game_mode: GameMode) {
-    self.invulnerable = game_mode == GameMode::Creative;
+    self.invulnerable = matches!(game_mode, GameMode::Creative | GameMode::Spectator);
     self.instant_break = game_mode == GameMode::Creative;
     self.flying_allowed = matches!(game_mode, GameMode::Creative | GameMode::Spectator);
+    if game_mode == GameMode::Spectator {
+      self.flying = true;
+    }
   }
 }
 
@@ -208,13 +251,24 @@ impl Player {
       scoreboard: Scoreboard::new(conn.clone()).into(),
       conn,
       ver: ProtocolVersion::from(info.ver as i32),
+      addr: info.addr,
       view_distance: world.world_manager().config().view_distance,
+      sim_distance: {
+        let config = world.world_manager().config();
+        if ProtocolVersion::from(info.ver as i32) >= ProtocolVersion::V1_18 {
+          config.simulation_distance.min(config.view_distance)
+        } else {
+          config.view_distance
+        }
+      },
       game_mode: Mutex::new(game_mode),
-      world,
+      vanished: Mutex::new(false),
+      world: RwLock::new(world),
       pos: PlayerPosition {
         curr:         pos,
         prev:         pos,
         next:         pos,
+        path:         vec![],
         teleport_to:  None,
         vel:          Vec3::new(0.0, 0.0, 0.0),
         yaw:          0.0,
@@ -232,6 +286,13 @@ impl Player {
       abilities: Mutex::new(abilities),
       health: PlayerHealth { health: 20.0, absorption: 0.0, hit_delay: 0 }.into(),
       food: PlayerFood { food: 20, saturation: 5.0 }.into(),
+      exp: Mutex::new(0),
+      effects: Mutex::new(vec![]),
+      fire: Mutex::new(0),
+      spawn_point: Mutex::new(None),
+      resource_pack_forced: Mutex::new(false),
+      resource_pack_status: Mutex::new(None),
+      client_channels: Mutex::new(HashSet::new()),
       loaded_chunks: Mutex::new(HashSet::new()),
     })
   }
@@ -239,6 +300,16 @@ impl Player {
   /// Returns the player's username.
   pub fn username(&self) -> &String { &self.username }
 
+  /// Returns the player's op level, from 0 to 4. Players listed in the `ops`
+  /// config have level 4, and everyone else has level 0.
+  pub fn op_level(&self) -> u8 {
+    if self.world().world_manager().config().ops.contains(&self.username) {
+      4
+    } else {
+      0
+    }
+  }
+
   /// Returns the player's entity id. Used to send packets about entities.
   pub fn eid(&self) -> i32 { self.eid }
   /// Returns the player's uuid. Used to lookup players in the world.
@@ -246,6 +317,9 @@ impl Player {
   /// Returns the player's view disstance. This is how far they can see in
   /// chunks.
   pub fn view_distance(&self) -> u32 { self.view_distance }
+  /// Returns the player's simulation distance. Entities within this many chunks
+  /// of the player will be ticked. This is never larger than the view distance.
+  pub fn simulation_distance(&self) -> u32 { self.sim_distance }
 
   /// Returns the player's health and absorbtion hearts. Modifying these values
   /// will not send an update to the client.
@@ -267,23 +341,68 @@ impl Player {
     });
   }
 
+  /// Returns the total amount of experience this player has.
+  pub fn experience(&self) -> i32 { *self.exp.lock() }
+  /// Returns the player's level. This is the number shown above their hotbar.
+  pub fn level(&self) -> i32 { exp::level_for_total(self.experience()).0 }
+  /// Adds the given amount of experience to this player, and sends them the
+  /// new experience bar. `amount` may be negative, but the total will never go
+  /// below 0.
+  pub fn add_experience(&self, amount: i32) {
+    let mut exp = self.exp.lock();
+    *exp = exp.saturating_add(amount).max(0);
+    self.send_experience(*exp);
+  }
+  /// Sets the player's level. This resets their progress towards the next
+  /// level.
+  pub fn set_level(&self, level: i32) {
+    let mut exp = self.exp.lock();
+    *exp = exp::total_for_level(level);
+    self.send_experience(*exp);
+  }
+  fn send_experience(&self, total: i32) {
+    let (level, bar) = exp::level_for_total(total);
+    self.send(cb::packet::SetExperience { bar, level, total });
+  }
+
   /// Returns the version that this client connected with. This will only change
   /// if the player disconnects and logs in with another client.
   pub fn ver(&self) -> ProtocolVersion { self.ver }
+  /// Returns the address this client connected to the proxy from. This is
+  /// `None` if the proxy didn't send the address.
+  pub fn addr(&self) -> Option<SocketAddr> { self.addr }
 
   /// Returns a locked reference to the player's inventory.
   pub fn lock_inventory(&self) -> MutexGuard<PlayerInventory> { self.inv.lock() }
   /// Returns a locked reference to the player's scoreboard.
   pub fn lock_scoreboard(&self) -> MutexGuard<Scoreboard> { self.scoreboard.lock() }
 
-  /// Returns a reference to the world the player is in.
-  pub fn world(&self) -> &Arc<World> { &self.world }
+  /// Returns the world the player is in. The player can be moved to another
+  /// world with [`WorldManager::move_player`], so this returns a clone of the
+  /// world, instead of a reference.
+  ///
+  /// [`WorldManager::move_player`]: crate::world::WorldManager::move_player
+  pub fn world(&self) -> Arc<World> { self.world.read().clone() }
+  /// Sets the world this player is in. This doesn't send any packets, or
+  /// update the world's player list. This should only be called from
+  /// [`WorldManager::move_player`](crate::world::WorldManager::move_player).
+  pub(crate) fn set_world(&self, world: Arc<World>) { *self.world.write() = world; }
 
   /// This will move the player on the next player tick. Used whenever a
   /// position packet is received.
   pub(crate) fn set_next_pos(&self, x: f64, y: f64, z: f64) {
     let mut pos = self.pos.lock();
     pos.next = FPos::new(x, y, z);
+    let next = pos.next;
+    pos.path.push(next);
+  }
+  /// Adds positions the client moved through, without changing where they
+  /// will be on the next tick. Used for movement packets that were coalesced
+  /// away before being handled, so that the whole path is still validated.
+  pub(crate) fn add_path(&self, path: &[FPos]) {
+    if !path.is_empty() {
+      self.pos.lock().path.extend_from_slice(path);
+    }
   }
 
   /// This will set the player's look direction on the next player tick. Used
@@ -294,6 +413,54 @@ impl Player {
     pos.next_pitch = pitch;
   }
 
+  /// Returns the position this player will respawn at. This is the position
+  /// passed to [`set_spawn_point`](Self::set_spawn_point), or the world's spawn
+  /// point if that was never called.
+  pub fn spawn_point(&self) -> FPos {
+    match *self.spawn_point.lock() {
+      Some(pos) => pos,
+      None => self.world().spawn_point(),
+    }
+  }
+  /// Sets the position this player will respawn at. This is used for things
+  /// like beds and respawn anchors.
+  pub fn set_spawn_point(&self, pos: FPos) { *self.spawn_point.lock() = Some(pos); }
+  /// Resets this player's spawn point, so that they will respawn at the world
+  /// spawn.
+  pub fn clear_spawn_point(&self) { *self.spawn_point.lock() = None; }
+
+  /// Respawns the player at their spawn point. This resets their health, and
+  /// sends them all the chunks around their spawn point. This is called when
+  /// the client clicks the respawn button.
+  pub fn respawn(self: &Arc<Self>) {
+    let world = self.world();
+    let spawn = world.safe_spawn(self.spawn_point());
+    self.send(cb::packet::Respawn {
+      difficulty:   1,
+      dimension:    world.dimension(),
+      game_mode:    self.game_mode(),
+      level_type:   "default".into(),
+      reset_meta:   true,
+      world_height: world.height(),
+      world_min_y:  world.min_y(),
+    });
+    // The client forgets about all of its chunks and effects when it respawns.
+    self.unload_all();
+    self.effects.lock().clear();
+    {
+      let mut pos = self.pos.lock();
+      pos.prev = spawn;
+      pos.curr = spawn;
+      pos.next = spawn;
+      pos.path.clear();
+    }
+    self.teleport(spawn, 0.0, 0.0);
+    self.set_health(20.0);
+    let v = self.view_distance as i32;
+    let chunk = spawn.block().chunk();
+    self.load_chunks(chunk - ChunkPos::new(v, v), chunk + ChunkPos::new(v, v));
+  }
+
   /// Teleports the player to the given position, and set's their looking
   /// direction to the given yaw/pitch. You cannot teleport a player without
   /// also setting their yaw/pitch.
@@ -324,10 +491,16 @@ impl Player {
       ty: 2, // Hotbar, not chat box or system message
     });
   }
+  /// Shows the given scoreboard objective on the right side of the player's
+  /// screen. The objective must first be created with
+  /// [`Scoreboard::create_objective`].
+  pub fn show_sidebar(&self, objective: &str) {
+    self.lock_scoreboard().display_objective(cb::ScoreboardDisplayPosition::Sidebar, objective);
+  }
   /// Sends the particle to the player. This will always send the packet, even
   /// if the particle is too far away.
   pub fn send_particle(&self, particle: Particle) {
-    self.send(particle.to_packet(self.world.world_manager().block_converter(), self.ver));
+    self.send(particle.to_packet(self.world().world_manager().block_converter(), self.ver));
   }
   /// Sends the particle to the player, if the player can see the particle. This
   /// uses `particle.long_distance` to check if the player is in range.
@@ -353,22 +526,126 @@ impl Player {
   pub fn show_title(&self, fade_in: u32, stay: u32, fade_out: u32) {
     self.send(cb::packet::Title { action: cb::TitleAction::Times { fade_in, stay, fade_out } });
   }
+  /// Shows a title to the player, with an optional subtitle. The `fade_in`,
+  /// `stay`, and `fade_out` arguments are all in ticks.
+  ///
+  /// This is the same as calling [`set_title`](Self::set_title),
+  /// [`set_subtitle`](Self::set_subtitle), and
+  /// [`show_title`](Self::show_title).
+  pub fn send_title(
+    &self,
+    title: Chat,
+    subtitle: Option<Chat>,
+    fade_in: u32,
+    stay: u32,
+    fade_out: u32,
+  ) {
+    self.show_title(fade_in, stay, fade_out);
+    if let Some(subtitle) = subtitle {
+      self.set_subtitle(subtitle);
+    }
+    // The client displays the title once it receives the title text, so this
+    // must be sent last.
+    self.set_title(title);
+  }
+  /// Shows a message above the player's hotbar. This uses the action bar or
+  /// title packet on versions that support it, and falls back to a chat
+  /// message otherwise.
+  pub fn send_action_bar(&self, msg: Chat) {
+    self.send(cb::packet::Title { action: cb::TitleAction::ActionBar(msg) });
+  }
 
   /// Shows the given inventory to the client. The title will be shown in the
-  /// top left of the window.
+  /// top left of the window. If the player already has a window open, it will
+  /// be replaced.
+  ///
+  /// Any clicks in this window will fire a `click_window` event, which can be
+  /// cancelled, and closing it will fire a `window_close` event.
   pub fn show_inventory(&self, win: Window, title: &Chat) {
-    self.send(cb::packet::WindowOpen {
-      wid:   1,
-      ty:    win.ty().into(),
-      size:  win.size(),
-      title: title.to_json(),
-    });
-    self.send(cb::packet::WindowItems {
-      wid:   1,
-      items: win.items().map(|i| i.to_item()).collect(),
-      held:  Stack::empty().to_item(),
-    });
-    self.lock_inventory().open_window(win);
+    let ty = win.ty().into();
+    let size = win.size();
+    let items = win.items().map(|i| i.to_item()).collect();
+    let wid = self.lock_inventory().open_window(win);
+    self.send(cb::packet::WindowOpen { wid, ty, size, title: title.to_json() });
+    self.send(cb::packet::WindowItems { wid, items, held: Stack::empty().to_item() });
+  }
+
+  /// Sets a property of the open window. This is used for things like furnace
+  /// progress bars, enchantment levels, and anvil repair costs. The meaning
+  /// of `property` depends on the type of window that is open.
+  ///
+  /// This does nothing if the player doesn't have a window open.
+  pub fn set_window_property(&self, property: i16, value: i16) {
+    if let Some(wid) = self.lock_inventory().window_id() {
+      self.send(cb::packet::WindowProperty { wid, property, value });
+    }
+  }
+
+  /// Sends the command tree to the player. This is sent when the player joins,
+  /// and should be sent again whenever a command is added, or the player's
+  /// permissions change. 1.13+ clients use this to parse and autocomplete
+  /// commands. Older clients don't support this, so nothing is sent to them.
+  pub fn send_commands(&self) {
+    if self.ver() >= ProtocolVersion::V1_13 {
+      self.send(self.world().commands().serialize(self.op_level()));
+    }
+  }
+
+  /// Sends a plugin message to the player on the given channel. `channel`
+  /// should be namespaced, like `minecraft:brand`. It will be converted for
+  /// pre-1.13 clients by the proxy.
+  pub fn send_plugin_message(&self, channel: &str, data: Vec<u8>) {
+    self.send(cb::packet::PluginMessage { channel: channel.into(), data });
+  }
+
+  /// Returns true if this client has registered the plugin message channel
+  /// `channel`, using `minecraft:register`. Clients that never register
+  /// channels might still listen on them, so this is only a hint.
+  pub fn listens_on(&self, channel: &str) -> bool { self.client_channels.lock().contains(channel) }
+
+  pub(crate) fn register_client_channel(&self, channel: String) {
+    self.client_channels.lock().insert(channel);
+  }
+  pub(crate) fn unregister_client_channel(&self, channel: &str) {
+    self.client_channels.lock().remove(channel);
+  }
+
+  /// Prompts the player to download the resource pack at `url`. `hash` is the
+  /// hex encoded SHA-1 hash of the pack, or an empty string.
+  ///
+  /// If `forced` is set, the player will be kicked if they decline the pack.
+  /// The `prompt` will be shown on the download screen. Both of these are only
+  /// shown to 1.17+ clients, but older clients will still be kicked for
+  /// declining a forced pack.
+  ///
+  /// The client's response can be read with
+  /// [`resource_pack_status`](Self::resource_pack_status), or with the
+  /// `resource_pack_status` event.
+  pub fn set_resource_pack(&self, url: &str, hash: &str, forced: bool, prompt: Option<Chat>) {
+    *self.resource_pack_forced.lock() = forced;
+    *self.resource_pack_status.lock() = None;
+    self.send(cb::packet::ResourcePack { url: url.into(), hash: hash.into(), forced, prompt });
+  }
+
+  /// Returns the last status the client sent about the resource pack from
+  /// [`set_resource_pack`](Self::set_resource_pack). This is `None` if no pack
+  /// has been sent, or if the client hasn't responded yet.
+  pub fn resource_pack_status(&self) -> Option<ResourcePackStatus> {
+    *self.resource_pack_status.lock()
+  }
+
+  /// Handles a resource pack status packet from the client.
+  pub(crate) fn handle_resource_pack_status(self: &Arc<Self>, status: ResourcePackStatus) {
+    *self.resource_pack_status.lock() = Some(status);
+    self
+      .world()
+      .events()
+      .player_event(event::ResourcePackResponse { player: self.clone(), status });
+    if status == ResourcePackStatus::Declined && *self.resource_pack_forced.lock() {
+      let mut reason = Chat::empty();
+      reason.add("This server requires a resource pack").color(Color::Red);
+      self.disconnect(reason);
+    }
   }
 
   /// Disconnects the player. The given chat message will be shown on the
@@ -377,12 +654,8 @@ impl Player {
   /// This may not have an effect immediately. This only sends a disconnect
   /// packet. Assuming normal operation, the client will then disconnect after
   /// they have received this packet.
-  ///
-  /// TODO: This should terminate the connection after this packet is sent.
-  /// Closing the channel will drop the packet before it can be sent, so we need
-  /// some other way of closing it later.
-  pub fn disconnect<C: Into<Chat>>(&self, _msg: C) {
-    // self.send(cb::packet::KickDisconnect { reason: msg.into().to_json() });
+  pub fn disconnect<C: Into<Chat>>(&self, msg: C) {
+    self.send(cb::packet::Disconnect { reason: msg.into() });
     self.remove();
   }
 
@@ -391,7 +664,7 @@ impl Player {
   ///
   /// This is used when a player disconnects on their own, and they need to be
   /// removed from the players list in the world.
-  pub(crate) fn remove(&self) { self.world.world_manager().remove_player(self.uuid); }
+  pub(crate) fn remove(&self) { self.world().world_manager().remove_player(self.uuid); }
 
   /// Returns the status byte for entity metadata. The bits are as follows:
   ///
@@ -404,8 +677,42 @@ impl Player {
   /// - `0x40`: Is glowing
   /// - `0x80`: Is flying with elytra
   pub fn status_byte(&self) -> i8 {
+    let on_fire = self.is_on_fire() as i8;
     let pos = self.pos.lock();
-    ((pos.crouching as i8) << 1) | ((pos.sprinting as i8) << 4) | ((pos.swimming as i8) << 5)
+    on_fire
+      | ((pos.crouching as i8) << 1)
+      | ((pos.sprinting as i8) << 4)
+      | ((pos.swimming as i8) << 5)
+  }
+
+  /// Returns `true` if this player is on fire.
+  pub fn is_on_fire(&self) -> bool { *self.fire.lock() > 0 }
+
+  /// Sets this player on fire for the given number of ticks. If `ticks` is 0,
+  /// the fire will be put out. This will update the on fire flag for this
+  /// player, and for everyone in view.
+  pub fn set_on_fire(&self, ticks: u32) {
+    let was_on_fire = std::mem::replace(&mut *self.fire.lock(), ticks) > 0;
+    if was_on_fire != (ticks > 0) {
+      self.send_status_byte();
+    }
+  }
+
+  /// Sends this player's status byte to this player and everyone in view.
+  fn send_status_byte(&self) {
+    let mut meta = Metadata::new();
+    meta.set_byte(0, self.status_byte());
+    // Players think they are EID 1.
+    self.send(cb::packet::EntityMetadata {
+      eid:  1,
+      ty:   entity::Type::Player.id(),
+      meta: meta.clone(),
+    });
+    self.send_to_in_view(cb::packet::EntityMetadata {
+      eid: self.eid(),
+      ty: entity::Type::Player.id(),
+      meta,
+    });
   }
 
   /// Generates the player's metadata for the given version. This will include
@@ -421,6 +728,32 @@ impl Player {
     meta
   }
 
+  /// Returns this player's entry in the tab list. This is sent to other players
+  /// in the same world.
+  pub(crate) fn player_list_info(&self) -> cb::PlayerListAdd {
+    cb::PlayerListAdd {
+      id:           self.id(),
+      name:         self.username().clone(),
+      game_mode:    self.game_mode(),
+      ping:         50,
+      display_name: self.tab_name().clone().map(|c| c.to_json()),
+    }
+  }
+
+  /// Returns a packet that will spawn this player for another client.
+  pub(crate) fn spawn_packet(&self) -> cb::packet::SpawnPlayer {
+    let (pos, pitch, yaw) = self.pos_look();
+    cb::packet::SpawnPlayer {
+      eid: self.eid(),
+      id: self.id(),
+      ty: entity::Type::Player.id(),
+      pos,
+      yaw: yaw as i8,
+      pitch: pitch as i8,
+      meta: self.metadata(),
+    }
+  }
+
   /// Locks the player's position. While this is locked, no ticks or movement
   /// updates can be processed.
   #[cfg(test)]
@@ -497,12 +830,15 @@ impl Player {
   /// Returns a unit vector which is the direction this player is facing.
   pub fn look_as_vec(&self) -> Vec3 {
     let (pitch, yaw) = self.look();
-    let pitch = (pitch as f64) / 180.0 * consts::PI;
-    let yaw = (yaw as f64) / 180.0 * consts::PI;
-    let m = pitch.cos();
-    // The coordinate system of minecraft means that we need to do this hell to get
-    // the axis to line up correctly.
-    Vec3::new(-yaw.sin() * m, -pitch.sin(), yaw.cos() * m)
+    Vec3::from_yaw_pitch(yaw, pitch)
+  }
+
+  /// Returns the block the player is looking at, and the face of that block
+  /// they are looking at. This will return `None` if there is no block within
+  /// `max_dist` blocks of the player's eyes.
+  pub fn target_block(&self, max_dist: f64) -> Option<(Pos, Face)> {
+    let eyes = self.pos() + FPos::new(0.0, self.view_offset(), 0.0);
+    self.world().raycast_block(eyes, self.look_as_vec(), max_dist)
   }
 
   /// Returns true if the player is within render distance of the given chunk
@@ -510,6 +846,12 @@ impl Player {
     let delta = pos - self.pos().block().chunk();
     delta.x().unsigned_abs() <= self.view_distance && delta.z().unsigned_abs() <= self.view_distance
   }
+  /// Returns true if the player is within simulation distance of the given
+  /// chunk. Entities in this chunk should be ticked.
+  pub fn in_simulation(&self, pos: ChunkPos) -> bool {
+    let delta = pos - self.pos().block().chunk();
+    delta.x().unsigned_abs() <= self.sim_distance && delta.z().unsigned_abs() <= self.sim_distance
+  }
 
   /// Sends the abilities of this player to the client.
   pub(crate) fn send_abilities(&self) {
@@ -587,11 +929,7 @@ impl Player {
         display_name: name,
       }]),
     };
-    for w in self.world().world_manager().worlds().iter() {
-      for p in w.players().iter() {
-        p.send(update.clone());
-      }
-    }
+    self.world().world_manager().send_to_all(update);
   }
   /// Returns the current tab list name.
   pub fn tab_name(&self) -> MutexGuard<'_, Option<Chat>> { self.tab_name.lock() }
@@ -621,13 +959,19 @@ impl Player {
   /// right now mean that no channel will block another channel, so in practice
   /// this will only produce slow downs, never deadlocks.
   pub fn send(&self, p: impl Into<cb::Packet>) { self.conn.send(p.into()); }
+  /// Returns the connection to this player's client.
+  pub(crate) fn conn(&self) -> &ConnSender { &self.conn }
+  /// Sends all of the given packets to this player. This should be preferred
+  /// over calling [`send`](Self::send) in a loop, as it only wakes up the
+  /// networking thread once.
+  pub fn send_all(&self, packets: Vec<cb::Packet>) { self.conn.send_all(packets); }
 
   /// Sends the given packet to all players in view of this player, *not
   /// including* `self`. If you want to also send the packet to `self`,
   /// call [`send_all_in_view`](Self::send_all_in_view).
   pub fn send_to_in_view(&self, p: impl Into<cb::Packet>) {
     let p = p.into();
-    for other in self.world.players().iter().in_view(self.pos().chunk()).not(self.uuid) {
+    for other in self.world().players().iter().in_view(self.pos().chunk()).not(self.uuid) {
       other.send(p.clone());
     }
   }
@@ -636,7 +980,7 @@ impl Player {
   /// [`send_to_in_view`](Self::send_to_in_view).
   pub fn send_all_in_view(&self, p: impl Into<cb::Packet>) {
     let p = p.into();
-    for other in self.world.players().iter().in_view(self.pos().chunk()) {
+    for other in self.world().players().iter().in_view(self.pos().chunk()) {
       other.send(p.clone());
     }
   }
@@ -670,12 +1014,62 @@ impl Player {
     *self.game_mode.lock() = mode;
     self.abilities.lock().set_from_game_mode(mode);
     self.send_abilities();
-    self.send(cb::packet::PlayerList {
+    // Clients use the tab list to decide if a player is a spectator, so this
+    // needs to go to everyone who can see us, not just ourselves.
+    let update = cb::packet::PlayerList {
       action: cb::PlayerListAction::UpdateGameMode(vec![cb::PlayerListGameMode {
         id:        self.id(),
         game_mode: mode,
       }]),
-    });
+    };
+    if self.is_vanished() {
+      self.send(update);
+    } else {
+      for other in self.world().player_list() {
+        other.send(update.clone());
+      }
+    }
+  }
+
+  /// Returns `true` if this player is vanished. See
+  /// [`set_vanished`](Self::set_vanished).
+  pub fn is_vanished(&self) -> bool { *self.vanished.lock() }
+
+  /// Hides or shows this player. A vanished player is removed from the tab list
+  /// of everyone else in their world, and will not be spawned for anyone.
+  /// Vanished players can still see everyone else.
+  ///
+  /// This is separate from the spectator game mode, so a spectator can still
+  /// be seen, and a vanished player can still be in survival.
+  pub fn set_vanished(&self, vanished: bool) {
+    {
+      let mut lock = self.vanished.lock();
+      if *lock == vanished {
+        return;
+      }
+      *lock = vanished;
+    }
+    let chunk = self.pos().chunk();
+    for other in self.world().player_list() {
+      if other.id() == self.id() {
+        continue;
+      }
+      if vanished {
+        if other.in_view(chunk) {
+          other.send(cb::packet::RemoveEntities { eids: vec![self.eid()] });
+        }
+        other.send(cb::packet::PlayerList {
+          action: cb::PlayerListAction::Remove(vec![cb::PlayerListRemove { id: self.id() }]),
+        });
+      } else {
+        other.send(cb::packet::PlayerList {
+          action: cb::PlayerListAction::Add(vec![self.player_list_info()]),
+        });
+        if other.in_view(chunk) {
+          self.spawn_for(&other);
+        }
+      }
+    }
   }
 
   /// Returns the current velocity of the player.
@@ -788,11 +1182,59 @@ impl Player {
   /// Attacks the given player. This is private to the crate, as it should only
   /// be called when we get an attack packet.
   pub(super) fn attack(&self, other: EntityRef) {
+    if let Some(target) = other.as_player() {
+      // Teammates can't hurt each other, unless their team has friendly fire.
+      let same_team = self.world().world_manager().teams().values().any(|team| {
+        let team = team.lock();
+        !team.friendly_fire() && team.contains(self.id()) && team.contains(target.id())
+      });
+      if same_team {
+        return;
+      }
+    }
     // Handles base damage and enchantments
-    let damage = self.lock_inventory().main_hand().attack_damage();
+    let (damage, wear) = {
+      let inv = self.lock_inventory();
+      (inv.main_hand().attack_damage(), inv.main_hand().item().attack_wear())
+    };
     // TODO: Strength
     let v = self.look_as_vec() * 0.4;
-    other.damage(damage, true, Vec3::new(v.x, 0.4, v.z));
+    if other.damage(damage, true, Vec3::new(v.x, 0.4, v.z)) {
+      self.damage_held_item(wear);
+    }
+  }
+
+  /// Damages the item in the player's main hand by `amount`, and sends the new
+  /// item to the client. If the item breaks, it is removed, and the break sound
+  /// is played. This does nothing in creative or spectator mode, or if the item
+  /// can't be damaged.
+  pub fn damage_held_item(&self, amount: u32) {
+    if amount == 0 || !matches!(self.game_mode(), GameMode::Survival | GameMode::Adventure) {
+      return;
+    }
+    let mut inv = self.lock_inventory();
+    let mut stack = inv.main_hand().clone();
+    if !stack.is_damageable() {
+      return;
+    }
+    let broke = stack.add_damage(amount);
+    let index = inv.selected_index() as u32;
+    inv.hotbar_mut().set_raw(index, stack);
+    drop(inv);
+    if broke {
+      self.send_to_in_view(cb::packet::EntityEquipment {
+        eid:  self.eid(),
+        slot: cb::EquipmentSlot::Hand(Hand::Main),
+        item: Stack::empty().to_item(),
+      });
+      self.send_all_in_view(cb::packet::PlaySound {
+        name:     "entity.item.break".into(),
+        category: cb::SoundCategory::Players,
+        pos:      self.pos(),
+        volume:   0.8,
+        pitch:    1.0,
+      });
+    }
   }
 
   /// Returns true if the player can be damaged. This will return `false` if
@@ -909,8 +1351,14 @@ impl Player {
 
 impl CommandSender for &Arc<Player> {
   fn block_pos(&self) -> Option<Pos> { Some(self.as_ref().block_pos()) }
+  fn pos(&self) -> Option<FPos> { Some(self.as_ref().pos()) }
+  fn rotation(&self) -> Option<(f32, f32)> {
+    let (pitch, yaw) = self.as_ref().look();
+    Some((yaw, pitch))
+  }
   fn send_message(&mut self, msg: Chat) { self.as_ref().send_message(msg); }
   fn error_format(&self) -> ErrorFormat { ErrorFormat::Minecraft }
+  fn op_level(&self) -> u8 { self.as_ref().op_level() }
   fn as_player(&self) -> Option<&Arc<Player>> { Some(self) }
 }
 
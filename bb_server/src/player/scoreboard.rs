@@ -1,12 +1,19 @@
 use crate::net::ConnSender;
 use bb_common::{net::cb, util::Chat};
+use std::collections::HashMap;
+
+/// The objective used by the line based API. Custom objectives cannot use this
+/// name.
+const LINES_OBJECTIVE: &str = "scoreboard";
 
 #[derive(Debug)]
 pub struct Scoreboard {
-  conn:     ConnSender,
-  position: cb::ScoreboardDisplayPosition,
-  shown:    bool,
-  lines:    Vec<String>,
+  conn:       ConnSender,
+  position:   cb::ScoreboardDisplayPosition,
+  shown:      bool,
+  lines:      Vec<String>,
+  /// A map of objective names to the scores within that objective.
+  objectives: HashMap<String, HashMap<String, i32>>,
 }
 
 impl Scoreboard {
@@ -16,13 +23,14 @@ impl Scoreboard {
       position: cb::ScoreboardDisplayPosition::Sidebar,
       shown: false,
       lines: vec!["".into(); 15],
+      objectives: HashMap::new(),
     }
   }
 
   pub fn show(&mut self) {
     if !self.shown {
       self.conn.send(cb::packet::ScoreboardObjective {
-        objective: "scoreboard".into(),
+        objective: LINES_OBJECTIVE.into(),
         mode:      cb::ObjectiveAction::Create {
           value: Chat::new("Scoreboard"),
           ty:    cb::ObjectiveType::Integer,
@@ -30,16 +38,16 @@ impl Scoreboard {
       });
       self.conn.send(cb::packet::ScoreboardDisplay {
         position:  self.position,
-        objective: "scoreboard".into(),
+        objective: LINES_OBJECTIVE.into(),
       });
       self.shown = true;
     }
   }
 
   pub fn hide(&mut self) {
-    if !self.shown {
+    if self.shown {
       self.conn.send(cb::packet::ScoreboardObjective {
-        objective: "scoreboard".into(),
+        objective: LINES_OBJECTIVE.into(),
         mode:      cb::ObjectiveAction::Remove,
       });
       self.shown = false;
@@ -50,7 +58,9 @@ impl Scoreboard {
     if position != self.position {
       self.position = position;
       if self.shown {
-        self.conn.send(cb::packet::ScoreboardDisplay { position, objective: "scoreboard".into() });
+        self
+          .conn
+          .send(cb::packet::ScoreboardDisplay { position, objective: LINES_OBJECTIVE.into() });
       }
     }
   }
@@ -58,7 +68,7 @@ impl Scoreboard {
   pub fn clear_line(&mut self, line: u8) {
     self.conn.send(cb::packet::ScoreboardUpdate {
       username:  self.lines[line as usize].clone(),
-      objective: "scoreboard".into(),
+      objective: LINES_OBJECTIVE.into(),
       action:    cb::ScoreboardAction::Remove,
     });
     self.lines[line as usize] = "".into();
@@ -73,14 +83,90 @@ impl Scoreboard {
     }
     self.conn.send(cb::packet::ScoreboardUpdate {
       username:  self.lines[line as usize].clone(),
-      objective: "scoreboard".into(),
+      objective: LINES_OBJECTIVE.into(),
       action:    cb::ScoreboardAction::Remove,
     });
     self.lines[line as usize] = text;
     self.conn.send(cb::packet::ScoreboardUpdate {
       username:  self.lines[line as usize].clone(),
-      objective: "scoreboard".into(),
+      objective: LINES_OBJECTIVE.into(),
       action:    cb::ScoreboardAction::Create(line.into()),
     });
   }
+
+  /// Creates a new objective with the given name. If the objective already
+  /// exists, this will update its display name instead. The name `scoreboard`
+  /// is used by the line based API above, so it is rejected, and this returns
+  /// `false`.
+  pub fn create_objective(&mut self, name: &str, display: Chat) -> bool {
+    if name == LINES_OBJECTIVE {
+      return false;
+    }
+    let ty = cb::ObjectiveType::Integer;
+    if self.objectives.contains_key(name) {
+      self.conn.send(cb::packet::ScoreboardObjective {
+        objective: name.into(),
+        mode:      cb::ObjectiveAction::Update { value: display, ty },
+      });
+    } else {
+      self.objectives.insert(name.into(), HashMap::new());
+      self.conn.send(cb::packet::ScoreboardObjective {
+        objective: name.into(),
+        mode:      cb::ObjectiveAction::Create { value: display, ty },
+      });
+    }
+    true
+  }
+
+  /// Removes the given objective. This will also remove it from any display
+  /// slot it was shown in. Does nothing if the objective doesn't exist.
+  pub fn remove_objective(&mut self, name: &str) {
+    if self.objectives.remove(name).is_some() {
+      self.conn.send(cb::packet::ScoreboardObjective {
+        objective: name.into(),
+        mode:      cb::ObjectiveAction::Remove,
+      });
+    }
+  }
+
+  /// Sets the score of `entry` within the given objective. Does nothing if the
+  /// objective doesn't exist.
+  pub fn set_score(&mut self, objective: &str, entry: &str, value: i32) {
+    if let Some(obj) = self.objectives.get_mut(objective) {
+      if obj.insert(entry.into(), value) == Some(value) {
+        return;
+      }
+      self.conn.send(cb::packet::ScoreboardUpdate {
+        username:  entry.into(),
+        objective: objective.into(),
+        action:    cb::ScoreboardAction::Create(value),
+      });
+    }
+  }
+
+  /// Returns the score of `entry` within the given objective, if present.
+  pub fn score(&self, objective: &str, entry: &str) -> Option<i32> {
+    self.objectives.get(objective)?.get(entry).copied()
+  }
+
+  /// Removes `entry` from the given objective.
+  pub fn remove_score(&mut self, objective: &str, entry: &str) {
+    if let Some(obj) = self.objectives.get_mut(objective) {
+      if obj.remove(entry).is_some() {
+        self.conn.send(cb::packet::ScoreboardUpdate {
+          username:  entry.into(),
+          objective: objective.into(),
+          action:    cb::ScoreboardAction::Remove,
+        });
+      }
+    }
+  }
+
+  /// Shows the given objective in the display slot `position`. Does nothing if
+  /// the objective doesn't exist.
+  pub fn display_objective(&mut self, position: cb::ScoreboardDisplayPosition, objective: &str) {
+    if self.objectives.contains_key(objective) {
+      self.conn.send(cb::packet::ScoreboardDisplay { position, objective: objective.into() });
+    }
+  }
 }
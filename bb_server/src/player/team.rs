@@ -30,8 +30,6 @@ pub struct Team {
 
 impl Team {
   pub fn new(wm: Arc<WorldManager>, name: String) -> Self {
-    let mut prefix = Chat::empty();
-    prefix.add("[BIG] ").color(Color::Red);
     let t = Team {
       name: name.clone(),
       members: HashSet::new(),
@@ -41,8 +39,8 @@ impl Team {
       name_tag_rule: TeamRule::Always,
       collision_rule: TeamRule::Always,
       color: Color::White,
-      prefix,
-      postfix: Chat::new(" whaa"),
+      prefix: Chat::empty(),
+      postfix: Chat::empty(),
       wm,
     };
     let out = cb::packet::Teams {
@@ -69,8 +67,43 @@ impl Team {
     self.update_info();
   }
 
+  /// Sets the display name of this team.
+  pub fn set_display_name(&mut self, name: Chat) {
+    self.display_name = name;
+    self.update_info();
+  }
+  /// Returns `true` if players on this team can attack each other.
+  pub fn friendly_fire(&self) -> bool { self.friendly_fire }
+  /// Sets if players on this team can attack each other.
+  pub fn set_friendly_fire(&mut self, friendly_fire: bool) {
+    self.friendly_fire = friendly_fire;
+    self.update_info();
+  }
+  /// Sets if players on this team can see invisible teammates.
+  pub fn set_see_invis(&mut self, see_invis: bool) {
+    self.see_invis = see_invis;
+    self.update_info();
+  }
+  /// Sets who can push players on this team.
+  pub fn set_collision_rule(&mut self, rule: TeamRule) {
+    self.collision_rule = rule;
+    self.update_info();
+  }
+  /// Sets who can see the name tags of players on this team.
+  pub fn set_name_tag_visibility(&mut self, rule: TeamRule) {
+    self.name_tag_rule = rule;
+    self.update_info();
+  }
+
+  /// Returns true if the given player is on this team.
+  pub fn contains(&self, id: UUID) -> bool { self.members.contains(&id) }
+
+  /// Adds the player to this team. Their name will show the team prefix,
+  /// postfix, and color above their head and in the tab list.
   pub fn add(&mut self, player: &Player) {
-    self.members.insert(player.id());
+    if !self.members.insert(player.id()) {
+      return;
+    }
     let out = cb::packet::Teams {
       team:   self.name.clone(),
       action: TeamAction::AddEntities { entities: vec![player.username().clone()] },
@@ -78,6 +111,19 @@ impl Team {
     self.wm.send_to_all(out);
   }
 
+  /// Removes the player from this team. Does nothing if they are not on this
+  /// team.
+  pub fn remove(&mut self, player: &Player) {
+    if !self.members.remove(&player.id()) {
+      return;
+    }
+    let out = cb::packet::Teams {
+      team:   self.name.clone(),
+      action: TeamAction::RemoveEntities { entities: vec![player.username().clone()] },
+    };
+    self.wm.send_to_all(out);
+  }
+
   fn update_info(&self) {
     let out = cb::packet::Teams {
       team:   self.name.clone(),
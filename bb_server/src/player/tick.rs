@@ -1,7 +1,7 @@
 use super::{DigProgress, Player, PlayerPosition};
 use crate::{block, event, event::EventFlow, math::Vec3};
 use bb_common::{
-  math::{ChunkPos, Pos},
+  math::{ChunkPos, FPos, Pos},
   net::cb,
   version::ProtocolVersion,
 };
@@ -11,6 +11,11 @@ use std::{
   time::{Duration, Instant},
 };
 
+/// The furthest a player can move in a single tick, measured along every
+/// position they sent during that tick. This matches the distance at which
+/// vanilla rejects a move.
+const MAX_MOVE_PER_TICK: f64 = 10.0;
+
 impl DigProgress {
   pub fn new(pos: Pos, kind: block::Kind) -> Self {
     DigProgress { progress: 0.0, pos, kind, wants_finish: false }
@@ -18,10 +23,47 @@ impl DigProgress {
 }
 
 impl Player {
+  /// Puts out or lights the player, depending on the fluid at their feet, and
+  /// counts down the fire. Burning deals 1 damage every second. This must be
+  /// called before the player's health is locked.
+  fn tick_fire(self: &Arc<Self>) {
+    match self.world().get_kind(self.pos().block()) {
+      Ok(block::Kind::Water) => {
+        self.set_on_fire(0);
+        return;
+      }
+      Ok(block::Kind::Lava) => {
+        // Vanilla sets players on fire for 15 seconds when they touch lava.
+        self.set_on_fire(300);
+        self.damage(4.0, false, Vec3::new(0.0, 0.0, 0.0));
+        return;
+      }
+      _ => {}
+    }
+    let ticks = {
+      let mut fire = self.fire.lock();
+      if *fire == 0 {
+        return;
+      }
+      *fire -= 1;
+      *fire
+    };
+    if ticks == 0 {
+      self.send_status_byte();
+    } else if ticks % 20 == 0 {
+      self.damage(1.0, false, Vec3::new(0.0, 0.0, 0.0));
+    }
+  }
+
   /// Updates the player's position/velocity. This will apply gravity, and do
   /// collision checks. Should never be called at a different rate than the
   /// global tick rate.
   pub(crate) fn tick(self: &Arc<Self>) {
+    self.tick_effects();
+    self.tick_fire();
+    // Slowness could make a client that is a tick behind fail the movement
+    // checks, so we only ever loosen them.
+    let speed = self.speed_multiplier().max(1.0);
     let mut health = self.health.lock();
     let old_chunk;
     let new_chunk;
@@ -60,7 +102,13 @@ impl Player {
         // goes above 2 every now and then.
         let flying = self.flying();
         let accel_len = accel.len();
-        if (!flying && accel_len > 3.0) || (flying && accel_len > f64::from(self.fly_speed()) * 5.0)
+        // A client could send a single large jump, followed by a position near
+        // where they started. So we check the whole path they sent, not just where
+        // they ended up.
+        let path_len = path_len(pos.curr, &pos.path);
+        if (!flying && accel_len > 3.0 * speed)
+          || (flying && accel_len > f64::from(self.fly_speed()) * 5.0 * speed)
+          || path_len > MAX_MOVE_PER_TICK * speed
         {
           warn!(
             "{} moved too fast (pos: {} {} {})",
@@ -81,6 +129,7 @@ impl Player {
         }
       }
       pos.vel = (pos.curr - pos.prev).into();
+      pos.path.clear();
 
       pos.yaw = pos.next_yaw;
       // We want to keep yaw within -180..=180
@@ -129,8 +178,10 @@ impl Player {
     }
     // Handle edge case for players sending dig finish too early.
     self.check_dig_wants_finish();
-    if pos_changed || look_changed {
-      for other in self.world.players().iter().in_view(pos.curr.chunk()).not(self.uuid) {
+    // Nobody else has spawned a vanished player, so they don't need movement
+    // updates.
+    if (pos_changed || look_changed) && !self.is_vanished() {
+      for other in self.world().players().iter().in_view(pos.curr.chunk()).not(self.uuid) {
         // Make player move for other
         let yaw;
         let pitch;
@@ -288,7 +339,7 @@ impl Player {
   }
 
   /// Loads the chunks between min and max, inclusive.
-  fn load_chunks(self: &Arc<Self>, min: ChunkPos, max: ChunkPos) {
+  pub(super) fn load_chunks(self: &Arc<Self>, min: ChunkPos, max: ChunkPos) {
     if min == max {
       return;
     }
@@ -297,29 +348,45 @@ impl Player {
     //
     // `queue_chunk` will simply send the chunk if it has already been loaded, so
     // there aren't any race conditions in this function.
+    //
+    // All the chunks that are already loaded are sent in one batch.
+    let world = self.world();
+    let mut packets = vec![];
     for x in min.x()..=max.x() {
       for z in min.z()..=max.z() {
         let pos = ChunkPos::new(x, z);
-        if self.world.has_loaded_chunk(pos) {
-          self.send_chunk(pos, || self.world.serialize_chunk(pos).into());
+        if world.has_loaded_chunk(pos) {
+          if self.mark_chunk_sent(pos) {
+            packets.push(world.serialize_chunk(pos).into());
+          }
         } else {
-          self.world.queue_chunk(pos, self);
+          world.queue_chunk(pos, self);
         }
       }
     }
+    self.send_all(packets);
   }
   /// Sends the chunk to the client, and records that the client knows about
   /// this chunk. This makes sure we avoid ever leaking memory on the client.
   pub(crate) fn send_chunk(&self, pos: ChunkPos, f: impl FnOnce() -> cb::Packet) {
+    if self.mark_chunk_sent(pos) {
+      self.send(f());
+    }
+  }
+  /// Records that the client knows about this chunk, if it is in view and
+  /// hasn't been sent already. If this returns `true`, the chunk must be sent
+  /// to the client.
+  fn mark_chunk_sent(&self, pos: ChunkPos) -> bool {
     if self.in_view(pos) {
       let mut lock = self.loaded_chunks.lock();
       if !lock.contains(&pos) {
         lock.insert(pos);
         drop(lock);
-        self.world.inc_view(pos);
-        self.send(f());
+        self.world().inc_view(pos);
+        return true;
       }
     }
+    false
   }
   /// Sends the unload packet for this chunk to the client, and records that the
   /// client no longer has that chunk in memory.
@@ -327,7 +394,7 @@ impl Player {
     let mut lock = self.loaded_chunks.lock();
     if lock.remove(&pos) {
       drop(lock);
-      self.world.dec_view(pos);
+      self.world().dec_view(pos);
       self.send(cb::packet::UnloadChunk { pos });
     }
   }
@@ -349,9 +416,10 @@ impl Player {
     let min = chunk - ChunkPos::new(v, v);
     for x in min.x()..=max.x() {
       for z in min.z()..=max.z() {
-        self.world.dec_view(ChunkPos::new(x, z));
+        self.world().dec_view(ChunkPos::new(x, z));
       }
     }
+    self.loaded_chunks.lock().clear();
   }
   fn unload_chunks(&self, min: ChunkPos, max: ChunkPos) {
     if min == max {
@@ -360,7 +428,7 @@ impl Player {
     for x in min.x()..=max.x() {
       for z in min.z()..=max.z() {
         let pos = ChunkPos::new(x, z);
-        self.world.unqueue_chunk(pos, self);
+        self.world().unqueue_chunk(pos, self);
         self.send_unload_chunk(pos);
       }
     }
@@ -368,7 +436,7 @@ impl Player {
 
   pub(crate) fn start_digging(self: &Arc<Self>, pos: Pos) {
     // Silently ignore dig packets outside the world.
-    if let Ok(kind) = self.world.get_kind(pos) {
+    if let Ok(kind) = self.world().get_kind(pos) {
       let mut ppos = self.pos.lock();
       let speed = self.mining_speed(ppos.curr.block(), kind);
       if speed >= 1.0 {
@@ -377,7 +445,7 @@ impl Player {
         if self.block_break_event(pos).is_handled() {
           self.sync_block_at(pos);
         } else {
-          let _ = self.world.break_block(pos);
+          self.dig_block(pos);
         }
       } else {
         let mut progress = DigProgress::new(pos, kind);
@@ -415,7 +483,7 @@ impl Player {
     }
     if finished {
       if self.block_break_event(pos).is_continue() {
-        if !self.world().break_block(pos).unwrap() {
+        if !self.dig_block(pos) {
           self.sync_block_at(pos);
         }
       } else {
@@ -433,12 +501,30 @@ impl Player {
     })
   }
 
+  /// Breaks the block at `pos`, and damages the item this player is holding.
+  /// Blocks that break instantly (like grass) don't damage the item. Returns
+  /// `false` if the block couldn't be broken.
+  fn dig_block(self: &Arc<Player>, pos: Pos) -> bool {
+    let kind = match self.world().get_kind(pos) {
+      Ok(kind) => kind,
+      Err(_) => return false,
+    };
+    if !self.world().break_block(pos).unwrap_or(false) {
+      return false;
+    }
+    if self.world().world_manager().block_converter().get(kind).hardness > 0.0 {
+      let wear = self.lock_inventory().main_hand().item().break_wear();
+      self.damage_held_item(wear);
+    }
+    true
+  }
+
   fn mining_speed(&self, curr_pos: Pos, kind: block::Kind) -> f64 {
     // Handles block/item type, and efficiency levels
     let mut speed = self
       .lock_inventory()
       .main_hand()
-      .mining_speed(self.world.world_manager().block_converter().get(kind));
+      .mining_speed(self.world().world_manager().block_converter().get(kind));
 
     // TODO: Multiply by haste:
     // if self.has_haste() {
@@ -454,7 +540,7 @@ impl Player {
     // }
 
     // If we are standing in water, digging is 5 times slower.
-    if self.world.get_kind(curr_pos) == Ok(block::Kind::Water) {
+    if self.world().get_kind(curr_pos) == Ok(block::Kind::Water) {
       speed *= 0.2;
     }
 
@@ -498,7 +584,7 @@ impl Player {
         })
         .is_continue()
       {
-        if !self.world().break_block(pos).unwrap() {
+        if !self.dig_block(pos) {
           self.sync_block_at(pos);
         }
       } else {
@@ -507,3 +593,15 @@ impl Player {
     }
   }
 }
+
+/// Returns the total distance travelled from `start`, through every position in
+/// `path`.
+fn path_len(start: FPos, path: &[FPos]) -> f64 {
+  let mut prev = start;
+  let mut len = 0.0;
+  for &p in path {
+    len += p.dist(prev);
+    prev = p;
+  }
+  len
+}
@@ -17,7 +17,7 @@ trait WindowData {
     F: FnOnce(&mut Stack) -> R;
   fn size(&self) -> u32;
   fn add(&mut self, stack: Stack) -> u8;
-  fn open(&self, id: UUID, conn: &ConnSender);
+  fn open(&self, id: UUID, conn: &ConnSender, wid: u8);
   fn close(&self, id: UUID);
 }
 
@@ -30,7 +30,7 @@ pub struct GenericWindow<const N: usize> {
   pub inv: SharedInventory<N>,
 }
 
-#[derive(Window, Debug, Clone)]
+#[derive(Window, Debug, Default, Clone)]
 pub struct SmeltingWindow {
   pub input:  SharedInventory<1>,
   // #[filter(fuel)]
@@ -39,6 +39,20 @@ pub struct SmeltingWindow {
   pub output: SharedInventory<1>,
 }
 
+#[derive(Window, Debug, Default, Clone)]
+pub struct AnvilWindow {
+  pub left:   SharedInventory<1>,
+  pub right:  SharedInventory<1>,
+  #[output]
+  pub output: SharedInventory<1>,
+}
+
+#[derive(Window, Debug, Default, Clone)]
+pub struct EnchantmentWindow {
+  pub item:  SharedInventory<1>,
+  pub lapis: SharedInventory<1>,
+}
+
 #[derive(Window, Debug, Clone)]
 pub struct CraftingWindow {
   #[output]
@@ -50,6 +64,8 @@ pub struct CraftingWindow {
 
 impl<const N: usize> WindowHandler for GenericWindow<N> {}
 impl WindowHandler for SmeltingWindow {}
+impl WindowHandler for AnvilWindow {}
+impl WindowHandler for EnchantmentWindow {}
 
 impl WindowHandler for CraftingWindow {
   fn on_update(&self, clicked: Option<u32>) {
@@ -88,9 +104,13 @@ pub enum Window {
   Generic3x3(GenericWindow<9>),
   #[name("minecraft:crafting")]
   Crafting(CraftingWindow),
-  /*
   #[name("minecraft:anvil")]
-  Anvil(Anvil),
+  Anvil(AnvilWindow),
+  #[name("minecraft:enchantment")]
+  Enchantment(EnchantmentWindow),
+  #[name("minecraft:furnace")]
+  Furnace(SmeltingWindow),
+  /*
   #[name("minecraft:beacon")]
   Beacon(inv: SharedInventory<1>),
   #[name("minecraft:blast_furnace")]
@@ -101,16 +121,6 @@ pub enum Window {
     ingredient: SharedInventory<1>,
     fuel:       SharedInventory<1>,
   },
-  #[name("minecraft:enchantment")]
-  Enchantment { book: SharedInventory<1>, lapis: SharedInventory<1> },
-  #[name("minecraft:furnace")]
-  Furnace {
-    input:  SharedInventory<1>,
-    #[filter(fuel)]
-    fuel:   SharedInventory<1>,
-    #[output]
-    output: SharedInventory<1>,
-  },
   #[name("minecraft:grindstone")]
   Grindstone {
     inputs: SharedInventory<2>,
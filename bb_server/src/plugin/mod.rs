@@ -54,7 +54,7 @@ use ::panda::runtime::{tree::Closure, LockedEnv, VarSend};
 use config::Config;
 use crossbeam_channel::{Receiver, Sender};
 use parking_lot::{Mutex, MutexGuard};
-use std::{error::Error, fmt, sync::Arc, thread};
+use std::{collections::HashMap, error::Error, fmt, sync::Arc, thread};
 
 #[derive(Debug)]
 struct Scheduled {
@@ -104,6 +104,8 @@ pub struct Bamboo {
   /// things within a scheduled callback. This will never be locked while
   /// `scheduled` is locked.
   scheduled_backup: Arc<Mutex<Vec<Scheduled>>>,
+  /// Closures registered with `on`, keyed by event name.
+  listeners:        Arc<Mutex<HashMap<String, Vec<Closure>>>>,
 }
 
 impl Bamboo {
@@ -114,6 +116,7 @@ impl Bamboo {
       data: Arc::new(Mutex::new(Some(VarSend::None))),
       scheduled: Arc::new(Mutex::new(vec![])),
       scheduled_backup: Arc::new(Mutex::new(vec![])),
+      listeners: Arc::new(Mutex::new(HashMap::new())),
     }
   }
 }
@@ -127,13 +130,13 @@ use self::panda::PandaPlugin;
 
 pub trait PluginImpl: std::any::Any {
   /// Calls an event. There is no reply for `GlobalEvent`.
-  fn call_global(&self, event: GlobalEvent) -> Result<(), CallError>;
+  fn call_global(&mut self, event: GlobalEvent) -> Result<(), CallError>;
   /// Calls an event. There is no reply for `PlayerEvent`. If an error is
   /// thrown, it will be logged, and the plugin will be removed if `keep` is
   /// `false`.
-  fn call(&self, event: PlayerEvent) -> Result<(), CallError>;
+  fn call(&mut self, event: PlayerEvent) -> Result<(), CallError>;
   /// Calls an event. This should block until it gets a reply.
-  fn req(&self, event: PlayerRequest) -> Result<PluginReply, CallError>;
+  fn req(&mut self, event: PlayerRequest) -> Result<PluginReply, CallError>;
   #[cfg(feature = "panda_plugins")]
   fn panda(&mut self) -> Option<&mut PandaPlugin> { None }
 }
@@ -106,30 +106,53 @@ impl PandaPlugin {
   /// functions.
   pub fn bb(&self) -> Bamboo { self.bb.clone() }
 
-  pub fn call_init(&self) { self.call("init", vec![]); }
+  pub fn call_init(&mut self) { self.call("init", vec![]); }
 
-  pub fn call(&self, name: &str, args: Vec<Var>) {
+  pub fn call(&mut self, name: &str, args: Vec<Var>) {
     match &self.sl {
-      Some(sl) => match sl.run_callback(name, args) {
+      Some(sl) => match sl.run_callback(name, args.clone()) {
         Ok(v) => v,
         Err(e) => self.print_err(e),
       },
       None => {}
     }
+    self.call_listeners(name, &args);
   }
-  pub fn req(&self, name: &str, mut args: Vec<Var>) -> bool {
+  pub fn req(&mut self, name: &str, mut args: Vec<Var>) -> bool {
     let event = super::types::event::PEventFlow::new();
     args.push(event.clone().into());
     match &self.sl {
-      Some(sl) => match sl.run_callback(name, args) {
+      Some(sl) => match sl.run_callback(name, args.clone()) {
         Ok(_) => {}
         Err(e) => self.print_err(e),
       },
       None => {}
     }
+    if !self.call_listeners(name, &args) {
+      event.cancel();
+    }
     !event.is_allowed()
   }
 
+  /// Calls all the closures registered with `bamboo.on` for the given event.
+  /// Returns `false` if any of those closures returned `false`.
+  fn call_listeners(&mut self, name: &str, args: &[Var]) -> bool {
+    if self.sl.is_none() {
+      return true;
+    }
+    let listeners = self.bb.listeners.lock().get(name).cloned().unwrap_or_default();
+    let mut allow = true;
+    for c in listeners {
+      let res = c.call(&mut self.lock_env(), args.to_vec());
+      match res {
+        Ok(Var::Bool(false)) => allow = false,
+        Ok(_) => {}
+        Err(e) => self.print_err(e),
+      }
+    }
+    allow
+  }
+
   pub fn print_err<E: PdError>(&self, err: E) {
     match &self.sl {
       Some(sl) => warn!("error in plugin `{}`:\n{}", self.name, sl.gen_err(err)),
@@ -139,15 +162,15 @@ impl PandaPlugin {
 }
 
 impl PluginImpl for PandaPlugin {
-  fn call_global(&self, ev: GlobalEvent) -> Result<(), CallError> {
+  fn call_global(&mut self, ev: GlobalEvent) -> Result<(), CallError> {
     self.call(ev.name(), vec![ev.into_panda()]);
     Ok(())
   }
-  fn call(&self, ev: PlayerEvent) -> Result<(), CallError> {
+  fn call(&mut self, ev: PlayerEvent) -> Result<(), CallError> {
     self.call(ev.name(), vec![ev.into_panda()]);
     Ok(())
   }
-  fn req(&self, req: PlayerRequest) -> Result<PluginReply, CallError> {
+  fn req(&mut self, req: PlayerRequest) -> Result<PluginReply, CallError> {
     Ok(PluginReply::Cancel { allow: self.req(req.name(), vec![req.into_panda()]) })
   }
   fn panda(&mut self) -> Option<&mut PandaPlugin> { Some(self) }
@@ -82,15 +82,15 @@ impl Plugin {
 }
 
 impl PluginImpl for Plugin {
-  fn call_global(&self, ev: GlobalEvent) -> Result<(), CallError> {
+  fn call_global(&mut self, ev: GlobalEvent) -> Result<(), CallError> {
     self.tx.send(Event { ev, reply: false }).map_err(CallError::no_keep)?;
     Ok(())
   }
-  fn call(&self, ev: PlayerEvent) -> Result<(), CallError> {
+  fn call(&mut self, ev: PlayerEvent) -> Result<(), CallError> {
     // self.tx.send(()).map_err(CallError::no_keep)?;
     Ok(())
   }
-  fn req(&self, req: PlayerRequest) -> Result<PluginReply, CallError> {
+  fn req(&mut self, req: PlayerRequest) -> Result<PluginReply, CallError> {
     // Ok(PluginReply::Cancel { allow: self.req(req.name(),
     // vec![req.into_panda()]) })
     Ok(PluginReply::Cancel { allow: true })
@@ -72,10 +72,10 @@ pub fn sl_from_arg(
     Arg::ScoreHolder(String),
     Arg::GameProfile(EntitySelector),
     */
-    Arg::BlockPos(pos) => PPos::from(pos).into(),
+    Arg::BlockPos(pos, _) => PPos::from(pos).into(),
     Arg::ColumnPos(pos) => PChunkPos::from(pos).into(),
     /*
-    Arg::Vec3(f64, f64, f64),
+    Arg::Vec3(FPos, [CoordKind; 3]),
     Arg::Vec2(f64, f64),
     */
     Arg::BlockState(kind, _props, _nbt) => PBlockKind::from(kind).into(),
@@ -92,10 +92,10 @@ pub fn sl_from_arg(
     ObjectiveCriteria(String),
     Operation(String),
     Particle(String), // TODO: Particles
-    Rotation(f32, f32),
+    Rotation { yaw: f32, pitch: f32, yaw_rel: bool, pitch_rel: bool },
     Angle(f32),
     ScoreboardSlot(String),
-    Swizzle(f64, f64, f64),
+    Swizzle { x: bool, y: bool, z: bool },
     Team(String),
     /// A name for an inventory slot. Unclear on what is valid. Parsed as a string
     /// for now.
@@ -113,12 +113,12 @@ pub fn sl_from_arg(
       max: f64,
     },
     IntRange {
-      min: i32,
-      max: i32,
+      min: Option<i32>,
+      max: Option<i32>,
     },
     FloatRange {
-      min: f64,
-      max: f64,
+      min: Option<f64>,
+      max: Option<f64>,
     },
     /// Represents a item enchantment. Parsed as a string.
     ItemEnchantment(String),
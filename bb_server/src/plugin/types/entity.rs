@@ -0,0 +1,87 @@
+use super::{player::PPlayer, util::PFPos, world::PWorld};
+use crate::{
+  entity::{
+    behavior::{Behavior, ShouldDespawn},
+    EntityData, EntityPos,
+  },
+  math::Vec3,
+  player::Player,
+  world::{World, WorldManager},
+};
+use panda::runtime::{tree::Closure, Var};
+use std::sync::Arc;
+
+/// An entity behavior that calls into a plugin every tick, and optionally
+/// when the entity is damaged or interacted with. This is created by
+/// `Bamboo::summon_custom`.
+pub struct PluginBehavior {
+  wm:          Arc<WorldManager>,
+  // Index into plugins array
+  idx:         usize,
+  on_tick:     Closure,
+  on_damage:   Option<Closure>,
+  on_interact: Option<Closure>,
+}
+
+impl PluginBehavior {
+  pub fn new(
+    wm: Arc<WorldManager>,
+    idx: usize,
+    on_tick: Closure,
+    on_damage: Option<Closure>,
+    on_interact: Option<Closure>,
+  ) -> Self {
+    PluginBehavior { wm, idx, on_tick, on_damage, on_interact }
+  }
+
+  /// Calls the given closure in this plugin. Returns `None` if the plugin
+  /// isn't a panda plugin, or if the closure returned an error.
+  fn call(&self, cb: &Closure, args: Vec<Var>) -> Option<Var> {
+    let lock = self.wm.plugins().plugins.lock();
+    let mut imp = lock[self.idx].lock_imp();
+    let panda = imp.panda()?;
+    match cb.call(&mut panda.lock_env(), args) {
+      Ok(v) => Some(v),
+      Err(e) => {
+        panda.print_err(e);
+        None
+      }
+    }
+  }
+}
+
+impl Behavior for PluginBehavior {
+  fn tick(&mut self, world: &Arc<World>, ent: &EntityData, p: &mut EntityPos) -> ShouldDespawn {
+    self.apply_physics(p);
+    // The callback is passed the world, the entity id, and the entity's position
+    // after this tick's movement.
+    let args =
+      vec![PWorld::from(world.clone()).into(), ent.eid().into(), PFPos::from(p.aabb.pos).into()];
+    self.call(&self.on_tick, args);
+    ShouldDespawn(false)
+  }
+
+  fn damage(&mut self, world: &Arc<World>, ent: &EntityData, amount: f32, _: Vec3) -> bool {
+    // The callback is passed the world, the entity id, and the amount of damage.
+    // Returning `false` cancels the damage.
+    match &self.on_damage {
+      Some(cb) => {
+        let args = vec![PWorld::from(world.clone()).into(), ent.eid().into(), amount.into()];
+        !matches!(self.call(cb, args), Some(Var::Bool(false)))
+      }
+      None => true,
+    }
+  }
+
+  fn interact(&mut self, world: &Arc<World>, ent: &EntityData, player: &Arc<Player>) {
+    // The callback is passed the world, the entity id, and the player.
+    if let Some(cb) = &self.on_interact {
+      let args = vec![
+        PWorld::from(world.clone()).into(),
+        ent.eid().into(),
+        PPlayer::from(player.clone()).into(),
+      ];
+      self.call(cb, args);
+    }
+  }
+}
@@ -23,6 +23,7 @@ use panda::{
 pub mod block;
 pub mod chat;
 pub mod command;
+pub mod entity;
 pub mod event;
 pub mod item;
 pub mod particle;
@@ -33,6 +34,7 @@ pub mod world;
 use command::PCommand;
 use player::PTeam;
 use std::{fmt, sync::Arc};
+use util::PFPos;
 use world::{gen::PBiome, PWorld};
 
 pub trait Callback: fmt::Debug + Send + Sync {
@@ -154,20 +156,14 @@ impl Bamboo {
           let plugin = &mut lock[idx];
           let mut imp = plugin.lock_imp();
           let panda = imp.panda().unwrap();
-          let default_world = wm.default_world();
+          let world = player.map(|p| p.world()).unwrap_or_else(|| wm.default_world());
           if let Err(e) = cb.call_panda(
             &mut panda.lock_env(),
             vec![
               player.map(|p| player::PPlayer::from(p.clone()).into()).unwrap_or(Var::None),
               args
                 .iter()
-                .map(|arg| {
-                  command::sl_from_arg(
-                    arg.clone(),
-                    player.map(|p| p.world()).unwrap_or(&default_world),
-                    player,
-                  )
-                })
+                .map(|arg| command::sl_from_arg(arg.clone(), &world, player))
                 .collect::<Vec<Var>>()
                 .into(),
             ],
@@ -191,6 +187,62 @@ impl Bamboo {
     });
     Ok(())
   }
+  /// Summons an entity with custom functionality. Returns the new entity's id.
+  ///
+  /// `on_tick` will be called every tick, after the entity has moved. It is
+  /// passed the world, the entity id, and the entity's position.
+  ///
+  /// `on_damage` and `on_interact` may be `none`. `on_damage` is called when
+  /// the entity is damaged, and is passed the world, the entity id, and the
+  /// amount of damage. If it returns `false`, the damage is cancelled.
+  /// `on_interact` is called when a player right clicks the entity, and is
+  /// passed the world, the entity id, and the player.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// let world = @bamboo.default_world()
+  /// @bamboo.summon_custom(
+  ///   world,
+  ///   "zombie",
+  ///   FPos::new(0.5, 64.0, 0.5),
+  ///   |world, eid, pos| {
+  ///     if pos.y() < 0.0 {
+  ///       bamboo::info("zombie " + eid + " fell out of the world")
+  ///     }
+  ///   },
+  ///   |world, eid, amount| {
+  ///     false
+  ///   },
+  ///   |world, eid, player| {
+  ///     player.send_message("hello from zombie " + eid)
+  ///   }
+  /// )
+  /// ```
+  pub fn summon_custom(
+    &self,
+    world: &PWorld,
+    ty: &str,
+    pos: &PFPos,
+    on_tick: Var,
+    on_damage: Var,
+    on_interact: Var,
+  ) -> Result<i32, RuntimeError> {
+    let ty = crate::entity::Type::from_name(ty).ok_or_else(|| {
+      RuntimeError::custom(format!("invalid entity name '{}'", ty), Span::call_site())
+    })?;
+    let on_tick = on_tick.closure(Span::call_site())?.clone();
+    let optional_closure = |v: Var| match v {
+      Var::None => Ok(None),
+      v => v.closure(Span::call_site()).map(|c| Some(c.clone())),
+    };
+    let on_damage = optional_closure(on_damage)?;
+    let on_interact = optional_closure(on_interact)?;
+    let behavior =
+      entity::PluginBehavior::new(self.wm.clone(), self.idx, on_tick, on_damage, on_interact);
+    Ok(world.inner.summon_custom(ty, pos.inner, behavior))
+  }
+
   /// Adds a new biome to the server. This works ontop of a terrain generator.
   /// By default, each of the biomes are chosen at random, for various regions
   /// of the map. Then, the biome generation takes place in each of these
@@ -273,6 +325,34 @@ impl Bamboo {
     ));
     Ok(())
   }
+
+  /// Registers the given closure as a handler for an event. The closure is
+  /// passed the same arguments as an `on <event>` function. `event` must be
+  /// one of `block_place`, `block_break`, `player_join`, or `chat`.
+  ///
+  /// For `block_place`, `block_break`, and `chat`, returning `false` from the
+  /// closure will cancel the event.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// fn main() {
+  ///   bamboo::instance().on("chat", |event, flow| {
+  ///     return event.text != "spam"
+  ///   })
+  /// }
+  /// ```
+  pub fn on(&self, event: &str, closure: Var) -> Result<(), RuntimeError> {
+    if !matches!(event, "block_place" | "block_break" | "player_join" | "chat") {
+      return Err(RuntimeError::custom(
+        format!("cannot listen for unknown event `{event}`"),
+        Span::call_site(),
+      ));
+    }
+    let closure = closure.closure(Span::call_site())?.clone();
+    self.listeners.lock().entry(event.into()).or_default().push(closure);
+    Ok(())
+  }
 }
 
 fn format(args: &[Var]) -> String {
@@ -417,8 +497,11 @@ impl PandaPlugin {
     }
     sl.add_builtin_ty::<Bamboo>();
     sl.add_builtin_ty::<util::PPos>();
+    sl.add_builtin_ty::<util::PChunkPos>();
     sl.add_builtin_ty::<util::PFPos>();
+    sl.add_builtin_ty::<util::PVec3>();
     sl.add_builtin_ty::<util::PUUID>();
+    sl.add_builtin_ty::<util::PGameMode>();
     sl.add_builtin_ty::<util::PCountdown>();
     sl.add_builtin_ty::<util::PDuration>();
     sl.add_builtin_ty::<event::PEventFlow>();
@@ -432,7 +515,9 @@ impl PandaPlugin {
     sl.add_builtin_ty::<item::PUI>();
     sl.add_builtin_ty::<particle::PParticle>();
     sl.add_builtin_ty::<command::PCommand>();
+    sl.add_builtin_ty::<command::PEntitySelector>();
     sl.add_builtin_ty::<player::PPlayer>();
+    sl.add_builtin_ty::<player::PResourcePackStatus>();
     sl.add_builtin_ty::<player::PTeam>();
     sl.add_builtin_ty::<player::PWindow>();
     sl.add_builtin_ty::<world::PWorld>();
@@ -450,8 +535,15 @@ impl PandaPlugin {
 
             /// To get started with writing a plugin, create a directory called
             /// `plugins` next to the server. Inside that directory, create a
-            /// file named something like `hello.sug`. In that file, put the
-            /// following code:
+            /// directory for your plugin, named something like `hello`. In that
+            /// directory, create a `plugin.toml` file with the following:
+            ///
+            /// ```
+            /// type = "panda"
+            /// ```
+            ///
+            /// Then, create a file named `main.pand` next to it, and put the
+            /// following code in it:
             ///
             /// ```
             /// on init() {
@@ -464,7 +556,7 @@ impl PandaPlugin {
             ///
             /// ```
             /// on init() {
-            ///   @bamboo.broadcast("This will show up in chat for everyone!")
+            ///   bamboo::instance().broadcast("This will show up in chat for everyone!")
             /// }
             /// ```
           ),
@@ -549,6 +641,27 @@ impl PandaPlugin {
             /// ```
           ),
         ),
+        (
+          path!(bamboo::event),
+          markdown!(
+            /// This module handles events. Every event that can be cancelled is passed
+            /// an `EventFlow`, which can be used to cancel that event.
+          ),
+        ),
+        (
+          path!(bamboo::item),
+          markdown!(
+            /// This module handles items and inventories. It includes item stacks
+            /// (`Stack`), inventories, and custom inventory windows (`UI`).
+          ),
+        ),
+        (
+          path!(bamboo::particle),
+          markdown!(
+            /// This module handles particles. Particles can be spawned in a world
+            /// with `World::spawn_particle`.
+          ),
+        ),
         (
           path!(bamboo::player),
           markdown!(
@@ -8,7 +8,10 @@ use crate::{
   item::Stack,
   player::{Player, Team, Window},
 };
-use bb_common::util::{chat::Color, GameMode, SwitchMode, UUID};
+use bb_common::{
+  net::{cb, cb::TeamRule, sb::ResourcePackStatus},
+  util::{chat::Color, Effect, GameMode, SwitchMode, UUID},
+};
 use bb_server_macros::define_ty;
 use panda::{
   parse::token::Span,
@@ -54,6 +57,39 @@ impl PWindow {
     Window::Generic9x3(crate::player::window::GenericWindow { inv: inv.inner.clone().into() })
       .into()
   }
+  /// Creates a new, empty anvil window. Use `Player::set_window_property` with
+  /// property `0` to set the repair cost.
+  pub fn anvil() -> Self { Window::Anvil(Default::default()).into() }
+  /// Creates a new, empty enchanting table window. Use
+  /// `Player::set_window_property` to set the enchantment levels.
+  pub fn enchantment() -> Self { Window::Enchantment(Default::default()).into() }
+  /// Creates a new, empty furnace window. Use `Player::set_window_property` to
+  /// set the fuel and progress bars.
+  pub fn furnace() -> Self { Window::Furnace(Default::default()).into() }
+}
+
+/// The response from a player after they are sent a resource pack.
+#[define_ty]
+impl PResourcePackStatus {
+  info! {
+    wrap: ResourcePackStatus,
+
+    panda: {
+      path: "bamboo::player::ResourcePackStatus",
+    },
+  }
+
+  /// Returns the status as a string. This is one of `accepted`, `declined`,
+  /// `loaded`, or `failed`.
+  pub fn to_s(&self) -> String {
+    match self.inner {
+      ResourcePackStatus::Accepted => "accepted",
+      ResourcePackStatus::Declined => "declined",
+      ResourcePackStatus::Loaded => "loaded",
+      ResourcePackStatus::Failed => "failed",
+    }
+    .into()
+  }
 }
 
 /// A Player. This struct is for online players. There is currently no way to
@@ -123,6 +159,21 @@ impl PPlayer {
     Ok(())
   }
 
+  /// Returns `true` if the player is vanished. Vanished players are hidden
+  /// from everyone else, but can still see other players.
+  ///
+  /// Returns `false` if the player is offline.
+  pub fn is_vanished(&self) -> bool { self.inner().map(|p| p.is_vanished()).unwrap_or(false) }
+  /// Hides or shows the player. This removes them from everyone else's tab
+  /// list, and despawns them for other players.
+  ///
+  /// This will do nothing if the player is offline.
+  pub fn set_vanished(&self, vanished: bool) {
+    if let Ok(i) = self.inner() {
+      i.set_vanished(vanished);
+    }
+  }
+
   /// Teleports the player to the given position, with a yaw and pitch.
   ///
   /// This will do nothing if the player is offline.
@@ -165,6 +216,37 @@ impl PPlayer {
     }
   }
 
+  /// Adds the given effect to the player, such as `"speed"` or
+  /// `"minecraft:poison"`. An amplifier of 0 is level I. `duration` is in
+  /// ticks. If the player already has this effect, it will be replaced.
+  ///
+  /// Returns an error if the effect name is invalid. Does nothing if the player
+  /// is offline.
+  pub fn add_effect(
+    &self,
+    effect: &str,
+    amplifier: u8,
+    duration: u32,
+    particles: bool,
+  ) -> Result<()> {
+    let effect = Effect::from_str(effect)
+      .map_err(|e| RuntimeError::custom(e.to_string(), Span::call_site()))?;
+    if let Ok(i) = self.inner() {
+      i.add_effect(effect, amplifier, duration, particles);
+    }
+    Ok(())
+  }
+  /// Removes the given effect from the player. Returns `true` if the player had
+  /// this effect.
+  ///
+  /// Returns an error if the effect name is invalid. Returns `false` if the
+  /// player is offline.
+  pub fn remove_effect(&self, effect: &str) -> Result<bool> {
+    let effect = Effect::from_str(effect)
+      .map_err(|e| RuntimeError::custom(e.to_string(), Span::call_site()))?;
+    Ok(self.inner().map(|i| i.remove_effect(effect)).unwrap_or(false))
+  }
+
   /// Sends the given chat message to a player. This accepts exactly one
   /// argument, which can be any type. If it is a `PChat`, then it will be
   /// formatted correctly. Anything else will show up with debug formatting.
@@ -219,6 +301,42 @@ impl PPlayer {
       i.show_title(fade_in, stay, fade_out);
     }
   }
+  /// Shows a title and subtitle to the player. If `subtitle` is `None`, only
+  /// the title is shown. The `fade_in`, `stay`, and `fade_out` arguments are
+  /// all in ticks.
+  ///
+  /// This will do nothing if the player is offline.
+  pub fn send_title(&self, title: Var, subtitle: Var, fade_in: u32, stay: u32, fade_out: u32) {
+    if let Ok(i) = self.inner() {
+      let subtitle = match subtitle {
+        Var::None => None,
+        v => Some(PChat::from_var(v)),
+      };
+      i.send_title(PChat::from_var(title), subtitle, fade_in, stay, fade_out);
+    }
+  }
+  /// Shows a message above the player's hotbar. This is useful for things
+  /// like countdowns, which would otherwise spam the chat box.
+  ///
+  /// This will do nothing if the player is offline.
+  pub fn send_action_bar(&self, msg: Var) {
+    if let Ok(i) = self.inner() {
+      i.send_action_bar(PChat::from_var(msg));
+    }
+  }
+
+  /// Prompts the player to download the resource pack at `url`. `hash` should
+  /// be the hex encoded SHA-1 hash of the pack, or an empty string. If `forced`
+  /// is set, the player will be kicked if they decline the pack.
+  ///
+  /// The player's response will be sent in the `resource_pack_status` event.
+  ///
+  /// This will do nothing if the player is offline.
+  pub fn set_resource_pack(&self, url: &str, hash: &str, forced: bool) {
+    if let Ok(i) = self.inner() {
+      i.set_resource_pack(url, hash, forced, None);
+    }
+  }
 
   /// Returns the world this player is in. This can be used to get/set
   /// blocks, access other players, and modify entities.
@@ -250,6 +368,16 @@ impl PPlayer {
       i.show_inventory(inv.inner.clone(), &PChat::from_var(title));
     }
   }
+  /// Sets a property of the window the player has open. This is used to
+  /// control things like furnace progress bars and anvil repair costs.
+  ///
+  /// This will do nothing if the player is offline, or if they don't have a
+  /// window open.
+  pub fn set_window_property(&self, property: i32, value: i32) {
+    if let Ok(i) = self.inner() {
+      i.set_window_property(property as i16, value as i16);
+    }
+  }
   /// Returns true if the player is in an inventory. This does not include their
   /// own inventory! The server cannot know if the player is in their survival
   /// inventory.
@@ -369,6 +497,76 @@ impl PPlayer {
     }
   }
 
+  /// Creates a scoreboard objective for this player. If the objective already
+  /// exists, this will change its display name. The name `scoreboard` is
+  /// reserved, and will produce an error.
+  ///
+  /// This will do nothing if the player is offline.
+  pub fn create_objective(&self, name: &str, display: Var) -> Result<()> {
+    if let Ok(i) = self.inner() {
+      if !i.lock_scoreboard().create_objective(name, PChat::from_var(display)) {
+        return Err(RuntimeError::custom(
+          format!("the objective name '{name}' is reserved"),
+          Span::call_site(),
+        ));
+      }
+    }
+    Ok(())
+  }
+  /// Removes a scoreboard objective from this player.
+  ///
+  /// This will do nothing if the player is offline.
+  pub fn remove_objective(&self, name: &str) {
+    if let Ok(i) = self.inner() {
+      i.lock_scoreboard().remove_objective(name);
+    }
+  }
+  /// Sets the score for `entry` in the given objective.
+  ///
+  /// This will do nothing if the player is offline.
+  pub fn set_score(&self, objective: &str, entry: &str, value: i32) {
+    if let Ok(i) = self.inner() {
+      i.lock_scoreboard().set_score(objective, entry, value);
+    }
+  }
+  /// Removes `entry` from the given objective.
+  ///
+  /// This will do nothing if the player is offline.
+  pub fn remove_score(&self, objective: &str, entry: &str) {
+    if let Ok(i) = self.inner() {
+      i.lock_scoreboard().remove_score(objective, entry);
+    }
+  }
+  /// Shows the given objective in a display slot. `slot` must be one of
+  /// `sidebar`, `list`, or `below_name`.
+  ///
+  /// This will do nothing if the player is offline.
+  pub fn display_objective(&self, slot: &str, objective: &str) -> Result<()> {
+    let position = match slot {
+      "sidebar" => cb::ScoreboardDisplayPosition::Sidebar,
+      "list" => cb::ScoreboardDisplayPosition::List,
+      "below_name" => cb::ScoreboardDisplayPosition::BelowName,
+      _ => {
+        return Err(RuntimeError::custom(
+          format!("invalid display slot `{slot}`"),
+          Span::call_site(),
+        ))
+      }
+    };
+    if let Ok(i) = self.inner() {
+      i.lock_scoreboard().display_objective(position, objective);
+    }
+    Ok(())
+  }
+  /// Shows the given objective on the right side of the player's screen.
+  ///
+  /// This will do nothing if the player is offline.
+  pub fn show_sidebar(&self, objective: &str) {
+    if let Ok(i) = self.inner() {
+      i.show_sidebar(objective);
+    }
+  }
+
   /// Sets the player's tab list name.
   ///
   /// Note that this does not update the name above the player's head. The only
@@ -430,6 +628,34 @@ impl PTeam {
     Ok(())
   }
 
+  /// Sets the prefix of this team. This is shown before the username of every
+  /// player on this team.
+  pub fn set_prefix(&self, prefix: Var) { self.inner.lock().set_prefix(PChat::from_var(prefix)); }
+  /// Sets the postfix of this team. This is shown after the username of every
+  /// player on this team.
+  pub fn set_postfix(&self, postfix: Var) {
+    self.inner.lock().set_postfix(PChat::from_var(postfix));
+  }
+  /// Sets if players on this team can attack each other.
+  pub fn set_friendly_fire(&self, friendly_fire: bool) {
+    self.inner.lock().set_friendly_fire(friendly_fire);
+  }
+  /// Sets who can push players on this team. This must be one of `always`,
+  /// `never`, `own_team`, or `other_teams`. `own_team` will only allow
+  /// teammates to push each other.
+  pub fn set_collision_rule(&self, rule: &str) -> Result<()> {
+    self.inner.lock().set_collision_rule(parse_team_rule(rule)?);
+    Ok(())
+  }
+  /// Sets who can see the name tags of players on this team. This must be one
+  /// of `always`, `never`, `own_team`, or `other_teams`. `own_team` will hide
+  /// name tags from teammates, and `other_teams` will hide name tags from
+  /// everyone else.
+  pub fn set_name_tag_visibility(&self, rule: &str) -> Result<()> {
+    self.inner.lock().set_name_tag_visibility(parse_team_rule(rule)?);
+    Ok(())
+  }
+
   /// Adds the player to this team.
   ///
   /// This will do nothing if the player is offline.
@@ -438,4 +664,24 @@ impl PTeam {
       self.inner.lock().add(i.as_ref());
     }
   }
+  /// Removes the player from this team.
+  ///
+  /// This will do nothing if the player is offline.
+  pub fn remove_player(&self, player: &PPlayer) {
+    if let Ok(i) = player.inner() {
+      self.inner.lock().remove(i.as_ref());
+    }
+  }
+}
+
+fn parse_team_rule(rule: &str) -> Result<TeamRule> {
+  Ok(match rule {
+    "always" => TeamRule::Always,
+    "never" => TeamRule::Never,
+    "own_team" => TeamRule::ForOwnTeam,
+    "other_teams" => TeamRule::ForOtherTeams,
+    _ => {
+      return Err(RuntimeError::custom(format!("invalid team rule `{rule}`"), Span::call_site()))
+    }
+  })
 }
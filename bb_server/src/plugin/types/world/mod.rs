@@ -1,13 +1,18 @@
 use super::{
   block::{PBlockKind, PBlockType},
+  chat::PChat,
   item::PStack,
   particle::PParticle,
+  player::PPlayer,
   util::{PFPos, PPos},
 };
 use crate::{entity, world::World};
 use bb_common::{math::Pos, metadata::Metadata, net::cb::SoundCategory};
 use bb_server_macros::define_ty;
-use panda::{parse::token::Span, runtime::RuntimeError};
+use panda::{
+  parse::token::Span,
+  runtime::{RuntimeError, Var},
+};
 use std::{fmt, sync::Arc};
 
 pub mod gen;
@@ -129,6 +134,37 @@ impl PWorld {
     Ok(())
   }
 
+  /// Sends the given chat message to every player in this world. Players in
+  /// other worlds will not see this message. To send a message to everyone on
+  /// the server, use `Bamboo::broadcast`.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// world = player.world()
+  /// world.broadcast("Hello world!")
+  ///
+  /// chat = Chat::new()
+  /// chat.add("The game is starting!").color("gold")
+  /// world.broadcast(chat)
+  /// ```
+  pub fn broadcast(&self, msg: Var) { self.inner.broadcast(PChat::from_var(msg)); }
+
+  /// Returns all of the players in this world. This is a copy of the player
+  /// list, so players who join or leave this world after calling `players`
+  /// will not be added or removed from the returned list.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// for player in world.players() {
+  ///   player.send_message("Hello " + player.username())
+  /// }
+  /// ```
+  pub fn players(&self) -> Vec<Var> {
+    self.inner.players().values().map(|p| PPlayer::from(p.clone()).into()).collect()
+  }
+
   /// Saves the world to disk. If saving is disabled, the world will not be
   /// saved.
   pub fn save(&self) { self.inner.save(); }
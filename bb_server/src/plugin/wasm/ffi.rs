@@ -129,6 +129,18 @@ where
     CList { first: ptr, len: self.len() as u32 }
   }
 }
+impl FromFfi for UUID {
+  type Ffi = CUUID;
+
+  fn from_ffi(_: &Env, ffi: CUUID) -> Self {
+    UUID::from_u128(
+      (ffi.bytes[3] as u128) << (3 * 32)
+        | (ffi.bytes[2] as u128) << (2 * 32)
+        | (ffi.bytes[1] as u128) << 32
+        | ffi.bytes[0] as u128,
+    )
+  }
+}
 impl ToFfi for UUID {
   type Ffi = CUUID;
 
@@ -7,10 +7,13 @@ use crate::{
 };
 use bb_common::{
   math::{FPos, Pos},
-  util::Chat,
+  util::{Chat, Effect, GameMode, UUID},
   version::BlockVersion,
 };
-use bb_ffi::{CBlockPropValue, CChat, CCommand, CCommandArg, CFPos, CList, CParticle, CPos, CUUID};
+use bb_ffi::{
+  CBlockPropValue, CChat, CCommand, CCommandArg, CEntity, CFPos, CList, CLogContext, CParticle,
+  CPos, CUUID,
+};
 use log::Level;
 use std::{mem, sync::Arc};
 use wasmer::{
@@ -111,54 +114,129 @@ fn log(
   file_ptr: WasmPtr<u8, Array>,
   file_len: u32,
   line: u32,
+) {
+  log_context(
+    env,
+    level,
+    message_ptr,
+    message_len,
+    target_ptr,
+    target_len,
+    module_path_ptr,
+    module_path_len,
+    file_ptr,
+    file_len,
+    line,
+    WasmPtr::new(0),
+  )
+}
+
+/// The same as `log`, but with an optional player and world, which are added
+/// to the start of the message.
+///
+/// The target is prefixed with `plugin::<name>`, so that logs can be filtered
+/// by plugin. If the plugin used the default target (its module path), the
+/// target is just `plugin::<name>`.
+#[allow(clippy::too_many_arguments)]
+fn log_context(
+  env: &Env,
+  level: u32,
+  message_ptr: WasmPtr<u8, Array>,
+  message_len: u32,
+  target_ptr: WasmPtr<u8, Array>,
+  target_len: u32,
+  module_path_ptr: WasmPtr<u8, Array>,
+  module_path_len: u32,
+  file_ptr: WasmPtr<u8, Array>,
+  file_len: u32,
+  line: u32,
+  context: WasmPtr<CLogContext>,
 ) {
   let level = match log_from_level(level) {
     Some(l) => l,
     None => return,
   };
+  let mem = env.mem();
+  let target = target_ptr.get_utf8_str(mem, target_len).unwrap_or("");
+  let module_path = module_path_ptr.get_utf8_str(mem, module_path_len).unwrap_or("");
+  let file = file_ptr.get_utf8_str(mem, file_len).unwrap_or("");
+  let target = if target.is_empty() || target == module_path {
+    format!("plugin::{}", env.name)
+  } else {
+    format!("plugin::{}::{target}", env.name)
+  };
+
+  let mut prefix = String::new();
+  // A null pointer means there is no context.
+  if context.offset() != 0 {
+    if let Some(ctx) = context.deref(mem).map(|c| c.get()) {
+      if ctx.has_player.as_bool() {
+        let id = UUID::from_ffi(env, ctx.player);
+        match env.wm.get_player(id) {
+          Some(p) => prefix.push_str(&format!("[player={}] ", p.username())),
+          None => prefix.push_str(&format!("[player={}] ", id.as_dashed_str())),
+        }
+      }
+      if ctx.world >= 0 {
+        match env.wm.worlds().get(ctx.world as usize) {
+          Some(w) => prefix.push_str(&format!("[world={}] ", w.name())),
+          None => prefix.push_str(&format!("[world={}] ", ctx.world)),
+        }
+      }
+    }
+  }
+
   // SAFETY: We aren't using the string outside this function,
   // so this is safe. It also avoids allocating, so that's why
   // we use this instead of `get_utf8_string_with_nul`.
 
   unsafe {
-    let s = if u64::from(message_ptr.offset() + message_len) > env.mem().data_size() {
+    let s = if u64::from(message_ptr.offset() + message_len) > mem.data_size() {
       std::borrow::Cow::Borrowed("")
     } else {
-      let ptr = env.mem().data_ptr().add(message_ptr.offset() as usize) as *const u8;
+      let ptr = mem.data_ptr().add(message_ptr.offset() as usize) as *const u8;
       let slice = std::slice::from_raw_parts(ptr, message_len as usize);
       String::from_utf8_lossy(slice)
     };
     log::logger().log(
       &log::Record::builder()
-        .args(format_args!("{s}"))
+        .args(format_args!("{prefix}{s}"))
         .level(level)
-        .target(target_ptr.get_utf8_str(env.mem(), target_len).unwrap())
-        .module_path(Some(module_path_ptr.get_utf8_str(env.mem(), module_path_len).unwrap()))
-        .file(Some(file_ptr.get_utf8_str(env.mem(), file_len).unwrap()))
+        .target(&target)
+        .module_path(Some(module_path))
+        .file(Some(file))
         .line(Some(line))
         .build(),
     );
   }
 }
 
+/// Reads a UUID from the plugin's memory. Returns `None` if the pointer is
+/// invalid.
+fn read_uuid(env: &Env, ptr: WasmPtr<CUUID>) -> Option<UUID> {
+  ptr.deref(env.mem()).map(|id| UUID::from_ffi(env, id.get()))
+}
+
 fn broadcast(env: &Env, message: WasmPtr<CChat>) {
   let chat = message.deref(env.mem()).unwrap().get();
   let s = chat.message.ptr.get_utf8_string_with_nul(env.mem()).unwrap();
   env.wm.broadcast(Chat::new(s));
 }
-
-fn player_username(env: &Env, id: WasmPtr<CUUID>) -> u32 {
+fn broadcast_world(env: &Env, wid: u32, message: WasmPtr<CChat>) {
   let mem = env.mem();
-  let uuid = match id.deref(mem) {
-    Some(id) => id.get(),
-    None => return 1,
+  let s = match message.deref(mem).and_then(|c| c.get().message.ptr.get_utf8_string_with_nul(mem)) {
+    Some(s) => s,
+    None => return,
   };
-  let player = match env.wm.get_player(bb_common::util::UUID::from_u128(
-    (uuid.bytes[3] as u128) << (3 * 32)
-      | (uuid.bytes[2] as u128) << (2 * 32)
-      | (uuid.bytes[1] as u128) << 32
-      | uuid.bytes[0] as u128,
-  )) {
+  let world = match env.wm.worlds().get(wid as usize) {
+    Some(w) => w.clone(),
+    None => return,
+  };
+  world.broadcast(Chat::new(s));
+}
+
+fn player_username(env: &Env, id: WasmPtr<CUUID>) -> u32 {
+  let player = match read_uuid(env, id).and_then(|id| env.wm.get_player(id)) {
     Some(p) => p,
     None => return 1,
   };
@@ -167,17 +245,7 @@ fn player_username(env: &Env, id: WasmPtr<CUUID>) -> u32 {
   ptr.offset()
 }
 fn player_pos(env: &Env, id: WasmPtr<CUUID>) -> u32 {
-  let mem = env.mem();
-  let uuid = match id.deref(mem) {
-    Some(id) => id.get(),
-    None => return 0,
-  };
-  let player = match env.wm.get_player(bb_common::util::UUID::from_u128(
-    (uuid.bytes[3] as u128) << (3 * 32)
-      | (uuid.bytes[2] as u128) << (2 * 32)
-      | (uuid.bytes[1] as u128) << 32
-      | uuid.bytes[0] as u128,
-  )) {
+  let player = match read_uuid(env, id).and_then(|id| env.wm.get_player(id)) {
     Some(p) => p,
     None => return 0,
   };
@@ -186,17 +254,7 @@ fn player_pos(env: &Env, id: WasmPtr<CUUID>) -> u32 {
   ptr.offset()
 }
 fn player_look_as_vec(env: &Env, id: WasmPtr<CUUID>) -> u32 {
-  let mem = env.mem();
-  let uuid = match id.deref(mem) {
-    Some(id) => id.get(),
-    None => return 0,
-  };
-  let player = match env.wm.get_player(bb_common::util::UUID::from_u128(
-    (uuid.bytes[3] as u128) << (3 * 32)
-      | (uuid.bytes[2] as u128) << (2 * 32)
-      | (uuid.bytes[1] as u128) << 32
-      | uuid.bytes[0] as u128,
-  )) {
+  let player = match read_uuid(env, id).and_then(|id| env.wm.get_player(id)) {
     Some(p) => p,
     None => return 0,
   };
@@ -206,16 +264,7 @@ fn player_look_as_vec(env: &Env, id: WasmPtr<CUUID>) -> u32 {
 }
 fn player_send_particle(env: &Env, id: WasmPtr<CUUID>, particle: WasmPtr<CParticle>) {
   let mem = env.mem();
-  let uuid = match id.deref(mem) {
-    Some(id) => id.get(),
-    None => return,
-  };
-  let player = match env.wm.get_player(bb_common::util::UUID::from_u128(
-    (uuid.bytes[3] as u128) << (3 * 32)
-      | (uuid.bytes[2] as u128) << (2 * 32)
-      | (uuid.bytes[1] as u128) << 32
-      | uuid.bytes[0] as u128,
-  )) {
+  let player = match read_uuid(env, id).and_then(|id| env.wm.get_player(id)) {
     Some(p) => p,
     None => return,
   };
@@ -227,73 +276,190 @@ fn player_send_particle(env: &Env, id: WasmPtr<CUUID>, particle: WasmPtr<CPartic
   player.send_particle(particle);
 }
 
-fn player_world(env: &Env, player: WasmPtr<CUUID>) -> i32 {
+fn player_send_message(env: &Env, id: WasmPtr<CUUID>, message: WasmPtr<CChat>) {
   let mem = env.mem();
-  let uuid = match player.deref(mem) {
-    Some(p) => p.get(),
+  let player = match read_uuid(env, id).and_then(|id| env.wm.get_player(id)) {
+    Some(p) => p,
+    None => return,
+  };
+  let s = match message.deref(mem).and_then(|c| c.get().message.ptr.get_utf8_string_with_nul(mem)) {
+    Some(s) => s,
+    None => return,
+  };
+  player.send_message(Chat::new(s));
+}
+
+fn player_world(env: &Env, player: WasmPtr<CUUID>) -> i32 {
+  let id = match read_uuid(env, player) {
+    Some(id) => id,
+    None => return -1,
+  };
+  match env.wm.all_players().get(&id) {
+    Some((idx, _)) => *idx as i32,
+    None => -1,
+  }
+}
+
+fn player_add_effect(
+  env: &Env,
+  player: WasmPtr<CUUID>,
+  effect: u32,
+  amplifier: u32,
+  duration: u32,
+  particles: u8,
+) -> i32 {
+  let player = match read_uuid(env, player).and_then(|id| env.wm.get_player(id)) {
+    Some(p) => p,
     None => return -1,
   };
-  let _player = match env.wm.get_player(bb_common::util::UUID::from_u128(
-    (uuid.bytes[3] as u128) << (3 * 32)
-      | (uuid.bytes[2] as u128) << (2 * 32)
-      | (uuid.bytes[1] as u128) << 32
-      | uuid.bytes[0] as u128,
-  )) {
+  let effect = match u8::try_from(effect).ok().and_then(Effect::from_id) {
+    Some(e) => e,
+    None => return -1,
+  };
+  player.add_effect(effect, amplifier.min(255) as u8, duration, particles == 1);
+  0
+}
+
+fn player_remove_effect(env: &Env, player: WasmPtr<CUUID>, effect: u32) -> i32 {
+  let player = match read_uuid(env, player).and_then(|id| env.wm.get_player(id)) {
+    Some(p) => p,
+    None => return -1,
+  };
+  let effect = match u8::try_from(effect).ok().and_then(Effect::from_id) {
+    Some(e) => e,
+    None => return -1,
+  };
+  player.remove_effect(effect) as i32
+}
+
+fn player_game_mode(env: &Env, player: WasmPtr<CUUID>) -> i32 {
+  let player = match read_uuid(env, player).and_then(|id| env.wm.get_player(id)) {
     Some(p) => p,
     None => return -1,
   };
+  player.game_mode().id().into()
+}
+
+fn player_set_game_mode(env: &Env, player: WasmPtr<CUUID>, game_mode: u32) -> i32 {
+  let player = match read_uuid(env, player).and_then(|id| env.wm.get_player(id)) {
+    Some(p) => p,
+    None => return -1,
+  };
+  if game_mode > 3 {
+    return -1;
+  }
+  player.set_game_mode(GameMode::from_id(game_mode as u8));
   0
 }
 
-fn world_set_block(env: &Env, _wid: u32, pos: WasmPtr<CPos>, id: u32) -> i32 {
+fn player_vanished(env: &Env, player: WasmPtr<CUUID>) -> i32 {
+  let player = match read_uuid(env, player).and_then(|id| env.wm.get_player(id)) {
+    Some(p) => p,
+    None => return -1,
+  };
+  player.is_vanished() as i32
+}
+
+fn player_set_vanished(env: &Env, player: WasmPtr<CUUID>, vanished: u8) -> i32 {
+  let player = match read_uuid(env, player).and_then(|id| env.wm.get_player(id)) {
+    Some(p) => p,
+    None => return -1,
+  };
+  player.set_vanished(vanished == 1);
+  0
+}
+
+fn world_set_block(env: &Env, wid: u32, pos: WasmPtr<CPos>, id: u32) -> i32 {
   let mem = env.mem();
   let pos = match pos.deref(mem) {
     Some(p) => p.get(),
     None => return -1,
   };
-  let world = env.wm.default_world();
+  let world = match env.wm.worlds().get(wid as usize) {
+    Some(w) => w.clone(),
+    None => return -1,
+  };
   let ty = env.wm.block_converter().type_from_id(id, env.ver);
   match world.set_block(Pos::new(pos.x, pos.y, pos.z), ty) {
     Ok(_) => 0,
     Err(_) => -1,
   }
 }
-fn world_set_block_kind(env: &Env, _wid: u32, pos: WasmPtr<CPos>, kind: u32) -> i32 {
+fn world_set_block_kind(env: &Env, wid: u32, pos: WasmPtr<CPos>, kind: u32) -> i32 {
   let mem = env.mem();
   let pos = match pos.deref(mem) {
     Some(p) => p.get(),
     None => return -1,
   };
-  let world = env.wm.default_world();
+  let world = match env.wm.worlds().get(wid as usize) {
+    Some(w) => w.clone(),
+    None => return -1,
+  };
   let kind = block::Kind::from_id(kind).unwrap_or(block::Kind::Air);
   match world.set_kind(Pos::new(pos.x, pos.y, pos.z), kind) {
     Ok(_) => 0,
     Err(_) => -1,
   }
 }
-fn world_get_block(env: &Env, _wid: u32, pos: WasmPtr<CPos>) -> u32 {
+fn world_fill_kind(env: &Env, wid: u32, min: WasmPtr<CPos>, max: WasmPtr<CPos>, kind: u32) -> i32 {
+  let mem = env.mem();
+  let (min, max) = match (min.deref(mem), max.deref(mem)) {
+    (Some(min), Some(max)) => (min.get(), max.get()),
+    _ => return -1,
+  };
+  let world = match env.wm.worlds().get(wid as usize) {
+    Some(w) => w.clone(),
+    None => return -1,
+  };
+  let kind = match block::Kind::from_id(kind) {
+    Some(k) => k,
+    None => return -2,
+  };
+  let (min, max) = Pos::new(min.x, min.y, min.z).min_max(Pos::new(max.x, max.y, max.z));
+  let size = |a: i32, b: i32| (b as i64 - a as i64 + 1) as u64;
+  // Each side can be up to 2^32 blocks long, so this can overflow.
+  let volume = size(min.x(), max.x())
+    .checked_mul(size(min.y(), max.y()))
+    .and_then(|v| v.checked_mul(size(min.z(), max.z())));
+  match volume {
+    Some(v) if v <= world.config().max_fill_volume => {}
+    _ => return -3,
+  }
+  match world.fill_rect_kind(min, max, kind) {
+    Ok(_) => 0,
+    Err(_) => -1,
+  }
+}
+fn world_get_block(env: &Env, wid: u32, pos: WasmPtr<CPos>) -> u32 {
   let mem = env.mem();
   let pos = match pos.deref(mem) {
     Some(p) => p.get(),
     None => return u32::MAX,
   };
-  let world = env.wm.default_world();
+  let world = match env.wm.worlds().get(wid as usize) {
+    Some(w) => w.clone(),
+    None => return u32::MAX,
+  };
   match world.get_block(Pos::new(pos.x, pos.y, pos.z)) {
     Ok(ty) => ty.id(),
     Err(_) => u32::MAX,
   }
 }
-fn world_players(env: &Env, _wid: u32) -> u32 {
-  // TODO: Use the world id.
-  let world = env.wm.default_world();
-  let players: Vec<_> = world.players().iter().map(|p| p.id()).collect();
+fn world_players(env: &Env, wid: u32) -> u32 {
+  // An invalid world has no players.
+  let players: Vec<_> = match env.wm.worlds().get(wid as usize) {
+    Some(world) => world.players().iter().map(|p| p.id()).collect(),
+    None => vec![],
+  };
   let cplayers = players.as_slice().to_ffi(env);
   let ptr = env.malloc_store(cplayers);
   ptr.offset()
 }
-fn world_spawn_particle(env: &Env, _wid: u32, particle: WasmPtr<CParticle>) {
-  // TODO: Use the world id.
-  let world = env.wm.default_world();
+fn world_spawn_particle(env: &Env, wid: u32, particle: WasmPtr<CParticle>) {
+  let world = match env.wm.worlds().get(wid as usize) {
+    Some(w) => w.clone(),
+    None => return,
+  };
   let mem = env.mem();
   let cparticle = match particle.deref(mem) {
     Some(p) => p.get(),
@@ -302,6 +468,35 @@ fn world_spawn_particle(env: &Env, _wid: u32, particle: WasmPtr<CParticle>) {
   let particle = Particle::from_ffi(env, cparticle);
   world.spawn_particle(particle);
 }
+fn world_entity_count(env: &Env, wid: u32) -> i32 {
+  match env.wm.worlds().get(wid as usize) {
+    Some(w) => w.entities.read().len() as i32,
+    None => -1,
+  }
+}
+fn world_entities_in_radius(env: &Env, wid: u32, center: WasmPtr<CFPos>, radius: f64) -> u32 {
+  let center = match center.deref(env.mem()) {
+    Some(p) => FPos::from_ffi(env, p.get()),
+    None => return 0,
+  };
+  // An invalid world has no entities.
+  let world = env.wm.worlds().get(wid as usize).cloned();
+  let entities: Vec<_> = match &world {
+    Some(w) => w
+      .entities_in_radius(center, radius)
+      .iter()
+      .filter_map(|e| {
+        let e = e.as_entity_ref(w)?;
+        Some(CEntity { eid: e.eid(), ty: e.ty().id(), pos: e.pos().to_ffi(env) })
+      })
+      .collect(),
+    None => vec![],
+  };
+  // A null list is read as an empty list by the plugin.
+  let first = if entities.is_empty() { WasmPtr::new(0) } else { env.malloc_array_store(&entities) };
+  let ptr = env.malloc_store(CList { first, len: entities.len() as u32 });
+  ptr.offset()
+}
 fn world_raycast(env: &Env, from: WasmPtr<CFPos>, to: WasmPtr<CFPos>, water: u8) -> u32 {
   let mem = env.mem();
   let from = match from.deref(mem) {
@@ -407,7 +602,7 @@ fn add_command(env: &Env, cmd: WasmPtr<CCommand>) {
         Some(p) => env.malloc_store(p.id().to_ffi(&env)),
         None => WasmPtr::new(0),
       };
-      let args = env.malloc_store(args.as_slice().to_ffi(&env));
+      let args = env.malloc_store((&*args).to_ffi(&env));
       match env.on_command.get_ref().unwrap().call(id, args) {
         Ok(()) => {}
         Err(e) => error!("couldn't execute command on wasm: {e}"),
@@ -445,22 +640,34 @@ pub fn imports(store: &Store, wm: Arc<WorldManager>, name: String) -> ImportObje
   imports! {
     "env" => {
       "bb_log" => Function::new_native_with_env(store, env.clone(), log),
+      "bb_log_context" => Function::new_native_with_env(store, env.clone(), log_context),
       "bb_add_command" => Function::new_native_with_env(store, env.clone(), add_command),
       "bb_block_data_for_kind" => Function::new_native_with_env(store, env.clone(), block_data_for_kind),
       "bb_block_kind_for_type" => Function::new_native_with_env(store, env.clone(), block_kind_for_type),
       "bb_block_prop" => Function::new_native_with_env(store, env.clone(), block_prop),
       "bb_block_set_prop" => Function::new_native_with_env(store, env.clone(), block_set_prop),
       "bb_broadcast" => Function::new_native_with_env(store, env.clone(), broadcast),
+      "bb_broadcast_world" => Function::new_native_with_env(store, env.clone(), broadcast_world),
       "bb_player_username" => Function::new_native_with_env(store, env.clone(), player_username),
       "bb_player_pos" => Function::new_native_with_env(store, env.clone(), player_pos),
       "bb_player_look_as_vec" => Function::new_native_with_env(store, env.clone(), player_look_as_vec),
       "bb_player_world" => Function::new_native_with_env(store, env.clone(), player_world),
+      "bb_player_send_message" => Function::new_native_with_env(store, env.clone(), player_send_message),
       "bb_player_send_particle" => Function::new_native_with_env(store, env.clone(), player_send_particle),
+      "bb_player_add_effect" => Function::new_native_with_env(store, env.clone(), player_add_effect),
+      "bb_player_remove_effect" => Function::new_native_with_env(store, env.clone(), player_remove_effect),
+      "bb_player_game_mode" => Function::new_native_with_env(store, env.clone(), player_game_mode),
+      "bb_player_set_game_mode" => Function::new_native_with_env(store, env.clone(), player_set_game_mode),
+      "bb_player_vanished" => Function::new_native_with_env(store, env.clone(), player_vanished),
+      "bb_player_set_vanished" => Function::new_native_with_env(store, env.clone(), player_set_vanished),
       "bb_world_set_block" => Function::new_native_with_env(store, env.clone(), world_set_block),
       "bb_world_set_block_kind" => Function::new_native_with_env(store, env.clone(), world_set_block_kind),
+      "bb_world_fill_kind" => Function::new_native_with_env(store, env.clone(), world_fill_kind),
       "bb_world_get_block" => Function::new_native_with_env(store, env.clone(), world_get_block),
       "bb_world_players" => Function::new_native_with_env(store, env.clone(), world_players),
       "bb_world_spawn_particle" => Function::new_native_with_env(store, env.clone(), world_spawn_particle),
+      "bb_world_entity_count" => Function::new_native_with_env(store, env.clone(), world_entity_count),
+      "bb_world_entities_in_radius" => Function::new_native_with_env(store, env.clone(), world_entities_in_radius),
       "bb_world_raycast" => Function::new_native_with_env(store, env.clone(), world_raycast),
       "bb_time_since_start" => Function::new_native_with_env(store, env, time_since_start),
     }
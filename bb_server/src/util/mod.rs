@@ -1,3 +1,22 @@
 mod threaded;
 
 pub use threaded::Threaded;
+
+/// Checks that every id in `0..count` has a name, and that the name parses
+/// back into the same value, both with and without the `minecraft:` prefix.
+/// This is used to test the generated `Type` enums.
+#[cfg(test)]
+pub(crate) fn check_names<T: Copy + PartialEq + std::fmt::Debug>(
+  count: u32,
+  from_id: impl Fn(u32) -> T,
+  to_str: impl Fn(&T) -> &'static str,
+  from_name: impl Fn(&str) -> Option<T>,
+) {
+  for id in 0..count {
+    let ty = from_id(id);
+    let name = to_str(&ty);
+    assert_eq!(from_name(name), Some(ty), "{} (id {}) didn't parse", name, id);
+    let namespaced = format!("minecraft:{}", name);
+    assert_eq!(from_name(&namespaced), Some(ty), "{} (id {}) didn't parse", namespaced, id);
+  }
+}
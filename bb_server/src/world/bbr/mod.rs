@@ -92,6 +92,11 @@ impl RegionMap {
     }
   }
 
+  /// Returns the number of chunks in every loaded region.
+  pub fn loaded_chunks(&self) -> usize {
+    self.regions.read().values().map(|r| r.lock().chunks.iter().flatten().count()).sum()
+  }
+
   pub fn unload_chunks(&self) {
     let mut unloadable = vec![];
     {
@@ -11,6 +11,7 @@ use bb_common::{
   math::{ChunkPos, FPos, Pos, PosError, RelPos},
   metadata::Metadata,
   net::cb,
+  util::Face,
 };
 use rand::Rng;
 use std::{cmp::Ordering, str::FromStr, sync::Arc};
@@ -25,15 +26,23 @@ impl World {
   pub fn get_kind(&self, pos: Pos) -> Result<block::Kind, PosError> {
     self.chunk(pos.chunk(), |c| c.get_kind(pos.chunk_rel()))
   }
+  /// Returns the light level at the given position. See
+  /// [`MultiChunk::get_light`](super::MultiChunk::get_light).
+  pub fn get_light(&self, pos: Pos) -> Result<u8, PosError> {
+    self.chunk(pos.chunk(), |c| c.get_light(pos.chunk_rel()))
+  }
   /// This is the same as `set_kind(pos, block::Kind::Air)`, but it spawns a
-  /// dropped item where the block was.
+  /// dropped item where the block was. If the block was waterlogged, this
+  /// leaves a water source behind instead of air.
   ///
   /// Returns `false` if the world is locked. In this case, a sync should be
   /// sent back to the client.
   pub fn break_block(self: &Arc<Self>, pos: Pos) -> Result<bool, PosError> {
     let old_type = self.get_block(pos)?;
     let old_block = self.block_converter.get(old_type.kind());
-    let res = self.set_kind(pos, block::Kind::Air)?;
+    let new_kind =
+      if old_type.ty().is_waterlogged() { block::Kind::Water } else { block::Kind::Air };
+    let res = self.set_kind(pos, new_kind)?;
     if old_block.drops.is_empty() {
       return Ok(res);
     }
@@ -171,57 +180,60 @@ impl World {
       self.set_block_no_update(min, ty)?;
       return Ok(());
     }
-    let (min, max) = Pos::min_max(min, max);
-    for x in min.chunk_x()..=max.chunk_x() {
-      for z in min.chunk_z()..=max.chunk_z() {
-        let pos = ChunkPos::new(x, z);
-
-        let min_x = if min.chunk_x() == x { min.chunk_rel_x() } else { 0 };
-        let min_z = if min.chunk_z() == z { min.chunk_rel_z() } else { 0 };
-        let max_x = if max.chunk_x() == x { max.chunk_rel_x() } else { 15 };
-        let max_z = if max.chunk_z() == z { max.chunk_rel_z() } else { 15 };
-
-        let min = RelPos::new(min_x as u8, min.y, min_z as u8);
-        let max = RelPos::new(max_x as u8, max.y, max_z as u8);
-
-        self.chunk(pos, |mut c| c.fill(min, max, ty))?;
-
-        let num_blocks_changed = min.to(max).len();
-        // 2048 block is where chunk data packets are smaller. Multi block change
-        // packets use varints, so this is not an exact value, but it would be ideal
-        // (for packet size) to just compare with 2048 here.
-        //
-        // However, the minecraft client is terrible, and does things very slowly. So
-        // any time there is a large multi block change, the client will freeze up. That
-        // is why this check is against such a low number.
-        if num_blocks_changed > 128 {
-          let serialized =
-            self.serialize_partial_chunk(pos, min.chunk_y() as u32, max.chunk_y() as u32);
-          for p in self.players().iter().in_view(pos) {
-            p.send(serialized.clone());
-          }
-        } else {
-          for y in min.chunk_y()..=max.chunk_y() {
-            let serialized = self.serialize_multi_block_change(
-              pos,
-              y,
-              min.to(max).filter_map(|pos| {
-                if pos.chunk_y() == y {
-                  Some((pos.section_rel(), ty.id()))
-                } else {
-                  None
-                }
-              }),
-            );
-            for p in self.players().iter().in_view(pos) {
-              p.send(serialized.clone());
+    for (pos, min, max) in chunk_rects(min, max) {
+      self.chunk(pos, |mut c| c.fill(min, max, ty))?;
+      self.send_rect_update(pos, min, max, |_| ty.id());
+    }
+
+    Ok(())
+  }
+
+  /// Sends the blocks between `min` and `max` (inclusive) within the given
+  /// chunk to everyone in view. `id` is called with each position in the
+  /// region, in the order of [`RelPos::to`], and should return the new block
+  /// id at that position.
+  ///
+  /// Small changes are sent as a multi block change, and large changes resend
+  /// the affected chunk sections.
+  pub(super) fn send_rect_update(
+    &self,
+    pos: ChunkPos,
+    min: RelPos,
+    max: RelPos,
+    id: impl Fn(RelPos) -> u32,
+  ) {
+    let num_blocks_changed = min.to(max).len();
+    // 2048 block is where chunk data packets are smaller. Multi block change
+    // packets use varints, so this is not an exact value, but it would be ideal
+    // (for packet size) to just compare with 2048 here.
+    //
+    // However, the minecraft client is terrible, and does things very slowly. So
+    // any time there is a large multi block change, the client will freeze up. That
+    // is why this check is against such a low number.
+    if num_blocks_changed > 128 {
+      let serialized =
+        self.serialize_partial_chunk(pos, min.chunk_y() as u32, max.chunk_y() as u32);
+      for p in self.players().iter().in_view(pos) {
+        p.send(serialized.clone());
+      }
+    } else {
+      for y in min.chunk_y()..=max.chunk_y() {
+        let serialized = self.serialize_multi_block_change(
+          pos,
+          y,
+          min.to(max).filter_map(|pos| {
+            if pos.chunk_y() == y {
+              Some((pos.section_rel(), id(pos)))
+            } else {
+              None
             }
-          }
+          }),
+        );
+        for p in self.players().iter().in_view(pos) {
+          p.send(serialized.clone());
         }
       }
     }
-
-    Ok(())
   }
 
   /// Fills the given region with the default type for the block kind. Min must
@@ -528,4 +540,120 @@ impl World {
     let res = from_vec.move_towards(to_vec - from_vec, &colliders);
     res.map(|res| (from_vec.into(), res))
   }
+
+  /// Steps through every block along the ray starting at `origin`, going in
+  /// the direction `dir`. This returns the first block that the ray hits, and
+  /// the face of that block that the ray entered through. If nothing is hit
+  /// within `max_dist` blocks, this returns `None`.
+  ///
+  /// The hitbox of each block is used to check for a hit, so blocks like tall
+  /// grass will be passed through, and the ray can go over a slab.
+  ///
+  /// This returns `None` if `origin`, `dir`, or `max_dist` are not finite.
+  pub fn raycast_block(
+    self: &Arc<World>,
+    origin: FPos,
+    dir: Vec3,
+    max_dist: f64,
+  ) -> Option<(Pos, Face)> {
+    self.raycast_block_dist(origin, dir, max_dist).map(|(pos, face, _)| (pos, face))
+  }
+
+  /// The same as [`raycast_block`](Self::raycast_block), but this also returns
+  /// the distance along the ray at which the block was hit.
+  pub fn raycast_block_dist(
+    self: &Arc<World>,
+    origin: FPos,
+    dir: Vec3,
+    max_dist: f64,
+  ) -> Option<(Pos, Face, f64)> {
+    let len = dir.len();
+    // Any of these being infinite or NaN would make the loop below run forever.
+    if len == 0.0
+      || !len.is_finite()
+      || !max_dist.is_finite()
+      || !(origin.x().is_finite() && origin.y().is_finite() && origin.z().is_finite())
+    {
+      return None;
+    }
+    let dir = dir / len;
+
+    // This is a DDA voxel traversal. `nx`, `ny`, and `nz` are how far along the
+    // ray we need to go to cross into the next block on each axis, and `dx`,
+    // `dy`, and `dz` are how far we need to go to cross an entire block.
+    let step = |d: f64| {
+      if d > 0.0 {
+        1
+      } else if d < 0.0 {
+        -1
+      } else {
+        0
+      }
+    };
+    let delta = |d: f64| if d == 0.0 { f64::INFINITY } else { 1.0 / d.abs() };
+    let first = |o: f64, d: f64| {
+      if d > 0.0 {
+        (o.floor() + 1.0 - o) / d
+      } else if d < 0.0 {
+        (o - o.floor()) / -d
+      } else {
+        f64::INFINITY
+      }
+    };
+    let (sx, sy, sz) = (step(dir.x), step(dir.y), step(dir.z));
+    let (dx, dy, dz) = (delta(dir.x), delta(dir.y), delta(dir.z));
+    let (mut nx, mut ny, mut nz) =
+      (first(origin.x(), dir.x), first(origin.y(), dir.y), first(origin.z(), dir.z));
+
+    let mut pos = origin.block();
+    loop {
+      // Blocks outside of the world are treated as air.
+      if let Ok(ty) = self.get_block(pos) {
+        if ty.kind() != block::Kind::Air {
+          let mut aabb =
+            self.wm.block_behaviors().call(ty.kind(), |b| b.hitbox(Block::new(self, pos, ty.ty())));
+          aabb.pos += FPos::from(pos);
+          if let Some((dist, face)) = aabb.ray_intersection(origin, dir) {
+            if dist <= max_dist {
+              return Some((pos, face, dist));
+            }
+          }
+        }
+      }
+      let dist = nx.min(ny).min(nz);
+      if dist > max_dist {
+        return None;
+      }
+      if nx == dist {
+        pos = pos.add_x(sx);
+        nx += dx;
+      } else if ny == dist {
+        pos = pos.add_y(sy);
+        ny += dy;
+      } else {
+        pos = pos.add_z(sz);
+        nz += dz;
+      }
+    }
+  }
+}
+
+/// Splits the region between the two corners into the part of it within each
+/// chunk column. This returns the chunk, and the min and max relative positions
+/// within that chunk.
+pub(super) fn chunk_rects(min: Pos, max: Pos) -> impl Iterator<Item = (ChunkPos, RelPos, RelPos)> {
+  let (min, max) = Pos::min_max(min, max);
+  (min.chunk_x()..=max.chunk_x()).flat_map(move |x| {
+    (min.chunk_z()..=max.chunk_z()).map(move |z| {
+      let min_x = if min.chunk_x() == x { min.chunk_rel_x() } else { 0 };
+      let min_z = if min.chunk_z() == z { min.chunk_rel_z() } else { 0 };
+      let max_x = if max.chunk_x() == x { max.chunk_rel_x() } else { 15 };
+      let max_z = if max.chunk_z() == z { max.chunk_rel_z() } else { 15 };
+      (
+        ChunkPos::new(x, z),
+        RelPos::new(min_x as u8, min.y, min_z as u8),
+        RelPos::new(max_x as u8, max.y, max_z as u8),
+      )
+    })
+  })
 }
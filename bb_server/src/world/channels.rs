@@ -0,0 +1,68 @@
+use super::World;
+use crate::player::Player;
+use bb_common::util::Buffer;
+use std::{collections::HashMap, sync::Arc};
+
+/// A callback for a plugin message channel. This is given the player who sent
+/// the message, and the raw data of the message.
+pub type ChannelHandler = Arc<dyn Fn(&Arc<Player>, &[u8]) + Send + Sync>;
+
+/// Returns the default channel handlers. This handles `minecraft:brand`, which
+/// every client sends on join, and the `minecraft:register` and
+/// `minecraft:unregister` channels, which clients use to list the channels
+/// they listen on.
+pub(super) fn default_channels() -> HashMap<String, ChannelHandler> {
+  let mut channels = HashMap::new();
+  let brand: ChannelHandler = Arc::new(|player, data| {
+    let mut buf = Buffer::new(data);
+    match buf.read_str(32767) {
+      Ok(brand) => debug!("{} is using client brand {brand}", player.username()),
+      Err(e) => warn!("invalid brand from {}: {e}", player.username()),
+    }
+  });
+  channels.insert("minecraft:brand".into(), brand);
+  // Both of these are a list of channel names, separated by null bytes.
+  let register: ChannelHandler = Arc::new(|player, data| {
+    for name in data.split(|&b| b == 0).filter(|n| !n.is_empty()) {
+      player.register_client_channel(String::from_utf8_lossy(name).into());
+    }
+  });
+  channels.insert("minecraft:register".into(), register);
+  let unregister: ChannelHandler = Arc::new(|player, data| {
+    for name in data.split(|&b| b == 0).filter(|n| !n.is_empty()) {
+      player.unregister_client_channel(&String::from_utf8_lossy(name));
+    }
+  });
+  channels.insert("minecraft:unregister".into(), unregister);
+  channels
+}
+
+impl World {
+  /// Registers a handler for the plugin message channel `name`. Any plugin
+  /// messages a client in this world sends on this channel will be passed to
+  /// `handler`. This replaces any handler already registered for `name` in
+  /// this world, and doesn't affect other worlds.
+  ///
+  /// Channel names should be namespaced, like `bungeecord:main`. The proxy
+  /// converts the few pre-1.13 channels that have a namespaced name, so
+  /// `BungeeCord` will show up as `bungeecord:main`. Other pre-1.13 channels
+  /// are passed through unchanged.
+  pub fn register_channel<F>(&self, name: &str, handler: F)
+  where
+    F: Fn(&Arc<Player>, &[u8]) + Send + Sync + 'static,
+  {
+    self.channels.write().insert(name.into(), Arc::new(handler));
+  }
+
+  /// Passes the given plugin message to the handler for `channel`. Messages on
+  /// unknown channels are ignored.
+  pub(crate) fn handle_plugin_message(&self, player: &Arc<Player>, channel: &str, data: &[u8]) {
+    // The lock must be released before calling the handler, so that the handler
+    // can register other channels.
+    let handler = self.channels.read().get(channel).cloned();
+    match handler {
+      Some(handler) => handler(player, data),
+      None => debug!("unknown plugin message channel {channel}"),
+    }
+  }
+}
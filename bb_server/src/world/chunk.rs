@@ -7,8 +7,8 @@ use crate::{
   },
 };
 use bb_common::{
-  chunk::{paletted::Section as PalettedSection, Chunk},
-  math::{PosError, RelPos},
+  chunk::{paletted::Section as PalettedSection, Chunk, Heightmaps, LightChunk, Section},
+  math::{PosError, RelPos, SectionRelPos},
   version::BlockVersion,
 };
 use parking_lot::{Mutex, MutexGuard};
@@ -45,6 +45,13 @@ pub struct MultiChunk {
   sky_light:   Option<SkyLightChunk>,
   block_light: BlockLightChunk,
 
+  /// The heightmaps sent to clients. These are only up to date for the columns
+  /// that aren't in `dirty_columns`.
+  heightmaps:    Heightmaps,
+  /// One bit for each column that has changed since the heightmaps were last
+  /// updated. Bit `z * 16 + x` is for the column at `x`, `z`.
+  dirty_columns: [u64; 4],
+
   /// Set to false when the world is generating, which makes things much faster.
   update_light: bool,
 }
@@ -136,10 +143,12 @@ impl MultiChunk {
   /// contain sky light information, so the sky light data is not present.
   pub fn new(wm: Arc<WorldManager>, sky: bool, height: u32, min_y: i32) -> MultiChunk {
     MultiChunk {
-      block:        BlockData::new(wm, height, min_y),
-      sky_light:    if sky { Some(SkyLightChunk::new()) } else { None },
-      block_light:  BlockLightChunk::new(),
-      update_light: true,
+      block:         BlockData::new(wm, height, min_y),
+      sky_light:     if sky { Some(SkyLightChunk::new()) } else { None },
+      block_light:   BlockLightChunk::new(),
+      heightmaps:    Heightmaps::default(),
+      dirty_columns: [u64::MAX; 4],
+      update_light:  true,
     }
   }
 
@@ -152,10 +161,12 @@ impl MultiChunk {
     chunk: Chunk<PalettedSection>,
   ) -> MultiChunk {
     MultiChunk {
-      block:        BlockData::from_data(wm, height, min_y, chunk),
-      sky_light:    if sky { Some(SkyLightChunk::new()) } else { None },
-      block_light:  BlockLightChunk::new(),
-      update_light: true,
+      block:         BlockData::from_data(wm, height, min_y, chunk),
+      sky_light:     if sky { Some(SkyLightChunk::new()) } else { None },
+      block_light:   BlockLightChunk::new(),
+      heightmaps:    Heightmaps::default(),
+      dirty_columns: [u64::MAX; 4],
+      update_light:  true,
     }
   }
 
@@ -168,9 +179,65 @@ impl MultiChunk {
   fn set_type_id(&mut self, p: RelPos, ty: u32, kind: block::Kind) -> Result<(), PosError> {
     self.block.set_type_id(p, ty, kind)?;
     self.update_light(p);
+    self.mark_dirty(p, p);
     Ok(())
   }
 
+  /// Marks every column between `min` and `max` as changed, so that the
+  /// heightmaps are updated the next time they are read.
+  fn mark_dirty(&mut self, min: RelPos, max: RelPos) {
+    for z in min.z()..=max.z() {
+      for x in min.x()..=max.x() {
+        let i = z as usize * 16 + x as usize;
+        self.dirty_columns[i / 64] |= 1 << (i % 64);
+      }
+    }
+  }
+
+  /// Returns the heightmaps for this chunk. These are cached, and only the
+  /// columns that have changed since the last call are recomputed.
+  pub fn heightmaps(&mut self) -> &Heightmaps {
+    for i in 0..256 {
+      if self.dirty_columns[i / 64] & 1 << (i % 64) != 0 {
+        self.update_column((i % 16) as u8, (i / 16) as u8);
+      }
+    }
+    self.dirty_columns = [0; 4];
+    &self.heightmaps
+  }
+
+  /// Recomputes both heightmaps for a single column, by searching down from
+  /// the top of the world.
+  fn update_column(&mut self, x: u8, z: u8) {
+    let conv = self.block.wm.block_converter();
+    let mut surface = None;
+    let mut motion = None;
+    'sections: for (index, s) in self.block.inner.sections().enumerate().rev() {
+      let s = match s {
+        Some(s) if s.non_air_blocks() != 0 => s,
+        _ => continue,
+      };
+      for y in (0..16).rev() {
+        let id = s.get_block(SectionRelPos::new(x, y, z));
+        if id == 0 {
+          continue;
+        }
+        let height = index as u32 * 16 + u32::from(y) + 1;
+        surface.get_or_insert(height);
+        let ty = conv.type_from_id(id, BlockVersion::latest());
+        let solid = matches!(conv.get(ty.kind()).bounding_box, block::BoundingBoxKind::Block);
+        let fluid =
+          matches!(ty.kind(), block::Kind::Water | block::Kind::Lava) || ty.is_waterlogged();
+        if solid || fluid {
+          motion = Some(height);
+          break 'sections;
+        }
+      }
+    }
+    self.heightmaps.world_surface.set_height(x, z, surface.unwrap_or(0));
+    self.heightmaps.motion_blocking.set_height(x, z, motion.unwrap_or(0));
+  }
+
   fn update_all_light(&mut self) {
     if let Some(sky) = &mut self.sky_light {
       sky.update_all(&self.block);
@@ -247,6 +314,7 @@ impl MultiChunk {
     // TODO: Update light correctly.
     self.update_light(min);
     self.update_light(max);
+    self.mark_dirty(min, max);
     Ok(())
   }
 
@@ -268,6 +336,7 @@ impl MultiChunk {
     // TODO: Update light correctly.
     self.update_light(min);
     self.update_light(max);
+    self.mark_dirty(min, max);
     Ok(())
   }
 
@@ -298,6 +367,84 @@ impl MultiChunk {
     )
   }
 
+  /// Gets the light level at the given position, which is the brighter of the
+  /// sky light and the block light. The sky light is not darkened at night.
+  pub fn get_light(&self, p: RelPos) -> Result<u8, PosError> {
+    let p = self.transform_pos(p)?;
+    let get = |c: &LightChunk| {
+      c.get_section_opt(p.chunk_y() as usize).map(|s| s.get(p.section_rel())).unwrap_or(0)
+    };
+    let sky = self.sky_light.as_ref().map(|c| get(&c.data)).unwrap_or(0);
+    Ok(sky.max(get(&self.block_light.data)))
+  }
+
+  /// Copies every block id between `min` and `max` (inclusive), along with any
+  /// tile entities in that region. The ids are in the same order as
+  /// [`RelPos::to`], and can be written back with
+  /// [`paste_region`](Self::paste_region). Min must be less than or equal to
+  /// max.
+  ///
+  /// Tile entities are deep copied with [`TileEntity::clone_te`], so changes
+  /// to the chunk won't affect the returned tile entities.
+  #[allow(clippy::type_complexity)]
+  pub fn copy_region(
+    &self,
+    min: RelPos,
+    max: RelPos,
+  ) -> Result<(Vec<u32>, Vec<(RelPos, Arc<dyn TileEntity>)>), PosError> {
+    let min = self.transform_pos(min)?;
+    let max = self.transform_pos(max)?;
+    let ids = min.to(max).map(|p| self.block.inner.get_block(p).unwrap()).collect();
+    let tes = self
+      .block
+      .tes
+      .iter()
+      .filter(|(p, _)| in_region(**p, min, max))
+      .map(|(p, te)| (p.add_y(-self.block.min_y), te.clone_te()))
+      .collect();
+    Ok((ids, tes))
+  }
+
+  /// Writes the given block ids between `min` and `max` (inclusive). `ids`
+  /// must be in the same order as [`RelPos::to`], which is what
+  /// [`copy_region`](Self::copy_region) returns. All the tile entities in this
+  /// region are replaced with `tes`.
+  ///
+  /// This returns an error, without changing anything, if `ids` doesn't have
+  /// exactly one id for every block in the region.
+  ///
+  /// WARNING: This will not send any packets to players! This function is meant
+  /// for use by the world directly, or during use terrain generation. If you
+  /// call this function without sending any updates yourself, no one in render
+  /// distance will see any of these changes!
+  pub fn paste_region(
+    &mut self,
+    min: RelPos,
+    max: RelPos,
+    ids: &[u32],
+    tes: &[(RelPos, Arc<dyn TileEntity>)],
+  ) -> Result<(), PosError> {
+    let len = min.to(max).len();
+    if ids.len() != len {
+      return Err(min.err(format!("expected {len} block ids, got {}", ids.len())));
+    }
+    let min = self.transform_pos(min)?;
+    let max = self.transform_pos(max)?;
+    for (p, id) in min.to(max).zip(ids) {
+      self.block.inner.set_block(p, *id).unwrap();
+    }
+    self.block.tes.retain(|p, _| !in_region(*p, min, max));
+    for (p, te) in tes {
+      let p = self.transform_pos(*p)?;
+      self.block.tes.insert(p, te.clone());
+    }
+    // TODO: Update light correctly.
+    self.update_light(min);
+    self.update_light(max);
+    self.mark_dirty(min, max);
+    Ok(())
+  }
+
   pub fn tes(&self) -> &HashMap<RelPos, Arc<dyn TileEntity>> { &self.block.tes }
   pub(crate) fn tes_mut(&mut self) -> &mut HashMap<RelPos, Arc<dyn TileEntity>> {
     &mut self.block.tes
@@ -320,6 +467,41 @@ impl MultiChunk {
     }
   }
 
+  /// Returns the number of non-air blocks in this chunk. Each section keeps
+  /// track of how much air it contains, so this only needs to look at each
+  /// section once.
+  pub fn non_air_count(&self) -> u32 {
+    self.block.inner.sections().flatten().map(|s| s.non_air_blocks()).sum()
+  }
+
+  /// Returns an iterator over every non-air block in this chunk. Empty
+  /// sections are skipped entirely, so this is much faster than calling
+  /// [`get_type`](Self::get_type) on every position in the chunk.
+  ///
+  /// The returned positions are the same as the ones passed to
+  /// [`get_type`](Self::get_type) (they can have a negative Y value).
+  pub fn iter_blocks(&self) -> impl Iterator<Item = (RelPos, block::Type)> + '_ {
+    let conv = self.wm().block_converter();
+    let min_y = self.block.min_y;
+    self
+      .block
+      .inner
+      .sections()
+      .enumerate()
+      .filter_map(|(y, s)| match s {
+        Some(s) if s.non_air_blocks() != 0 => Some((y as i32, s)),
+        _ => None,
+      })
+      .flat_map(move |(y, s)| {
+        s.iter_non_air().map(move |(pos, id)| {
+          (
+            RelPos::new(pos.x(), y * 16 + i32::from(pos.y()) - min_y, pos.z()),
+            conv.type_from_id(id, BlockVersion::latest()),
+          )
+        })
+      })
+  }
+
   /// Returns the inner paletted chunk in this MultiChunk. This can be used to
   /// access the block data directly. All ids are the latest version block
   /// states.
@@ -331,8 +513,12 @@ impl MultiChunk {
   /// will allocate all the space it needs to place a block at whatever `Y`
   /// value you specify.
   pub fn inner(&self) -> &Chunk<PalettedSection> { &self.block.inner }
-  /// Same as [`inner`](Self::inner), but returns a mutable reference.
-  pub fn inner_mut(&mut self) -> &mut Chunk<PalettedSection> { &mut self.block.inner }
+  /// Same as [`inner`](Self::inner), but returns a mutable reference. This
+  /// marks the whole chunk as changed, so the heightmaps will be recomputed.
+  pub fn inner_mut(&mut self) -> &mut Chunk<PalettedSection> {
+    self.dirty_columns = [u64::MAX; 4];
+    &mut self.block.inner
+  }
 
   /// Returns a reference to the global type converter. Used to convert a block
   /// id to/from any version.
@@ -355,3 +541,10 @@ impl MultiChunk {
     self.update_light = enabled;
   }
 }
+
+/// Returns `true` if `p` is between `min` and `max`, inclusive.
+fn in_region(p: RelPos, min: RelPos, max: RelPos) -> bool {
+  (min.x()..=max.x()).contains(&p.x())
+    && (min.y()..=max.y()).contains(&p.y())
+    && (min.z()..=max.z()).contains(&p.z())
+}
@@ -1,15 +1,19 @@
 use super::World;
 use crate::{
-  entity,
-  entity::{Entity, EntityData, EntityRef},
+  block, entity,
+  entity::{
+    behavior::{Behavior, Projectile},
+    Entity, EntityData, EntityRef,
+  },
   math::Vec3,
   player::Player,
 };
 use bb_common::{
   math::{ChunkPos, FPos},
   metadata::Metadata,
+  nbt::{Tag, WrongTag},
   net::cb,
-  util::UUID,
+  util::{Chat, UUID},
 };
 use parking_lot::RwLockReadGuard;
 use std::{
@@ -18,6 +22,7 @@ use std::{
     HashMap,
   },
   ops::{Deref, DerefMut},
+  str::FromStr,
   sync::Arc,
 };
 
@@ -61,6 +66,10 @@ impl EntitiesMapRef<'_> {
   pub fn get(&self, eid: i32) -> Option<EntityRef> {
     self.inner.get(&eid)?.as_entity_ref(self.world)
   }
+  /// Returns a clone of the entity for the given id. Unlike
+  /// [`get`](Self::get), the result can be used after the entities lock has
+  /// been released.
+  pub fn get_cloned(&self, eid: i32) -> Option<Entity> { self.inner.get(&eid).cloned() }
   /// Returns the entity for the given id. Returns `None` if the id is invalid,
   /// or if the entity is a player.
   pub fn get_ent(&self, eid: i32) -> Option<&Arc<EntityData>> { self.inner.get(&eid)?.as_entity() }
@@ -116,6 +125,29 @@ impl World {
     EntitiesMapRef { inner: self.entities.read(), world: self }
   }
 
+  /// Returns every entity (including players) within `radius` blocks of
+  /// `center`, sorted by distance, closest first. If `radius` is negative or
+  /// NaN, this returns nothing.
+  pub fn entities_in_radius(self: &Arc<Self>, center: FPos, radius: f64) -> Vec<Entity> {
+    if radius.is_nan() || radius < 0.0 {
+      return vec![];
+    }
+    let entities = self.entities();
+    let mut found: Vec<(f64, Entity)> = entities
+      .iter_values()
+      .filter_map(|(_, ent)| {
+        let dist = ent.as_entity_ref(self)?.pos().dist_squared(center);
+        if dist <= radius * radius {
+          Some((dist, ent.clone()))
+        } else {
+          None
+        }
+      })
+      .collect();
+    found.sort_by(|a, b| a.0.total_cmp(&b.0));
+    found.into_iter().map(|(_, ent)| ent).collect()
+  }
+
   pub fn summon(self: &Arc<Self>, ty: entity::Type, pos: FPos) -> i32 {
     self.summon_meta(ty, pos, Metadata::new())
   }
@@ -142,6 +174,130 @@ impl World {
     }
     eid
   }
+  /// Summons an entity with custom functionality. The behavior will be used
+  /// instead of the default behavior for `ty`, and it will be stored within
+  /// the entity until it despawns. Returns the new entity's id.
+  pub fn summon_custom<B: Behavior + Send + 'static>(
+    self: &Arc<Self>,
+    ty: entity::Type,
+    pos: FPos,
+    behavior: B,
+  ) -> i32 {
+    let eid = self.new_eid();
+    let ent = Entity::Entity(Arc::new(EntityData::new_custom(
+      eid,
+      ty,
+      pos,
+      self.clone(),
+      behavior,
+      Metadata::new(),
+      1,
+    )));
+    self.add_entity(eid, ent.clone());
+    let entity_ref = ent.as_entity_ref(self).unwrap();
+    for p in self.players().iter().in_view(pos.chunk()) {
+      self.send_entity_spawn(p, &entity_ref);
+    }
+    eid
+  }
+  /// Summons an entity, and applies the given NBT to it. This is the NBT used
+  /// in `/summon`. This handles `CustomName` and `CustomNameVisible` for all
+  /// entities, and `BlockState` for falling blocks. Any other tags are ignored.
+  ///
+  /// Returns an error if any of those tags have the wrong type.
+  pub fn summon_nbt(
+    self: &Arc<Self>,
+    ty: entity::Type,
+    pos: FPos,
+    nbt: &Tag,
+  ) -> Result<i32, WrongTag> {
+    let nbt = nbt.compound()?;
+    let mut meta = Metadata::new();
+    if let Some(name) = nbt.inner.get("CustomName") {
+      let text = name.string()?;
+      meta.set_opt_chat(2, Some(Chat::from_json(text).unwrap_or_else(|_| Chat::new(text))));
+    }
+    if let Some(visible) = nbt.inner.get("CustomNameVisible") {
+      meta.set_bool(3, visible.byte()? != 0);
+    }
+    let data = if ty == entity::Type::FallingBlock {
+      // Vanilla summons sand if there is no block state.
+      let ty = match nbt.inner.get("BlockState") {
+        Some(state) => self.block_state_from_nbt(state)?,
+        None => self.block_converter().get(block::Kind::Sand).default_type(),
+      };
+      ty.id() as i32
+    } else {
+      1
+    };
+    Ok(self.summon_meta_data(ty, pos, meta, data))
+  }
+
+  /// Parses a block state, like `{Name: "minecraft:sand"}`. Unknown block
+  /// names and properties are replaced with air and ignored, like vanilla.
+  fn block_state_from_nbt(&self, nbt: &Tag) -> Result<block::Type, WrongTag> {
+    let nbt = nbt.compound()?;
+    let name = match nbt.inner.get("Name") {
+      Some(name) => name.string()?,
+      None => "air",
+    };
+    let kind = block::Kind::from_str(name.strip_prefix("minecraft:").unwrap_or(name))
+      .unwrap_or(block::Kind::Air);
+    let mut ty = self.block_converter().get(kind).default_type();
+    if let Some(props) = nbt.inner.get("Properties") {
+      for (key, value) in props.compound()?.iter() {
+        let value = value.string()?;
+        let _ = match value {
+          "true" | "false" => ty.try_set_prop(key, value == "true"),
+          _ => match value.parse::<u32>() {
+            Ok(v) => ty.try_set_prop(key, v),
+            Err(_) => ty.try_set_prop(key, value),
+          },
+        };
+      }
+    }
+    Ok(ty)
+  }
+
+  /// Spawns a projectile (such as an arrow or snowball) at `pos`, moving with
+  /// the given velocity. If `shooter` is set, the projectile will never hit
+  /// that entity. Returns the new entity's id.
+  pub fn spawn_projectile(
+    self: &Arc<Self>,
+    ty: entity::Type,
+    pos: FPos,
+    vel: Vec3,
+    shooter: Option<i32>,
+  ) -> i32 {
+    let mut behavior = Projectile::for_type(ty);
+    if let Some(shooter) = shooter {
+      behavior = behavior.with_shooter(shooter);
+    }
+    let eid = self.new_eid();
+    let ent = Entity::Entity(Arc::new(EntityData::new_custom(
+      eid,
+      ty,
+      pos,
+      self.clone(),
+      behavior,
+      Metadata::new(),
+      1,
+    )));
+    self.add_entity(eid, ent.clone());
+    let entity_ref = ent.as_entity_ref(self).unwrap();
+    for p in self.players().iter().in_view(pos.chunk()) {
+      self.send_entity_spawn(p, &entity_ref);
+    }
+    entity_ref.set_vel(vel);
+    eid
+  }
+
+  /// Sends entity metadata packets to everyone in view of `pos`.
+  pub(crate) fn send_entity_meta(&self, pos: ChunkPos, eid: i32, ty: entity::Type, meta: Metadata) {
+    for p in self.players().iter().in_view(pos) {
+      p.send(cb::packet::EntityMetadata { eid, ty: ty.id(), meta: meta.clone() });
+    }
+  }
 
   /// Sends entity velocity packets to everyone in view of `pos`.
   pub(crate) fn send_entity_vel(&self, pos: ChunkPos, eid: i32, vel: Vec3) {
@@ -204,6 +360,9 @@ impl World {
   /// of me, figure out how to get the clients to update a custom name for a
   /// player.
   pub fn respawn_player(self: &Arc<Self>, player: &Player) {
+    if player.is_vanished() {
+      return;
+    }
     let (pos, pitch, yaw) = player.pos_look();
     let chunk = pos.block().chunk();
     let remove = cb::packet::RemoveEntities { eids: vec![player.eid()] };
@@ -219,13 +378,21 @@ impl World {
     for p in self.players().iter().in_view(chunk).not(player.id()) {
       p.send(remove.clone());
       p.send(add.clone());
+      player.send_effects_to(p);
     }
   }
 
-  fn add_entity(&self, eid: i32, entity: Entity) { self.entities.write().insert(eid, entity); }
+  pub(crate) fn add_entity(&self, eid: i32, entity: Entity) {
+    self.entities.write().insert(eid, entity);
+  }
+  /// Removes the given entity from this world, without sending any packets.
+  /// Used when an entity moves to another world.
+  pub(crate) fn take_entity(&self, eid: i32) -> Option<Entity> {
+    self.entities.write().remove(&eid)
+  }
 
   #[allow(clippy::if_same_then_else)]
-  pub(super) fn send_entity_spawn(&self, player: &Player, ent: &EntityRef) {
+  pub(crate) fn send_entity_spawn(&self, player: &Player, ent: &EntityRef) {
     let p = ent.aabb();
     if ent.ty() == entity::Type::ExperienceOrb {
       // player.send(cb::packet::SpawnEntityExperienceOrb {
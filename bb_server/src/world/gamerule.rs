@@ -0,0 +1,230 @@
+use super::World;
+use crate::config::WorldConfig;
+use bb_common::net::cb;
+use parking_lot::RwLockReadGuard;
+use std::{collections::BTreeMap, fmt};
+
+/// The value of a single gamerule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameRule {
+  Bool(bool),
+  Int(i32),
+}
+
+impl fmt::Display for GameRule {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Bool(v) => write!(f, "{v}"),
+      Self::Int(v) => write!(f, "{v}"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameRuleError {
+  /// There is no gamerule with this name.
+  Unknown(String),
+  /// The value has the wrong type for this gamerule.
+  WrongType { name: String, expected: GameRule },
+  /// The value could not be parsed.
+  Invalid { name: String, value: String },
+  /// The value is outside of the range allowed for this gamerule.
+  OutOfRange { name: String, min: i32, max: i32 },
+}
+
+impl fmt::Display for GameRuleError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Unknown(name) => write!(f, "unknown gamerule {name}"),
+      Self::WrongType { name, expected: GameRule::Bool(_) } => {
+        write!(f, "gamerule {name} must be true or false")
+      }
+      Self::WrongType { name, expected: GameRule::Int(_) } => {
+        write!(f, "gamerule {name} must be an integer")
+      }
+      Self::Invalid { name, value } => write!(f, "invalid value {value:?} for gamerule {name}"),
+      Self::OutOfRange { name, min, max } => {
+        write!(f, "gamerule {name} must be between {min} and {max}")
+      }
+    }
+  }
+}
+
+/// All of the gamerules in a world. These use the same names as vanilla, so
+/// the `/gamerule` command works the same way.
+///
+/// The set of rules is fixed when this is created, and setting a rule will
+/// never change its type. Plugins can use [`add`](Self::add) to store their own
+/// rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRules {
+  rules: BTreeMap<String, GameRule>,
+}
+
+impl GameRules {
+  /// If set, the time of day will advance every tick.
+  pub const DO_DAYLIGHT_CYCLE: &'static str = "doDaylightCycle";
+  /// If set, players will respawn right away, without seeing the death screen.
+  pub const DO_IMMEDIATE_RESPAWN: &'static str = "doImmediateRespawn";
+  /// If set, players won't drop their items when they die.
+  pub const KEEP_INVENTORY: &'static str = "keepInventory";
+  /// If set, mobs and explosions can break blocks.
+  pub const MOB_GRIEFING: &'static str = "mobGriefing";
+  /// The number of blocks picked in each chunk section for a random tick.
+  pub const RANDOM_TICK_SPEED: &'static str = "randomTickSpeed";
+  /// The largest allowed value for
+  /// [`RANDOM_TICK_SPEED`](Self::RANDOM_TICK_SPEED). A section only has 4096
+  /// blocks, so a higher speed would only slow down the server.
+  pub const MAX_RANDOM_TICK_SPEED: i32 = 4096;
+  /// If set, the debug screen will hide coordinates and other info.
+  pub const REDUCED_DEBUG_INFO: &'static str = "reducedDebugInfo";
+
+  /// Creates the default gamerules, using the values from the given config.
+  pub fn new(config: &WorldConfig) -> Self {
+    let c = &config.gamerules;
+    let mut rules = GameRules { rules: BTreeMap::new() };
+    rules.add(Self::DO_DAYLIGHT_CYCLE, GameRule::Bool(c.do_daylight_cycle));
+    rules.add(Self::DO_IMMEDIATE_RESPAWN, GameRule::Bool(c.do_immediate_respawn));
+    rules.add(Self::KEEP_INVENTORY, GameRule::Bool(c.keep_inventory));
+    rules.add(Self::MOB_GRIEFING, GameRule::Bool(c.mob_griefing));
+    rules.add(
+      Self::RANDOM_TICK_SPEED,
+      GameRule::Int(
+        config.random_tick_speed.try_into().unwrap_or(i32::MAX).min(Self::MAX_RANDOM_TICK_SPEED),
+      ),
+    );
+    rules.add(Self::REDUCED_DEBUG_INFO, GameRule::Bool(c.reduced_debug_info));
+    rules
+  }
+
+  /// Adds a new gamerule. If the rule already exists, this replaces it, even
+  /// if the old value was a different type.
+  pub fn add(&mut self, name: impl Into<String>, value: GameRule) {
+    self.rules.insert(name.into(), value);
+  }
+
+  /// Returns the value of the given gamerule, if it exists.
+  pub fn get(&self, name: &str) -> Option<GameRule> { self.rules.get(name).copied() }
+  /// Returns the value of the given boolean gamerule. If the rule doesn't
+  /// exist, or it is not a boolean, this returns `false`.
+  pub fn bool(&self, name: &str) -> bool { matches!(self.get(name), Some(GameRule::Bool(true))) }
+  /// Returns the value of the given integer gamerule. If the rule doesn't
+  /// exist, or it is not an integer, this returns 0.
+  pub fn int(&self, name: &str) -> i32 {
+    match self.get(name) {
+      Some(GameRule::Int(v)) => v,
+      _ => 0,
+    }
+  }
+
+  /// Sets the given gamerule. This fails if the rule doesn't exist, if the
+  /// value is a different type than the current value, or if the value is out
+  /// of range (see [`MAX_RANDOM_TICK_SPEED`](Self::MAX_RANDOM_TICK_SPEED)).
+  pub fn set(&mut self, name: &str, value: GameRule) -> Result<(), GameRuleError> {
+    let rule = self.rules.get_mut(name).ok_or_else(|| GameRuleError::Unknown(name.into()))?;
+    if let (Self::RANDOM_TICK_SPEED, GameRule::Int(v)) = (name, value) {
+      if !(0..=Self::MAX_RANDOM_TICK_SPEED).contains(&v) {
+        return Err(GameRuleError::OutOfRange {
+          name: name.into(),
+          min:  0,
+          max:  Self::MAX_RANDOM_TICK_SPEED,
+        });
+      }
+    }
+    match (*rule, value) {
+      (GameRule::Bool(_), GameRule::Bool(_)) | (GameRule::Int(_), GameRule::Int(_)) => {
+        *rule = value;
+        Ok(())
+      }
+      (expected, _) => Err(GameRuleError::WrongType { name: name.into(), expected }),
+    }
+  }
+
+  /// Parses `value` as the same type as the given gamerule.
+  pub fn parse(&self, name: &str, value: &str) -> Result<GameRule, GameRuleError> {
+    let invalid = || GameRuleError::Invalid { name: name.into(), value: value.into() };
+    match self.get(name) {
+      Some(GameRule::Bool(_)) => value.parse().map(GameRule::Bool).map_err(|_| invalid()),
+      Some(GameRule::Int(_)) => value.parse().map(GameRule::Int).map_err(|_| invalid()),
+      None => Err(GameRuleError::Unknown(name.into())),
+    }
+  }
+
+  /// Iterates through all the gamerules, sorted by name.
+  pub fn iter(&self) -> impl Iterator<Item = (&str, GameRule)> {
+    self.rules.iter().map(|(k, v)| (k.as_str(), *v))
+  }
+}
+
+/// Gamerule functions
+impl World {
+  /// Returns a read lock on the gamerules in this world.
+  pub fn gamerules(&self) -> RwLockReadGuard<'_, GameRules> { self.gamerules.read() }
+
+  /// Sets a gamerule, and sends the change to every player in this world, for
+  /// the rules that clients know about.
+  pub fn set_gamerule(&self, name: &str, value: GameRule) -> Result<(), GameRuleError> {
+    self.gamerules.write().set(name, value)?;
+    match (name, value) {
+      (GameRules::REDUCED_DEBUG_INFO, GameRule::Bool(v)) => {
+        for p in self.player_list() {
+          // Players think they are EID 1. Status 22 enables reduced debug info,
+          // and 23 disables it.
+          p.send(cb::packet::EntityStatus { eid: 1, status: if v { 22 } else { 23 } });
+        }
+      }
+      (GameRules::DO_IMMEDIATE_RESPAWN, GameRule::Bool(v)) => {
+        for p in self.player_list() {
+          p.send(cb::packet::ChangeGameState {
+            action: cb::ChangeGameStateKind::EnableRespawnScreen(!v),
+          });
+        }
+      }
+      _ => {}
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn set_rules() {
+    let mut rules = GameRules::new(&WorldConfig::default());
+    assert!(rules.bool(GameRules::DO_DAYLIGHT_CYCLE));
+    assert!(!rules.bool(GameRules::KEEP_INVENTORY));
+    assert_eq!(rules.int(GameRules::RANDOM_TICK_SPEED), 3);
+
+    rules.set(GameRules::KEEP_INVENTORY, GameRule::Bool(true)).unwrap();
+    assert!(rules.bool(GameRules::KEEP_INVENTORY));
+    assert_eq!(
+      rules.set(GameRules::KEEP_INVENTORY, GameRule::Int(1)),
+      Err(GameRuleError::WrongType {
+        name:     GameRules::KEEP_INVENTORY.into(),
+        expected: GameRule::Bool(true),
+      })
+    );
+    assert_eq!(rules.set("fooBar", GameRule::Int(1)), Err(GameRuleError::Unknown("fooBar".into())));
+
+    assert_eq!(rules.parse(GameRules::RANDOM_TICK_SPEED, "10"), Ok(GameRule::Int(10)));
+    assert!(rules.parse(GameRules::RANDOM_TICK_SPEED, "true").is_err());
+    assert_eq!(rules.parse(GameRules::MOB_GRIEFING, "false"), Ok(GameRule::Bool(false)));
+
+    let out_of_range = Err(GameRuleError::OutOfRange {
+      name: GameRules::RANDOM_TICK_SPEED.into(),
+      min:  0,
+      max:  4096,
+    });
+    assert_eq!(rules.set(GameRules::RANDOM_TICK_SPEED, GameRule::Int(-1)), out_of_range);
+    assert_eq!(rules.set(GameRules::RANDOM_TICK_SPEED, GameRule::Int(4097)), out_of_range);
+    assert_eq!(rules.int(GameRules::RANDOM_TICK_SPEED), 3);
+    rules.set(GameRules::RANDOM_TICK_SPEED, GameRule::Int(4096)).unwrap();
+    assert_eq!(rules.int(GameRules::RANDOM_TICK_SPEED), 4096);
+
+    // Large values in the config are clamped.
+    let config = WorldConfig { random_tick_speed: 100_000, ..Default::default() };
+    assert_eq!(GameRules::new(&config).int(GameRules::RANDOM_TICK_SPEED), 4096);
+  }
+}
@@ -1,17 +1,21 @@
-use super::{World, WorldManager};
+use super::{GameRule, GameRules, World, WorldManager};
 use crate::{
-  command::{Arg, Command, Parser, StringType},
-  entity,
+  block,
+  command::{Arg, Args, Command, Parser, StringType},
+  entity::EntityRef,
   player::Player,
 };
 use bb_common::{
-  math::ChunkPos,
+  math::{ChunkPos, Pos},
   net::cb,
-  util::{Buffer, Chat, GameMode, JoinInfo, JoinMode, SwitchMode},
+  util::{Buffer, Chat, Dimension, GameMode, JoinInfo, JoinMode, SwitchMode},
   version::ProtocolVersion,
 };
 use std::sync::Arc;
 
+/// The largest radius allowed in `/fill circle` and `/fill sphere`.
+const MAX_FILL_RADIUS: f32 = 256.0;
+
 impl World {
   pub fn init(self: &Arc<World>) {
     if self.config().vanilla.enabled {
@@ -31,41 +35,204 @@ impl World {
       .add_arg("block", Parser::BlockState);
     c.add_lit("circle")
       .add_arg("center", Parser::BlockPos)
-      .add_arg("radius", Parser::Float { min: Some(0.0), max: None })
+      .add_arg("radius", Parser::Float { min: Some(0.0), max: Some(MAX_FILL_RADIUS) })
       .add_arg("block", Parser::BlockState);
     c.add_lit("sphere")
       .add_arg("center", Parser::BlockPos)
-      .add_arg("radius", Parser::Float { min: Some(0.0), max: None })
+      .add_arg("radius", Parser::Float { min: Some(0.0), max: Some(MAX_FILL_RADIUS) })
       .add_arg("block", Parser::BlockState);
-    self.commands().add(c, |world, _, args| {
+    let block = c
+      .add_arg("from", Parser::BlockPos)
+      .add_arg("to", Parser::BlockPos)
+      .add_arg("block", Parser::BlockState);
+    block.add_lit_opt("replace");
+    block.add_lit_opt("keep");
+    block.add_lit_opt("outline");
+    self.commands().add_op(c, 2, |wm, runner, args| {
       // args[0] is `fill`
-      match args[1].lit() {
+      if args.has("from") {
+        handle_fill(wm, runner, &args);
+        return;
+      }
+      let block = args[4].block();
+      let w = wm.default_world();
+      let res = match args[1].lit() {
         "rect" => {
-          let min = args[2].pos();
-          let max = args[3].pos();
-          let block = args[4].block();
+          let min = args.get_block_pos("min").unwrap();
+          let max = args.get_block_pos("max").unwrap();
           let (min, max) = min.min_max(max);
-          let w = world.default_world();
-          w.fill_rect_kind(min, max, block).unwrap();
+          if !check_fill_volume(&w, runner, fill_volume(min, max)) {
+            return;
+          }
+          w.fill_rect_kind(min, max, block)
         }
         "circle" => {
-          let pos = args[2].pos();
-          let radius = args[3].float();
-          let block = args[4].block();
-          let w = world.default_world();
-          w.fill_circle_kind(pos, radius, block).unwrap();
+          let pos = args.get_block_pos("center").unwrap();
+          let radius = args.get_float("radius").unwrap();
+          let side = 2 * radius as u64 + 1;
+          if !check_fill_volume(&w, runner, side * side) {
+            return;
+          }
+          w.fill_circle_kind(pos, radius, block)
         }
         "sphere" => {
-          let pos = args[2].pos();
-          let radius = args[3].float();
-          let block = args[4].block();
-          let w = world.default_world();
-          w.fill_sphere_kind(pos, radius, block).unwrap();
+          let pos = args.get_block_pos("center").unwrap();
+          let radius = args.get_float("radius").unwrap();
+          let side = 2 * radius as u64 + 1;
+          if !check_fill_volume(&w, runner, side * side * side) {
+            return;
+          }
+          w.fill_sphere_kind(pos, radius, block)
         }
         _ => unreachable!(),
+      };
+      if let (Err(e), Some(p)) = (res, runner) {
+        p.send_message(Chat::new(e.to_string()));
+      }
+    });
+    fn handle_fill(wm: &Arc<WorldManager>, runner: Option<&Arc<Player>>, args: &Args) {
+      let (min, max) =
+        args.get_block_pos("from").unwrap().min_max(args.get_block_pos("to").unwrap());
+      let kind = args.get_arg("block").unwrap().block();
+      let world = runner.map(|p| p.world()).unwrap_or_else(|| wm.default_world());
+      if !check_fill_volume(&world, runner, fill_volume(min, max)) {
+        return;
+      }
+      let reply = |msg: String| {
+        if let Some(p) = runner {
+          p.send_message(Chat::new(msg));
+        }
+      };
+      let rects = if args.has("outline") { outline(min, max) } else { vec![(min, max)] };
+      let keep = args.has("keep");
+      let ty = world.block_converter().get(kind).default_type();
+      let mut changed = 0;
+      let res = rects.into_iter().try_for_each(|(min, max)| {
+        if !keep {
+          world.fill_rect_kind(min, max, kind)?;
+          changed += fill_volume(min, max);
+          return Ok(());
+        }
+        // Only air is replaced, so we need to check every block.
+        for pos in min.to(max) {
+          if world.get_kind(pos)? == block::Kind::Air && world.set_block_no_update(pos, ty)? {
+            changed += 1;
+          }
+        }
+        Ok(())
+      });
+      match res {
+        Ok(()) => reply(format!("filled {changed} blocks")),
+        Err(e) => reply(e.to_string()),
+      }
+    }
+    /// Returns `true` if `volume` is within the world's `max_fill_volume`.
+    /// Otherwise, this sends an error to `runner` and returns `false`.
+    fn check_fill_volume(world: &World, runner: Option<&Arc<Player>>, volume: u64) -> bool {
+      let limit = world.config().max_fill_volume;
+      if volume > limit {
+        if let Some(p) = runner {
+          p.send_message(Chat::new(format!(
+            "too many blocks in the specified area (maximum {limit}, specified {volume})"
+          )));
+        }
+        return false;
+      }
+      true
+    }
+    let mut c = Command::new("setblock");
+    c.add_arg("pos", Parser::BlockPos).add_arg("block", Parser::BlockState);
+    self.commands().add_op(c, 2, |wm, runner, args| {
+      let pos = args.get_block_pos("pos").unwrap();
+      let kind = args.get_arg("block").unwrap().block();
+      let world = runner.map(|p| p.world()).unwrap_or_else(|| wm.default_world());
+      let msg = match world.set_kind(pos, kind) {
+        Ok(true) => format!("changed the block at {pos}"),
+        Ok(false) => "this world is locked".to_string(),
+        Err(e) => e.to_string(),
+      };
+      if let Some(p) = runner {
+        p.send_message(Chat::new(msg));
+      }
+    });
+    // The location branch is first, so that `/tp 1 2 3` isn't parsed as a
+    // player named `1`.
+    let mut c = Command::new("tp");
+    c.add_arg("location", Parser::Vec3);
+    let targets = c.add_arg("targets", Parser::Entity { single: false, only_players: false });
+    targets.add_arg("location", Parser::Vec3).add_arg_opt("rotation", Parser::Rotation);
+    targets.add_arg("destination", Parser::Entity { single: true, only_players: false });
+    c.add_arg("destination", Parser::Entity { single: true, only_players: false });
+    self.commands().add_op(c, 2, |wm, runner, args| {
+      let reply = |msg: String| {
+        if let Some(p) = runner {
+          p.send_message(Chat::new(msg));
+        }
+      };
+      let (dest_world, pos) = match args.get_vec3("location") {
+        Some(pos) => (runner.map(|p| p.world()), pos),
+        None => {
+          let dest = args.get_entity("destination").unwrap();
+          let found = wm.worlds().iter().find_map(|w| {
+            let entities = w.entities();
+            let pos = dest.clone().iter(&entities, runner).next().map(|e| e.pos());
+            pos.map(|pos| (Some(w.clone()), pos))
+          });
+          match found {
+            Some(found) => found,
+            None => {
+              reply("no entity was found".into());
+              return;
+            }
+          }
+        }
+      };
+      let mut moved = 0;
+      let mut teleport = |ent: &EntityRef| {
+        let (pitch, yaw) = ent.look();
+        let (yaw, pitch) = args.get_rotation("rotation", (yaw, pitch)).unwrap_or((yaw, pitch));
+        match &dest_world {
+          Some(w) => ent.teleport_to_world(w, pos, yaw, pitch),
+          None => ent.teleport(pos, yaw, pitch),
+        }
+        moved += 1;
+      };
+      match args.get_entity("targets") {
+        Some(targets) => {
+          // Collect every target first, as moving an entity between worlds
+          // needs to lock the entities of both worlds.
+          let mut found = vec![];
+          for world in wm.worlds().iter() {
+            let entities = world.entities();
+            let eids: Vec<i32> = targets.clone().iter(&entities, runner).map(|e| e.eid()).collect();
+            for (eid, ent) in entities.iter_values() {
+              if eids.contains(eid) {
+                found.push((world.clone(), ent.clone()));
+              }
+            }
+          }
+          for (world, ent) in &found {
+            if let Some(ent) = ent.as_entity_ref(world) {
+              teleport(&ent);
+            }
+          }
+        }
+        None => match runner {
+          Some(p) => teleport(&EntityRef::Player(p.clone())),
+          None => {
+            reply("a target must be specified".into());
+            return;
+          }
+        },
       }
+      let (x, y, z) = (pos.x(), pos.y(), pos.z());
+      reply(match moved {
+        0 => "no entity was found".into(),
+        1 => format!("teleported 1 entity to {x:.2} {y:.2} {z:.2}"),
+        _ => format!("teleported {moved} entities to {x:.2} {y:.2} {z:.2}"),
+      });
     });
-    fn handle_gamemode(wm: &Arc<WorldManager>, runner: Option<&Arc<Player>>, args: Vec<Arg>) {
+    fn handle_gamemode(wm: &Arc<WorldManager>, runner: Option<&Arc<Player>>, args: Args) {
       let gm = match &args[1] {
         Arg::Literal(lit) => match lit.as_str() {
           "survival" | "s" => GameMode::Survival,
@@ -140,6 +307,18 @@ impl World {
         p.set_flying_allowed(!p.flying_allowed());
       }
     });
+    let c = Command::new("vanish");
+    self.commands().add_op(c, 2, |_, player, _| {
+      if let Some(p) = player {
+        let vanished = !p.is_vanished();
+        p.set_vanished(vanished);
+        p.send_message(Chat::new(if vanished {
+          "you are now vanished"
+        } else {
+          "you are no longer vanished"
+        }));
+      }
+    });
     let mut c = Command::new("flyspeed");
     c.add_arg("multiplier", Parser::Float { min: None, max: None });
     self.commands().add(c, |_, player, args| {
@@ -151,14 +330,75 @@ impl World {
     });
 
     let mut c = Command::new("summon");
-    c.add_arg("entity", Parser::EntitySummon);
-    self.commands().add(c, |_, player, args| {
+    c.add_arg("entity", Parser::EntitySummon)
+      .add_arg_opt("pos", Parser::Vec3)
+      .add_arg_opt("nbt", Parser::NbtCompoundTag);
+    self.commands().add_op(c, 2, |wm, player, args| {
       // args[0] is `summon`
       let ty = args[1].entity_summon();
+      let world = player.map(|p| p.world()).unwrap_or_else(|| wm.default_world());
+      let pos = match (args.get_vec3("pos"), player) {
+        (Some(pos), _) => pos,
+        (None, Some(p)) => p.pos(),
+        (None, None) => world.spawn_point(),
+      };
+      let res = match args.get_arg("nbt") {
+        Some(Arg::NbtCompoundTag(nbt)) => world.summon_nbt(ty, pos, nbt.tag()),
+        _ => Ok(world.summon(ty, pos)),
+      };
+      let msg = match res {
+        Ok(eid) => {
+          info!("eid of mob: {}", eid);
+          format!("summoned {}", ty.to_str())
+        }
+        Err(e) => e.to_string(),
+      };
       if let Some(p) = player {
-        let eid = p.world().summon(ty, p.pos());
-        info!("eid of mob: {}", eid);
-        p.send_message(Chat::new(format!("summoned {:?}", ty)));
+        p.send_message(Chat::new(msg));
+      }
+    });
+
+    let mut c = Command::new("give");
+    c.add_arg("target", Parser::Entity { single: false, only_players: true })
+      .add_arg("item", Parser::ItemStack)
+      .add_arg_opt("count", Parser::Int { min: Some(1), max: Some(6400) });
+    self.commands().add_op(c, 2, |wm, runner, args| {
+      let stack = args.get_item_stack("item").unwrap();
+      let count = args.get_int("count").unwrap_or(1) as u32;
+      let max = u32::from(stack.max_amount().max(1));
+      let mut given = 0;
+      for world in wm.worlds().iter() {
+        for target in args[1].entity().iter(&world.entities(), runner) {
+          let p = match target.as_player() {
+            Some(p) => p,
+            None => continue,
+          };
+          // A single stack can only hold `max` items, so large counts are given
+          // one stack at a time.
+          let mut left = count;
+          let mut inv = p.lock_inventory();
+          while left > 0 {
+            let amount = left.min(max);
+            let remaining = u32::from(inv.give(stack.clone().with_amount(amount as u8)));
+            left -= amount - remaining;
+            if remaining > 0 {
+              break;
+            }
+          }
+          drop(inv);
+          if left > 0 {
+            p.send_message(Chat::new(format!("{} items did not fit in your inventory", left)));
+          }
+          given += 1;
+        }
+      }
+      if let Some(p) = runner {
+        let name = stack.item().data().name();
+        p.send_message(Chat::new(match given {
+          0 => "no players found".to_string(),
+          1 => format!("gave {count} {name} to 1 player"),
+          _ => format!("gave {count} {name} to {given} players"),
+        }));
       }
     });
 
@@ -177,6 +417,64 @@ impl World {
       }
     });
 
+    let mut c = Command::new("world");
+    // This can't be called `world`, as `get_string` would find the literal
+    // `world` at the start of the command instead.
+    c.add_arg("name", Parser::String(StringType::Word));
+    self.commands().add_op(c, 2, |wm, player, args| {
+      // The name argument is required, so this is always present.
+      let name = args.get_string("name").unwrap();
+      if let Some(p) = player {
+        let idx = match name.parse::<usize>() {
+          Ok(idx) => Some(idx),
+          Err(_) => wm.world_index(name),
+        };
+        if !idx.map(|idx| wm.move_player(p, idx)).unwrap_or(false) {
+          p.send_message(Chat::new(format!("cannot move to world {name}")));
+        }
+      }
+    });
+
+    let mut c = Command::new("gamerule");
+    for (name, value) in self.gamerules().iter() {
+      let parser = match value {
+        GameRule::Bool(_) => Parser::Bool,
+        GameRule::Int(_) if name == GameRules::RANDOM_TICK_SPEED => {
+          Parser::Int { min: Some(0), max: Some(GameRules::MAX_RANDOM_TICK_SPEED) }
+        }
+        GameRule::Int(_) => Parser::Int { min: None, max: None },
+      };
+      c.add_lit(name).add_arg_opt("value", parser);
+    }
+    self.commands().add_op(c, 2, |wm, player, args| {
+      let world = player.map(|p| p.world()).unwrap_or_else(|| wm.default_world());
+      let name = args[1].lit();
+      let msg = match args.get(2) {
+        Some(arg) => {
+          let value = match arg {
+            Arg::Bool(v) => GameRule::Bool(*v),
+            Arg::Int(v) => GameRule::Int(*v),
+            _ => unreachable!(),
+          };
+          match world.set_gamerule(name, value) {
+            Ok(()) => format!("gamerule {name} is now set to {value}"),
+            Err(e) => e.to_string(),
+          }
+        }
+        None => match world.gamerules().get(name) {
+          Some(value) => format!("gamerule {name} is currently set to {value}"),
+          None => format!("unknown gamerule {name}"),
+        },
+      };
+      match player {
+        Some(p) => p.send_message(Chat::new(msg)),
+        None => info!("{msg}"),
+      }
+    });
+
+    let c = Command::new("stop");
+    self.commands().add_op(c, 4, |wm, _, _| wm.request_stop());
+
     info!("generating terrain...");
     /*
     let chunks = Mutex::new(vec![]);
@@ -252,12 +550,13 @@ impl World {
           eid:                   1,
           hardcore_mode:         false,
           game_mode:             player.game_mode(),
-          dimension:             0, // Overworld
+          dimension:             self.dimension,
           level_type:            "default".into(),
           difficulty:            1, // Normal
           view_distance:         player.view_distance() as u16,
-          reduced_debug_info:    false,
-          enable_respawn_screen: true,
+          simulation_distance:   player.simulation_distance() as u16,
+          reduced_debug_info:    self.gamerules().bool(GameRules::REDUCED_DEBUG_INFO),
+          enable_respawn_screen: !self.gamerules().bool(GameRules::DO_IMMEDIATE_RESPAWN),
           world_height:          self.height,
           world_min_y:           self.min_y,
         };
@@ -265,19 +564,29 @@ impl World {
         player.send(out);
       }
       JoinMode::Switch(SwitchMode::Loading) => {
+        // The client won't reload the world if it respawns into the same dimension
+        // it's already in, so we send it to some other dimension first.
+        let other = match self.dimension {
+          Dimension::Overworld => Dimension::End,
+          _ => Dimension::Overworld,
+        };
         player.send(cb::packet::Respawn {
-          difficulty: 1,
-          dimension:  1,
-          game_mode:  player.game_mode(),
-          level_type: "default".into(),
-          reset_meta: true,
+          difficulty:   1,
+          dimension:    other,
+          game_mode:    player.game_mode(),
+          level_type:   "default".into(),
+          reset_meta:   true,
+          world_height: self.height,
+          world_min_y:  self.min_y,
         });
         player.send(cb::packet::Respawn {
-          difficulty: 1,
-          dimension:  0,
-          game_mode:  player.game_mode(),
-          level_type: "default".into(),
-          reset_meta: true,
+          difficulty:   1,
+          dimension:    self.dimension,
+          game_mode:    player.game_mode(),
+          level_type:   "default".into(),
+          reset_meta:   true,
+          world_height: self.height,
+          world_min_y:  self.min_y,
         });
         let pos = player.pos().chunk();
         for x in -(player.view_distance() as i32)..=player.view_distance() as i32 {
@@ -289,20 +598,17 @@ impl World {
       JoinMode::Switch(SwitchMode::Seemless) => {}
     }
 
-    if player.ver() >= ProtocolVersion::V1_13 {
-      // TODO: Fix tags for older versions.
-      if player.ver() >= ProtocolVersion::V1_18_2 {
-        player.send(self.world_manager().tags().serialize());
-      }
-      player.send(self.commands().serialize());
+    // TODO: Fix tags for older versions.
+    if player.ver() >= ProtocolVersion::V1_18_2 {
+      player.send(self.world_manager().tags().serialize());
     }
+    player.send_commands();
 
     player.send(cb::packet::EntityStatus {
       // Players think they are EID 1.
       eid:    1,
-      // Set op permission to level 4
       // Note that 24 is op permission 0, 25 is op permission 1, etc.
-      status: 28,
+      status: 24 + player.op_level(),
     });
 
     let d = player.view_distance() as i32;
@@ -317,7 +623,9 @@ impl World {
     let mut data = vec![];
     let mut buf = Buffer::new(&mut data);
     buf.write_str("Bamboo");
-    player.send(cb::packet::PluginMessage { channel: "minecraft:brand".into(), data });
+    player.send_plugin_message("minecraft:brand", data);
+
+    player.send(cb::packet::SpawnPosition { pos: self.spawn_point().block() });
 
     let pos = player.pos();
     player.send(cb::packet::SetPosLook {
@@ -329,13 +637,7 @@ impl World {
       should_dismount: true,
     });
 
-    let my_info = cb::PlayerListAdd {
-      id:           player.id(),
-      name:         player.username().clone(),
-      game_mode:    GameMode::Creative,
-      ping:         50,
-      display_name: player.tab_name().clone().map(|c| c.to_json()),
-    };
+    let my_info = player.player_list_info();
     let my_info_packet =
       cb::packet::PlayerList { action: cb::PlayerListAction::Add(vec![my_info.clone()]) };
 
@@ -343,17 +645,16 @@ impl World {
     // weird.
     let mut info = vec![my_info];
     for other in self.players().iter().not(player.id()) {
-      // Lets `other` know that I exist
-      other.send(my_info_packet.clone());
+      // Lets `other` know that I exist. Vanished players aren't shown to anyone
+      // else, but they can still see everyone.
+      if !player.is_vanished() {
+        other.send(my_info_packet.clone());
+      }
 
       // Add `other` to the list of players that I know about
-      info.push(cb::PlayerListAdd {
-        id:           other.id(),
-        name:         other.username().clone(),
-        game_mode:    GameMode::Creative,
-        ping:         50,
-        display_name: other.tab_name().clone().map(|c| c.to_json()),
-      });
+      if !other.is_vanished() {
+        info.push(other.player_list_info());
+      }
     }
     player.send(cb::packet::PlayerList { action: cb::PlayerListAction::Add(info) });
 
@@ -366,29 +667,15 @@ impl World {
         if other.id() == player.id() {
           continue;
         }
-        // Create a packet that will spawn me for `other`
-        let (pos, pitch, yaw) = player.pos_look();
-        other.send(cb::packet::SpawnPlayer {
-          eid: player.eid(),
-          id: player.id(),
-          ty: entity::Type::Player.id(),
-          pos,
-          yaw: yaw as i8,
-          pitch: pitch as i8,
-          meta: player.metadata(),
-        });
+        // Spawn me for `other`
+        if !player.is_vanished() {
+          player.spawn_for(other);
+        }
 
-        // Create a packet that will spawn `other` for me
-        let (pos, pitch, yaw) = other.pos_look();
-        player.send(cb::packet::SpawnPlayer {
-          eid: other.eid(),
-          id: other.id(),
-          ty: entity::Type::Player.id(),
-          pos,
-          yaw: yaw as i8,
-          pitch: pitch as i8,
-          meta: other.metadata(),
-        });
+        // Spawn `other` for me
+        if !other.is_vanished() {
+          other.spawn_for(player);
+        }
       }
       if other.as_entity().is_some() {
         // Create a packet that will spawn `other` for me
@@ -401,3 +688,83 @@ impl World {
     }
   }
 }
+
+/// Returns the number of blocks between `min` and `max` (inclusive). This
+/// uses 64 bit math, so that huge regions can't overflow.
+fn fill_volume(min: Pos, max: Pos) -> u64 {
+  let size = |a: i32, b: i32| (b as i64 - a as i64 + 1) as u64;
+  size(min.x(), max.x())
+    .saturating_mul(size(min.y(), max.y()))
+    .saturating_mul(size(min.z(), max.z()))
+}
+
+/// Splits the faces of the region between `min` and `max` into rectangles
+/// that don't overlap.
+fn outline(min: Pos, max: Pos) -> Vec<(Pos, Pos)> {
+  let mut rects = vec![(min, max.with_x(min.x()))];
+  if max.x() > min.x() {
+    rects.push((min.with_x(max.x()), max));
+  }
+  // These saturate, so that regions at the edge of the world can't overflow.
+  let (x0, x1) = (min.x().saturating_add(1), max.x().saturating_sub(1));
+  if x0 > x1 {
+    return rects;
+  }
+  rects.push((Pos::new(x0, min.y(), min.z()), Pos::new(x1, min.y(), max.z())));
+  if max.y() > min.y() {
+    rects.push((Pos::new(x0, max.y(), min.z()), Pos::new(x1, max.y(), max.z())));
+  }
+  let (y0, y1) = (min.y().saturating_add(1), max.y().saturating_sub(1));
+  if y0 > y1 {
+    return rects;
+  }
+  rects.push((Pos::new(x0, y0, min.z()), Pos::new(x1, y1, min.z())));
+  if max.z() > min.z() {
+    rects.push((Pos::new(x0, y0, max.z()), Pos::new(x1, y1, max.z())));
+  }
+  rects
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::collections::HashSet;
+
+  #[test]
+  fn fill_outline() {
+    let check = |min: Pos, max: Pos, expected: u64| {
+      let mut seen = HashSet::new();
+      for (a, b) in outline(min, max) {
+        for pos in a.to(b) {
+          // Every block must be on a face, and rectangles must not overlap.
+          assert!(
+            pos.x() == min.x()
+              || pos.x() == max.x()
+              || pos.y() == min.y()
+              || pos.y() == max.y()
+              || pos.z() == min.z()
+              || pos.z() == max.z(),
+            "{pos} is not on the outline of {min} to {max}"
+          );
+          assert!(seen.insert(pos), "{pos} was filled twice");
+        }
+      }
+      assert_eq!(seen.len() as u64, expected);
+    };
+    check(Pos::new(0, 0, 0), Pos::new(0, 0, 0), 1);
+    check(Pos::new(0, 0, 0), Pos::new(2, 0, 2), 9);
+    check(Pos::new(0, 0, 0), Pos::new(2, 2, 2), 26);
+    // The inside is 5x3x3.
+    check(Pos::new(-3, 10, 5), Pos::new(3, 14, 9), 7 * 5 * 5 - 5 * 3 * 3);
+    // Regions at the edge of the world must not overflow.
+    check(Pos::new(i32::MAX, 0, 0), Pos::new(i32::MAX, 0, 0), 1);
+  }
+
+  #[test]
+  fn fill_volume_overflow() {
+    assert_eq!(fill_volume(Pos::new(0, 0, 0), Pos::new(1, 1, 1)), 8);
+    let (min, max) =
+      (Pos::new(i32::MIN, i32::MIN, i32::MIN), Pos::new(i32::MAX, i32::MAX, i32::MAX));
+    assert_eq!(fill_volume(min, max), u64::MAX);
+  }
+}
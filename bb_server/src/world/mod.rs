@@ -13,31 +13,38 @@
 
 mod bbr;
 mod blocks;
+mod channels;
 mod chunk;
 mod chunks;
 mod entities;
+mod gamerule;
 pub mod gen;
 mod init;
 mod players;
+mod redstone;
 mod region;
+mod scheduled;
 pub mod schematic;
+mod snapshot;
 
 use crate::config::{Config, WorldConfig};
 use bb_common::{
-  math::{ChunkPos, FPos, Pos, SectionRelPos},
+  chunk::Section,
+  math::{ChunkPos, FPos, Pos, SectionRelPos, WyhashRng},
   net::cb,
   util::{
     chat::{Chat, Color},
-    GameMode, JoinInfo, ThreadPool, UUID,
+    Dimension, GameMode, JoinInfo, ThreadPool, UUID,
   },
 };
 use parking_lot::{Mutex, MutexGuard, RwLock, RwLockReadGuard};
+use rand::Rng;
 use std::{
-  collections::HashMap,
+  collections::{HashMap, HashSet},
   convert::TryInto,
   fmt,
   sync::{
-    atomic::{AtomicBool, AtomicI32, AtomicU32, Ordering},
+    atomic::{AtomicBool, AtomicI32, AtomicU32, AtomicU64, Ordering},
     Arc,
   },
   thread,
@@ -58,14 +65,21 @@ use crate::{
   tags::Tags,
 };
 
+pub use channels::ChannelHandler;
 pub use chunk::{BlockData, CountedChunk, MultiChunk};
 pub use entities::{EntitiesIter, EntitiesMap, EntitiesMapRef};
+pub use gamerule::{GameRule, GameRuleError, GameRules};
 pub use players::{PlayersIter, PlayersMap};
+pub use snapshot::RegionSnapshot;
 
 use bbr::{RegionMap, RegionRelPos};
 use chunks::ChunksToLoad;
 use gen::WorldGen;
 
+/// Set once the server should shut down. This is a static, so that the signal
+/// handler in [`WorldManager::stop_on_ctrlc`] can set it.
+static STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
 // pub struct ChunkRef<'a> {
 //   pos:    ChunkPos,
 //   // Need to keep this is scope while we mess with the chunk
@@ -88,7 +102,6 @@ pub struct World {
   pub gen:              WorldGen,
   pub players:          RwLock<PlayersMap>,
   pub entities:         RwLock<EntitiesMap>,
-  eid:                  AtomicI32,
   pub block_converter:  Arc<block::TypeConverter>,
   pub item_converter:   Arc<item::TypeConverter>,
   pub entity_converter: Arc<entity::TypeConverter>,
@@ -100,6 +113,38 @@ pub struct World {
   // If set, then the world cannot be modified.
   pub locked:           AtomicBool,
 
+  /// The name of this world. Set in [`WorldManager::add_world`].
+  name:      String,
+  /// The dimension type of this world. Set in [`WorldManager::add_world`].
+  dimension: Dimension,
+
+  /// The number of ticks since this world was created.
+  tick:            AtomicU64,
+  /// The time the last tick took, in microseconds.
+  last_tick_time:  AtomicU64,
+  /// The time all ticks have taken, in microseconds.
+  total_tick_time: AtomicU64,
+  /// The number of ticks run in the last second, stored as the bits of an
+  /// `f64`. See [`tps`](Self::tps).
+  tps:             AtomicU64,
+  /// Blocks that need a scheduled tick, along with the tick they should be
+  /// updated on.
+  scheduled_ticks: Mutex<scheduled::ScheduledTicks>,
+  /// Blocks that need their redstone power recalculated.
+  redstone_queue:  Mutex<redstone::RedstoneQueue>,
+  /// Where new players spawn, and where players without their own spawn point
+  /// respawn.
+  spawn_point:     RwLock<FPos>,
+  /// Used to pick blocks for random ticks. See
+  /// [`seed_random_ticks`](Self::seed_random_ticks).
+  random:          Mutex<WyhashRng>,
+  /// Plugin message channel handlers. See
+  /// [`register_channel`](Self::register_channel).
+  channels:        RwLock<HashMap<String, ChannelHandler>>,
+  /// The gamerules in this world. These start out with the values in the
+  /// config.
+  gamerules:       RwLock<GameRules>,
+
   chunks_to_load: Mutex<ChunksToLoad>,
 
   /// A height in blocks. Default is `256`.
@@ -133,16 +178,28 @@ pub struct WorldManager {
   data:             Arc<Data>,
 
   default_game_mode: GameMode,
-  spawn_point:       FPos,
+
+  // The next EID to use. This is shared between all worlds.
+  eid: AtomicI32,
+}
+
+/// Returned from [`WorldManager::add_world`] when there is already a world with
+/// the given name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorldExistsError(pub String);
+
+impl fmt::Display for WorldExistsError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "a world with the name `{}` already exists", self.0)
+  }
 }
 
+impl std::error::Error for WorldExistsError {}
+
 struct State {
-  uspt:  Arc<AtomicU32>,
   world: Arc<World>,
 }
 
-const TICK_TIME: Duration = Duration::from_millis(50);
-
 impl World {
   /// Creates a new world. See also [`WorldManager::add_world`].
   pub(crate) fn new(
@@ -155,6 +212,8 @@ impl World {
     wm: Arc<WorldManager>,
   ) -> Self {
     let gen = WorldGen::from_config(&config);
+    let spawn_point = wm.config().spawn_point;
+    let gamerules = GameRules::new(&config);
     /*
     for schematic in config.get::<_, Vec<String>>("schematics") {
       let path = schematic.get("path");
@@ -171,8 +230,6 @@ impl World {
       gen,
       players: RwLock::new(PlayersMap::new()),
       entities: RwLock::new(EntitiesMap::new()),
-      // All player's think they are EID 1, so we start at 2. EID 0 is invalid.
-      eid: 2.into(),
       block_converter,
       item_converter,
       entity_converter,
@@ -184,7 +241,19 @@ impl World {
       min_y: config.min_y,
       config,
       wm,
+      name: "world".into(),
+      dimension: Dimension::Overworld,
       chunks_to_load: Mutex::new(ChunksToLoad::new()),
+      tick: 0.into(),
+      last_tick_time: 0.into(),
+      total_tick_time: 0.into(),
+      tps: 0.into(),
+      scheduled_ticks: Mutex::new(scheduled::ScheduledTicks::new()),
+      redstone_queue: Mutex::new(redstone::RedstoneQueue::new()),
+      random: Mutex::new(WyhashRng::new(rand::random())),
+      channels: RwLock::new(channels::default_channels()),
+      spawn_point: RwLock::new(spawn_point),
+      gamerules: RwLock::new(gamerules),
     }
   }
 
@@ -192,10 +261,7 @@ impl World {
   pub fn config(&self) -> &WorldConfig { &self.config }
 
   fn global_tick_loop(self: Arc<Self>) {
-    let pool = ThreadPool::auto("global tick loop", || State {
-      uspt:  self.uspt.clone(),
-      world: Arc::clone(&self),
-    });
+    let pool = ThreadPool::auto("global tick loop", || State { world: Arc::clone(&self) });
     // We set a limit to double the number of cores. This means that we will only
     // hit an artificial limit if we can generate a chunk in 10 ms. The more we
     // increase this, the worse the ordering for generating chunks gets.
@@ -203,32 +269,43 @@ impl World {
     // TODO: Make this configurable.
     let chunk_pool =
       ThreadPool::auto_with_limit("chunk generator", bb_common::util::num_cpus() * 5, || State {
-        uspt:  self.uspt.clone(),
         world: Arc::clone(&self),
       });
+    let interval = self.wm.tick_time();
+    // The number of ticks in one second, and the number of microseconds in one
+    // tick. Anything that should happen on a timer (instead of every N ticks)
+    // is derived from these.
+    let tick_rate = self.wm.config.tick_rate.max(1);
+    let tick_budget = u32::try_from(interval.as_micros()).unwrap_or(u32::MAX);
     let mut tick = 0;
     let mut start = Instant::now();
+    // Used to measure the effective TPS.
+    let mut second_start = start;
+    let mut ticks_this_second = 0;
     let mut needs_to_unload = false;
     loop {
-      if self.wm.config.debug_playerlist && tick % 20 == 0 {
+      let tick_start = Instant::now();
+      if self.wm.config.debug_playerlist && tick % tick_rate == 0 {
         let mut header = Chat::empty();
         let mut footer = Chat::empty();
 
         header.add("big gaming\n").color(Color::Blue);
         footer.add("\nuspt: ");
-        let uspt = self.uspt.swap(0, Ordering::SeqCst) / 20;
-        footer.add(uspt.to_string()).color(if uspt > 50_000 {
+        let uspt = self.uspt.swap(0, Ordering::SeqCst) / tick_rate;
+        footer.add(uspt.to_string()).color(if uspt > tick_budget {
           Color::Red
-        } else if uspt > 20_000 {
+        } else if uspt > tick_budget / 5 * 2 {
           Color::Gold
-        } else if uspt > 10_000 {
+        } else if uspt > tick_budget / 5 {
           Color::Yellow
         } else {
           Color::BrightGreen
         });
+        footer.add("\ntps: ");
+        footer.add(format!("{:.1}", self.tps()));
 
         let out = cb::packet::PlayerHeader { header: header.to_json(), footer: footer.to_json() };
-        for p in self.players().values() {
+        for p in self.player_list() {
           p.send(out.clone());
         }
       }
@@ -238,7 +315,7 @@ impl World {
       // try again next tick. We do all of this before `check_chunks_queue`, as this
       // might also push tasks to `chunk_pool`, and unloading chunks should have
       // higher priority than loading more chunks.
-      if tick % (20 * 30) == 0 {
+      if tick % tick_rate.saturating_mul(30) == 0 {
         needs_to_unload = true;
       }
       if needs_to_unload {
@@ -250,6 +327,9 @@ impl World {
         }
       }
       self.check_chunks_queue(&chunk_pool);
+      self.run_scheduled_ticks();
+      self.run_random_ticks();
+      self.tick_redstone();
       /*
       for p in self.players().iter() {
         let p = p.clone();
@@ -269,31 +349,75 @@ impl World {
       */
       for (&eid, ent) in self.entities().iter_values() {
         let ent = ent.clone();
-        let w = self.clone();
-        pool.execute(move |s| {
-          if let Some(ent) = ent.as_entity_ref(w.as_ref()) {
-            let start = Instant::now();
-            if ent.tick() {
-              s.world.entities.write().remove(&eid);
-              for p in s.world.players().iter().in_view(ent.pos().block().chunk()) {
-                p.send(cb::packet::RemoveEntities { eids: vec![eid] });
-              }
-            }
-            s.uspt.fetch_add(start.elapsed().as_micros().try_into().unwrap(), Ordering::SeqCst);
-          }
-        });
+        pool.execute(move |s| s.world.tick_entity(eid, ent));
       }
       // We don't want overlapping tick loops
       pool.wait();
+      let tick_time = tick_start.elapsed().as_micros().try_into().unwrap_or(u64::MAX);
+      self.last_tick_time.store(tick_time, Ordering::Relaxed);
+      self.total_tick_time.fetch_add(tick_time, Ordering::Relaxed);
       tick += 1;
+      self.tick.fetch_add(1, Ordering::SeqCst);
+      ticks_this_second += 1;
+      let since_second = second_start.elapsed();
+      if since_second >= Duration::from_secs(1) {
+        let tps = f64::from(ticks_this_second) / since_second.as_secs_f64();
+        self.tps.store(tps.to_bits(), Ordering::Relaxed);
+        second_start = Instant::now();
+        ticks_this_second = 0;
+      }
       let passed = Instant::now().duration_since(start);
-      start += TICK_TIME;
-      match TICK_TIME.checked_sub(passed) {
+      start += interval;
+      match interval.checked_sub(passed) {
         Some(t) => spin_sleep::sleep(t),
-        None => warn!("tick took {passed:?} (more than 50 ms)"),
+        None => warn!("tick took {passed:?} (more than {interval:?})"),
+      }
+    }
+  }
+  /// Ticks a single entity (or player), and removes it if it despawned.
+  fn tick_entity(self: &Arc<Self>, eid: i32, ent: Entity) {
+    if let Some(ent) = ent.as_entity_ref(self) {
+      // Entities outside of every player's simulation distance are frozen.
+      if ent.as_player().is_none() && !self.in_simulation(ent.pos().block().chunk()) {
+        return;
+      }
+      let start = Instant::now();
+      if ent.tick() {
+        self.entities.write().remove(&eid);
+        for p in self.players().iter().in_view(ent.pos().block().chunk()) {
+          p.send(cb::packet::RemoveEntities { eids: vec![eid] });
+        }
       }
+      self.uspt.fetch_add(start.elapsed().as_micros().try_into().unwrap(), Ordering::SeqCst);
+    }
+  }
+
+  /// Runs a single tick on the current thread. This is meant for tests, using a
+  /// world without a tick loop (see [`new_test`](Self::new_test)), so that the
+  /// world only changes when this is called.
+  ///
+  /// This runs scheduled ticks, random ticks, redstone, and entities, in the
+  /// same order as the tick loop. It doesn't load or unload chunks.
+  pub fn step(self: &Arc<Self>) {
+    self.run_scheduled_ticks();
+    self.run_random_ticks();
+    self.tick_redstone();
+    let entities: Vec<_> =
+      self.entities().iter_values().map(|(&eid, ent)| (eid, ent.clone())).collect();
+    for (eid, ent) in entities {
+      self.tick_entity(eid, ent);
     }
+    self.tick.fetch_add(1, Ordering::SeqCst);
   }
+
+  /// Creates a world with a default config, without a tick loop. This also
+  /// creates a new [`WorldManager`], which only contains this world. Use
+  /// [`step`](Self::step) to run ticks.
+  pub fn new_test() -> Arc<World> {
+    let wm = Arc::new(WorldManager::new(false));
+    wm.add_world_no_tick(wm.new_world())
+  }
+
   fn new_player(self: Arc<Self>, player: Arc<Player>, info: JoinInfo) {
     {
       // let mut meta = bb_common::metadata::Metadata::new();
@@ -338,8 +462,9 @@ impl World {
     self.events().player_event(event::PlayerJoin { player });
   }
 
-  /// Returns a new, unique EID.
-  pub fn new_eid(&self) -> i32 { self.eid.fetch_add(1, Ordering::SeqCst) }
+  /// Returns a new, unique EID. EIDs are unique across all worlds, so that
+  /// players can be moved between worlds.
+  pub fn new_eid(&self) -> i32 { self.wm.new_eid() }
 
   /// Returns the current block converter. This can be used to convert old block
   /// ids to new ones, and vice versa. This can also be used to convert block
@@ -363,9 +488,22 @@ impl World {
   /// things like what players are all online.
   pub fn world_manager(&self) -> &Arc<WorldManager> { &self.wm }
 
+  /// Returns the name of this world. Worlds added with
+  /// [`WorldManager::add_world`] all have unique names.
+  pub fn name(&self) -> &str { &self.name }
+  /// Returns the dimension type of this world. This changes the sky and fog
+  /// for clients.
+  pub fn dimension(&self) -> Dimension { self.dimension }
+
   pub fn height(&self) -> u32 { self.height }
   pub fn min_y(&self) -> i32 { self.min_y }
 
+  /// Returns true if the given chunk is within the simulation distance of any
+  /// player in this world. Entities are only ticked in these chunks.
+  pub fn in_simulation(&self, pos: ChunkPos) -> bool {
+    self.players().values().any(|p| p.in_simulation(pos))
+  }
+
   /// Generates a chunk for the given chunk position. This will not store the
   /// chunk, or even look in the chunks table at all. It should be used if you
   /// have a list of chunks to generate, and you would like to generate them in
@@ -393,6 +531,8 @@ impl World {
   /// Checks if the given chunk position is loaded. This will not check for any
   /// data saved on disk, it only checks if the given chunk is in memory.
   pub fn has_loaded_chunk(&self, pos: ChunkPos) -> bool { self.regions.has_chunk(pos) }
+  /// Returns the number of chunks currently in memory.
+  pub fn loaded_chunks(&self) -> usize { self.regions.loaded_chunks() }
 
   /// Stores a list of chunks in the internal map. This should be used after
   /// calling [`pre_generate_chunk`](Self::pre_generate_chunk) a number of
@@ -448,7 +588,8 @@ impl World {
   /// If you are trying to produce a large block change packet, use
   /// [`serialize_partial_chunk`](Self::serialize_partial_chunk).
   pub fn serialize_chunk(&self, pos: ChunkPos) -> cb::packet::Chunk {
-    self.chunk(pos, |c| {
+    self.chunk(pos, |mut c| {
+      let heightmaps = c.heightmaps().clone();
       let inner = c.inner();
 
       let mut sections: Vec<_> = inner.sections().cloned().collect();
@@ -459,6 +600,7 @@ impl World {
         sections,
         sky_light: c.sky_light().as_ref().map(|c| c.data.clone()),
         block_light: c.block_light().data.clone(),
+        heightmaps,
       }
     })
   }
@@ -475,7 +617,8 @@ impl World {
   /// panic. `min` and `max` should not be outside of 0..15, unless you are
   /// sending this to a 1.17+ client.
   pub fn serialize_partial_chunk(&self, pos: ChunkPos, min: u32, max: u32) -> cb::packet::Chunk {
-    self.chunk(pos, |c| {
+    self.chunk(pos, |mut c| {
+      let heightmaps = c.heightmaps().clone();
       let inner = c.inner();
 
       let mut sections: Vec<_> = inner
@@ -491,6 +634,7 @@ impl World {
         // TODO: Only clone the sections we care about
         sky_light: c.sky_light().as_ref().map(|c| c.data.clone()),
         block_light: c.block_light().data.clone(),
+        heightmaps,
       }
     })
   }
@@ -554,33 +698,32 @@ impl World {
     CountedChunk::new(MultiChunk::new(self.wm.clone(), false, self.height, self.min_y))
   }
 
-  /// This broadcasts a chat message to everybody in the world. Note that this
-  /// does not lock the players map exclusively. So, if this is called twice,
-  /// both operations will execute in parallel. This might cause some packets to
-  /// arrive out of order between clients (one client would see one broadcast
-  /// before the other). This is only possible if you call broadcast from
-  /// multiple threads, as this blocks until all the packets are queued.
+  /// This broadcasts a chat message to everybody in the world. The players map
+  /// is only locked long enough to copy the list of players, so players can
+  /// join or leave while the message is being sent. If this is called from
+  /// multiple threads at once, some packets might arrive out of order between
+  /// clients (one client would see one broadcast before the other).
   pub fn broadcast(&self, msg: impl Into<Chat>) {
     let m = msg.into();
-    for p in self.players.read().values() {
+    for p in self.player_list() {
       p.send_message(m.clone());
     }
   }
 
   /// Returns a read lock on the players map.
   pub fn players(&self) -> RwLockReadGuard<'_, PlayersMap> { self.players.read() }
+  /// Returns a copy of every player in this world. This should be preferred
+  /// over [`players`](Self::players) when sending something to every player,
+  /// as the lock is released before this returns. Holding the lock blocks
+  /// players from joining or leaving the world.
+  pub fn player_list(&self) -> Vec<Arc<Player>> { self.players.read().values().cloned().collect() }
 
   /// Removes the given player from this world. This should be called from
   /// WorldManagger, so that the world managger's table of players to worlds
   /// stays synced.
   fn remove_player(&self, id: UUID) {
-    let mut lock = self.players.write();
     // If the player is not present, this player has already been removed.
-    if let Some(p) = lock.remove(&id) {
-      let players_is_empty = lock.is_empty();
-      drop(lock);
-
-      self.entities.write().remove(&p.eid());
+    if let Some(p) = self.unregister_player(id) {
       self.events().player_event(event::PlayerLeave { player: p.clone() });
       info!("{} left the game", p.username());
 
@@ -591,27 +734,60 @@ impl World {
         msg.add(" has left").color(Color::Gray);
         self.world_manager().broadcast(msg);
       }
+    }
+  }
 
-      let entity_remove = cb::packet::RemoveEntities { eids: vec![p.eid()] };
-      let list_remove = cb::packet::PlayerList {
-        action: cb::PlayerListAction::Remove(vec![cb::PlayerListRemove { id: p.id() }]),
-      };
-      for other in p.world().players().iter().in_view(p.pos().block().chunk()).not(p.id()) {
-        other.send(entity_remove.clone());
-        other.send(list_remove.clone());
-      }
-      p.unload_all();
-
-      if players_is_empty {
-        self.unload_chunks();
-        /*
-        let len = self.chunks.read().len();
-        if len != 0 {
-          warn!("chunks remaining after last player logged off: {}", len);
-        }
-        */
+  /// Removes the given player from this world's player and entity lists, and
+  /// despawns them for everyone nearby. This does not send any leave messages
+  /// or events. Returns the player if they were in this world.
+  fn unregister_player(&self, id: UUID) -> Option<Arc<Player>> {
+    let mut lock = self.players.write();
+    let p = lock.remove(&id)?;
+    let players_is_empty = lock.is_empty();
+    drop(lock);
+
+    self.entities.write().remove(&p.eid());
+
+    let entity_remove = cb::packet::RemoveEntities { eids: vec![p.eid()] };
+    let list_remove = cb::packet::PlayerList {
+      action: cb::PlayerListAction::Remove(vec![cb::PlayerListRemove { id: p.id() }]),
+    };
+    for other in self.players().iter().in_view(p.pos().block().chunk()).not(p.id()) {
+      other.send(entity_remove.clone());
+      other.send(list_remove.clone());
+    }
+    p.unload_all();
+
+    if players_is_empty {
+      self.unload_chunks();
+      /*
+      let len = self.chunks.read().len();
+      if len != 0 {
+        warn!("chunks remaining after last player logged off: {}", len);
       }
+      */
     }
+    Some(p)
+  }
+
+  /// Adds a player that was moved from another world. This is the same as
+  /// `new_player`, but it shows a loading screen instead of a join game
+  /// packet, and it doesn't send any join messages.
+  fn add_moved_player(self: &Arc<Self>, player: Arc<Player>) {
+    {
+      let mut players = self.players.write();
+      players.insert(player.id(), player.clone());
+      let mut entities = self.entities.write();
+      entities.insert(player.eid(), Entity::Player(player.id()));
+    }
+    let info = JoinInfo {
+      mode:     JoinMode::Switch(SwitchMode::Loading),
+      username: player.username().clone(),
+      uuid:     player.id(),
+      ver:      player.ver().id(),
+      addr:     player.addr(),
+    };
+    self.player_init(&player, info);
   }
 
   // Unloads all the chunks that are cached for unloading.
@@ -622,6 +798,90 @@ impl World {
   /// is also checked, so it won't end up being a problem.
   pub fn is_locked(&self) -> bool { self.locked.load(Ordering::Relaxed) }
 
+  /// Returns the number of ticks since this world was created.
+  pub fn tick(&self) -> u64 { self.tick.load(Ordering::SeqCst) }
+  /// Returns how long the last tick took. This doesn't include the time spent
+  /// sleeping until the next tick.
+  pub fn last_tick_time(&self) -> Duration {
+    Duration::from_micros(self.last_tick_time.load(Ordering::Relaxed))
+  }
+  /// Returns how long all the ticks in this world have taken. Divide this by
+  /// [`tick`](Self::tick) to get the average tick time.
+  pub fn total_tick_time(&self) -> Duration {
+    Duration::from_micros(self.total_tick_time.load(Ordering::Relaxed))
+  }
+  /// Returns the number of ticks run in the last second. This will be close to
+  /// the `tick-rate` in the config, unless ticks are taking too long. This is 0
+  /// until the world has been running for a second.
+  pub fn tps(&self) -> f64 { f64::from_bits(self.tps.load(Ordering::Relaxed)) }
+
+  /// Resets the random number generator used for random ticks. By default, this
+  /// is seeded randomly, so this is mostly useful in tests.
+  pub fn seed_random_ticks(&self, seed: u64) { *self.random.lock() = WyhashRng::new(seed); }
+
+  /// Returns `true` with a probability of `num / den`. This uses the same
+  /// generator as random ticks, so that growth is reproducible after calling
+  /// [`seed_random_ticks`](Self::seed_random_ticks).
+  pub fn random_chance(&self, num: u32, den: u32) -> bool {
+    self.random.lock().gen_range(0..den) < num
+  }
+
+  /// Runs random ticks in every loaded chunk within simulation distance of a
+  /// player. Called once per tick.
+  fn run_random_ticks(self: &Arc<Self>) {
+    let chunks: HashSet<ChunkPos> = self
+      .players()
+      .values()
+      .flat_map(|p| {
+        let center = p.pos().block().chunk();
+        let d = p.simulation_distance() as i32;
+        (-d..=d).flat_map(move |x| (-d..=d).map(move |z| center + ChunkPos::new(x, z)))
+      })
+      .collect();
+    for pos in chunks {
+      if self.has_loaded_chunk(pos) {
+        self.random_tick_chunk(pos);
+      }
+    }
+  }
+
+  /// Picks [`randomTickSpeed`](GameRules::RANDOM_TICK_SPEED) blocks from
+  /// each non-empty section in the given chunk, and calls
+  /// [`random_tick`](block::Behavior::random_tick) on them.
+  pub(crate) fn random_tick_chunk(self: &Arc<Self>, pos: ChunkPos) {
+    let speed = self.gamerules().int(GameRules::RANDOM_TICK_SPEED);
+    if speed <= 0 {
+      return;
+    }
+    let picked: Vec<Pos> = self.chunk(pos, |c| {
+      let mut rng = self.random.lock();
+      let mut picked = vec![];
+      for (y, s) in c.inner().sections().enumerate() {
+        let s = match s {
+          Some(s) if s.non_air_blocks() != 0 => s,
+          _ => continue,
+        };
+        for _ in 0..speed {
+          let rel =
+            SectionRelPos::new(rng.gen_range(0..16), rng.gen_range(0..16), rng.gen_range(0..16));
+          // Air never does anything on a random tick.
+          if s.get_block(rel) != 0 {
+            picked.push(pos.block() + rel.as_pos().add_y(y as i32 * 16 - self.min_y));
+          }
+        }
+      }
+      picked
+    });
+    for pos in picked {
+      if let Ok(ty) = self.get_block(pos) {
+        self
+          .world_manager()
+          .block_behaviors()
+          .call(ty.kind(), |b| b.random_tick(self, block::Block::new(self, pos, ty.ty())));
+      }
+    }
+  }
+
   /// Plays the given sound at the given positions. All nearby players will be
   /// able to hear it.
   pub fn play_sound(
@@ -644,6 +904,28 @@ impl World {
     }
   }
 
+  /// Returns the spawn point of this world. New players spawn here, and players
+  /// without their own spawn point respawn here.
+  pub fn spawn_point(&self) -> FPos { *self.spawn_point.read() }
+  /// Sets the spawn point of this world. This also updates the compass target
+  /// for every player in this world.
+  pub fn set_spawn_point(&self, pos: FPos) {
+    *self.spawn_point.write() = pos;
+    for p in self.player_list() {
+      p.send(cb::packet::SpawnPosition { pos: pos.block() });
+    }
+  }
+  /// Returns the position a player should be placed at when spawning at `pos`.
+  /// If `find-spawn` is enabled in the config, this will search upwards for an
+  /// open space. Otherwise, this returns `pos`.
+  pub fn safe_spawn(&self, pos: FPos) -> FPos {
+    if self.wm.config().find_spawn {
+      self.find_spawn_point(pos.block()).into()
+    } else {
+      pos
+    }
+  }
+
   /// Searches upwards for an open spawn point, based on the `start` position.
   /// This may return a position outside the world.
   pub fn find_spawn_point(&self, start: Pos) -> Pos {
@@ -694,8 +976,9 @@ impl WorldManager {
       players:           RwLock::new(HashMap::new()),
       teams:             RwLock::new(HashMap::new()),
       default_game_mode: conf.default_gamemode,
-      spawn_point:       conf.spawn_point,
       config:            conf,
+      // All player's think they are EID 1, so we start at 2. EID 0 is invalid.
+      eid:               2.into(),
     }
   }
 
@@ -722,17 +1005,36 @@ impl WorldManager {
   pub fn run(self: Arc<Self>) { self.global_tick_loop(); }
 
   /// Adds a new world. This will spawn a tick loop for this world.
-  pub fn add_world(self: &Arc<Self>, world: World) -> Arc<World> {
+  ///
+  /// The world will be given the name `name`, and the dimension type
+  /// `dimension`. If there is already a world with the same name, this
+  /// returns an error, and the world is not added.
+  pub fn add_world(
+    self: &Arc<Self>,
+    mut world: World,
+    name: &str,
+    dimension: Dimension,
+  ) -> Result<Arc<World>, WorldExistsError> {
+    world.name = name.into();
+    world.dimension = dimension;
     let world = Arc::new(world);
+    {
+      // This is checked while holding the lock, so that two worlds with the same
+      // name can't be added at the same time.
+      let mut worlds = self.worlds.write();
+      if worlds.iter().any(|w| w.name() == name) {
+        return Err(WorldExistsError(name.into()));
+      }
+      worlds.push(Arc::clone(&world));
+    }
     let w = Arc::clone(&world);
     thread::spawn(move || {
       w.global_tick_loop();
     });
-    let w2 = Arc::clone(&world);
-    self.worlds.write().push(world);
-    w2
+    Ok(world)
   }
-  /// Adds a new world, without spawning a tick loop.
+  /// Adds a new world, without spawning a tick loop. The world will be named
+  /// `world`, and will be an overworld.
   pub fn add_world_no_tick(self: &Arc<Self>, world: World) -> Arc<World> {
     let world = Arc::new(world);
     self.worlds.write().push(Arc::clone(&world));
@@ -819,11 +1121,11 @@ impl WorldManager {
   /// ```
   pub fn broadcast(&self, msg: impl Into<Chat>) {
     let m = msg.into();
-    let worlds = self.worlds.read();
-    for w in worlds.iter() {
-      for p in w.players.read().values() {
-        p.send_message(m.clone());
-      }
+    // We copy the players out, so that players can join or leave (and worlds can
+    // be added) while this is sending.
+    let players: Vec<_> = self.players.read().values().map(|(_, p)| p.clone()).collect();
+    for p in players {
+      p.send_message(m.clone());
     }
   }
 
@@ -831,6 +1133,11 @@ impl WorldManager {
   /// any other context.
   pub fn default_world(&self) -> Arc<World> { self.worlds.read()[0].clone() }
 
+  /// Returns the index of the world with the given name, if there is one.
+  pub fn world_index(&self, name: &str) -> Option<usize> {
+    self.worlds.read().iter().position(|w| w.name() == name)
+  }
+
   // /// Adds a new player into the game. This should be called when a new grpc
   // /// proxy connects.
   // pub async fn new_player(&self, req: Streaming<Packet>, tx:
@@ -852,17 +1159,42 @@ impl WorldManager {
   /// proxy connects.
   pub fn new_player(&self, conn: ConnSender, info: JoinInfo) -> Arc<Player> {
     let w = self.worlds.read()[0].clone();
-    let spawn = if self.config().find_spawn {
-      w.find_spawn_point(self.spawn_point.block()).into()
-    } else {
-      self.spawn_point
-    };
+    let spawn = w.safe_spawn(w.spawn_point());
     let player = Player::new(w.new_eid(), conn, info.clone(), w.clone(), spawn);
     self.players.write().insert(info.uuid, (0, player.clone()));
     w.new_player(player.clone(), info);
     player
   }
 
+  /// Returns a new, unique EID. This is unique across all worlds.
+  pub fn new_eid(&self) -> i32 { self.eid.fetch_add(1, Ordering::SeqCst) }
+
+  /// Moves the given player into the world at `index`. The player will be
+  /// removed from their current world, and will see a loading screen while
+  /// the new world's chunks are sent. Their position will not change.
+  ///
+  /// Returns `false` if there is no world at `index`, or if the player is
+  /// already in that world.
+  pub fn move_player(&self, player: &Arc<Player>, index: usize) -> bool {
+    let new_world = match self.worlds.read().get(index) {
+      Some(w) => w.clone(),
+      None => return false,
+    };
+    let old_index = match self.players.read().get(&player.id()) {
+      Some(v) => v.0,
+      None => return false,
+    };
+    if old_index == index {
+      return false;
+    }
+    let old_world = self.worlds.read()[old_index].clone();
+    old_world.unregister_player(player.id());
+    player.set_world(new_world.clone());
+    self.players.write().insert(player.id(), (index, player.clone()));
+    new_world.add_moved_player(player.clone());
+    true
+  }
+
   /// Removes the player. This is not part of the public API because it does not
   /// terminate their connection. This is called after their connection is
   /// terminated.
@@ -890,7 +1222,11 @@ impl WorldManager {
     self.players.read()
   }
 
+  /// Returns the time between ticks, from the `tick-rate` in the config.
+  pub fn tick_time(&self) -> Duration { Duration::from_secs(1) / self.config().tick_rate.max(1) }
+
   fn global_tick_loop(self: Arc<Self>) {
+    let interval = self.tick_time();
     let mut start = Instant::now();
     loop {
       // runs on tick() for plugins
@@ -898,20 +1234,19 @@ impl WorldManager {
       // updates after() things
       self.plugins().tick();
       let passed = Instant::now().duration_since(start);
-      start += TICK_TIME;
-      match TICK_TIME.checked_sub(passed) {
+      start += interval;
+      match interval.checked_sub(passed) {
         Some(t) => spin_sleep::sleep(t),
-        None => warn!("plugin tick took {passed:?} (more than 50 ms)"),
+        None => warn!("plugin tick took {passed:?} (more than {interval:?})"),
       }
     }
   }
 
   pub fn send_to_all(&self, out: impl Into<cb::Packet>) {
     let out = out.into();
-    for w in self.worlds.read().iter() {
-      for p in w.players().iter() {
-        p.send(out.clone());
-      }
+    let players: Vec<_> = self.players.read().values().map(|(_, p)| p.clone()).collect();
+    for p in players {
+      p.send(out.clone());
     }
   }
 
@@ -934,38 +1269,312 @@ impl WorldManager {
     }
   }
 
-  #[cfg(not(target_family = "unix"))]
-  pub fn stop_on_ctrlc(self: &Arc<Self>) {}
-  #[cfg(target_family = "unix")]
-  pub fn stop_on_ctrlc(self: &Arc<Self>) {
-    use parking_lot::lock_api::RawMutex;
+  /// Shuts down the server. This will send a message to everyone online,
+  /// disconnect all players, and then save all the worlds. Once the worlds are
+  /// saved, this waits (for up to a second) for the disconnect packets to be
+  /// sent, so that players can see why they were kicked. This does not exit
+  /// the process, so the caller should do that once this returns.
+  pub fn shutdown(&self) {
+    info!("shutting down server...");
+    self.broadcast("[Server] Server is shutting down");
+    // We cannot hold a lock on `players` here, as disconnecting will remove them
+    // from the map.
+    let players: Vec<_> = self.players.read().values().map(|(_, p)| p.clone()).collect();
+    for p in &players {
+      p.disconnect("Server closing");
+    }
+    self.save_all();
+    info!("saved all worlds");
 
-    static CTRLC: Mutex<Option<Arc<WorldManager>>> =
-      Mutex::const_new(parking_lot::RawMutex::INIT, None);
+    let deadline = Instant::now() + Duration::from_secs(1);
+    while Instant::now() < deadline && !players.iter().all(|p| p.conn().is_flushed()) {
+      thread::sleep(Duration::from_millis(10));
+    }
+  }
 
-    let mut lock = CTRLC.lock();
-    if lock.is_some() {
-      panic!(
-        "cannot stop this worldmanager on ctrlc, as another worldmanager is already registered"
-      );
+  /// Requests that the server shuts down. This returns immediately, and the
+  /// actual shutdown happens on the thread blocked in
+  /// [`wait_for_stop`](Self::wait_for_stop).
+  pub fn request_stop(&self) { STOP_REQUESTED.store(true, Ordering::SeqCst); }
+
+  /// Blocks until [`request_stop`](Self::request_stop) is called (or a signal
+  /// is received, see [`stop_on_ctrlc`](Self::stop_on_ctrlc)), and then calls
+  /// [`shutdown`](Self::shutdown). This should be called on the main thread,
+  /// which should exit once this returns.
+  pub fn wait_for_stop(&self) {
+    while !STOP_REQUESTED.load(Ordering::SeqCst) {
+      thread::sleep(Duration::from_millis(50));
     }
-    *lock = Some(self.clone());
+    self.shutdown();
+  }
 
+  /// Makes SIGINT and SIGTERM request a shutdown. The signal handler only sets
+  /// a flag, so [`wait_for_stop`](Self::wait_for_stop) must be running for the
+  /// server to actually stop. A second signal exits immediately.
+  #[cfg(not(target_family = "unix"))]
+  pub fn stop_on_ctrlc(&self) {}
+  #[cfg(target_family = "unix")]
+  pub fn stop_on_ctrlc(&self) {
     use nix::{
       libc,
       sys::signal::{signal, SigHandler, Signal},
     };
 
-    extern "C" fn handle_sigint(_sig: libc::c_int) {
-      let lock = CTRLC.lock();
-      println!();
-      if let Some(wm) = &*lock {
-        wm.save_all();
+    extern "C" fn handle_stop(_sig: libc::c_int) {
+      // Only async-signal-safe operations are allowed in here, so we can't lock
+      // anything or shut down from this thread.
+      if STOP_REQUESTED.swap(true, Ordering::SeqCst) {
+        unsafe { libc::_exit(1) };
       }
-      std::process::exit(0);
     }
 
-    let handler = SigHandler::Handler(handle_sigint);
+    let handler = SigHandler::Handler(handle_stop);
     unsafe { signal(Signal::SIGINT, handler) }.unwrap();
+    unsafe { signal(Signal::SIGTERM, handler) }.unwrap();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{
+    block::{Kind, TileEntity},
+    entity::{self, behavior::Behavior, EntityData},
+    math::Vec3,
+    world::World,
+  };
+  use bb_common::math::{ChunkPos, FPos, Pos, RelPos};
+  use bb_transfer::{MessageWriter, WriteError};
+  use parking_lot::Mutex;
+  use std::{any::Any, sync::Arc};
+
+  fn grown_crops(seed: u64) -> Vec<Pos> {
+    let world = World::new_test();
+    world.seed_random_ticks(seed);
+    let (min, max) = (Pos::new(0, 96, 0), Pos::new(15, 96, 15));
+    world.fill_rect_kind(min, max, Kind::Wheat).unwrap();
+    // Crops need light to grow. This is placed one block at a time, so that the
+    // light is updated everywhere.
+    for p in Pos::new(0, 98, 0).to(Pos::new(15, 98, 15)) {
+      world.set_kind(p, Kind::Glowstone).unwrap();
+    }
+    for _ in 0..50 {
+      world.random_tick_chunk(ChunkPos::new(0, 0));
+    }
+    min.to(max).filter(|p| world.get_block(*p).unwrap().prop("age").int() > 0).collect()
+  }
+
+  #[test]
+  fn random_ticks() {
+    let grown = grown_crops(1234);
+    // The default random tick speed is 3, and crops only grow on some of the
+    // ticks they are picked for.
+    assert!(!grown.is_empty() && grown.len() <= 150, "{grown:?}");
+    assert_eq!(grown, grown_crops(1234));
+  }
+
+  #[test]
+  fn crops_need_light() {
+    let world = World::new_test();
+    let pos = Pos::new(0, 96, 0);
+    world.fill_rect_kind(Pos::new(-1, 95, -1), Pos::new(1, 98, 1), Kind::Stone).unwrap();
+    world.set_kind(pos, Kind::Wheat).unwrap();
+    world.set_kind(pos.add_y(1), Kind::Air).unwrap();
+    assert_eq!(world.get_light(pos.add_y(1)), Ok(0));
+    for _ in 0..50 {
+      world.random_tick_chunk(ChunkPos::new(0, 0));
+    }
+    assert_eq!(world.get_block(pos).unwrap().prop("age").int(), 0);
+  }
+
+  #[test]
+  fn custom_damage() {
+    // Only allows damage below 5, and records every hit.
+    struct Shield(Arc<Mutex<Vec<f32>>>);
+    impl Behavior for Shield {
+      fn damage(&mut self, _: &Arc<World>, _: &EntityData, amount: f32, _: Vec3) -> bool {
+        self.0.lock().push(amount);
+        amount < 5.0
+      }
+    }
+
+    let world = World::new_test();
+    let hits = Arc::new(Mutex::new(vec![]));
+    let eid =
+      world.summon_custom(entity::Type::Zombie, FPos::new(0.0, 100.0, 0.0), Shield(hits.clone()));
+    let damage =
+      |amount| world.entities().get(eid).unwrap().damage(amount, true, Vec3::new(0.0, 0.0, 0.0));
+    assert!(damage(1.0));
+    assert!(!damage(10.0));
+    assert_eq!(*hits.lock(), vec![1.0, 10.0]);
+    // Only the damage that was allowed is applied.
+    assert_eq!(world.entities().get(eid).unwrap().health(), 19.0);
+  }
+
+  #[test]
+  fn projectile_hit() {
+    let world = World::new_test();
+    let zombie = world.summon(entity::Type::Zombie, FPos::new(2.5, 100.0, 0.5));
+    let arrow = world.spawn_projectile(
+      entity::Type::Arrow,
+      FPos::new(0.5, 100.5, 0.5),
+      Vec3::new(3.0, 0.0, 0.0),
+      None,
+    );
+    // The arrow is ticked without holding the entities lock, like the tick loop.
+    let ent = world.entities().get_cloned(arrow).unwrap();
+    assert!(ent.as_entity_ref(&world).unwrap().tick());
+    assert_eq!(world.entities().get(zombie).unwrap().health(), 18.0);
+  }
+
+  #[test]
+  fn heightmaps() {
+    let world = World::new_test();
+    let heights = || {
+      let maps = world.chunk(ChunkPos::new(0, 0), |mut c| c.heightmaps().clone());
+      (maps.world_surface.height(0, 0), maps.motion_blocking.height(0, 0))
+    };
+    let height = |y: i32| (y - world.min_y() + 1) as u32;
+
+    world.set_kind(Pos::new(0, 200, 0), Kind::Stone).unwrap();
+    assert_eq!(heights(), (height(200), height(200)));
+    // Torches don't block motion.
+    world.set_kind(Pos::new(0, 210, 0), Kind::Torch).unwrap();
+    assert_eq!(heights(), (height(210), height(200)));
+    // Fluids do, even though they have no hitbox.
+    world.set_kind(Pos::new(0, 205, 0), Kind::Water).unwrap();
+    assert_eq!(heights(), (height(210), height(205)));
+    world.set_kind(Pos::new(0, 210, 0), Kind::Air).unwrap();
+    world.set_kind(Pos::new(0, 205, 0), Kind::Air).unwrap();
+    assert_eq!(heights(), (height(200), height(200)));
+  }
+
+  #[test]
+  fn entities_in_radius() {
+    let world = World::new_test();
+    let far = world.summon(entity::Type::Zombie, FPos::new(10.0, 100.0, 0.0));
+    let near = world.summon(entity::Type::Zombie, FPos::new(1.0, 100.0, 0.0));
+    let edge = world.summon(entity::Type::Pig, FPos::new(0.0, 100.0, 5.0));
+
+    let found = |radius| -> Vec<i32> {
+      world
+        .entities_in_radius(FPos::new(0.0, 100.0, 0.0), radius)
+        .iter()
+        .map(|e| e.as_entity_ref(&world).unwrap().eid())
+        .collect()
+    };
+    assert_eq!(found(0.5), vec![]);
+    assert_eq!(found(5.0), vec![near, edge]);
+    assert_eq!(found(20.0), vec![near, edge, far]);
+    assert_eq!(found(-20.0), vec![]);
+    assert_eq!(found(f64::NAN), vec![]);
+  }
+
+  #[test]
+  fn break_waterlogged() {
+    let world = World::new_test();
+    let pos = Pos::new(0, 100, 0);
+    let slab = world.block_converter.get(Kind::OakSlab).default_type();
+    assert!(slab.can_waterlog());
+    assert!(!slab.is_waterlogged());
+    assert!(!world.block_converter.get(Kind::Stone).default_type().can_waterlog());
+
+    world.set_block_no_update(pos, slab).unwrap();
+    world.break_block(pos).unwrap();
+    assert_eq!(world.get_kind(pos), Ok(Kind::Air));
+
+    world.set_block_no_update(pos, slab.with_prop("waterlogged", true)).unwrap();
+    world.break_block(pos).unwrap();
+    assert_eq!(world.get_kind(pos), Ok(Kind::Water));
+  }
+
+  #[test]
+  fn snapshot_region() {
+    let world = World::new_test();
+    // This crosses a chunk border, so it needs to be split up.
+    let (min, max) = (Pos::new(-4, 96, 0), Pos::new(4, 100, 4));
+    world.fill_rect_kind(min, max, Kind::Stone).unwrap();
+    world.fill_rect_kind(Pos::new(0, 98, 0), Pos::new(0, 98, 4), Kind::Dirt).unwrap();
+    let blocks =
+      |world: &World| -> Vec<Kind> { min.to(max).map(|p| world.get_kind(p).unwrap()).collect() };
+    let before = blocks(&world);
+
+    let snapshot = world.snapshot_region(max, min).unwrap();
+    assert_eq!(snapshot.min(), min);
+    assert_eq!(
+      snapshot.get(Pos::new(0, 98, 2)),
+      Some(world.get_block(Pos::new(0, 98, 2)).unwrap().id())
+    );
+    assert_eq!(snapshot.get(Pos::new(5, 98, 2)), None);
+
+    world.fill_rect_kind(min, max, Kind::Air).unwrap();
+    assert_ne!(blocks(&world), before);
+
+    world.restore_region(&snapshot.clone()).unwrap();
+    assert_eq!(blocks(&world), before);
+
+    // The number of ids must match the region exactly, and nothing is written
+    // if it doesn't.
+    let (rel_min, rel_max) = (RelPos::new(0, 96, 0), RelPos::new(1, 96, 0));
+    let air = world.block_converter().get(Kind::Air).default_type().id();
+    for ids in [&[air][..], &[air; 3][..]] {
+      assert!(world
+        .chunk(ChunkPos::new(0, 0), |mut c| c.paste_region(rel_min, rel_max, ids, &[]))
+        .is_err());
+    }
+    assert_eq!(blocks(&world), before);
+  }
+
+  #[test]
+  fn snapshot_tile_entities() {
+    struct Counter(Mutex<u32>);
+    impl TileEntity for Counter {
+      fn save(&self, _: &mut MessageWriter<&mut Vec<u8>>) -> Result<(), WriteError> { Ok(()) }
+      fn as_any(&self) -> &dyn Any { self }
+      fn clone_te(&self) -> Arc<dyn TileEntity> { Arc::new(Counter(Mutex::new(*self.0.lock()))) }
+    }
+
+    let world = World::new_test();
+    let pos = Pos::new(0, 100, 0);
+    let te: Arc<dyn TileEntity> = Arc::new(Counter(Mutex::new(1)));
+    world.chunk(pos.chunk(), |mut c| c.tes_mut().insert(pos.chunk_rel(), te));
+    let count = || {
+      let te = world.chunk(pos.chunk(), |c| c.get_te(pos.chunk_rel()).unwrap().unwrap());
+      let v = *te.as_any().downcast_ref::<Counter>().unwrap().0.lock();
+      v
+    };
+    let set_count = |v| {
+      let te = world.chunk(pos.chunk(), |c| c.get_te(pos.chunk_rel()).unwrap().unwrap());
+      *te.as_any().downcast_ref::<Counter>().unwrap().0.lock() = v;
+    };
+
+    let snapshot = world.snapshot_region(pos, pos).unwrap();
+    set_count(5);
+    assert_eq!(count(), 5);
+    world.restore_region(&snapshot).unwrap();
+    assert_eq!(count(), 1);
+    // The snapshot must not share the restored tile entity either.
+    set_count(7);
+    world.restore_region(&snapshot).unwrap();
+    assert_eq!(count(), 1);
+  }
+
+  #[test]
+  fn scheduled_ticks() {
+    let world = World::new_test();
+    let pos = Pos::new(0, 100, 0);
+    let button = world.block_converter.get(Kind::StoneButton).default_type();
+    world.set_block_no_update(pos, button.with("powered", true)).unwrap();
+    assert!(world.schedule_tick(pos, 3, 0));
+    assert!(!world.schedule_tick(pos, 3, 0));
+    assert!(!world.schedule_tick(pos, 1, 0));
+
+    let powered = || world.get_block(pos).unwrap().prop("powered").bool();
+    for _ in 0..3 {
+      world.step();
+      assert!(powered());
+    }
+    // The button should pop out on the third tick.
+    world.step();
+    assert!(!powered());
   }
 }
@@ -0,0 +1,213 @@
+use super::World;
+use crate::block::{Block, BoundingBoxKind, Kind};
+use bb_common::{math::Pos, util::Face};
+use std::{
+  collections::{HashSet, VecDeque},
+  sync::Arc,
+};
+
+/// The maximum number of redstone updates we will process in a single tick.
+/// Anything past this will be processed on the next tick. This stops clocks
+/// from freezing the tick loop.
+const MAX_UPDATES_PER_TICK: usize = 10_000;
+
+/// The blocks that need their redstone power recalculated, in the order they
+/// were queued.
+#[derive(Debug, Default)]
+pub(super) struct RedstoneQueue {
+  queue:   VecDeque<Pos>,
+  /// Every position in `queue`. Used to ignore duplicates, without searching
+  /// the whole queue.
+  pending: HashSet<Pos>,
+}
+
+impl RedstoneQueue {
+  pub fn new() -> Self { RedstoneQueue::default() }
+
+  /// Adds `pos` to the end of the queue. Returns `false` if `pos` is already
+  /// queued.
+  fn push(&mut self, pos: Pos) -> bool {
+    if !self.pending.insert(pos) {
+      return false;
+    }
+    self.queue.push_back(pos);
+    true
+  }
+
+  /// Removes the first position in the queue.
+  fn pop(&mut self) -> Option<Pos> {
+    let pos = self.queue.pop_front()?;
+    self.pending.remove(&pos);
+    Some(pos)
+  }
+}
+
+/// Redstone power functions
+impl World {
+  /// Returns true if the block at `pos` conducts redstone power. These are
+  /// solid blocks, which can be strongly or weakly powered.
+  pub fn is_conductor(&self, pos: Pos) -> bool {
+    match self.get_kind(pos) {
+      // Redstone blocks are solid, but they don't conduct power.
+      Ok(Kind::RedstoneBlock) | Err(_) => false,
+      Ok(kind) => {
+        let data = self.block_converter.get(kind);
+        matches!(data.bounding_box, BoundingBoxKind::Block) && !data.transparent
+      }
+    }
+  }
+
+  /// Returns the power going out of the block at `pos`, through `face`. If the
+  /// block is a conductor, this is the strongest power going into that block.
+  /// Otherwise, this is the weak power that block is outputting.
+  pub fn power(self: &Arc<Self>, pos: Pos, face: Face) -> u8 { self.power_inner(pos, face, true) }
+
+  /// Returns the strongest power going into the block at `pos`, from all 6
+  /// sides.
+  pub fn received_power(self: &Arc<Self>, pos: Pos) -> u8 { self.received_power_inner(pos, true) }
+
+  /// Returns the strong power going into the conductor at `pos`. This is the
+  /// maximum of the strong power of all 6 neighbors.
+  pub fn strong_power_to(self: &Arc<Self>, pos: Pos) -> u8 { self.strong_power_to_inner(pos, true) }
+
+  /// Same as [`received_power`](Self::received_power), but this ignores any
+  /// power coming from redstone dust. Dust uses this to find its power level,
+  /// so that it doesn't power itself through the block below it.
+  pub(crate) fn received_power_no_wire(self: &Arc<Self>, pos: Pos) -> u8 {
+    self.received_power_inner(pos, false)
+  }
+
+  fn received_power_inner(self: &Arc<Self>, pos: Pos, wires: bool) -> u8 {
+    Face::ALL.iter().map(|&f| self.power_inner(pos + f, f.opposite(), wires)).max().unwrap_or(0)
+  }
+
+  fn power_inner(self: &Arc<Self>, pos: Pos, face: Face, wires: bool) -> u8 {
+    if self.is_conductor(pos) {
+      return self.strong_power_to_inner(pos, wires);
+    }
+    match self.get_block(pos) {
+      Ok(ty) if wires || ty.kind() != Kind::RedstoneWire => self
+        .world_manager()
+        .block_behaviors()
+        .call(ty.kind(), |b| b.weak_power(Block::new(self, pos, ty.ty()), face)),
+      _ => 0,
+    }
+  }
+
+  fn strong_power_to_inner(self: &Arc<Self>, pos: Pos, wires: bool) -> u8 {
+    Face::ALL
+      .iter()
+      .filter_map(|&f| {
+        let neighbor = pos + f;
+        let ty = self.get_block(neighbor).ok()?;
+        if !wires && ty.kind() == Kind::RedstoneWire {
+          return None;
+        }
+        Some(
+          self
+            .world_manager()
+            .block_behaviors()
+            .call(ty.kind(), |b| b.strong_power(Block::new(self, neighbor, ty.ty()), f.opposite())),
+        )
+      })
+      .max()
+      .unwrap_or(0)
+  }
+
+  /// Queues a redstone update for the block at `pos`. This will call
+  /// [`redstone_update`](crate::block::Behavior::redstone_update) on the block
+  /// at `pos` during the next tick.
+  pub fn queue_redstone_update(&self, pos: Pos) { self.redstone_queue.lock().push(pos); }
+
+  /// Queues a redstone update for every block next to `pos`, and every block
+  /// next to those. This should be called whenever a block changes the power
+  /// it outputs, as that power may go through a solid block next to it.
+  pub fn queue_redstone_updates_around(&self, pos: Pos) {
+    for f in Face::ALL {
+      let neighbor = pos + f;
+      self.queue_redstone_update(neighbor);
+      for f2 in Face::ALL {
+        if neighbor + f2 != pos {
+          self.queue_redstone_update(neighbor + f2);
+        }
+      }
+    }
+  }
+
+  /// Processes all the queued redstone updates. Updates that are queued while
+  /// this is running will also be processed, so that dust can update
+  /// instantly.
+  pub(crate) fn tick_redstone(self: &Arc<Self>) {
+    for _ in 0..MAX_UPDATES_PER_TICK {
+      let pos = match self.redstone_queue.lock().pop() {
+        Some(pos) => pos,
+        None => break,
+      };
+      if let Ok(ty) = self.get_block(pos) {
+        self
+          .world_manager()
+          .block_behaviors()
+          .call(ty.kind(), |b| b.redstone_update(self, Block::new(self, pos, ty.ty())));
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::RedstoneQueue;
+  use crate::{block::Kind, world::WorldManager};
+  use bb_common::math::Pos;
+  use std::sync::Arc;
+
+  #[test]
+  fn queue_order() {
+    let mut queue = RedstoneQueue::new();
+    let (a, b) = (Pos::new(0, 0, 0), Pos::new(1, 0, 0));
+    assert!(queue.push(a));
+    assert!(queue.push(b));
+    assert!(!queue.push(a));
+    assert_eq!(queue.pop(), Some(a));
+    // `a` isn't queued anymore, so it can be added again.
+    assert!(queue.push(a));
+    assert_eq!(queue.pop(), Some(b));
+    assert_eq!(queue.pop(), Some(a));
+    assert_eq!(queue.pop(), None);
+  }
+
+  #[test]
+  fn lever_dust_lamp() {
+    let wm = Arc::new(WorldManager::new(false));
+    let world = wm.add_world_no_tick(wm.new_world());
+    let conv = wm.block_converter();
+
+    let lever = Pos::new(0, 100, 0);
+    let dust = Pos::new(1, 100, 0);
+    let lamp = Pos::new(2, 100, 0);
+    for x in 0..3 {
+      world.set_kind(Pos::new(x, 99, 0), Kind::Stone).unwrap();
+    }
+    world.set_block(lever, conv.get(Kind::Lever).default_type().with("face", "floor")).unwrap();
+    world.set_kind(dust, Kind::RedstoneWire).unwrap();
+    world.set_kind(lamp, Kind::RedstoneLamp).unwrap();
+    world.tick_redstone();
+    assert_eq!(world.get_block(dust).unwrap().prop("power").int(), 0);
+    assert!(!world.get_block(lamp).unwrap().prop("lit").bool());
+
+    // Flip the lever on.
+    let ty = world.get_block(lever).unwrap();
+    world.set_block(lever, ty.with("powered", true).ty()).unwrap();
+    world.queue_redstone_updates_around(lever);
+    world.tick_redstone();
+    assert_eq!(world.get_block(dust).unwrap().prop("power").int(), 15);
+    assert!(world.get_block(lamp).unwrap().prop("lit").bool());
+
+    // And back off.
+    let ty = world.get_block(lever).unwrap();
+    world.set_block(lever, ty.with("powered", false).ty()).unwrap();
+    world.queue_redstone_updates_around(lever);
+    world.tick_redstone();
+    assert_eq!(world.get_block(dust).unwrap().prop("power").int(), 0);
+    assert!(!world.get_block(lamp).unwrap().prop("lit").bool());
+  }
+}
@@ -0,0 +1,132 @@
+use super::World;
+use crate::block;
+use bb_common::math::Pos;
+use std::{
+  cmp::Ordering,
+  collections::{BinaryHeap, HashSet},
+  sync::Arc,
+};
+
+/// A block that needs a scheduled tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ScheduledTick {
+  /// The tick this should run on.
+  tick:     u64,
+  priority: i32,
+  /// Increases with every scheduled tick, so that ticks with the same time and
+  /// priority run in the order they were scheduled.
+  order:    u64,
+  pos:      Pos,
+}
+
+impl Ord for ScheduledTick {
+  // `BinaryHeap` is a max heap, so this is reversed to pop the earliest tick
+  // first.
+  fn cmp(&self, other: &Self) -> Ordering {
+    (other.tick, other.priority, other.order).cmp(&(self.tick, self.priority, self.order))
+  }
+}
+impl PartialOrd for ScheduledTick {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+/// All of the scheduled ticks in a world. This is a priority queue, sorted by
+/// the tick each block should be updated on.
+#[derive(Debug, Default)]
+pub(super) struct ScheduledTicks {
+  queue:   BinaryHeap<ScheduledTick>,
+  /// Every position in `queue`. Used to ignore duplicates.
+  pending: HashSet<Pos>,
+  order:   u64,
+}
+
+impl ScheduledTicks {
+  pub fn new() -> Self { ScheduledTicks::default() }
+
+  /// Adds a tick to the queue. Returns `false` if `pos` already has a tick
+  /// pending, on any tick.
+  fn push(&mut self, pos: Pos, tick: u64, priority: i32) -> bool {
+    if !self.pending.insert(pos) {
+      return false;
+    }
+    self.queue.push(ScheduledTick { tick, priority, order: self.order, pos });
+    self.order += 1;
+    true
+  }
+
+  /// Removes the next tick that should run on or before `now`.
+  fn pop(&mut self, now: u64) -> Option<Pos> {
+    if self.queue.peek()?.tick > now {
+      return None;
+    }
+    let next = self.queue.pop()?;
+    self.pending.remove(&next.pos);
+    Some(next.pos)
+  }
+}
+
+/// Scheduled tick functions
+impl World {
+  /// Schedules a block update at `pos`, `delay` ticks from now. Once the delay
+  /// has passed, [`scheduled_tick`](block::Behavior::scheduled_tick) will be
+  /// called on whatever block is at `pos`.
+  ///
+  /// Blocks updated on the same tick are updated in order of `priority`, lowest
+  /// first. This is the same as vanilla, where repeaters facing into other
+  /// repeaters have a priority of -3, and most blocks have a priority of 0.
+  ///
+  /// If `pos` already has a tick pending, this does nothing and returns
+  /// `false`, even if the pending tick has a different delay. This is the same
+  /// as vanilla, and stops blocks like redstone torches from queueing a tick
+  /// on every update while they wait to change.
+  pub fn schedule_tick(&self, pos: Pos, delay: u32, priority: i32) -> bool {
+    self.scheduled_ticks.lock().push(pos, self.tick() + u64::from(delay), priority)
+  }
+
+  /// Runs all of the scheduled ticks that are ready. Called once per tick.
+  pub(super) fn run_scheduled_ticks(self: &Arc<Self>) {
+    let now = self.tick();
+    // Anything scheduled while running these ticks will run next tick, even if
+    // it has no delay.
+    let ready: Vec<Pos> = {
+      let mut scheduled = self.scheduled_ticks.lock();
+      std::iter::from_fn(|| scheduled.pop(now)).collect()
+    };
+    for pos in ready {
+      if let Ok(ty) = self.get_block(pos) {
+        self
+          .world_manager()
+          .block_behaviors()
+          .call(ty.kind(), |b| b.scheduled_tick(self, block::Block::new(self, pos, ty.ty())));
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn queue_order() {
+    let mut queue = ScheduledTicks::new();
+    assert!(queue.push(Pos::new(0, 0, 0), 5, 0));
+    assert!(queue.push(Pos::new(1, 0, 0), 3, 0));
+    assert!(queue.push(Pos::new(2, 0, 0), 5, -3));
+    assert!(queue.push(Pos::new(3, 0, 0), 5, 0));
+    // Same position and tick
+    assert!(!queue.push(Pos::new(1, 0, 0), 3, 2));
+    // Same position, different tick
+    assert!(!queue.push(Pos::new(1, 0, 0), 4, 0));
+
+    assert_eq!(queue.pop(2), None);
+    assert_eq!(queue.pop(4), Some(Pos::new(1, 0, 0)));
+    assert_eq!(queue.pop(4), None);
+    assert_eq!(queue.pop(5), Some(Pos::new(2, 0, 0)));
+    assert_eq!(queue.pop(5), Some(Pos::new(0, 0, 0)));
+    assert_eq!(queue.pop(5), Some(Pos::new(3, 0, 0)));
+    assert_eq!(queue.pop(5), None);
+    // Popped ticks can be scheduled again.
+    assert!(queue.push(Pos::new(1, 0, 0), 3, 0));
+  }
+}
@@ -0,0 +1,98 @@
+use super::{blocks::chunk_rects, World};
+use crate::block::TileEntity;
+use bb_common::math::{Pos, PosError, RelPos};
+use std::{collections::HashMap, fmt, sync::Arc};
+
+/// A copy of every block in a cuboid of the world. This is created with
+/// [`World::snapshot_region`], and can be written back with
+/// [`World::restore_region`].
+///
+/// The block data is reference counted, so cloning a snapshot is cheap. This
+/// makes it easy to keep a stack of snapshots for undoing changes.
+#[derive(Clone)]
+pub struct RegionSnapshot {
+  min:    Pos,
+  max:    Pos,
+  /// Block ids, in the same order as [`Pos::to`].
+  blocks: Arc<[u32]>,
+  /// Deep copies of the tile entities in the region. These are copied again
+  /// when restoring, so a snapshot can be restored more than once.
+  tes:    Arc<HashMap<Pos, Arc<dyn TileEntity>>>,
+}
+
+impl fmt::Debug for RegionSnapshot {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("RegionSnapshot")
+      .field("min", &self.min)
+      .field("max", &self.max)
+      .field("tes", &self.tes.len())
+      .finish()
+  }
+}
+
+impl RegionSnapshot {
+  /// Returns the minimum corner of this snapshot.
+  pub fn min(&self) -> Pos { self.min }
+  /// Returns the maximum corner of this snapshot.
+  pub fn max(&self) -> Pos { self.max }
+
+  /// Returns the block id stored at the given position. This returns `None` if
+  /// the position is outside of this snapshot.
+  pub fn get(&self, pos: Pos) -> Option<u32> {
+    if pos.min(self.min) != self.min || pos.max(self.max) != self.max {
+      return None;
+    }
+    Some(self.blocks[index(self.min, self.max, pos)])
+  }
+}
+
+/// Returns the index of `pos` within the blocks of a snapshot from `min` to
+/// `max`. This matches the order of [`Pos::to`].
+fn index(min: Pos, max: Pos, pos: Pos) -> usize {
+  let size = max - min + Pos::new(1, 1, 1);
+  let rel = pos - min;
+  ((rel.y * size.z + rel.z) * size.x + rel.x) as usize
+}
+
+impl World {
+  /// Copies every block between the two corners (inclusive), along with any
+  /// tile entities in that region. Use [`restore_region`](Self::restore_region)
+  /// to write the blocks back.
+  pub fn snapshot_region(&self, min: Pos, max: Pos) -> Result<RegionSnapshot, PosError> {
+    let (min, max) = Pos::min_max(min, max);
+    let mut blocks = vec![0; min.to(max).len()];
+    let mut tes = HashMap::new();
+    for (pos, rel_min, rel_max) in chunk_rects(min, max) {
+      let (ids, chunk_tes) = self.chunk(pos, |c| c.copy_region(rel_min, rel_max))?;
+      for (rel, id) in rel_min.to(rel_max).zip(ids) {
+        blocks[index(min, max, pos.block() + rel.as_pos())] = id;
+      }
+      for (rel, te) in chunk_tes {
+        tes.insert(pos.block() + rel.as_pos(), te);
+      }
+    }
+    Ok(RegionSnapshot { min, max, blocks: blocks.into(), tes: Arc::new(tes) })
+  }
+
+  /// Writes all of the blocks and tile entities in the snapshot back into the
+  /// world. Any tile entities in the region that weren't in the snapshot are
+  /// removed. This sends the changes to everyone in view, in the same way as
+  /// [`fill_rect`](Self::fill_rect).
+  pub fn restore_region(&self, snapshot: &RegionSnapshot) -> Result<(), PosError> {
+    for (pos, min, max) in chunk_rects(snapshot.min, snapshot.max) {
+      let id = |rel: RelPos| {
+        snapshot.blocks[index(snapshot.min, snapshot.max, pos.block() + rel.as_pos())]
+      };
+      let ids: Vec<u32> = min.to(max).map(&id).collect();
+      let tes: Vec<_> = snapshot
+        .tes
+        .iter()
+        .filter(|(p, _)| p.chunk() == pos)
+        .map(|(p, te)| (p.chunk_rel(), te.clone_te()))
+        .collect();
+      self.chunk(pos, |mut c| c.paste_region(min, max, &ids, &tes))?;
+      self.send_rect_update(pos, min, max, id);
+    }
+    Ok(())
+  }
+}
@@ -1,13 +1,13 @@
 use proc_macro::TokenStream;
-use proc_macro2::Span;
+use proc_macro2::{Span, TokenStream as TokenStream2};
 use proc_macro_error::{abort, emit_error};
 use quote::quote;
 use std::collections::HashMap;
 use syn::{
   braced,
   parse::{Parse, ParseStream},
-  parse_macro_input, Attribute, FnArg, Ident, ItemFn, LitBool, LitStr, Path, PathArguments, Result,
-  ReturnType, Token, Type,
+  parse_macro_input, Attribute, FnArg, GenericArgument, Ident, ItemFn, LitBool, LitStr, Path,
+  PathArguments, Result, ReturnType, Token, Type,
 };
 
 struct Impl {
@@ -142,29 +142,38 @@ pub fn define_ty(_: TokenStream, input: TokenStream) -> TokenStream {
     let name_str = name.to_string();
     let py_name = Ident::new(&format!("py_{}", method.sig.ident), name.span());
     let py_args = python_args(method.sig.inputs.iter());
-    let py_arg_names = python_arg_names(method.sig.inputs.iter());
-    let (py_ret, conv_ret) = python_ret(&method.sig.output);
+    let args_supported = method.sig.inputs.iter().all(python_arg_supported);
+    let (py_ret, py_body) = match python_ret(&method.sig.output) {
+      Some((py_ret, conv_ret)) if args_supported => {
+        let py_arg_names = python_arg_names(method.sig.inputs.iter());
+        (quote!(#py_ret), quote!(Self::#name(#(#py_arg_names),*) #conv_ret))
+      }
+      _ => python_unsupported(&name_str, name == "new"),
+    };
     panda_funcs.push(method);
     if name == "new" {
       python_funcs.push(quote!(
         #[new]
+        #[allow(unused_variables)]
         fn #py_name(#(#py_args),*) #py_ret {
-          Self::#name(#(#py_arg_names),*) #conv_ret
+          #py_body
         }
       ));
     } else if method.sig.receiver().is_none() {
       python_funcs.push(quote!(
         #[staticmethod]
+        #[allow(unused_variables)]
         #[pyo3(name = #name_str)]
         fn #py_name(#(#py_args),*) #py_ret {
-          Self::#name(#(#py_arg_names),*) #conv_ret
+          #py_body
         }
       ));
     } else {
       python_funcs.push(quote!(
+        #[allow(unused_variables)]
         #[pyo3(name = #name_str)]
         fn #py_name(#(#py_args),*) #py_ret {
-          Self::#name(#(#py_arg_names),*) #conv_ret
+          #py_body
         }
       ));
     }
@@ -280,7 +289,9 @@ fn python_args<'a>(args: impl Iterator<Item = &'a FnArg>) -> Vec<impl quote::ToT
             "Var" => quote!(#name: i32),
             "Callback" => quote!(#name: ::pyo3::PyObject),
             "Closure" => quote!(#name: ::pyo3::PyObject),
+            "Vec" | "HashMap" if python_convertible(&ty.ty) => quote!(#name: #path),
             "Vec" => quote!(#name: Vec<::pyo3::PyObject>),
+            "HashMap" => quote!(#name: ::std::collections::HashMap<String, ::pyo3::PyObject>),
             _ => quote!(#name: #path),
           },
           Type::Reference(path) => match &*path.elem {
@@ -310,9 +321,7 @@ fn python_arg_names<'a>(args: impl Iterator<Item = &'a FnArg>) -> Vec<impl quote
             }
             "Box" => quote!(Box::new(#name)),
             "Var" => quote!(#name.into()),
-            "Callback" => quote!(todo!("callbacks in python")),
-            "Closure" => quote!(todo!("closures in python")),
-            "Vec" => quote!(todo!("vecs in python")),
+            // Anything else unsupported is filtered out by `python_arg_supported`.
             // _ => abort!(ty.ty, "cannot handle type"),
             _ => quote!(#name),
           },
@@ -330,33 +339,88 @@ fn python_arg_names<'a>(args: impl Iterator<Item = &'a FnArg>) -> Vec<impl quote
     .collect()
 }
 
-fn python_ret(out: &ReturnType) -> (impl quote::ToTokens, Option<impl quote::ToTokens>) {
-  (
-    match &out {
-      ReturnType::Type(_, ty) => match &**ty {
-        Type::Path(path) => match path.path.segments[0].ident.to_string().as_str() {
-          "Result" => {
-            let arg = match &path.path.segments[0].arguments {
-              PathArguments::AngleBracketed(args) => args.args.first().cloned().unwrap(),
-              _ => unreachable!(),
-            };
-            return (
-              quote!(-> ::pyo3::PyResult<#arg>),
-              Some(quote!(.map_err(crate::plugin::python::conv_err))),
-            );
-          }
-          "Var" => {
-            return (quote!(-> ::pyo3::PyObject), Some(quote!(; todo!("convert var to python"))))
-          }
-          "Vec" => {
-            return (quote!(-> ::pyo3::PyObject), Some(quote!(; todo!("convert vec to python"))))
-          }
-          _ => quote!(#out),
-        },
-        _ => quote!(#out),
+/// Returns true if the given argument can be converted from python. Callbacks,
+/// closures, and containers of panda values cannot be converted yet.
+fn python_arg_supported(arg: &FnArg) -> bool {
+  match arg {
+    FnArg::Receiver(_) => true,
+    FnArg::Typed(ty) => match &*ty.ty {
+      Type::Path(path) => match path.path.segments[0].ident.to_string().as_str() {
+        "Callback" | "Closure" => false,
+        "Vec" | "HashMap" => python_convertible(&ty.ty),
+        _ => true,
       },
-      _ => quote!(),
+      _ => true,
     },
-    None,
-  )
+  }
+}
+
+/// Returns the python return type, and the tokens to append to the call of the
+/// original function. `Result`s are converted into python exceptions, and any
+/// other type is returned as-is. If the type contains a panda `Var`, this
+/// returns `None`, as it cannot be converted to python.
+fn python_ret(out: &ReturnType) -> Option<(impl quote::ToTokens, Option<impl quote::ToTokens>)> {
+  let ty = match out {
+    ReturnType::Type(_, ty) => ty,
+    ReturnType::Default => return Some((quote!(), None)),
+  };
+  match &**ty {
+    Type::Path(path) if path.path.segments[0].ident == "Result" => {
+      let arg = match &path.path.segments[0].arguments {
+        PathArguments::AngleBracketed(args) => args.args.first().cloned().unwrap(),
+        _ => unreachable!(),
+      };
+      let can_return = match &arg {
+        GenericArgument::Type(ty) => python_convertible(ty),
+        _ => true,
+      };
+      if can_return {
+        Some((
+          quote!(-> ::pyo3::PyResult<#arg>),
+          Some(quote!(.map_err(crate::plugin::python::conv_err))),
+        ))
+      } else {
+        None
+      }
+    }
+    ty if python_convertible(ty) => Some((quote!(#out), None)),
+    _ => None,
+  }
+}
+
+/// Returns the return type and body of a python function that cannot call the
+/// original function, because its arguments or return type cannot be converted.
+/// The function raises a `NotImplementedError` when called.
+fn python_unsupported(name: &str, is_new: bool) -> (TokenStream2, TokenStream2) {
+  let msg = format!("`{name}` cannot be called from python, as it uses panda values");
+  let ret = if is_new {
+    quote!(-> ::pyo3::PyResult<Self>)
+  } else {
+    quote!(-> ::pyo3::PyResult<::pyo3::PyObject>)
+  };
+  (ret, quote!(Err(::pyo3::exceptions::PyNotImplementedError::new_err(#msg))))
+}
+
+/// Returns true if the given type can be passed to and from python directly.
+/// This is true for primitives, plugin types, and containers of them (like
+/// `Vec`, `Option`, or `HashMap`). Panda values (`Var`) cannot be converted to
+/// python yet.
+fn python_convertible(ty: &Type) -> bool {
+  match ty {
+    Type::Path(path) => {
+      let seg = path.path.segments.last().unwrap();
+      if seg.ident == "Var" {
+        return false;
+      }
+      match &seg.arguments {
+        PathArguments::AngleBracketed(args) => args.args.iter().all(|a| match a {
+          GenericArgument::Type(ty) => python_convertible(ty),
+          _ => true,
+        }),
+        _ => true,
+      }
+    }
+    Type::Tuple(tup) => tup.elems.iter().all(python_convertible),
+    _ => true,
+  }
 }
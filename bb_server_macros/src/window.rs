@@ -105,9 +105,9 @@ pub fn window(input: TokenStream) -> TokenStream {
         )*
         panic!("cannot sync index out of bounds {}", index);
       }
-      fn open(&self, id: UUID, conn: &ConnSender) {
+      fn open(&self, id: UUID, conn: &ConnSender, wid: u8) {
         #(
-          self.#field_names.lock().open(id, conn.clone());
+          self.#field_names.lock().open(id, conn.clone(), wid);
         )*
       }
       fn close(&self, id: UUID) {
@@ -198,10 +198,10 @@ pub fn window_enum(input: TokenStream) -> TokenStream {
           )*
         }
       }
-      pub fn open(&self, id: UUID, conn: &ConnSender) {
+      pub fn open(&self, id: UUID, conn: &ConnSender, wid: u8) {
         match self {
           #(
-            Self::#variant(win) => win.open(id, conn),
+            Self::#variant(win) => win.open(id, conn, wid),
           )*
         }
       }